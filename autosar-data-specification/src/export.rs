@@ -0,0 +1,654 @@
+//! Export of the specification of an Autosar version as XSD or as JSON Schema
+
+use crate::parents::ParentIndex;
+use crate::*;
+use alloc::collections::BTreeSet;
+use alloc::string::String;
+use core::fmt::Write;
+
+const AUTOSAR_NAMESPACE: &str = "http://autosar.org/schema/r4.0";
+
+// the content of an element type or of a group, restricted to one version
+enum Particle {
+    Element(ElementType),
+    Group(ContentMode, Vec<Particle>),
+}
+
+// all data types that can occur in a file of one version
+struct SchemaTypes {
+    version: AutosarVersion,
+    // the schema type name of each data type, or None if the data type is not used in this version
+    type_names: Vec<Option<String>>,
+    // one element type for each data type in type_names, in order of the data types
+    element_types: Vec<ElementType>,
+    // the indices into CHARACTER_DATA of all character data specs that are used in this version
+    chardata: BTreeSet<u16>,
+}
+
+impl SchemaTypes {
+    fn new(version: AutosarVersion) -> Self {
+        let paths = ParentIndex::get().paths_from_root(version);
+        let mut type_names: Vec<Option<String>> = vec![None; DATATYPES.len()];
+        let mut used_names = BTreeSet::new();
+        let mut element_types = Vec::new();
+        let mut chardata = BTreeSet::new();
+        // the types are named after the element closest to the root element that uses them.
+        // Element names never contain "--", so a suffix "--<n>" can be used to make the type names unique
+        let mut definitions: Vec<(usize, usize)> = (0..ELEMENTS.len())
+            .filter(|def| paths[*def] != u16::MAX)
+            .map(|def| {
+                let mut depth = 0;
+                let mut current = def;
+                while current != AUTOSAR_ELEMENT as usize {
+                    current = paths[current] as usize;
+                    depth += 1;
+                }
+                (depth, def)
+            })
+            .collect();
+        definitions.sort_unstable();
+        for (_, def) in definitions {
+            let definition = &ELEMENTS[def];
+            let typ = definition.elemtype as usize;
+            if type_names[typ].is_some() {
+                continue;
+            }
+            let mut name = String::from(definition.name.to_str());
+            let mut counter = 1;
+            while used_names.contains(&name) {
+                counter += 1;
+                name = format!("{}--{counter}", definition.name);
+            }
+            used_names.insert(name.clone());
+            type_names[typ] = Some(name);
+
+            let element_type = ElementType::new(def as u16);
+            if let Some(chardata_id) = DATATYPES[typ].character_data {
+                chardata.insert(chardata_id);
+            }
+            for (attribute, ..) in element_type.attribute_spec_iter() {
+                if let Some(chardata_id) = attribute_chardata_id(element_type, attribute, version) {
+                    chardata.insert(chardata_id);
+                }
+            }
+            element_types.push(element_type);
+        }
+        element_types.sort_by_key(|etype| etype.typ);
+
+        Self {
+            version,
+            type_names,
+            element_types,
+            chardata,
+        }
+    }
+
+    fn type_name(&self, element_type: ElementType) -> &str {
+        self.type_names[element_type.typ as usize]
+            .as_deref()
+            .unwrap_or_default()
+    }
+
+    // get the content of a data type, without sub elements and groups that are not valid in this version
+    fn particles(&self, typ: u16) -> Vec<Particle> {
+        let ver_idx = ElementType::get_sub_element_ver(typ);
+        ElementType::get_sub_elements(typ)
+            .iter()
+            .enumerate()
+            .filter(|(pos, _)| self.version.compatible(VERSION_INFO[ver_idx + pos]))
+            .map(|(_, sub_element)| match sub_element {
+                SubElement::Element(def) => Particle::Element(ElementType::new(*def)),
+                SubElement::Group(groupid) => {
+                    Particle::Group(DATATYPES[*groupid as usize].mode, self.particles(*groupid))
+                }
+            })
+            .collect()
+    }
+
+    // get the attributes of an element type which are valid in this version
+    fn attributes(&self, element_type: ElementType) -> Vec<(AttributeName, u16, bool)> {
+        element_type
+            .attribute_spec_iter()
+            .filter_map(|(attribute, _, required)| {
+                attribute_chardata_id(element_type, attribute, self.version).map(|id| (attribute, id, required))
+            })
+            .collect()
+    }
+}
+
+// get the character data spec of an attribute, if the attribute is valid in the version
+//
+// The namespace declarations xmlns and xmlns:xsi are not attributes in a schema, so they are not included
+fn attribute_chardata_id(element_type: ElementType, attribute: AttributeName, version: AutosarVersion) -> Option<u16> {
+    if attribute == AttributeName::xmlns || attribute == AttributeName::xmlnsXsi {
+        return None;
+    }
+    let (idx_start, idx_end) = ElementType::get_attributes_idx(element_type.typ);
+    let ver_idx = ElementType::get_attributes_ver(element_type.typ);
+    ATTRIBUTES[idx_start..idx_end]
+        .iter()
+        .enumerate()
+        .find(|(pos, (name, ..))| *name == attribute && version.compatible(VERSION_INFO[ver_idx + pos]))
+        .map(|(_, (_, chardata_id, _))| *chardata_id)
+}
+
+/// Generate an XML schema (XSD) for the given Autosar version
+///
+/// The schema is generated from the specification in this crate, so it describes exactly the files that
+/// are accepted for this version. The content modes are represented as follows:
+/// - `Sequence`: `xsd:sequence`
+/// - `Choice`: `xsd:choice`
+/// - `Bag`: `xsd:choice` with `maxOccurs="unbounded"`
+/// - `Mixed`: a complex type with `mixed="true"`, containing an unbounded `xsd:choice`
+///
+/// Each data type is written as a named complex type, which is named after the first element that uses it.
+/// Character data specifications are written as simple types named `chardata-<n>`.
+///
+/// # Example
+///
+/// ```
+/// # use autosar_data_specification::*;
+/// let xsd = generate_xsd(AutosarVersion::Autosar_00050);
+/// assert!(xsd.contains(r#"<xsd:element name="AUTOSAR" type="AUTOSAR"/>"#));
+/// ```
+#[must_use]
+pub fn generate_xsd(version: AutosarVersion) -> String {
+    let types = SchemaTypes::new(version);
+    let mut out = String::new();
+
+    // writing to a String cannot fail, so the results of write! are ignored here and below
+    let _ = write!(
+        out,
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+<xsd:schema xmlns:xsd="http://www.w3.org/2001/XMLSchema" xmlns="{AUTOSAR_NAMESPACE}" targetNamespace="{AUTOSAR_NAMESPACE}" elementFormDefault="qualified" attributeFormDefault="unqualified">
+  <xsd:import namespace="http://www.w3.org/XML/1998/namespace" schemaLocation="http://www.w3.org/2001/xml.xsd"/>
+  <xsd:element name="AUTOSAR" type="{}"/>
+"#,
+        types.type_name(ElementType::ROOT)
+    );
+
+    for element_type in &types.element_types {
+        write_xsd_complex_type(&mut out, &types, *element_type);
+    }
+    for chardata_id in &types.chardata {
+        write_xsd_simple_type(&mut out, *chardata_id, version);
+    }
+
+    out.push_str("</xsd:schema>\n");
+    out
+}
+
+fn write_xsd_complex_type(out: &mut String, types: &SchemaTypes, element_type: ElementType) {
+    let name = types.type_name(element_type);
+    let mode = element_type.content_mode();
+    let mixed = if mode == ContentMode::Mixed {
+        r#" mixed="true""#
+    } else {
+        ""
+    };
+    let _ = writeln!(out, r#"  <xsd:complexType name="{name}"{mixed}>"#);
+
+    if mode == ContentMode::Characters {
+        let base = DATATYPES[element_type.typ as usize]
+            .character_data
+            .map_or_else(|| String::from("xsd:string"), |id| format!("chardata-{id}"));
+        let _ = writeln!(out, "    <xsd:simpleContent>");
+        let _ = writeln!(out, r#"      <xsd:extension base="{base}">"#);
+        write_xsd_attributes(out, types, element_type, 8);
+        let _ = writeln!(out, "      </xsd:extension>");
+        let _ = writeln!(out, "    </xsd:simpleContent>");
+    } else {
+        let particles = types.particles(element_type.typ);
+        if !particles.is_empty() {
+            write_xsd_group(out, types, mode, &particles, 4);
+        }
+        write_xsd_attributes(out, types, element_type, 4);
+    }
+
+    let _ = writeln!(out, "  </xsd:complexType>");
+}
+
+fn write_xsd_group(out: &mut String, types: &SchemaTypes, mode: ContentMode, particles: &[Particle], indent: usize) {
+    let (compositor, occurs) = match mode {
+        ContentMode::Sequence | ContentMode::Characters => ("sequence", ""),
+        ContentMode::Choice => ("choice", ""),
+        ContentMode::Bag | ContentMode::Mixed => ("choice", r#" minOccurs="0" maxOccurs="unbounded""#),
+    };
+    // the elements of an unbounded choice may repeat without restrictions
+    let unbounded = !occurs.is_empty();
+    let _ = writeln!(out, "{:indent$}<xsd:{compositor}{occurs}>", "");
+    for particle in particles {
+        match particle {
+            Particle::Element(element_type) => {
+                let occurs = if unbounded {
+                    ""
+                } else {
+                    match ELEMENTS[element_type.def as usize].multiplicity {
+                        ElementMultiplicity::ZeroOrOne => r#" minOccurs="0""#,
+                        ElementMultiplicity::One => "",
+                        ElementMultiplicity::Any => r#" minOccurs="0" maxOccurs="unbounded""#,
+                    }
+                };
+                let _ = writeln!(
+                    out,
+                    r#"{:indent$}  <xsd:element name="{}" type="{}"{occurs}/>"#,
+                    "",
+                    ELEMENTS[element_type.def as usize].name,
+                    types.type_name(*element_type),
+                );
+            }
+            Particle::Group(group_mode, group_particles) => {
+                write_xsd_group(out, types, *group_mode, group_particles, indent + 2);
+            }
+        }
+    }
+    let _ = writeln!(out, "{:indent$}</xsd:{compositor}>", "");
+}
+
+fn write_xsd_attributes(out: &mut String, types: &SchemaTypes, element_type: ElementType, indent: usize) {
+    for (attribute, chardata_id, required) in types.attributes(element_type) {
+        let required = if required { r#" use="required""# } else { "" };
+        if attribute == AttributeName::xmlSpace {
+            let _ = writeln!(out, r#"{:indent$}<xsd:attribute ref="xml:space"{required}/>"#, "");
+        } else if attribute == AttributeName::xsiSchemalocation {
+            // attributes from the xsi namespace are always allowed by schema validators
+        } else {
+            let _ = writeln!(
+                out,
+                r#"{:indent$}<xsd:attribute name="{attribute}" type="chardata-{chardata_id}"{required}/>"#,
+                ""
+            );
+        }
+    }
+}
+
+fn write_xsd_simple_type(out: &mut String, chardata_id: u16, version: AutosarVersion) {
+    let _ = writeln!(out, r#"  <xsd:simpleType name="chardata-{chardata_id}">"#);
+    match &CHARACTER_DATA[chardata_id as usize] {
+        CharacterDataSpec::Enum { items } => {
+            let _ = writeln!(out, r#"    <xsd:restriction base="xsd:string">"#);
+            for (item, version_mask) in *items {
+                if version.compatible(*version_mask) {
+                    let _ = writeln!(out, r#"      <xsd:enumeration value="{}"/>"#, xml_escape(item.to_str()));
+                }
+            }
+            let _ = writeln!(out, "    </xsd:restriction>");
+        }
+        CharacterDataSpec::Pattern { regex, max_length, .. } => {
+            let _ = writeln!(out, r#"    <xsd:restriction base="xsd:string">"#);
+            let _ = writeln!(out, r#"      <xsd:pattern value="{}"/>"#, xml_escape(regex));
+            if let Some(max_length) = max_length {
+                let _ = writeln!(out, r#"      <xsd:maxLength value="{max_length}"/>"#);
+            }
+            let _ = writeln!(out, "    </xsd:restriction>");
+        }
+        CharacterDataSpec::String { max_length, .. } => {
+            if let Some(max_length) = max_length {
+                let _ = writeln!(out, r#"    <xsd:restriction base="xsd:string">"#);
+                let _ = writeln!(out, r#"      <xsd:maxLength value="{max_length}"/>"#);
+                let _ = writeln!(out, "    </xsd:restriction>");
+            } else {
+                let _ = writeln!(out, r#"    <xsd:restriction base="xsd:string"/>"#);
+            }
+        }
+        CharacterDataSpec::UnsignedInteger => {
+            let _ = writeln!(out, r#"    <xsd:restriction base="xsd:unsignedLong"/>"#);
+        }
+        CharacterDataSpec::Float => {
+            let _ = writeln!(out, r#"    <xsd:restriction base="xsd:double"/>"#);
+        }
+    }
+    let _ = writeln!(out, "  </xsd:simpleType>");
+}
+
+fn xml_escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Generate a JSON Schema (draft 2020-12) for the given Autosar version
+///
+/// JSON has no direct equivalent of XML, so the schema describes the following representation of an arxml file:
+/// - the document is an object with the single property `AUTOSAR`, which contains the root element
+/// - each element is an object
+/// - attributes are stored as properties whose names are the attribute name prefixed with `@`, e.g. `@UUID`
+/// - sub elements are stored as properties named after the element. Elements which may occur more than
+///   once are stored in arrays.
+/// - the character data of an element is stored in the property `#text`
+/// - the content of elements with mixed content is stored in the property `#content`, which is an array
+///   of strings and of objects with exactly one sub element
+///
+/// If an element type has the content mode `Choice`, then at most one of the sub elements may be present.
+/// Choices and sequences inside of groups are not represented, and the order of the sub elements is lost.
+///
+/// Each data type is written to `$defs`, with the same name as in [`generate_xsd`].
+///
+/// # Example
+///
+/// ```
+/// # use autosar_data_specification::*;
+/// let json_schema = generate_json_schema(AutosarVersion::Autosar_00050);
+/// assert!(json_schema.contains(r##""AUTOSAR": { "$ref": "#/$defs/AUTOSAR" }"##));
+/// ```
+#[must_use]
+pub fn generate_json_schema(version: AutosarVersion) -> String {
+    let types = SchemaTypes::new(version);
+    let mut out = String::new();
+
+    let _ = write!(
+        out,
+        r##"{{
+  "$schema": "https://json-schema.org/draft/2020-12/schema",
+  "title": "{}",
+  "type": "object",
+  "properties": {{
+    "AUTOSAR": {{ "$ref": "#/$defs/{}" }}
+  }},
+  "required": ["AUTOSAR"],
+  "additionalProperties": false,
+  "$defs": {{"##,
+        json_escape(version.describe()),
+        types.type_name(ElementType::ROOT)
+    );
+
+    let mut separator = "\n";
+    for element_type in &types.element_types {
+        out.push_str(separator);
+        write_json_element_type(&mut out, &types, *element_type);
+        separator = ",\n";
+    }
+    for chardata_id in &types.chardata {
+        out.push_str(separator);
+        write_json_chardata(&mut out, *chardata_id, version);
+    }
+
+    out.push_str("\n  }\n}\n");
+    out
+}
+
+fn write_json_element_type(out: &mut String, types: &SchemaTypes, element_type: ElementType) {
+    let mode = element_type.content_mode();
+    let mut properties = Vec::new();
+    let mut required = Vec::new();
+
+    for (attribute, chardata_id, attribute_required) in types.attributes(element_type) {
+        let name = format!("@{attribute}");
+        if attribute_required {
+            required.push(name.clone());
+        }
+        properties.push((name, format!(r##"{{ "$ref": "#/$defs/chardata-{chardata_id}" }}"##)));
+    }
+
+    let particles = types.particles(element_type.typ);
+    let mut sub_elements = Vec::new();
+    flatten_particles(&particles, &mut sub_elements);
+    match mode {
+        ContentMode::Characters => {
+            let text = DATATYPES[element_type.typ as usize].character_data.map_or_else(
+                || String::from(r#"{ "type": "string" }"#),
+                |id| format!(r##"{{ "$ref": "#/$defs/chardata-{id}" }}"##),
+            );
+            properties.push((String::from("#text"), text));
+        }
+        ContentMode::Mixed => {
+            let mut choice = String::new();
+            for sub_element in &sub_elements {
+                if !choice.is_empty() {
+                    choice.push_str(", ");
+                }
+                let _ = write!(
+                    choice,
+                    r##""{}": {{ "$ref": "#/$defs/{}" }}"##,
+                    ELEMENTS[sub_element.def as usize].name,
+                    types.type_name(*sub_element)
+                );
+            }
+            properties.push((
+                String::from("#content"),
+                format!(
+                    r#"{{ "type": "array", "items": {{ "anyOf": [{{ "type": "string" }}, {{ "type": "object", "properties": {{ {choice} }}, "additionalProperties": false, "minProperties": 1, "maxProperties": 1 }}] }} }}"#
+                ),
+            ));
+        }
+        ContentMode::Sequence | ContentMode::Choice | ContentMode::Bag => {
+            for sub_element in &sub_elements {
+                let name = ELEMENTS[sub_element.def as usize].name;
+                let multiplicity = ELEMENTS[sub_element.def as usize].multiplicity;
+                let reference = format!(r##"{{ "$ref": "#/$defs/{}" }}"##, types.type_name(*sub_element));
+                if mode == ContentMode::Bag || multiplicity == ElementMultiplicity::Any {
+                    properties.push((
+                        String::from(name.to_str()),
+                        format!(r#"{{ "type": "array", "items": {reference} }}"#),
+                    ));
+                } else {
+                    properties.push((String::from(name.to_str()), reference));
+                }
+            }
+            // only the direct sub elements of a sequence are required; elements inside of groups are optional
+            if mode == ContentMode::Sequence {
+                for particle in &particles {
+                    if let Particle::Element(sub_element) = particle
+                        && ELEMENTS[sub_element.def as usize].multiplicity == ElementMultiplicity::One
+                    {
+                        required.push(String::from(ELEMENTS[sub_element.def as usize].name.to_str()));
+                    }
+                }
+            }
+        }
+    }
+
+    let _ = write!(
+        out,
+        r#"    "{}": {{
+      "type": "object",
+      "properties": {{"#,
+        types.type_name(element_type)
+    );
+    let mut separator = "\n";
+    for (name, schema) in &properties {
+        let _ = write!(out, r#"{separator}        "{}": {schema}"#, json_escape(name));
+        separator = ",\n";
+    }
+    out.push_str("\n      },\n");
+    if !required.is_empty() {
+        let _ = writeln!(out, r#"      "required": [{}],"#, json_string_list(&required));
+    }
+    if mode == ContentMode::Choice && sub_elements.len() > 1 {
+        // exactly one of the alternatives matches if at most one of the sub elements is present
+        let names: Vec<String> = sub_elements
+            .iter()
+            .map(|sub_element| String::from(ELEMENTS[sub_element.def as usize].name.to_str()))
+            .collect();
+        let alternatives: Vec<String> = names
+            .iter()
+            .map(|name| format!(r#"{{ "required": ["{name}"] }}"#))
+            .collect();
+        let alternatives = alternatives.join(", ");
+        let _ = writeln!(
+            out,
+            r#"      "oneOf": [{alternatives}, {{ "not": {{ "anyOf": [{alternatives}] }} }}],"#
+        );
+    }
+    out.push_str("      \"additionalProperties\": false\n    }");
+}
+
+// collect the sub elements of all groups
+fn flatten_particles(particles: &[Particle], sub_elements: &mut Vec<ElementType>) {
+    for particle in particles {
+        match particle {
+            Particle::Element(element_type) => {
+                let name = ELEMENTS[element_type.def as usize].name;
+                if !sub_elements
+                    .iter()
+                    .any(|sub_element| ELEMENTS[sub_element.def as usize].name == name)
+                {
+                    sub_elements.push(*element_type);
+                }
+            }
+            Particle::Group(_, group_particles) => flatten_particles(group_particles, sub_elements),
+        }
+    }
+}
+
+fn write_json_chardata(out: &mut String, chardata_id: u16, version: AutosarVersion) {
+    let _ = write!(out, r#"    "chardata-{chardata_id}": "#);
+    match &CHARACTER_DATA[chardata_id as usize] {
+        CharacterDataSpec::Enum { items } => {
+            let items: Vec<&str> = items
+                .iter()
+                .filter(|(_, version_mask)| version.compatible(*version_mask))
+                .map(|(item, _)| item.to_str())
+                .collect();
+            let _ = write!(out, r#"{{ "enum": [{}] }}"#, json_string_list(&items));
+        }
+        CharacterDataSpec::Pattern { regex, max_length, .. } => {
+            // xsd patterns always match the whole text, while JSON Schema patterns are not anchored
+            let pattern = json_escape(&format!("^(?:{regex})$"));
+            let _ = write!(out, r#"{{ "type": "string", "pattern": "{pattern}""#);
+            if let Some(max_length) = max_length {
+                let _ = write!(out, r#", "maxLength": {max_length}"#);
+            }
+            out.push_str(" }");
+        }
+        CharacterDataSpec::String { max_length, .. } => {
+            out.push_str(r#"{ "type": "string""#);
+            if let Some(max_length) = max_length {
+                let _ = write!(out, r#", "maxLength": {max_length}"#);
+            }
+            out.push_str(" }");
+        }
+        CharacterDataSpec::UnsignedInteger => out.push_str(r#"{ "type": "integer", "minimum": 0 }"#),
+        CharacterDataSpec::Float => out.push_str(r#"{ "type": "number" }"#),
+    }
+}
+
+fn json_escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            c if c.is_control() => {
+                let _ = write!(escaped, "\\u{:04x}", c as u32);
+            }
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+fn json_string_list<T: AsRef<str>>(items: &[T]) -> String {
+    let items: Vec<String> = items
+        .iter()
+        .map(|item| format!("\"{}\"", json_escape(item.as_ref())))
+        .collect();
+    items.join(", ")
+}
+
+#[cfg(test)]
+mod test {
+    extern crate std;
+    use super::*;
+
+    #[test]
+    fn xsd() {
+        let xsd = generate_xsd(AutosarVersion::Autosar_00050);
+        assert!(xsd.starts_with("<?xml"));
+        assert!(xsd.ends_with("</xsd:schema>\n"));
+        assert!(xsd.contains(r#"<xsd:element name="AR-PACKAGES" type="AR-PACKAGES" minOccurs="0"/>"#));
+        assert!(xsd.contains(r#"<xsd:element name="SHORT-NAME" type="SHORT-NAME"/>"#));
+        assert!(xsd.contains(r#"<xsd:attribute ref="xml:space"/>"#));
+        assert!(xsd.contains(r#"<xsd:choice minOccurs="0" maxOccurs="unbounded">"#));
+        assert!(xsd.contains(r#" mixed="true">"#));
+        assert!(xsd.contains(r#"<xsd:pattern value="0[xX][0-9a-fA-F]+"/>"#));
+        assert_eq!(
+            xsd.matches("<xsd:complexType ").count(),
+            xsd.matches("</xsd:complexType>").count()
+        );
+
+        // every referenced type is defined
+        let defined: BTreeSet<&str> = xsd
+            .split(r#"Type name=""#)
+            .skip(1)
+            .map(|part| part.split('"').next().unwrap())
+            .collect();
+        for part in xsd.split(r#" type=""#).skip(1).chain(xsd.split(r#" base=""#).skip(1)) {
+            let type_name = part.split('"').next().unwrap();
+            assert!(
+                type_name.starts_with("xsd:") || defined.contains(type_name),
+                "{type_name}"
+            );
+        }
+    }
+
+    #[test]
+    fn xsd_version() {
+        // only the element types of the selected version are included
+        let xsd_old = generate_xsd(AutosarVersion::Autosar_4_0_1);
+        let xsd_new = generate_xsd(AutosarVersion::Autosar_00050);
+        assert!(!xsd_old.contains(r#"name="ADAPTIVE-APPLICATION-SW-COMPONENT-TYPE""#));
+        assert!(xsd_new.contains(r#"name="ADAPTIVE-APPLICATION-SW-COMPONENT-TYPE""#));
+    }
+
+    #[test]
+    fn json_schema() {
+        let json_schema = generate_json_schema(AutosarVersion::Autosar_00050);
+        assert!(json_schema.contains(r#""$schema": "https://json-schema.org/draft/2020-12/schema""#));
+        assert!(
+            json_schema.contains(r##""AR-PACKAGE": { "type": "array", "items": { "$ref": "#/$defs/AR-PACKAGE" } }"##)
+        );
+        assert!(json_schema.contains(r#""@UUID": "#));
+        assert!(json_schema.contains(r##""#text": "##));
+        assert!(json_schema.contains(r##""#content": "##));
+        assert!(json_schema.contains(r#""pattern": "^(?:0[xX][0-9a-fA-F]+)$""#));
+        assert!(json_schema.contains(r#""oneOf": "#));
+
+        // brackets and braces are balanced outside of strings
+        let mut depth = 0i32;
+        let mut in_string = false;
+        let mut escaped = false;
+        for c in json_schema.chars() {
+            if in_string {
+                match c {
+                    _ if escaped => escaped = false,
+                    '\\' => escaped = true,
+                    '"' => in_string = false,
+                    _ => {}
+                }
+            } else {
+                match c {
+                    '"' => in_string = true,
+                    '{' | '[' => depth += 1,
+                    '}' | ']' => depth -= 1,
+                    _ => {}
+                }
+                assert!(depth >= 0);
+            }
+        }
+        assert_eq!(depth, 0);
+        assert!(!in_string);
+
+        // every referenced definition exists
+        let defined: BTreeSet<&str> = json_schema
+            .split("\n    \"")
+            .skip(1)
+            .map(|part| part.split('"').next().unwrap())
+            .collect();
+        for part in json_schema.split(r##""$ref": "#/$defs/"##).skip(1) {
+            let name = part.split('"').next().unwrap();
+            assert!(defined.contains(name), "{name}");
+        }
+    }
+}
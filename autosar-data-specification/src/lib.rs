@@ -86,13 +86,19 @@ mod attributename;
 mod autosarversion;
 mod elementname;
 mod enumitem;
+mod export;
+mod parents;
 mod regex;
+mod schemadiff;
 mod specification;
 
 pub use attributename::{AttributeName, ParseAttributeNameError};
 pub use autosarversion::{AutosarVersion, ParseAutosarVersionError};
 pub use elementname::{ElementName, ParseElementNameError};
 pub use enumitem::{EnumItem, ParseEnumItemError};
+pub use export::{generate_json_schema, generate_xsd};
+pub use parents::PossibleParent;
+pub use schemadiff::{SchemaChange, compare_versions};
 use specification::{
     ATTRIBUTES, AUTOSAR_ELEMENT, CHARACTER_DATA, DATATYPES, ELEMENTS, REF_ITEMS, REFERENCE_TYPE_IDX, SUBELEMENTS,
     VERSION_INFO,
@@ -507,6 +513,15 @@ impl ElementType {
         ELEMENTS[self.def as usize].ordered
     }
 
+    /// Do this `ElementType` and the other `ElementType` use the same data type
+    ///
+    /// Elements with different names can share a data type. In this case they have the same attributes, sub elements
+    /// and character data. This happens for example when an element was renamed in a newer Autosar version.
+    #[must_use]
+    pub const fn same_datatype(&self, other: &ElementType) -> bool {
+        self.typ == other.typ
+    }
+
     /// Is this `ElementType` splittable
     ///
     /// This function returns a bitfield that indicates in which versions (if any) the `ElementType` is marked as splittable.
@@ -1211,6 +1226,24 @@ mod test {
         assert_eq!(defapp_timeout_type.std_restriction(), StdRestrict::AdaptivePlatform);
     }
 
+    #[test]
+    fn same_datatype() {
+        let (ar_packages_type, _) = ElementType::ROOT
+            .find_sub_element(ElementName::ArPackages, u32::MAX)
+            .unwrap();
+        let (ar_package_type, _) = ar_packages_type
+            .find_sub_element(ElementName::ArPackage, u32::MAX)
+            .unwrap();
+        let (desc_type, _) = ar_package_type.find_sub_element(ElementName::Desc, u32::MAX).unwrap();
+        let (introduction_type, _) = ar_package_type
+            .find_sub_element(ElementName::Introduction, u32::MAX)
+            .unwrap();
+
+        assert!(ar_package_type.same_datatype(&ar_package_type));
+        assert!(!ar_package_type.same_datatype(&ar_packages_type));
+        assert!(!desc_type.same_datatype(&introduction_type));
+    }
+
     #[test]
     fn reference_dest() {
         let (ar_packages_type, _) = ElementType::ROOT
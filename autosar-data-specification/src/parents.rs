@@ -0,0 +1,220 @@
+//! Reverse lookup in the specification: which elements may contain an element with a given name
+//!
+//! The specification only lists the sub elements of each element type. The reverse index is built from this
+//! information the first time it is needed, and it is kept for the lifetime of the program.
+use crate::*;
+use alloc::boxed::Box;
+use alloc::collections::VecDeque;
+use core::ptr::null_mut;
+use core::sync::atomic::{AtomicPtr, Ordering};
+
+/// A parent element type which may contain a given element, as returned by [`ElementName::possible_parents`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PossibleParent {
+    /// the type of the parent element
+    pub parent_type: ElementType,
+    /// the type of the element when it is placed inside this parent
+    pub element_type: ElementType,
+    /// the names of the elements on the shortest path from the root element to the parent element.
+    /// The path starts with AUTOSAR and ends with the name of the parent element.
+    pub path: Vec<ElementName>,
+}
+
+pub(crate) struct ParentIndex {
+    // for each element definition: the definitions of the elements that may contain it, and the version mask
+    parents: Vec<Vec<(u16, u32)>>,
+    // for each element name: the element definitions with this name
+    definitions: Vec<Vec<u16>>,
+    // for each data type: the definitions of its sub elements, and their version masks
+    sub_elements: Vec<Vec<(u16, u32)>>,
+}
+
+static PARENT_INDEX: AtomicPtr<ParentIndex> = AtomicPtr::new(null_mut());
+
+impl ParentIndex {
+    fn build() -> Self {
+        let mut parents = vec![Vec::new(); ELEMENTS.len()];
+        let mut definitions: Vec<Vec<u16>> = Vec::new();
+        let mut sub_elements: Vec<Option<Vec<(u16, u32)>>> = vec![None; DATATYPES.len()];
+        for (def, definition) in ELEMENTS.iter().enumerate() {
+            let def = def as u16;
+            let name_idx = definition.name as usize;
+            if definitions.len() <= name_idx {
+                definitions.resize(name_idx + 1, Vec::new());
+            }
+            definitions[name_idx].push(def);
+
+            // many element definitions share a data type, so the sub elements of each data type are only collected once
+            let type_sub_elements = sub_elements[definition.elemtype as usize].get_or_insert_with(|| {
+                ElementType::new(def)
+                    .sub_element_spec_iter()
+                    .map(|(_, etype, version_mask, _)| (etype.def, version_mask))
+                    .collect()
+            });
+            for (sub_def, version_mask) in type_sub_elements.iter() {
+                parents[*sub_def as usize].push((def, *version_mask));
+            }
+        }
+
+        Self {
+            parents,
+            definitions,
+            sub_elements: sub_elements.into_iter().map(Option::unwrap_or_default).collect(),
+        }
+    }
+
+    // get the index, and build it if this is the first use
+    pub(crate) fn get() -> &'static Self {
+        let index = PARENT_INDEX.load(Ordering::Acquire);
+        if !index.is_null() {
+            // SAFETY: a stored index is never freed
+            return unsafe { &*index };
+        }
+
+        let new_index = Box::into_raw(Box::new(Self::build()));
+        match PARENT_INDEX.compare_exchange(null_mut(), new_index, Ordering::AcqRel, Ordering::Acquire) {
+            // SAFETY: the new index was stored, so it is never freed
+            Ok(_) => unsafe { &*new_index },
+            Err(existing) => {
+                // another thread stored its index first, so the new index is not needed
+                // SAFETY: new_index was created by Box::into_raw above and it was never shared
+                drop(unsafe { Box::from_raw(new_index) });
+                // SAFETY: a stored index is never freed
+                unsafe { &*existing }
+            }
+        }
+    }
+
+    // find the shortest path from the root element to every element definition that is reachable in the given version
+    //
+    // The result contains the previous element on the path for each definition, or u16::MAX if it is not reachable
+    pub(crate) fn paths_from_root(&self, version: AutosarVersion) -> Vec<u16> {
+        let mut previous = vec![u16::MAX; ELEMENTS.len()];
+        previous[AUTOSAR_ELEMENT as usize] = AUTOSAR_ELEMENT;
+        let mut queue = VecDeque::from([AUTOSAR_ELEMENT]);
+        while let Some(def) = queue.pop_front() {
+            let elemtype = ELEMENTS[def as usize].elemtype;
+            for (sub_def, version_mask) in &self.sub_elements[elemtype as usize] {
+                if version.compatible(*version_mask) && previous[*sub_def as usize] == u16::MAX {
+                    previous[*sub_def as usize] = def;
+                    queue.push_back(*sub_def);
+                }
+            }
+        }
+        previous
+    }
+}
+
+impl ElementName {
+    /// Find all element types that may contain an element with this name in the given version
+    ///
+    /// Each result contains the type of the parent, the type of the element inside this parent, and the path
+    /// of element names from the root element to the parent. Parents that cannot occur in a file of the
+    /// given version are not included.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use autosar_data_specification::*;
+    /// let parents = ElementName::ArPackages.possible_parents(AutosarVersion::Autosar_00050);
+    /// assert!(parents.iter().any(|parent| parent.path == vec![ElementName::Autosar]));
+    /// ```
+    #[must_use]
+    pub fn possible_parents(self, version: AutosarVersion) -> Vec<PossibleParent> {
+        let index = ParentIndex::get();
+        let Some(definitions) = index.definitions.get(self as usize) else {
+            return Vec::new();
+        };
+
+        let previous = index.paths_from_root(version);
+        let mut result: Vec<PossibleParent> = Vec::new();
+        for def in definitions {
+            for (parent_def, version_mask) in &index.parents[*def as usize] {
+                let parent_type = ElementType::new(*parent_def);
+                let element_type = ElementType::new(*def);
+                if !version.compatible(*version_mask)
+                    || previous[*parent_def as usize] == u16::MAX
+                    || result
+                        .iter()
+                        .any(|item| item.parent_type == parent_type && item.element_type == element_type)
+                {
+                    continue;
+                }
+
+                let mut path = vec![ELEMENTS[*parent_def as usize].name];
+                let mut current = *parent_def;
+                while current != AUTOSAR_ELEMENT {
+                    current = previous[current as usize];
+                    path.push(ELEMENTS[current as usize].name);
+                }
+                path.reverse();
+                result.push(PossibleParent {
+                    parent_type,
+                    element_type,
+                    path,
+                });
+            }
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn possible_parents() {
+        let version = AutosarVersion::Autosar_00050;
+
+        // the root element has no parents
+        assert!(ElementName::Autosar.possible_parents(version).is_empty());
+
+        // AR-PACKAGE is contained in AR-PACKAGES, either directly below the root element or in another package
+        let parents = ElementName::ArPackage.possible_parents(version);
+        assert!(!parents.is_empty());
+        for parent in &parents {
+            assert_eq!(*parent.path.first().unwrap(), ElementName::Autosar);
+            assert_eq!(*parent.path.last().unwrap(), ElementName::ArPackages);
+            let (element_type, _) = parent
+                .parent_type
+                .find_sub_element(ElementName::ArPackage, version as u32)
+                .unwrap();
+            assert_eq!(element_type, parent.element_type);
+        }
+        assert!(
+            parents
+                .iter()
+                .any(|parent| parent.path == vec![ElementName::Autosar, ElementName::ArPackages])
+        );
+
+        // the results of every parent are consistent with the forward lookup
+        let parents = ElementName::ShortName.possible_parents(version);
+        assert!(parents.len() > 100);
+        for parent in &parents {
+            assert!(
+                parent
+                    .parent_type
+                    .find_sub_element(ElementName::ShortName, version as u32)
+                    .is_some()
+            );
+        }
+    }
+
+    #[test]
+    fn possible_parents_version() {
+        // elements that were added in a later version have no parents in earlier versions
+        let parents = ElementName::AdaptiveApplicationSwComponentType.possible_parents(AutosarVersion::Autosar_4_0_1);
+        assert!(parents.is_empty());
+        let parents = ElementName::AdaptiveApplicationSwComponentType.possible_parents(AutosarVersion::Autosar_00050);
+        assert!(parents.iter().any(|parent| {
+            parent.path
+                == vec![
+                    ElementName::Autosar,
+                    ElementName::ArPackages,
+                    ElementName::ArPackage,
+                    ElementName::Elements,
+                ]
+        }));
+    }
+}
@@ -0,0 +1,382 @@
+//! Comparison of the specification of two Autosar versions
+
+use crate::parents::ParentIndex;
+use crate::*;
+
+/// A single difference between the specifications of two Autosar versions, as returned by [`compare_versions`]
+///
+/// Element types that share a data type (see [`ElementType::same_datatype`]) have identical content, so each
+/// change of their content is reported once for every one of these element types.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum SchemaChange {
+    /// A sub element is only allowed in the new version
+    ElementAdded {
+        /// the parent element type
+        parent: ElementType,
+        /// name of the added sub element
+        element: ElementName,
+    },
+    /// A sub element is only allowed in the old version
+    ElementRemoved {
+        /// the parent element type
+        parent: ElementType,
+        /// name of the removed sub element
+        element: ElementName,
+    },
+    /// The multiplicity of a sub element has changed
+    MultiplicityChanged {
+        /// the parent element type
+        parent: ElementType,
+        /// name of the sub element
+        element: ElementName,
+        /// multiplicity in the old version
+        old: ElementMultiplicity,
+        /// multiplicity in the new version
+        new: ElementMultiplicity,
+    },
+    /// A sub element became splittable, or it is no longer splittable
+    SplittableChanged {
+        /// the parent element type
+        parent: ElementType,
+        /// name of the sub element
+        element: ElementName,
+        /// is the sub element splittable in the new version
+        splittable: bool,
+    },
+    /// An attribute is only allowed in the new version
+    AttributeAdded {
+        /// the element type containing the attribute
+        element_type: ElementType,
+        /// name of the added attribute
+        attribute: AttributeName,
+    },
+    /// An attribute is only allowed in the old version
+    AttributeRemoved {
+        /// the element type containing the attribute
+        element_type: ElementType,
+        /// name of the removed attribute
+        attribute: AttributeName,
+    },
+    /// An enum item is only valid in the new version
+    EnumItemAdded {
+        /// the element type whose character data or attribute uses the enum
+        element_type: ElementType,
+        /// the attribute which uses the enum, or None if the enum is used by the character data of the element
+        attribute: Option<AttributeName>,
+        /// the added enum item
+        item: EnumItem,
+    },
+    /// An enum item is only valid in the old version
+    EnumItemRemoved {
+        /// the element type whose character data or attribute uses the enum
+        element_type: ElementType,
+        /// the attribute which uses the enum, or None if the enum is used by the character data of the element
+        attribute: Option<AttributeName>,
+        /// the removed enum item
+        item: EnumItem,
+    },
+}
+
+/// Compare the specifications of two Autosar versions
+///
+/// Only element types which can occur in files of both versions are compared. If an element is added
+/// or removed, then its content is not reported separately.
+///
+/// # Example
+///
+/// ```
+/// # use autosar_data_specification::*;
+/// let changes = compare_versions(AutosarVersion::Autosar_00049, AutosarVersion::Autosar_00053);
+/// for change in &changes {
+///     if let SchemaChange::ElementAdded { parent, element } = change {
+///         // ...
+///     }
+/// }
+/// # assert!(!changes.is_empty());
+/// assert!(compare_versions(AutosarVersion::Autosar_00050, AutosarVersion::Autosar_00050).is_empty());
+/// ```
+#[must_use]
+pub fn compare_versions(old_version: AutosarVersion, new_version: AutosarVersion) -> Vec<SchemaChange> {
+    let index = ParentIndex::get();
+    let old_paths = index.paths_from_root(old_version);
+    let new_paths = index.paths_from_root(new_version);
+
+    // find the data types that are used in both versions, and all element types that use each of them
+    let mut in_old_version = vec![false; DATATYPES.len()];
+    let mut in_new_version = vec![false; DATATYPES.len()];
+    let mut element_types: Vec<Vec<u16>> = vec![Vec::new(); DATATYPES.len()];
+    for (def, definition) in ELEMENTS.iter().enumerate() {
+        let typ = definition.elemtype as usize;
+        in_old_version[typ] |= old_paths[def] != u16::MAX;
+        in_new_version[typ] |= new_paths[def] != u16::MAX;
+        if old_paths[def] != u16::MAX || new_paths[def] != u16::MAX {
+            element_types[typ].push(def as u16);
+        }
+    }
+
+    let mut changes = Vec::new();
+    for (typ, defs) in element_types.iter().enumerate() {
+        if !in_old_version[typ] || !in_new_version[typ] {
+            continue;
+        }
+        for def in defs {
+            let element_type = ElementType::new(*def);
+            compare_sub_elements(element_type, old_version, new_version, &mut changes);
+            compare_attributes(element_type, old_version, new_version, &mut changes);
+            if let Some(CharacterDataSpec::Enum { items }) = element_type.chardata_spec() {
+                compare_enum_items(element_type, None, items, old_version, new_version, &mut changes);
+            }
+        }
+    }
+    changes
+}
+
+fn compare_sub_elements(
+    parent: ElementType,
+    old_version: AutosarVersion,
+    new_version: AutosarVersion,
+    changes: &mut Vec<SchemaChange>,
+) {
+    let sub_elements: Vec<_> = parent.sub_element_spec_iter().collect();
+    let find_sub_element = |name: ElementName, version: AutosarVersion| {
+        sub_elements
+            .iter()
+            .find(|(sub_name, _, version_mask, _)| *sub_name == name && version.compatible(*version_mask))
+            .map(|(_, etype, ..)| *etype)
+    };
+
+    // the same name can occur several times, with different types for different versions
+    let mut names: Vec<ElementName> = Vec::new();
+    for (name, ..) in &sub_elements {
+        if !names.contains(name) {
+            names.push(*name);
+        }
+    }
+
+    for element in names {
+        match (
+            find_sub_element(element, old_version),
+            find_sub_element(element, new_version),
+        ) {
+            (None, Some(_)) => changes.push(SchemaChange::ElementAdded { parent, element }),
+            (Some(_), None) => changes.push(SchemaChange::ElementRemoved { parent, element }),
+            (Some(old_type), Some(new_type)) => {
+                let old = ELEMENTS[old_type.def as usize].multiplicity;
+                let new = ELEMENTS[new_type.def as usize].multiplicity;
+                if old != new {
+                    changes.push(SchemaChange::MultiplicityChanged {
+                        parent,
+                        element,
+                        old,
+                        new,
+                    });
+                }
+                let splittable = new_type.splittable_in(new_version);
+                if old_type.splittable_in(old_version) != splittable {
+                    changes.push(SchemaChange::SplittableChanged {
+                        parent,
+                        element,
+                        splittable,
+                    });
+                }
+            }
+            (None, None) => {}
+        }
+    }
+}
+
+fn compare_attributes(
+    element_type: ElementType,
+    old_version: AutosarVersion,
+    new_version: AutosarVersion,
+    changes: &mut Vec<SchemaChange>,
+) {
+    for (attribute, ..) in element_type.attribute_spec_iter() {
+        let Some(attribute_spec) = element_type.find_attribute_spec(attribute) else {
+            continue;
+        };
+        match (
+            old_version.compatible(attribute_spec.version),
+            new_version.compatible(attribute_spec.version),
+        ) {
+            (false, true) => changes.push(SchemaChange::AttributeAdded {
+                element_type,
+                attribute,
+            }),
+            (true, false) => changes.push(SchemaChange::AttributeRemoved {
+                element_type,
+                attribute,
+            }),
+            (true, true) => {
+                if let CharacterDataSpec::Enum { items } = attribute_spec.spec {
+                    compare_enum_items(element_type, Some(attribute), items, old_version, new_version, changes);
+                }
+            }
+            (false, false) => {}
+        }
+    }
+}
+
+fn compare_enum_items(
+    element_type: ElementType,
+    attribute: Option<AttributeName>,
+    items: &[(EnumItem, u32)],
+    old_version: AutosarVersion,
+    new_version: AutosarVersion,
+    changes: &mut Vec<SchemaChange>,
+) {
+    for (item, version_mask) in items {
+        match (
+            old_version.compatible(*version_mask),
+            new_version.compatible(*version_mask),
+        ) {
+            (false, true) => changes.push(SchemaChange::EnumItemAdded {
+                element_type,
+                attribute,
+                item: *item,
+            }),
+            (true, false) => changes.push(SchemaChange::EnumItemRemoved {
+                element_type,
+                attribute,
+                item: *item,
+            }),
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn compare_same_version() {
+        for version in [
+            AutosarVersion::Autosar_4_0_1,
+            AutosarVersion::Autosar_00049,
+            AutosarVersion::LATEST,
+        ] {
+            assert!(compare_versions(version, version).is_empty());
+        }
+    }
+
+    #[test]
+    fn compare_versions_symmetric() {
+        let forward = compare_versions(AutosarVersion::Autosar_00049, AutosarVersion::Autosar_00053);
+        let backward = compare_versions(AutosarVersion::Autosar_00053, AutosarVersion::Autosar_00049);
+        assert!(!forward.is_empty());
+        assert_eq!(forward.len(), backward.len());
+
+        for change in &forward {
+            let reverse = match change {
+                SchemaChange::ElementAdded { parent, element } => SchemaChange::ElementRemoved {
+                    parent: *parent,
+                    element: *element,
+                },
+                SchemaChange::AttributeAdded {
+                    element_type,
+                    attribute,
+                } => SchemaChange::AttributeRemoved {
+                    element_type: *element_type,
+                    attribute: *attribute,
+                },
+                SchemaChange::EnumItemAdded {
+                    element_type,
+                    attribute,
+                    item,
+                } => SchemaChange::EnumItemRemoved {
+                    element_type: *element_type,
+                    attribute: *attribute,
+                    item: *item,
+                },
+                _ => continue,
+            };
+            assert!(backward.contains(&reverse), "{reverse:?} not found");
+        }
+    }
+
+    #[test]
+    fn compare_versions_changes() {
+        // every added element is valid in its parent only in the new version
+        let old_version = AutosarVersion::Autosar_00049;
+        let new_version = AutosarVersion::Autosar_00053;
+        let changes = compare_versions(old_version, new_version);
+        for change in &changes {
+            match change {
+                SchemaChange::ElementAdded { parent, element } => {
+                    assert!(parent.find_sub_element(*element, old_version as u32).is_none());
+                    assert!(parent.find_sub_element(*element, new_version as u32).is_some());
+                }
+                SchemaChange::ElementRemoved { parent, element } => {
+                    assert!(parent.find_sub_element(*element, old_version as u32).is_some());
+                    assert!(parent.find_sub_element(*element, new_version as u32).is_none());
+                }
+                SchemaChange::AttributeAdded {
+                    element_type,
+                    attribute,
+                } => {
+                    let spec = element_type.find_attribute_spec(*attribute).unwrap();
+                    assert!(!old_version.compatible(spec.version));
+                    assert!(new_version.compatible(spec.version));
+                }
+                SchemaChange::SplittableChanged {
+                    parent,
+                    element,
+                    splittable,
+                } => {
+                    let (old_type, _) = parent.find_sub_element(*element, old_version as u32).unwrap();
+                    let (new_type, _) = parent.find_sub_element(*element, new_version as u32).unwrap();
+                    assert_eq!(old_type.splittable_in(old_version), !splittable);
+                    assert_eq!(new_type.splittable_in(new_version), *splittable);
+                }
+                _ => {}
+            }
+        }
+        assert!(
+            changes
+                .iter()
+                .any(|change| matches!(change, SchemaChange::ElementAdded { .. }))
+        );
+        assert!(
+            changes
+                .iter()
+                .any(|change| matches!(change, SchemaChange::SplittableChanged { .. }))
+        );
+        assert!(
+            changes
+                .iter()
+                .any(|change| matches!(change, SchemaChange::EnumItemAdded { .. }))
+        );
+    }
+
+    #[test]
+    fn compare_versions_shared_datatype() {
+        // a change of a data type is reported for every element type that uses it
+        let old_version = AutosarVersion::Autosar_00049;
+        let new_version = AutosarVersion::Autosar_00053;
+        let changes = compare_versions(old_version, new_version);
+        let index = ParentIndex::get();
+        let old_paths = index.paths_from_root(old_version);
+        let new_paths = index.paths_from_root(new_version);
+        let mut shared = false;
+        for change in &changes {
+            if let SchemaChange::ElementAdded { parent, element } = change {
+                let parents: Vec<ElementType> = (0..ELEMENTS.len())
+                    .filter(|def| old_paths[*def] != u16::MAX || new_paths[*def] != u16::MAX)
+                    .map(|def| ElementType::new(def as u16))
+                    .filter(|element_type| element_type.same_datatype(parent))
+                    .collect();
+                shared |= parents.len() > 1;
+                for other in parents {
+                    let expected = SchemaChange::ElementAdded {
+                        parent: other,
+                        element: *element,
+                    };
+                    assert!(changes.contains(&expected), "{expected:?} not found");
+                }
+            }
+        }
+        assert!(shared);
+    }
+}
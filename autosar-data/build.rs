@@ -0,0 +1,311 @@
+//! Build script of autosar-data
+//!
+//! When the feature `typed` is enabled, the typed element wrappers of the module `typed` are generated
+//! from the specification in autosar-data-specification.
+
+fn main() {
+    println!("cargo::rerun-if-changed=build.rs");
+
+    #[cfg(feature = "typed")]
+    typed::generate();
+}
+
+#[cfg(feature = "typed")]
+mod typed {
+    use autosar_data_specification::{
+        AutosarVersion, CharacterDataSpec, ContentMode, ElementMultiplicity, ElementName, ElementType,
+        expand_version_mask,
+    };
+    use std::collections::{BTreeMap, HashSet};
+    use std::fmt::Write;
+
+    /// how the content of a sub element is returned by its accessor
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    enum AccessorKind {
+        /// the sub element has its own wrapper
+        Wrapper,
+        /// the target of a reference
+        Reference,
+        Enum,
+        String,
+        UnsignedInteger,
+        SignedInteger,
+        Float,
+        Bool,
+        /// the sub element contains character data, but the type is not the same everywhere
+        CharacterData,
+        /// the sub element is sometimes a wrapper and sometimes character data
+        Element,
+    }
+
+    struct Accessor {
+        /// containers between the wrapped element and the sub element
+        path: Vec<ElementName>,
+        element_name: ElementName,
+        kind: AccessorKind,
+        multiple: bool,
+        version_mask: u32,
+    }
+
+    /// collect the accessors of all elements that can contain sub elements, then write them to `$OUT_DIR/typed.rs`
+    pub(super) fn generate() {
+        let all_versions = expand_version_mask(u32::MAX)
+            .into_iter()
+            .fold(0, |mask, version| mask | version as u32);
+        let mut wrappers: BTreeMap<&'static str, BTreeMap<String, Accessor>> = BTreeMap::new();
+        let mut seen = HashSet::new();
+        let mut worklist = vec![(ElementName::Autosar, ElementType::ROOT)];
+
+        while let Some((name, elemtype)) = worklist.pop() {
+            if !seen.insert(elemtype) || elemtype.content_mode() == ContentMode::Characters {
+                continue;
+            }
+            let accessors = wrappers.entry(name.to_str()).or_default();
+            let mut variants = Vec::new();
+            for (sub_name, sub_type, version_mask, _) in elemtype.sub_element_spec_iter() {
+                worklist.push((sub_name, sub_type));
+                let multiple = is_multiple(elemtype, sub_name, version_mask);
+                add_accessor(accessors, &[], sub_name, sub_type, multiple, version_mask);
+                if !multiple && sub_name.to_str().ends_with("-VARIANTS") {
+                    variants.push((sub_name, sub_type, version_mask));
+                }
+            }
+
+            // the content of the first <X>-VARIANTS/<X>-CONDITIONAL is also made available directly
+            for (variants_name, variants_type, variants_mask) in variants {
+                let mut sub_elements = variants_type.sub_element_spec_iter();
+                if let (Some((conditional_name, conditional_type, conditional_mask, _)), None) =
+                    (sub_elements.next(), sub_elements.next())
+                    && conditional_name.to_str().ends_with("-CONDITIONAL")
+                {
+                    for (sub_name, sub_type, version_mask, _) in conditional_type.sub_element_spec_iter() {
+                        let method = method_name(sub_name, is_multiple(conditional_type, sub_name, version_mask));
+                        if accessors.get(&method).is_none_or(|accessor| !accessor.path.is_empty()) {
+                            add_accessor(
+                                accessors,
+                                &[variants_name, conditional_name],
+                                sub_name,
+                                sub_type,
+                                is_multiple(conditional_type, sub_name, version_mask),
+                                variants_mask & conditional_mask & version_mask,
+                            );
+                        }
+                    }
+                }
+            }
+        }
+
+        let mut output = String::new();
+        for (xml_name, accessors) in &wrappers {
+            let type_name = type_name(xml_name);
+            writeln!(output, "typed_element!({type_name}, \"{xml_name}\");").unwrap();
+            writeln!(output, "impl {type_name} {{").unwrap();
+            for (method, accessor) in accessors {
+                write_accessor(&mut output, method, accessor, all_versions);
+            }
+            writeln!(output, "}}").unwrap();
+        }
+
+        let out_dir = std::env::var("OUT_DIR").unwrap();
+        std::fs::write(std::path::Path::new(&out_dir).join("typed.rs"), output).unwrap();
+    }
+
+    /// sub elements can occur multiple times if their multiplicity is Any, or if they are part of a Bag
+    fn is_multiple(elemtype: ElementType, sub_name: ElementName, version_mask: u32) -> bool {
+        let Some((_, indices)) = elemtype.find_sub_element(sub_name, version_mask) else {
+            return false;
+        };
+        let container_mode = elemtype.get_sub_element_container_mode(&indices);
+        elemtype.get_sub_element_multiplicity(&indices) == Some(ElementMultiplicity::Any)
+            || container_mode == ContentMode::Bag
+            || container_mode == ContentMode::Mixed
+    }
+
+    fn add_accessor(
+        accessors: &mut BTreeMap<String, Accessor>,
+        path: &[ElementName],
+        element_name: ElementName,
+        elemtype: ElementType,
+        multiple: bool,
+        version_mask: u32,
+    ) {
+        let kind = accessor_kind(elemtype);
+        let method = method_name(element_name, multiple);
+        if let Some(existing) = accessors.get_mut(&method) {
+            // the same element name occurs in several types, or several times within one type
+            if existing.path == path && existing.element_name == element_name {
+                existing.version_mask |= version_mask;
+                if existing.kind != kind {
+                    existing.kind = if existing.kind == AccessorKind::Wrapper || kind == AccessorKind::Wrapper {
+                        AccessorKind::Element
+                    } else {
+                        AccessorKind::CharacterData
+                    };
+                }
+            }
+        } else {
+            accessors.insert(
+                method,
+                Accessor {
+                    path: path.to_vec(),
+                    element_name,
+                    kind,
+                    multiple,
+                    version_mask,
+                },
+            );
+        }
+    }
+
+    fn accessor_kind(elemtype: ElementType) -> AccessorKind {
+        if elemtype.content_mode() != ContentMode::Characters {
+            return AccessorKind::Wrapper;
+        }
+        if elemtype.is_ref() {
+            return AccessorKind::Reference;
+        }
+        match elemtype.chardata_spec() {
+            Some(CharacterDataSpec::Enum { .. }) => AccessorKind::Enum,
+            Some(CharacterDataSpec::String { .. }) => AccessorKind::String,
+            Some(CharacterDataSpec::UnsignedInteger) => AccessorKind::UnsignedInteger,
+            Some(CharacterDataSpec::Float) => AccessorKind::Float,
+            Some(CharacterDataSpec::Pattern { regex, .. }) => pattern_kind(regex),
+            None => AccessorKind::CharacterData,
+        }
+    }
+
+    /// classify a pattern by its regex
+    ///
+    /// Only patterns that exclusively describe numbers or booleans are converted; patterns with symbolic
+    /// alternatives like `ANY`, and all unknown patterns, are returned as strings.
+    fn pattern_kind(regex: &str) -> AccessorKind {
+        match regex {
+            r"0|1|true|false" => AccessorKind::Bool,
+            r"(0[xX][0-9a-fA-F]+)|(0[0-7]+)|(0[bB][0-1]+)|(([+\-]?[1-9][0-9]+(\.[0-9]+)?|[+\-]?[0-9](\.[0-9]+)?)([eE]([+\-]?)[0-9]+)?)|\.0|INF|-INF|NaN" => {
+                AccessorKind::Float
+            }
+            r"0|[\+\-]?[1-9][0-9]*|0[xX][0-9a-fA-F]+|0[bB][0-1]+|0[0-7]+" => AccessorKind::SignedInteger,
+            r"0|[\+]?[1-9][0-9]*|0[xX][0-9a-fA-F]+|0[bB][0-1]+|0[0-7]+" | r"[1-9][0-9]*" | r"[0-1]" => {
+                AccessorKind::UnsignedInteger
+            }
+            _ => AccessorKind::String,
+        }
+    }
+
+    fn write_accessor(output: &mut String, method: &str, accessor: &Accessor, all_versions: u32) {
+        let element_name = accessor.element_name.to_str();
+        let location = if accessor.path.is_empty() {
+            format!("`{element_name}`")
+        } else {
+            let path: Vec<&str> = accessor.path.iter().map(ElementName::to_str).collect();
+            format!("`{element_name}` in `{}`", path.join("/"))
+        };
+        let (item_type, conversion) = match accessor.kind {
+            AccessorKind::Wrapper => (type_name(element_name), type_name(element_name)),
+            AccessorKind::Reference => ("crate::Element".to_string(), "support::reference_target".to_string()),
+            AccessorKind::Enum => ("crate::EnumItem".to_string(), "support::enum_value".to_string()),
+            AccessorKind::String => ("String".to_string(), "support::string_value".to_string()),
+            AccessorKind::UnsignedInteger => ("u64".to_string(), "support::integer_value".to_string()),
+            AccessorKind::SignedInteger => ("i64".to_string(), "support::integer_value".to_string()),
+            AccessorKind::Float => ("f64".to_string(), "support::float_value".to_string()),
+            AccessorKind::Bool => ("bool".to_string(), "support::bool_value".to_string()),
+            AccessorKind::CharacterData => (
+                "crate::CharacterData".to_string(),
+                "support::character_data".to_string(),
+            ),
+            AccessorKind::Element => ("crate::Element".to_string(), String::new()),
+        };
+        let description = match accessor.kind {
+            AccessorKind::Reference => format!("the target of the reference {location}"),
+            _ => location,
+        };
+        let versions = describe_versions(accessor.version_mask, all_versions);
+        // elements that are valid in all versions don't need a version check
+        let version_mask = if accessor.version_mask & all_versions == all_versions {
+            "u32::MAX".to_string()
+        } else {
+            format!("0x{:x}", accessor.version_mask)
+        };
+        let path: Vec<String> = accessor
+            .path
+            .iter()
+            .map(|name| format!("ElementName::{}", type_name(name.to_str())))
+            .collect();
+        let args = format!(
+            "&self.0, &[{}], ElementName::{}, {version_mask}",
+            path.join(", "),
+            type_name(element_name),
+        );
+        let (return_type, body) = if accessor.multiple {
+            let body = if accessor.kind == AccessorKind::Wrapper {
+                format!("support::sub_elements({args}).map({conversion})")
+            } else if accessor.kind == AccessorKind::Element {
+                format!("support::sub_elements({args})")
+            } else {
+                format!("support::sub_elements({args}).filter_map({conversion})")
+            };
+            (format!("impl Iterator<Item = {item_type}>"), body)
+        } else {
+            let body = if accessor.kind == AccessorKind::Wrapper {
+                format!("support::sub_element({args}).map({conversion})")
+            } else if accessor.kind == AccessorKind::Element {
+                format!("support::sub_element({args})")
+            } else {
+                format!("support::sub_element({args}).and_then({conversion})")
+            };
+            (format!("::core::option::Option<{item_type}>"), body)
+        };
+        writeln!(output, "    /// Get {description}{versions}").unwrap();
+        writeln!(output, "    pub fn {method}(&self) -> {return_type} {{ {body} }}").unwrap();
+    }
+
+    fn describe_versions(version_mask: u32, all_versions: u32) -> String {
+        if version_mask & all_versions == all_versions {
+            return String::new();
+        }
+        let versions = expand_version_mask(version_mask);
+        let contiguous = (version_mask >> version_mask.trailing_zeros()).count_ones()
+            == 32 - (version_mask >> version_mask.trailing_zeros()).leading_zeros();
+        match versions.as_slice() {
+            [] => String::new(),
+            [version] => format!("\n    ///\n    /// Only valid in {version}"),
+            [first, .., last] if contiguous => format!("\n    ///\n    /// Only valid in {first} to {last}"),
+            _ => {
+                let names: Vec<&str> = versions.iter().map(AutosarVersion::describe).collect();
+                format!("\n    ///\n    /// Only valid in {}", names.join(", "))
+            }
+        }
+    }
+
+    /// `CAN-CLUSTER` -> `CanCluster`, which matches the names of the variants of `ElementName`
+    fn type_name(xml_name: &str) -> String {
+        xml_name
+            .split('-')
+            .map(|part| {
+                let mut chars = part.chars();
+                chars.next().map_or_else(String::new, |first| {
+                    first.to_ascii_uppercase().to_string() + &chars.as_str().to_ascii_lowercase()
+                })
+            })
+            .collect()
+    }
+
+    /// `CAN-CLUSTER` -> `can_cluster`, or `can_cluster_iter` if the element can occur multiple times
+    fn method_name(element_name: ElementName, multiple: bool) -> String {
+        const KEYWORDS: &[&str] = &[
+            "abstract", "as", "async", "await", "become", "box", "break", "const", "continue", "crate", "do", "dyn",
+            "else", "enum", "extern", "false", "final", "fn", "for", "gen", "if", "impl", "in", "let", "loop", "macro",
+            "match", "mod", "move", "mut", "override", "priv", "pub", "ref", "return", "self", "static", "struct",
+            "super", "trait", "true", "try", "type", "typeof", "unsafe", "unsized", "use", "virtual", "where", "while",
+            "yield",
+        ];
+        let name = element_name.to_str().to_ascii_lowercase().replace('-', "_");
+        if multiple {
+            format!("{name}_iter")
+        } else if KEYWORDS.contains(&name.as_str()) {
+            format!("{name}_")
+        } else {
+            name
+        }
+    }
+}
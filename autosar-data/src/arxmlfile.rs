@@ -1,5 +1,6 @@
 use std::hash::Hash;
 
+use crate::serialize::ArxmlWriter;
 use crate::*;
 
 impl ArxmlFile {
@@ -9,10 +10,22 @@ impl ArxmlFile {
             model: model.downgrade(),
             filename: filename.as_ref().to_path_buf(),
             xml_standalone: None,
+            // a new file does not exist on disk yet
+            modified: true,
+            original_text: None,
         }
         .wrap()
     }
 
+    /// lock the file for writing, and record its state in the active transactions
+    ///
+    /// Changes of the version, filename and xml header must use this function, so that they can be reverted by a transaction.
+    pub(crate) fn write_lock(&self) -> parking_lot::RwLockWriteGuard<'_, ArxmlFileRaw> {
+        let file_locked = self.0.write();
+        transaction::record_file(self, &file_locked);
+        file_locked
+    }
+
     /// Get the filename of this `ArxmlFile`
     ///
     /// # Example
@@ -66,14 +79,85 @@ impl ArxmlFile {
     pub fn set_version(&self, new_ver: AutosarVersion) -> Result<(), AutosarDataError> {
         let (compat_errors, _) = self.check_version_compatibility(new_ver);
         if compat_errors.is_empty() {
-            let mut file = self.0.write();
+            let mut file = self.write_lock();
             file.version = new_ver;
+            file.modified = true;
             Ok(())
         } else {
             Err(AutosarDataError::VersionIncompatibleData { version: new_ver })
         }
     }
 
+    /// Migrate the content of the file to a different [`AutosarVersion`], and set the new version
+    ///
+    /// Unlike `set_version()`, this function rewrites content that is not compatible with the new version:
+    ///  - elements and attributes that were renamed between the versions get their new names
+    ///  - enum items are mapped to the corresponding item of the new version
+    ///  - character data and attribute values are converted if their data type was changed
+    ///  - elements and attributes that cannot be represented in the new version are removed
+    ///
+    /// All modifications are returned as a list of [`MigrationChange`]s.
+    /// If the content is still incompatible after the migration, then all modifications are reverted.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use autosar_data::*;
+    /// # fn main() -> Result<(), AutosarDataError> {
+    /// # let model = AutosarModel::new();
+    /// let file = model.create_file("test", AutosarVersion::Autosar_4_3_0)?;
+    /// let changes = file.migrate_version(AutosarVersion::Autosar_00051)?;
+    /// for change in changes {
+    ///     println!("{change:?}");
+    /// }
+    /// assert_eq!(file.version(), AutosarVersion::Autosar_00051);
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    ///  - [`AutosarDataError::ItemDeleted`]: The model is no longer valid
+    ///  - [`AutosarDataError::VersionIncompatibleData`]: The content could not be migrated to the new version
+    pub fn migrate_version(&self, new_ver: AutosarVersion) -> Result<Vec<MigrationChange>, AutosarDataError> {
+        migration::migrate_file(self, new_ver)
+    }
+
+    /// Reload the file from disk and reconcile the model with the new content
+    ///
+    /// The file is parsed again, and the differences to its current content are applied to the model.
+    /// Elements that are unchanged keep their identity, so existing [`Element`] handles remain valid.
+    /// Elements that were removed from the file are deleted, unless they are also part of other files.
+    /// References are updated together with the character data of the reference elements.
+    ///
+    /// Afterwards the file is no longer modified, see [`ArxmlFile::is_modified`].
+    /// Like [`AutosarModel::load_file`], the function returns the warnings of the parser if `strict` is false.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use autosar_data::*;
+    /// # fn main() -> Result<(), AutosarDataError> {
+    /// let model = AutosarModel::new();
+    /// let (file, _) = model.load_file("filename.arxml", true)?;
+    /// let element = model.get_element_by_path("/Pkg").unwrap();
+    /// // the file is changed by another program
+    /// file.reload(true)?;
+    /// // the element still exists, unless the package was removed from the file
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    ///  - [`AutosarDataError::ItemDeleted`]: The model is no longer valid, or the file was removed from the model
+    ///  - [`AutosarDataError::IoErrorRead`]: There was an error while reading the file
+    ///  - [`AutosarDataError::ParserError`]: The parser detected an error; the source field gives further details
+    ///  - Any error that is returned while modifying the model. In this case the model is not changed.
+    pub fn reload(&self, strict: bool) -> Result<Vec<AutosarDataError>, AutosarDataError> {
+        reload::reload_file(self, strict)
+    }
+
     /// Check if the elements and attributes in this file are compatible with some `target_version`
     ///
     /// All elements and their attributes will be evaluated against the target version according to the specification.
@@ -126,7 +210,10 @@ impl ArxmlFile {
                 filename: new_filename,
             })
         } else {
-            self.0.write().filename = new_filename;
+            let mut file = self.write_lock();
+            file.filename = new_filename;
+            // there is no file with the new name on disk yet
+            file.modified = true;
             Ok(())
         }
     }
@@ -215,24 +302,135 @@ impl ArxmlFile {
     /// [`AutosarDataError::ItemDeleted`]: The model is no longer valid
     /// [`AutosarDataError::EmptyFile`]: The file is empty and cannot be serialized
     pub fn serialize(&self) -> Result<String, AutosarDataError> {
+        self.serialize_with_options(&SerializeOptions::default())
+    }
+
+    /// Serialize the content of the file to a String, using the given formatting options
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use autosar_data::*;
+    /// # fn main() -> Result<(), AutosarDataError> {
+    /// # let model = AutosarModel::new();
+    /// # let file = model.create_file("test", AutosarVersion::Autosar_00050)?;
+    /// let options = SerializeOptions {
+    ///     indentation: Indentation::Spaces(4),
+    ///     empty_elements: EmptyElementStyle::StartEndTags,
+    ///     ..Default::default()
+    /// };
+    /// let text = file.serialize_with_options(&options)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// [`AutosarDataError::ItemDeleted`]: The model is no longer valid
+    /// [`AutosarDataError::EmptyFile`]: The file is empty and cannot be serialized
+    pub fn serialize_with_options(&self, options: &SerializeOptions) -> Result<String, AutosarDataError> {
+        let mut outstring = String::with_capacity(1024 * 1024);
+        self.serialize_internal(&mut outstring, options)?;
+
+        Ok(outstring)
+    }
+
+    /// Serialize the content of the file and write it to `writer`
+    ///
+    /// Unlike [`ArxmlFile::serialize`], this function does not build the whole text in memory.
+    /// The text is written to the writer in chunks as it is generated, and it is identical to the output of `serialize`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use autosar_data::*;
+    /// # fn main() -> Result<(), AutosarDataError> {
+    /// # let model = AutosarModel::new();
+    /// # let file = model.create_file("test", AutosarVersion::Autosar_00050)?;
+    /// let mut buffer = Vec::new();
+    /// file.write_to(&mut buffer)?;
+    /// assert_eq!(buffer, file.serialize()?.into_bytes());
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// [`AutosarDataError::ItemDeleted`]: The model is no longer valid
+    /// [`AutosarDataError::EmptyFile`]: The file is empty and cannot be serialized
+    /// [`AutosarDataError::IoErrorWrite`]: The data could not be written
+    pub fn write_to<W: std::io::Write>(&self, writer: W) -> Result<(), AutosarDataError> {
+        self.write_to_with_options(writer, &SerializeOptions::default())
+    }
+
+    /// Serialize the content of the file and write it to `writer`, using the given formatting options
+    ///
+    /// The output is identical to the output of [`ArxmlFile::serialize_with_options`] with the same options.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use autosar_data::*;
+    /// # fn main() -> Result<(), AutosarDataError> {
+    /// # let model = AutosarModel::new();
+    /// # let file = model.create_file("test", AutosarVersion::Autosar_00050)?;
+    /// let options = SerializeOptions {
+    ///     line_ending: LineEnding::CrLf,
+    ///     ..Default::default()
+    /// };
+    /// let mut buffer = Vec::new();
+    /// file.write_to_with_options(&mut buffer, &options)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// [`AutosarDataError::ItemDeleted`]: The model is no longer valid
+    /// [`AutosarDataError::EmptyFile`]: The file is empty and cannot be serialized
+    /// [`AutosarDataError::IoErrorWrite`]: The data could not be written
+    pub fn write_to_with_options<W: std::io::Write>(
+        &self,
+        writer: W,
+        options: &SerializeOptions,
+    ) -> Result<(), AutosarDataError> {
+        let mut output = ArxmlWriter::new(writer);
+        self.serialize_internal(&mut output, options)?;
+        output.finish().map_err(|ioerror| AutosarDataError::IoErrorWrite {
+            filename: self.filename(),
+            ioerror,
+        })
+    }
+
+    fn serialize_internal<O: SerializeOutput>(
+        &self,
+        output: &mut O,
+        options: &SerializeOptions,
+    ) -> Result<(), AutosarDataError> {
         let model = self.model()?;
         if !model.root_element().file_membership()?.1.contains(&self.downgrade()) {
             return Err(AutosarDataError::EmptyFile);
         }
 
-        let mut outstring = String::with_capacity(1024 * 1024);
-
-        match self.xml_standalone() {
-            Some(true) => outstring.push_str("<?xml version=\"1.0\" encoding=\"utf-8\" standalone=\"yes\"?>"),
-            Some(false) => outstring.push_str("<?xml version=\"1.0\" encoding=\"utf-8\" standalone=\"no\"?>"),
-            None => outstring.push_str("<?xml version=\"1.0\" encoding=\"utf-8\"?>"),
-        }
         model.0.write().set_version(self.0.read().version);
-        model
-            .root_element()
-            .serialize_internal(&mut outstring, 0, false, &Some(self.downgrade()));
+        let root_element = model.root_element();
+        let for_file = Some(self.downgrade());
+        let original = self.0.read().original_text.clone();
+        if let Some(original) = original.as_deref()
+            && original.standalone == self.xml_standalone()
+            && original.root_comment == root_element.comment()
+        {
+            // the prolog and epilog of a file loaded in lossless mode are written unchanged
+            output.push_str(original.text(&original.prolog));
+            root_element.serialize_body(output, 0, &for_file, options, Some(original));
+            output.push_str(original.text(&original.epilog));
+        } else {
+            let has_declaration = options.write_xml_declaration(output, self.xml_standalone());
+            // without a declaration, the root element is not preceded by a line break
+            root_element.serialize_formatted(output, 0, !has_declaration, &for_file, options, original.as_deref());
+        }
 
-        Ok(outstring)
+        Ok(())
     }
 
     /// Return the standalone attribute from the xml header
@@ -258,6 +456,34 @@ impl ArxmlFile {
         self.0.read().xml_standalone
     }
 
+    /// Check if the file was modified since it was loaded or written
+    ///
+    /// A file is modified if any element it contains was changed through the methods of [`Element`], or if its
+    /// version or filename was changed. Newly created files are always modified, since they do not exist on disk yet.
+    /// The flag is set directly by each modification, independent of any observers, and it is cleared when the file is
+    /// written by [`AutosarModel::write`], [`AutosarModel::write_modified`] or [`AutosarModel::write_modified_with_options`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use autosar_data::*;
+    /// # fn main() -> Result<(), AutosarDataError> {
+    /// let model = AutosarModel::new();
+    /// # let file_text = r#"<?xml version="1.0" encoding="utf-8"?>
+    /// # <AUTOSAR xsi:schemaLocation="http://autosar.org/schema/r4.0 AUTOSAR_00050.xsd" xmlns="http://autosar.org/schema/r4.0" xmlns:xsi="http://www.w3.org/2001/XMLSchema-instance">
+    /// # </AUTOSAR>"#.as_bytes();
+    /// let (file, _) = model.load_buffer(file_text, "filename.arxml", true)?;
+    /// assert!(!file.is_modified());
+    /// model.root_element().create_sub_element(ElementName::ArPackages)?;
+    /// assert!(file.is_modified());
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[must_use]
+    pub fn is_modified(&self) -> bool {
+        self.0.read().modified
+    }
+
     /// Create a weak reference to this `ArxmlFile`
     ///
     /// A weak reference can be stored without preventing the file from being deallocated.
@@ -389,6 +615,85 @@ mod test {
         assert_eq!(file.version(), AutosarVersion::Autosar_4_0_1);
     }
 
+    #[test]
+    fn modified() {
+        const FILEBUF: &[u8] = r#"<?xml version="1.0" encoding="utf-8"?>
+<AUTOSAR xsi:schemaLocation="http://autosar.org/schema/r4.0 AUTOSAR_00050.xsd" xmlns="http://autosar.org/schema/r4.0" xmlns:xsi="http://www.w3.org/2001/XMLSchema-instance">
+  <AR-PACKAGES>
+    <AR-PACKAGE>
+      <SHORT-NAME>Pkg</SHORT-NAME>
+      <ELEMENTS>
+        <SYSTEM><SHORT-NAME>System</SHORT-NAME></SYSTEM>
+        <I-SIGNAL><SHORT-NAME>Sig</SHORT-NAME></I-SIGNAL>
+      </ELEMENTS>
+    </AR-PACKAGE>
+  </AR-PACKAGES>
+</AUTOSAR>"#.as_bytes();
+        let load = || {
+            let model = AutosarModel::new();
+            let (file, _) = model.load_buffer(FILEBUF, "test", true).unwrap();
+            assert!(!file.is_modified());
+            (model, file)
+        };
+
+        let (model, file) = load();
+        let system = model.get_element_by_path("/Pkg/System").unwrap();
+        system.set_attribute(AttributeName::Uuid, "1234").unwrap();
+        assert!(file.is_modified());
+
+        let (model, file) = load();
+        model
+            .get_element_by_path("/Pkg/System")
+            .and_then(|system| system.get_sub_element(ElementName::ShortName))
+            .unwrap()
+            .set_character_data("Renamed")
+            .unwrap();
+        assert!(file.is_modified());
+
+        let (model, file) = load();
+        model.root_element().sort();
+        assert!(file.is_modified());
+
+        let (model, file) = load();
+        model
+            .get_element_by_path("/Pkg/Sig")
+            .unwrap()
+            .set_comment(Some("text".to_string()));
+        assert!(file.is_modified());
+
+        let (model, file) = load();
+        let elements = model
+            .get_element_by_path("/Pkg/System")
+            .unwrap()
+            .parent()
+            .unwrap()
+            .unwrap();
+        let signal = model.get_element_by_path("/Pkg/Sig").unwrap();
+        elements.remove_sub_element(signal).unwrap();
+        assert!(file.is_modified());
+
+        // changes to the file itself
+        let (_model, file) = load();
+        file.set_filename("other").unwrap();
+        assert!(file.is_modified());
+
+        let (_model, file) = load();
+        file.set_version(AutosarVersion::Autosar_00051).unwrap();
+        assert!(file.is_modified());
+
+        // removing an element from a file modifies that file, but not the other one
+        let (model, file) = load();
+        let file2 = model.create_file("test2", AutosarVersion::Autosar_00050).unwrap();
+        file2.0.write().modified = false;
+        model
+            .get_element_by_path("/Pkg/Sig")
+            .unwrap()
+            .remove_from_file(&file)
+            .unwrap();
+        assert!(file.is_modified());
+        assert!(!file2.is_modified());
+    }
+
     #[test]
     fn references() {
         let model = AutosarModel::new();
@@ -438,6 +743,59 @@ mod test {
         );
     }
 
+    #[test]
+    fn write_to() {
+        const FILEBUF: &[u8] = r#"<?xml version="1.0" encoding="utf-8" standalone="no"?>
+<AUTOSAR xsi:schemaLocation="http://autosar.org/schema/r4.0 AUTOSAR_00050.xsd" xmlns="http://autosar.org/schema/r4.0" xmlns:xsi="http://www.w3.org/2001/XMLSchema-instance">
+  <AR-PACKAGES>
+    <!--comment-->
+    <AR-PACKAGE UUID="1234">
+      <SHORT-NAME>Pkg</SHORT-NAME>
+      <DESC><L-2 L="EN">text &amp; <BR/>more &lt;text&gt;</L-2></DESC>
+      <ELEMENTS>
+        <SYSTEM><SHORT-NAME>System</SHORT-NAME></SYSTEM>
+      </ELEMENTS>
+    </AR-PACKAGE>
+  </AR-PACKAGES>
+</AUTOSAR>"#.as_bytes();
+        let model = AutosarModel::new();
+        let (file, _) = model.load_buffer(FILEBUF, "test", true).unwrap();
+        let file2 = model.create_file("test2", AutosarVersion::Autosar_00050).unwrap();
+        model
+            .get_element_by_path("/Pkg")
+            .unwrap()
+            .remove_from_file(&file2)
+            .unwrap();
+
+        // the streamed output is identical to the serialized string
+        let mut buffer = Vec::new();
+        file.write_to(&mut buffer).unwrap();
+        assert_eq!(buffer, file.serialize().unwrap().into_bytes());
+        let mut buffer = Vec::new();
+        file2.write_to(&mut buffer).unwrap();
+        assert_eq!(buffer, file2.serialize().unwrap().into_bytes());
+
+        // errors of the writer are reported
+        struct FailingWriter;
+        impl std::io::Write for FailingWriter {
+            fn write(&mut self, _buf: &[u8]) -> std::io::Result<usize> {
+                Err(std::io::Error::other("failed"))
+            }
+            fn flush(&mut self) -> std::io::Result<()> {
+                Ok(())
+            }
+        }
+        let result = file.write_to(FailingWriter);
+        assert!(matches!(result, Err(AutosarDataError::IoErrorWrite { .. })));
+
+        // empty files cannot be written
+        let mut membership = HashSet::new();
+        membership.insert(file.downgrade());
+        model.root_element().0.write().file_membership = membership;
+        let result = file2.write_to(Vec::new());
+        assert!(matches!(result, Err(AutosarDataError::EmptyFile)));
+    }
+
     #[test]
     fn elements_dfs_iterator() {
         const FILEBUF_1: &[u8] = r#"<?xml version="1.0" encoding="utf-8"?>
@@ -603,6 +961,7 @@ mod test {
     }
 
     #[test]
+    #[allow(clippy::mutable_key_type)]
     fn traits() {
         let model = AutosarModel::new();
         let file = model.create_file("filename", AutosarVersion::LATEST).unwrap();
@@ -1,6 +1,7 @@
 use std::{
     collections::{HashMap, VecDeque},
     hash::Hash,
+    sync::atomic::{AtomicUsize, Ordering},
 };
 
 use crate::*;
@@ -14,6 +15,22 @@ enum MergeAction {
     BOnly(usize),
 }
 
+// the result of parsing a buffer, before it is added to a model
+//
+// Parsing does not depend on the model, so this can be done in parallel for multiple files
+struct ParsedFile {
+    filename: PathBuf,
+    root_element: Element,
+    version: AutosarVersion,
+    xml_standalone: Option<bool>,
+    root_comment: Option<String>,
+    identifiables: Vec<(String, WeakElement)>,
+    references: Vec<(String, WeakElement, Option<String>)>,
+    reference_bases: Vec<(String, ReferenceBaseInfo)>,
+    warnings: Vec<AutosarDataError>,
+    original_text: Option<lossless::OriginalText>,
+}
+
 impl AutosarModel {
     /// Create an `AutosarData` model
     ///
@@ -54,16 +71,20 @@ impl AutosarModel {
             content: SmallVec::new(),
             attributes: root_attributes,
             file_membership: HashSet::with_capacity(0),
-            comment: None,
+            source_location: None,
+            modified: false,
         }
         .wrap();
         let model = AutosarModelRaw {
             files: Vec::new(),
-            identifiables: FxIndexMap::default(),
-            reference_origins: FxHashMap::default(),
-            relative_reference_origins: FxHashMap::default(),
-            reference_bases: FxHashMap::default(),
+            root_comment: None,
+            identifiables: transaction::TrackedIndex::default(),
+            reference_origins: transaction::TrackedIndex::default(),
+            relative_reference_origins: transaction::TrackedIndex::default(),
+            reference_bases: transaction::TrackedIndex::default(),
             root_element: root_elem.clone(),
+            history: transaction::ModelHistory::default(),
+            observers: observer::ObserverList::default(),
         }
         .wrap();
         root_elem.set_parent(ElementOrModel::Model(model.downgrade()));
@@ -113,6 +134,7 @@ impl AutosarModel {
 
         let new_file = ArxmlFile::new(filename, version, self);
 
+        data.record_file_list();
         data.files.push(new_file.clone());
 
         // every file contains the root element (but not its children)
@@ -157,34 +179,101 @@ impl AutosarModel {
         filename: P,
         strict: bool,
     ) -> Result<(ArxmlFile, Vec<AutosarDataError>), AutosarDataError> {
-        self.load_buffer_internal(buffer, filename.as_ref().to_path_buf(), strict)
+        self.load_buffer_internal(buffer, filename.as_ref().to_path_buf(), strict_options(strict))
+    }
+
+    /// Load a named buffer containing arxml data, using the given [`LoadOptions`]
+    ///
+    /// This works like [`AutosarModel::load_buffer`], but it can also load the data in lossless mode.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use autosar_data::*;
+    /// # fn main() -> Result<(), AutosarDataError> {
+    /// let model = AutosarModel::new();
+    /// # let buffer = b"";
+    /// let options = LoadOptions {
+    ///     lossless: true,
+    ///     ..Default::default()
+    /// };
+    /// model.load_buffer_with_options(buffer, "filename.arxml", &options)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    ///  - [`AutosarDataError::DuplicateFilenameError`]: The model already contains a file with this filename
+    ///  - [`AutosarDataError::OverlappingDataError`]: The new data contains Autosar paths that are already defined by the existing data
+    ///  - [`AutosarDataError::ParserError`]: The parser detected an error; the source field gives further details
+    ///
+    pub fn load_buffer_with_options<P: AsRef<Path>>(
+        &self,
+        buffer: &[u8],
+        filename: P,
+        options: &LoadOptions,
+    ) -> Result<(ArxmlFile, Vec<AutosarDataError>), AutosarDataError> {
+        self.load_buffer_internal(buffer, filename.as_ref().to_path_buf(), *options)
     }
 
     fn load_buffer_internal(
         &self,
         buffer: &[u8],
         filename: PathBuf,
-        strict: bool,
+        options: LoadOptions,
     ) -> Result<(ArxmlFile, Vec<AutosarDataError>), AutosarDataError> {
+        // reject a duplicate filename before spending time on parsing
+        if self.files().any(|file| file.filename() == filename) {
+            return Err(AutosarDataError::DuplicateFilenameError { verb: "load", filename });
+        }
+
+        let parsed_file = ParsedFile::parse(buffer, filename, options)?;
+        self.import_parsed_file(parsed_file)
+    }
+
+    // add the data of a parsed file to the model
+    //
+    // The parsed root element either becomes the root of the model, or it is merged into the existing data
+    fn import_parsed_file(
+        &self,
+        parsed_file: ParsedFile,
+    ) -> Result<(ArxmlFile, Vec<AutosarDataError>), AutosarDataError> {
+        let ParsedFile {
+            filename,
+            root_element,
+            version,
+            xml_standalone,
+            root_comment,
+            identifiables,
+            references,
+            reference_bases,
+            warnings,
+            original_text,
+        } = parsed_file;
+
+        // load_files parses all files before importing them, so duplicates within its input are only detected here
         if self.files().any(|file| file.filename() == filename) {
             return Err(AutosarDataError::DuplicateFilenameError { verb: "load", filename });
         }
 
-        let mut parser = ArxmlParser::new(filename.clone(), buffer, strict);
-        let root_element = parser.parse_arxml()?;
-        let version = parser.get_fileversion();
         let arxml_file = ArxmlFileRaw {
             version,
             model: self.downgrade(),
             filename: filename.clone(),
-            xml_standalone: parser.get_standalone(),
+            xml_standalone,
+            modified: false,
+            original_text: original_text.map(Arc::new),
         }
         .wrap();
 
         if self.0.read().files.is_empty() {
             root_element.set_parent(ElementOrModel::Model(self.downgrade()));
-            root_element.0.write().file_membership.insert(arxml_file.downgrade());
-            self.0.write().root_element = root_element;
+            root_element.write_lock().file_membership.insert(arxml_file.downgrade());
+            let mut data = self.0.write();
+            data.record_file_list();
+            data.root_element = root_element;
+            data.root_comment = root_comment;
         } else {
             let result = self.merge_file_data(&root_element, arxml_file.downgrade());
             if let Err(error) = result {
@@ -195,8 +284,8 @@ impl AutosarModel {
 
         let mut data = self.0.write();
         // import identifiables from the parser, check for conflicts with existing data
-        data.identifiables.reserve(parser.identifiables.len());
-        for (key, value) in parser.identifiables {
+        data.identifiables.reserve(identifiables.len());
+        for (key, value) in identifiables {
             // the same identifiables can be present in multiple files
             // in this case we only keep the first one
             if let Some(existing_element) = data.identifiables.get(&key).and_then(WeakElement::upgrade) {
@@ -216,8 +305,8 @@ impl AutosarModel {
         }
 
         // import references from the parser
-        data.reference_origins.reserve(parser.references.len());
-        for (refpath, referring_element, base) in parser.references {
+        data.reference_origins.reserve(references.len());
+        for (refpath, referring_element, base) in references {
             if let Some(base_label) = base {
                 //relative reference
                 if let Some(xref) = data.relative_reference_origins.get_mut(&refpath) {
@@ -237,8 +326,8 @@ impl AutosarModel {
         }
 
         // import reference bases from the parser
-        data.reference_bases.reserve(parser.reference_bases.len());
-        for (base_key, base_info) in parser.reference_bases {
+        data.reference_bases.reserve(reference_bases.len());
+        for (base_key, base_info) in reference_bases {
             if let Some(existing_base) = data.reference_bases.get_mut(&base_key) {
                 existing_base.push(base_info);
             } else {
@@ -246,9 +335,10 @@ impl AutosarModel {
             }
         }
 
+        data.record_file_list();
         data.files.push(arxml_file.clone());
 
-        Ok((arxml_file, parser.warnings))
+        Ok((arxml_file, warnings))
     }
 
     // Merge the elements from an incoming arxml file into the overall model
@@ -274,7 +364,7 @@ impl AutosarModel {
             }
         })?;
 
-        self.root_element().0.write().file_membership.insert(new_file);
+        self.root_element().write_lock().file_membership.insert(new_file);
 
         Ok(())
     }
@@ -376,7 +466,7 @@ impl AutosarModel {
         // elements in elements_a_only are already present in the model, so they only need to be restricted
         for element in elements_a_only {
             // files contains the permisions of the parent
-            let mut elem_locked = element.0.write();
+            let mut elem_locked = element.write_lock();
             if elem_locked.file_membership.is_empty() {
                 files.clone_into(&mut elem_locked.file_membership);
             }
@@ -498,12 +588,12 @@ impl AutosarModel {
         new_file: &WeakArxmlFile,
         version: AutosarVersion,
     ) -> Result<(), AutosarDataError> {
-        let mut parent_a_locked = parent_a.0.write();
+        let mut parent_a_locked = parent_a.write_lock();
         let weak_parent_a = parent_a.downgrade();
 
         new_element.set_parent(ElementOrModel::Element(weak_parent_a));
         // restrict new_element, it is only present in new_file
-        new_element.0.write().file_membership.insert(new_file.clone());
+        new_element.write_lock().file_membership.insert(new_file.clone());
 
         // add the new_element (from side b) to the content of parent_a
         // to do this, first check valid element insertion positions
@@ -516,6 +606,8 @@ impl AutosarModel {
         parent_a_locked
             .content
             .insert(dest, ElementContent::Element(new_element));
+        // the original text of parent_a does not contain the new element
+        parent_a_locked.mark_modified();
 
         Ok(())
     }
@@ -540,7 +632,7 @@ impl AutosarModel {
             match result {
                 Ok(()) => {
                     // update the file membership of the merged element, if there was any
-                    let mut elem_a_locked = elem_a.0.write();
+                    let mut elem_a_locked = elem_a.write_lock();
                     if !elem_a_locked.file_membership.is_empty() {
                         elem_a_locked.file_membership.insert(new_file.clone());
                     }
@@ -610,6 +702,221 @@ impl AutosarModel {
         self.load_buffer(&buffer, &filename_buf, strict)
     }
 
+    /// Load an arxml file, using the given [`LoadOptions`]
+    ///
+    /// This works like [`AutosarModel::load_file`], but it can also load the file in lossless mode.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use autosar_data::*;
+    /// # fn main() -> Result<(), AutosarDataError> {
+    /// let model = AutosarModel::new();
+    /// let options = LoadOptions {
+    ///     lossless: true,
+    ///     ..Default::default()
+    /// };
+    /// model.load_file_with_options("filename.arxml", &options)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    ///  - [`AutosarDataError::IoErrorRead`]: There was an error while reading the file
+    ///  - [`AutosarDataError::DuplicateFilenameError`]: The model already contains a file with this filename
+    ///  - [`AutosarDataError::OverlappingDataError`]: The new data contains Autosar paths that are already defined by the existing data
+    ///  - [`AutosarDataError::ParserError`]: The parser detected an error; the source field gives further details
+    ///
+    pub fn load_file_with_options<P: AsRef<Path>>(
+        &self,
+        filename: P,
+        options: &LoadOptions,
+    ) -> Result<(ArxmlFile, Vec<AutosarDataError>), AutosarDataError> {
+        let filename_buf = filename.as_ref().to_path_buf();
+        let buffer = std::fs::read(&filename_buf).map_err(|err| AutosarDataError::IoErrorRead {
+            filename: filename_buf.clone(),
+            ioerror: err,
+        })?;
+
+        self.load_buffer_internal(&buffer, filename_buf, *options)
+    }
+
+    /// Load arxml data from a reader
+    ///
+    /// This function reads all data from `reader`, e.g. from stdin or from an entry of an archive, and then loads it like `load_buffer`.
+    ///
+    /// # Parameters:
+    ///
+    ///  - `reader`: the source of the data. It must provide valid utf-8, optionally starting with a UTF-8-BOM.
+    ///  - `filename`: the original filename of the data, or a newly generated name that is unique within the `AutosarData` instance.
+    ///  - `strict`: toggle strict parsing. Some parsing errors are recoverable and can be issued as warnings.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use autosar_data::*;
+    /// # fn main() -> Result<(), AutosarDataError> {
+    /// let model = AutosarModel::new();
+    /// model.load_reader(std::io::stdin(), "stdin.arxml", true)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    ///  - [`AutosarDataError::IoErrorRead`]: There was an error while reading the data
+    ///  - [`AutosarDataError::DuplicateFilenameError`]: The model already contains a file with this filename
+    ///  - [`AutosarDataError::OverlappingDataError`]: The new data contains Autosar paths that are already defined by the existing data
+    ///  - [`AutosarDataError::ParserError`]: The parser detected an error; the source field gives further details
+    ///
+    pub fn load_reader<R: std::io::Read, P: AsRef<Path>>(
+        &self,
+        mut reader: R,
+        filename: P,
+        strict: bool,
+    ) -> Result<(ArxmlFile, Vec<AutosarDataError>), AutosarDataError> {
+        let filename_buf = filename.as_ref().to_path_buf();
+        let mut buffer = Vec::new();
+        reader
+            .read_to_end(&mut buffer)
+            .map_err(|err| AutosarDataError::IoErrorRead {
+                filename: filename_buf.clone(),
+                ioerror: err,
+            })?;
+
+        self.load_buffer_internal(&buffer, filename_buf, strict_options(strict))
+    }
+
+    /// Load an arxml file through a memory map
+    ///
+    /// This works like `load_file`, but the content of the file is mapped into memory instead of being copied into a buffer.
+    /// This avoids an extra copy of large files before they are parsed.
+    ///
+    /// This function is only available if the feature `mmap` is enabled.
+    ///
+    /// The file must not be modified by other processes while it is being loaded.
+    /// Once this function returns, the memory map has been released and the model no longer depends on the file.
+    ///
+    /// # Parameters:
+    ///
+    ///  - `filename`: the original filename of the data, or a newly generated name that is unique within the `AutosarData` instance.
+    ///  - `strict`: toggle strict parsing. Some parsing errors are recoverable and can be issued as warnings.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use autosar_data::*;
+    /// # fn main() -> Result<(), AutosarDataError> {
+    /// let model = AutosarModel::new();
+    /// model.load_file_mmap("filename.arxml", true)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    ///  - [`AutosarDataError::IoErrorRead`]: There was an error while opening or mapping the file
+    ///  - [`AutosarDataError::DuplicateFilenameError`]: The model already contains a file with this filename
+    ///  - [`AutosarDataError::OverlappingDataError`]: The new data contains Autosar paths that are already defined by the existing data
+    ///  - [`AutosarDataError::ParserError`]: The parser detected an error; the source field gives further details
+    ///
+    #[cfg(feature = "mmap")]
+    pub fn load_file_mmap<P: AsRef<Path>>(
+        &self,
+        filename: P,
+        strict: bool,
+    ) -> Result<(ArxmlFile, Vec<AutosarDataError>), AutosarDataError> {
+        let filename_buf = filename.as_ref().to_path_buf();
+        let map_err = |err| AutosarDataError::IoErrorRead {
+            filename: filename_buf.clone(),
+            ioerror: err,
+        };
+        let file = File::open(&filename_buf).map_err(map_err)?;
+        // SAFETY: the map is only read while parsing, and it is dropped before this function returns.
+        // Concurrent modification of the file by another process is documented as unsupported.
+        let mmap = unsafe { memmap2::Mmap::map(&file) }.map_err(map_err)?;
+
+        self.load_buffer_internal(&mmap, filename_buf, strict_options(strict))
+    }
+
+    /// Load multiple arxml files
+    ///
+    /// The files are read and parsed in parallel on several threads. Afterwards the parsed data is
+    /// merged into the model sequentially, in the order of the given filenames.
+    ///
+    /// Each file is handled as if it had been loaded with `load_file`, so loading one file may fail while others succeed.
+    /// The returned `Vec` contains one result per filename, in the same order as the input.
+    ///
+    /// # Parameters:
+    ///
+    ///  - `filenames`: the names of the files to load. Each of them must be unique within the `AutosarData` instance.
+    ///  - `strict`: toggle strict parsing. Some parsing errors are recoverable and can be issued as warnings.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use autosar_data::*;
+    /// # fn main() -> Result<(), AutosarDataError> {
+    /// let model = AutosarModel::new();
+    /// for result in model.load_files(&["file1.arxml", "file2.arxml"], true) {
+    ///     let (file, warnings) = result?;
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Each individual result may contain one of these errors:
+    ///
+    ///  - [`AutosarDataError::IoErrorRead`]: There was an error while reading the file
+    ///  - [`AutosarDataError::DuplicateFilenameError`]: The model already contains a file with this filename
+    ///  - [`AutosarDataError::OverlappingDataError`]: The new data contains Autosar paths that are already defined by the existing data
+    ///  - [`AutosarDataError::ParserError`]: The parser detected an error; the source field gives further details
+    ///
+    pub fn load_files<P: AsRef<Path>>(
+        &self,
+        filenames: &[P],
+        strict: bool,
+    ) -> Vec<Result<(ArxmlFile, Vec<AutosarDataError>), AutosarDataError>> {
+        let filenames: Vec<PathBuf> = filenames.iter().map(|f| f.as_ref().to_path_buf()).collect();
+        let num_threads = std::thread::available_parallelism()
+            .map_or(1, std::num::NonZeroUsize::get)
+            .min(filenames.len());
+        let next_index = AtomicUsize::new(0);
+
+        // read and parse all files in parallel; each worker repeatedly takes the next unprocessed file
+        let mut parse_results: Vec<(usize, Result<ParsedFile, AutosarDataError>)> = std::thread::scope(|scope| {
+            let workers: Vec<_> = (0..num_threads)
+                .map(|_| {
+                    scope.spawn(|| {
+                        let mut results = Vec::new();
+                        loop {
+                            let idx = next_index.fetch_add(1, Ordering::Relaxed);
+                            let Some(filename) = filenames.get(idx) else {
+                                break;
+                            };
+                            results.push((idx, ParsedFile::read_and_parse(filename.clone(), strict)));
+                        }
+                        results
+                    })
+                })
+                .collect();
+            workers
+                .into_iter()
+                .flat_map(|worker| worker.join().expect("arxml parser thread panicked"))
+                .collect()
+        });
+        parse_results.sort_by_key(|(idx, _)| *idx);
+
+        // merge the parsed files into the model in the original order
+        parse_results
+            .into_iter()
+            .map(|(_, parse_result)| self.import_parsed_file(parse_result?))
+            .collect()
+    }
+
     /// remove a file from the model
     ///
     /// # Parameters:
@@ -637,10 +944,11 @@ impl AutosarModel {
             .map(|(pos, _)| pos);
         // find_result is stored first so that the lock on model is dropped
         if let Some(pos) = find_result {
+            locked_model.record_file_list();
             locked_model.files.swap_remove(pos);
             if locked_model.files.is_empty() {
                 // no other files remain in the model, so it reverts to being empty
-                locked_model.root_element.0.write().content.clear();
+                locked_model.root_element.write_lock().content.clear();
                 locked_model.root_element.set_file_membership(HashSet::new());
                 locked_model.identifiables.clear();
                 locked_model.reference_origins.clear();
@@ -684,10 +992,13 @@ impl AutosarModel {
 
     /// write all files in the model
     ///
-    /// This is a wrapper around `serialize_files`. The current filename of each file will be used to write the serialized data.
+    /// Each file is written with [`ArxmlFile::write_to`], so the serialized data is streamed to disk without building it in memory.
+    /// The current filename of each file will be used to write the serialized data. Empty files are skipped.
+    /// Use [`AutosarModel::write_modified`] to only write the files that were changed.
     ///
-    /// If any of the individual files cannot be written, then `write()` will abort and return the error.
-    /// This may result in a situation where some files have been written and others have not.
+    /// The write is crash-safe: all files are first serialized to temporary files in the same directories as their targets,
+    /// and then the temporary files are renamed into place. If any of the individual files cannot be written or renamed,
+    /// then `write()` restores the original files and returns the error.
     ///
     /// # Example
     ///
@@ -705,11 +1016,101 @@ impl AutosarModel {
     ///
     ///  - [`AutosarDataError::IoErrorWrite`]: There was an error while writing a file
     pub fn write(&self) -> Result<(), AutosarDataError> {
-        for (pathbuf, filedata) in self.serialize_files() {
-            std::fs::write(pathbuf.clone(), filedata).map_err(|err| AutosarDataError::IoErrorWrite {
-                filename: pathbuf,
-                ioerror: err,
-            })?;
+        self.write_with_options(&SerializeOptions::default())
+    }
+
+    /// write all files in the model, using the given formatting options
+    ///
+    /// This works like [`AutosarModel::write`], but the files are formatted according to `options`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use autosar_data::*;
+    /// # fn main() -> Result<(), AutosarDataError> {
+    /// let model = AutosarModel::new();
+    /// // load or create files
+    /// let options = SerializeOptions {
+    ///     indentation: Indentation::Tabs,
+    ///     ..Default::default()
+    /// };
+    /// model.write_with_options(&options)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    ///  - [`AutosarDataError::IoErrorWrite`]: There was an error while writing a file
+    pub fn write_with_options(&self, options: &SerializeOptions) -> Result<(), AutosarDataError> {
+        self.write_files(options, false)
+    }
+
+    /// write all files in the model that were modified since they were loaded or last written
+    ///
+    /// Files that are unchanged are not touched, so that their timestamps are preserved.
+    /// See [`ArxmlFile::is_modified`] for the details of which changes are tracked.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use autosar_data::*;
+    /// # fn main() -> Result<(), AutosarDataError> {
+    /// let model = AutosarModel::new();
+    /// // load or create files
+    /// model.write_modified()?;
+    /// assert!(model.files().all(|file| !file.is_modified()));
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    ///  - [`AutosarDataError::IoErrorWrite`]: There was an error while writing a file
+    pub fn write_modified(&self) -> Result<(), AutosarDataError> {
+        self.write_files(&SerializeOptions::default(), true)
+    }
+
+    /// write all files in the model that were modified since they were loaded or last written, using the given [`SerializeOptions`]
+    ///
+    /// This works like [`AutosarModel::write_modified`], but the options control the formatting of the files.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use autosar_data::*;
+    /// # fn main() -> Result<(), AutosarDataError> {
+    /// let model = AutosarModel::new();
+    /// // load or create files
+    /// let options = SerializeOptions {
+    ///     indentation: Indentation::Tabs,
+    ///     ..Default::default()
+    /// };
+    /// model.write_modified_with_options(&options)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    ///  - [`AutosarDataError::IoErrorWrite`]: There was an error while writing a file
+    pub fn write_modified_with_options(&self, options: &SerializeOptions) -> Result<(), AutosarDataError> {
+        self.write_files(options, true)
+    }
+
+    fn write_files(&self, options: &SerializeOptions, only_modified: bool) -> Result<(), AutosarDataError> {
+        let used_files = self
+            .root_element()
+            .file_membership()
+            .map(|(_, files)| files)
+            .unwrap_or_default();
+        let files: Vec<ArxmlFile> = self
+            .files()
+            .filter(|file| used_files.contains(&file.downgrade()) && (!only_modified || file.is_modified()))
+            .collect();
+        filewriter::write_files(&files, options)?;
+        for file in files {
+            file.0.write().modified = false;
         }
         Ok(())
     }
@@ -810,7 +1211,7 @@ impl AutosarModel {
         for orig_file in self.files() {
             let filename = orig_file.filename();
             let new_file = copy.create_file(filename.clone(), orig_file.version())?;
-            new_file.0.write().xml_standalone = orig_file.0.read().xml_standalone;
+            new_file.write_lock().xml_standalone = orig_file.0.read().xml_standalone;
             filemap.insert(filename, new_file.downgrade());
         }
 
@@ -826,7 +1227,9 @@ impl AutosarModel {
         let copy_iter = copy.elements_dfs();
         let combined = std::iter::zip(orig_iter, copy_iter);
         for ((_, orig_elem), (_, copy_elem)) in combined {
-            let mut locked_copy = copy_elem.0.try_write().ok_or(AutosarDataError::ParentElementLocked)?;
+            let mut locked_copy = copy_elem
+                .try_write_lock()
+                .ok_or(AutosarDataError::ParentElementLocked)?;
             locked_copy.file_membership.clear();
 
             for orig_file in orig_elem.0.read().file_membership.iter().filter_map(|w| w.upgrade()) {
@@ -839,6 +1242,46 @@ impl AutosarModel {
         Ok(copy)
     }
 
+    /// Extract a self-contained part of the model
+    ///
+    /// The new model contains the given root elements, all elements that they refer to directly or indirectly,
+    /// and the enclosing AR-PACKAGE hierarchy. Relative references that use a REFERENCE-BASE are followed as well.
+    /// Of the enclosing elements only the SHORT-NAMEs, and the REFERENCE-BASES of the packages, are kept.
+    /// Files that do not contain any of the extracted elements are not part of the new model.
+    ///
+    /// Root elements that are not part of this model are ignored.
+    ///
+    /// In addition to the new model, the function returns a list of all references in the new model that could not be resolved.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use autosar_data::*;
+    /// # fn main() -> Result<(), AutosarDataError> {
+    /// # let model = AutosarModel::new();
+    /// # model.create_file("filename", AutosarVersion::LATEST)?;
+    /// # model.root_element().create_sub_element(ElementName::ArPackages)?
+    /// #     .create_named_sub_element(ElementName::ArPackage, "Pkg")?
+    /// #     .create_sub_element(ElementName::Elements)?
+    /// #     .create_named_sub_element(ElementName::System, "System")?;
+    /// let system = model.get_element_by_path("/Pkg/System").unwrap();
+    /// let (extracted_model, unresolved_references) = model.extract_closure(&[system])?;
+    /// assert!(extracted_model.get_element_by_path("/Pkg/System").is_some());
+    /// assert!(unresolved_references.is_empty());
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    ///  - [`AutosarDataError::ItemDeleted`]: The model is no longer valid
+    ///  - [`AutosarDataError::ParentElementLocked`]: a parent element was locked and did not become available after waiting briefly.
+    ///    The operation was aborted to avoid a deadlock, but can be retried.
+    ///  - [`AutosarDataError::NoFilesInModel`]: The operation cannot be completed because the model does not contain any files
+    pub fn extract_closure(&self, roots: &[Element]) -> Result<(AutosarModel, Vec<WeakElement>), AutosarDataError> {
+        closure::extract_closure(self, roots)
+    }
+
     /// create a depth-first iterator over all [Element]s in the model
     ///
     /// The iterator returns all elements from the merged model, consisting of
@@ -938,6 +1381,32 @@ impl AutosarModel {
         IdentifiablesIterator::new(self)
     }
 
+    /// Find all elements that match a query
+    ///
+    /// See [`Query`] for a description of the query syntax. Queries that begin with SHORT-NAME steps
+    /// are resolved using the index of identifiable elements.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use autosar_data::*;
+    /// # fn main() -> Result<(), AutosarDataError> {
+    /// # let model = AutosarModel::new();
+    /// # model.create_file("filename", AutosarVersion::LATEST)?;
+    /// for baudrate in model.query("/Pkg/**/CAN-CLUSTER/CAN-CLUSTER-VARIANTS/*/BAUDRATE")? {
+    ///     // [...]
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    ///  - [`AutosarDataError::InvalidQuery`]: The query could not be parsed
+    pub fn query(&self, query: &str) -> Result<QueryIterator, AutosarDataError> {
+        Ok(Query::new(query)?.evaluate(self))
+    }
+
     /// return all elements referring to the given target path
     ///
     /// It returns [`WeakElement`]s which must be upgraded to get usable [Element]s.
@@ -1001,6 +1470,32 @@ impl AutosarModel {
         origins
     }
 
+    /// Check all elements of the model against the Autosar specification
+    ///
+    /// The parser checks the multiplicity of sub elements, required sub elements and attributes, and the versions of
+    /// elements, attributes and enum items while a file is loaded. Elements that were created or modified through the API
+    /// can be checked with this function instead. Each file is checked using its own `AutosarVersion`.
+    ///
+    /// All violations are returned as a list of [`ValidationDiagnostic`]s. The list is empty if the model is valid.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use autosar_data::*;
+    /// # fn main() -> Result<(), AutosarDataError> {
+    /// # let model = AutosarModel::new();
+    /// # model.create_file("filename", AutosarVersion::LATEST)?;
+    /// for diagnostic in model.validate() {
+    ///     println!("{diagnostic}");
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[must_use]
+    pub fn validate(&self) -> Vec<ValidationDiagnostic> {
+        validation::validate_model(self)
+    }
+
     /// check all Autosar path references and return a list of elements with invalid references
     ///
     /// For each reference: The target must exist and the DEST attribute must correctly specify the type of the target
@@ -1025,7 +1520,7 @@ impl AutosarModel {
         let mut broken_refs = Vec::new();
 
         let model = self.0.read();
-        for (path, element_list) in &model.reference_origins {
+        for (path, element_list) in model.reference_origins.iter() {
             if let Some(target_elem_weak) = model.identifiables.get(path) {
                 // reference target exists
                 if let Some(target_elem) = target_elem_weak.upgrade() {
@@ -1061,6 +1556,254 @@ impl AutosarModel {
         broken_refs
     }
 
+    /// Begin a transaction
+    ///
+    /// All modifications of the model that are made while the returned [`Transaction`] is active are recorded.
+    /// When the transaction is committed, the changes become one step in the undo history of the model.
+    /// If it is rolled back or dropped, then the changes are reverted.
+    ///
+    /// If another transaction is already active, then the new transaction is nested inside it and works like a
+    /// savepoint: committing it adds its changes to the enclosing transaction, and rolling it back only reverts
+    /// the changes made since it began. Changes made by other threads while the transaction is active also
+    /// become part of the transaction.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use autosar_data::*;
+    /// # fn main() -> Result<(), AutosarDataError> {
+    /// let model = AutosarModel::new();
+    /// model.create_file("filename.arxml", AutosarVersion::Autosar_00050)?;
+    /// let transaction = model.begin_transaction();
+    /// model.root_element().create_sub_element(ElementName::ArPackages)?;
+    /// transaction.commit();
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[must_use]
+    pub fn begin_transaction(&self) -> Transaction {
+        Transaction::new(self)
+    }
+
+    /// Undo the most recently committed transaction
+    ///
+    /// Returns `true` if a step in the undo history was reverted, or `false` if the undo history is empty.
+    /// The reverted step can be re-applied with [`AutosarModel::redo`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use autosar_data::*;
+    /// # fn main() -> Result<(), AutosarDataError> {
+    /// # let model = AutosarModel::new();
+    /// # model.create_file("filename.arxml", AutosarVersion::Autosar_00050)?;
+    /// let transaction = model.begin_transaction();
+    /// model.root_element().create_sub_element(ElementName::ArPackages)?;
+    /// transaction.commit();
+    /// model.undo()?;
+    /// assert!(model.root_element().get_sub_element(ElementName::ArPackages).is_none());
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    ///  - [`AutosarDataError::TransactionInProgress`]: The history cannot be used while a transaction is active
+    ///  - [`AutosarDataError::HistoryConflict`]: The elements changed by the transaction were modified outside of a transaction.
+    ///    The model is not changed, and the whole undo and redo history is discarded.
+    pub fn undo(&self) -> Result<bool, AutosarDataError> {
+        transaction::step_history(self, true)
+    }
+
+    /// Redo the most recently undone transaction
+    ///
+    /// Returns `true` if a step was re-applied, or `false` if there is nothing to redo.
+    /// Committing a new transaction clears the redo history.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use autosar_data::*;
+    /// # fn main() -> Result<(), AutosarDataError> {
+    /// # let model = AutosarModel::new();
+    /// # model.create_file("filename.arxml", AutosarVersion::Autosar_00050)?;
+    /// let transaction = model.begin_transaction();
+    /// model.root_element().create_sub_element(ElementName::ArPackages)?;
+    /// transaction.commit();
+    /// model.undo()?;
+    /// model.redo()?;
+    /// assert!(model.root_element().get_sub_element(ElementName::ArPackages).is_some());
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    ///  - [`AutosarDataError::TransactionInProgress`]: The history cannot be used while a transaction is active
+    ///  - [`AutosarDataError::HistoryConflict`]: The elements changed by the transaction were modified outside of a transaction.
+    ///    The model is not changed, and the whole undo and redo history is discarded.
+    pub fn redo(&self) -> Result<bool, AutosarDataError> {
+        transaction::step_history(self, false)
+    }
+
+    /// Check if there is a step in the undo history
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use autosar_data::*;
+    /// let model = AutosarModel::new();
+    /// assert!(!model.can_undo());
+    /// ```
+    #[must_use]
+    pub fn can_undo(&self) -> bool {
+        self.0.read().history.can_undo()
+    }
+
+    /// Check if there is a step in the redo history
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use autosar_data::*;
+    /// let model = AutosarModel::new();
+    /// assert!(!model.can_redo());
+    /// ```
+    #[must_use]
+    pub fn can_redo(&self) -> bool {
+        self.0.read().history.can_redo()
+    }
+
+    /// Discard the undo and redo history
+    ///
+    /// The recorded history keeps all elements that were modified or removed alive, so it can be useful to clear it
+    /// e.g. after saving the model.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use autosar_data::*;
+    /// let model = AutosarModel::new();
+    /// model.clear_history();
+    /// ```
+    pub fn clear_history(&self) {
+        self.0.write().history.clear();
+    }
+
+    /// Apply a [`Patch`](patch::Patch) to the model
+    ///
+    /// The operations of the patch are applied in order, using the usual methods of [`Element`].
+    /// Applying the patch is all-or-nothing: if any operation conflicts with the content of the model, then
+    /// all changes are reverted and the conflicts of all operations are returned.
+    ///
+    /// A successfully applied patch becomes one step in the undo history of the model. If a transaction is active,
+    /// then the changes become part of that transaction instead.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use autosar_data::*;
+    /// # fn main() -> Result<(), AutosarDataError> {
+    /// let model_a = AutosarModel::new();
+    /// model_a.create_file("a.arxml", AutosarVersion::LATEST)?;
+    /// let model_b = AutosarModel::new();
+    /// model_b.create_file("b.arxml", AutosarVersion::LATEST)?;
+    /// model_b.root_element().create_sub_element(ElementName::ArPackages)?;
+    ///
+    /// let patch = diff::create_patch(&model_a, &model_b)?;
+    /// model_a.apply_patch(&patch)?;
+    /// assert!(model_a.root_element().get_sub_element(ElementName::ArPackages).is_some());
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    ///  - [`AutosarDataError::PatchConflict`]: Some operations of the patch could not be applied. The model is not changed.
+    pub fn apply_patch(&self, patch: &patch::Patch) -> Result<(), AutosarDataError> {
+        let transaction = self.begin_transaction();
+        let conflicts = patch::apply(self, patch);
+        if conflicts.is_empty() {
+            transaction.commit();
+            Ok(())
+        } else {
+            transaction.rollback();
+            Err(AutosarDataError::PatchConflict { conflicts })
+        }
+    }
+
+    /// Register an observer that is notified about every modification of the model
+    ///
+    /// The observer receives a [`ModelEvent`] for each element that is created, removed, moved or renamed, and for
+    /// each change of character data, attributes, comments, file membership and reference targets.
+    /// Operations that change many parts of the model at once emit a single [`ModelEvent::ModelReset`]: reverting
+    /// changes with [`Transaction::rollback`], [`AutosarModel::undo`] or [`AutosarModel::redo`], reloading a file
+    /// with [`ArxmlFile::reload`] and migrating a file with [`ArxmlFile::migrate_version`]. Loading files does not emit events.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use autosar_data::*;
+    /// # fn main() -> Result<(), AutosarDataError> {
+    /// let model = AutosarModel::new();
+    /// model.create_file("filename.arxml", AutosarVersion::LATEST)?;
+    /// let observer_id = model.add_observer(|event: &ModelEvent| {
+    ///     if let ModelEvent::ElementCreated { element, .. } = event {
+    ///         println!("created {}", element.element_name());
+    ///     }
+    /// });
+    /// model.root_element().create_sub_element(ElementName::ArPackages)?;
+    /// model.remove_observer(observer_id);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn add_observer<O: ModelObserver + 'static>(&self, observer: O) -> ObserverId {
+        self.0.write().observers.add(Arc::new(observer))
+    }
+
+    /// Remove an observer that was registered with [`AutosarModel::add_observer`]
+    ///
+    /// Returns true if the observer was found and removed.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use autosar_data::*;
+    /// let model = AutosarModel::new();
+    /// let observer_id = model.add_observer(|_event: &ModelEvent| {});
+    /// assert!(model.remove_observer(observer_id));
+    /// ```
+    pub fn remove_observer(&self, id: ObserverId) -> bool {
+        self.0.write().observers.remove(id)
+    }
+
+    // queue an event for the observers; the event is only created if there are any observers
+    // This must not be called while the model is locked.
+    pub(crate) fn queue_event(&self, make_event: impl FnOnce() -> ModelEvent) {
+        let mut model = self.0.write();
+        if !model.observers.is_empty() {
+            model.observers.push_event(make_event());
+        }
+    }
+
+    // queue an event and deliver all pending events to the observers
+    pub(crate) fn notify_observers(&self, make_event: impl FnOnce() -> ModelEvent) {
+        self.queue_event(make_event);
+        self.dispatch_events();
+    }
+
+    // remember an element whose files must be marked as modified by the next call of dispatch_events()
+    // This is used where the files can't be marked directly, because some elements are still locked.
+    pub(crate) fn queue_modified(&self, element: Element) {
+        self.0.write().observers.push_modified(element);
+    }
+
+    // mark the files of the queued elements as modified, and deliver all pending events to the observers.
+    // This must not be called while any element or the model is locked.
+    pub(crate) fn dispatch_events(&self) {
+        observer::dispatch_events(self);
+    }
+
     /// create a weak reference to this data
     pub(crate) fn downgrade(&self) -> WeakAutosarModel {
         WeakAutosarModel(Arc::downgrade(&self.0))
@@ -1085,7 +1828,7 @@ impl AutosarModel {
             {
                 let new_key = format!("{new_path}{suffix}");
                 // fix the identifiables hashmap
-                if let Some(entry) = model.identifiables.swap_remove(&key) {
+                if let Some(entry) = model.identifiables.remove(&key) {
                     model.identifiables.insert(new_key, entry);
                 }
             }
@@ -1095,7 +1838,7 @@ impl AutosarModel {
     // remove a deleted element from the cache
     pub(crate) fn remove_identifiable(&self, path: &str) {
         let mut model = self.0.write();
-        model.identifiables.swap_remove(path);
+        model.identifiables.remove(path);
     }
 
     pub(crate) fn add_reference_origin(&self, new_ref: &str, base: Option<&str>, origin: WeakElement) {
@@ -1242,7 +1985,7 @@ impl AutosarModel {
         }
 
         // Upsert the new entry for this owner package under the new label.
-        let ref_base_info_list = data.reference_bases.entry(new_label).or_default();
+        let ref_base_info_list = data.reference_bases.get_or_insert_default(new_label);
         if let Some(existing_info) = ref_base_info_list
             .iter_mut()
             .find(|info| info.owner_package_path == owner_package_path)
@@ -1274,10 +2017,59 @@ impl AutosarModel {
     }
 }
 
+// the load options of the functions that only have a strict parameter
+fn strict_options(strict: bool) -> LoadOptions {
+    LoadOptions {
+        strict,
+        lossless: false,
+    }
+}
+
+impl ParsedFile {
+    fn parse(buffer: &[u8], filename: PathBuf, options: LoadOptions) -> Result<Self, AutosarDataError> {
+        let mut parser = ArxmlParser::new(filename.clone(), buffer, options.strict);
+        if options.lossless {
+            parser.keep_original_text();
+        }
+        let root_element = parser.parse_arxml()?;
+        let original_text = parser.take_original_text();
+        Ok(Self {
+            filename,
+            root_element,
+            version: parser.get_fileversion(),
+            xml_standalone: parser.get_standalone(),
+            root_comment: parser.take_root_comment(),
+            identifiables: parser.identifiables,
+            references: parser.references,
+            reference_bases: parser.reference_bases,
+            warnings: parser.warnings,
+            original_text,
+        })
+    }
+
+    fn read_and_parse(filename: PathBuf, strict: bool) -> Result<Self, AutosarDataError> {
+        let buffer = std::fs::read(&filename).map_err(|err| AutosarDataError::IoErrorRead {
+            filename: filename.clone(),
+            ioerror: err,
+        })?;
+        Self::parse(&buffer, filename, strict_options(strict))
+    }
+}
+
 impl AutosarModelRaw {
     pub(crate) fn set_version(&mut self, new_ver: AutosarVersion) {
         let attribute_value = CharacterData::String(format!("http://autosar.org/schema/r4.0 {}", new_ver.filename()));
-        let _ = self.root_element.0.write().set_attribute_internal(
+        // setting the attribute marks the root element as modified, so it is only set if the value changes
+        if self
+            .root_element
+            .0
+            .read()
+            .attribute_value(AttributeName::xsiSchemalocation)
+            == Some(attribute_value.clone())
+        {
+            return;
+        }
+        let _ = self.root_element.write_lock().set_attribute_internal(
             AttributeName::xsiSchemalocation,
             attribute_value,
             new_ver,
@@ -1285,7 +2077,24 @@ impl AutosarModelRaw {
     }
 
     pub(crate) fn wrap(self) -> AutosarModel {
-        AutosarModel(Arc::new(RwLock::new(self)))
+        AutosarModel(Arc::new(AutosarModelData {
+            raw: RwLock::new(self),
+            journal: transaction::Journal::default(),
+        }))
+    }
+}
+
+impl AutosarModelData {
+    pub(crate) fn journal(&self) -> &transaction::Journal {
+        &self.journal
+    }
+}
+
+impl std::ops::Deref for AutosarModelData {
+    type Target = RwLock<AutosarModelRaw>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.raw
     }
 }
 
@@ -1332,6 +2141,14 @@ impl WeakAutosarModel {
     }
 }
 
+impl PartialEq for WeakAutosarModel {
+    fn eq(&self, other: &Self) -> bool {
+        Weak::as_ptr(&self.0) == Weak::as_ptr(&other.0)
+    }
+}
+
+impl Eq for WeakAutosarModel {}
+
 impl std::fmt::Debug for WeakAutosarModel {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.write_fmt(format_args!("AutosarModel:WeakRef {:p}", Weak::as_ptr(&self.0)))
@@ -1425,6 +2242,119 @@ mod test {
         assert!(el_pkg.is_some());
     }
 
+    #[test]
+    fn load_reader() {
+        const FILEBUF: &[u8] = br#"<?xml version="1.0" encoding="utf-8"?>
+<AUTOSAR xsi:schemaLocation="http://autosar.org/schema/r4.0 AUTOSAR_00050.xsd" xmlns="http://autosar.org/schema/r4.0" xmlns:xsi="http://www.w3.org/2001/XMLSchema-instance">
+  <AR-PACKAGES><AR-PACKAGE><SHORT-NAME>Pkg</SHORT-NAME></AR-PACKAGE></AR-PACKAGES>
+</AUTOSAR>"#;
+        let model = AutosarModel::new();
+        let (file, warnings) = model.load_reader(std::io::Cursor::new(FILEBUF), "test", true).unwrap();
+        assert!(warnings.is_empty());
+        assert_eq!(file.filename(), PathBuf::from("test"));
+        assert!(model.get_element_by_path("/Pkg").is_some());
+
+        // the filename must be unique
+        let result = model.load_reader(FILEBUF, "test", true);
+        assert!(matches!(result, Err(AutosarDataError::DuplicateFilenameError { .. })));
+        // the filename is checked before the data is parsed
+        let result = model.load_buffer(b"invalid", "test", true);
+        assert!(matches!(result, Err(AutosarDataError::DuplicateFilenameError { .. })));
+
+        // read errors are reported
+        struct FailingReader;
+        impl std::io::Read for FailingReader {
+            fn read(&mut self, _buf: &mut [u8]) -> std::io::Result<usize> {
+                Err(std::io::Error::other("failed"))
+            }
+        }
+        let result = model.load_reader(FailingReader, "test2", true);
+        assert!(matches!(result, Err(AutosarDataError::IoErrorRead { .. })));
+    }
+
+    #[cfg(feature = "mmap")]
+    #[test]
+    fn load_file_mmap() {
+        let dir = tempdir().unwrap();
+
+        let model = AutosarModel::new();
+        let filename = dir.path().join("nonexistent.arxml");
+        let result = model.load_file_mmap(&filename, true);
+        assert!(matches!(result, Err(AutosarDataError::IoErrorRead { .. })));
+
+        let filename = dir.path().join("test.arxml");
+        model.create_file(&filename, AutosarVersion::LATEST).unwrap();
+        model
+            .root_element()
+            .create_sub_element(ElementName::ArPackages)
+            .and_then(|ap| ap.create_named_sub_element(ElementName::ArPackage, "Pkg"))
+            .unwrap();
+        model.write().unwrap();
+
+        let model = AutosarModel::new();
+        model.load_file_mmap(&filename, true).unwrap();
+        assert!(model.get_element_by_path("/Pkg").is_some());
+
+        // an empty file can be mapped, but it is not valid arxml
+        let filename = dir.path().join("empty.arxml");
+        File::create(&filename).unwrap();
+        let result = model.load_file_mmap(&filename, true);
+        assert!(matches!(result, Err(AutosarDataError::ParserError { .. })));
+    }
+
+    #[test]
+    fn load_files() {
+        let dir = tempdir().unwrap();
+
+        // write several files, each containing a different package
+        let mut filenames = Vec::new();
+        for idx in 0..5 {
+            let model = AutosarModel::new();
+            let filename = dir.path().join(format!("file_{idx}.arxml"));
+            model.create_file(&filename, AutosarVersion::Autosar_00050).unwrap();
+            model
+                .root_element()
+                .create_sub_element(ElementName::ArPackages)
+                .and_then(|ap| ap.create_named_sub_element(ElementName::ArPackage, &format!("Pkg{idx}")))
+                .unwrap();
+            model.write().unwrap();
+            filenames.push(filename);
+        }
+        std::fs::write(
+            dir.path().join("invalid.arxml"),
+            "The quick brown fox jumps over the lazy dog",
+        )
+        .unwrap();
+        filenames.push(dir.path().join("invalid.arxml"));
+        filenames.push(dir.path().join("nonexistent.arxml"));
+        // the first file is listed twice
+        filenames.push(filenames[0].clone());
+
+        let model = AutosarModel::new();
+        let results = model.load_files(&filenames, true);
+        assert_eq!(results.len(), filenames.len());
+        // the files were loaded in order
+        for idx in 0..5 {
+            let (file, warnings) = results[idx].as_ref().unwrap();
+            assert_eq!(file.filename(), filenames[idx]);
+            assert!(warnings.is_empty());
+            assert!(model.get_element_by_path(&format!("/Pkg{idx}")).is_some());
+        }
+        assert!(matches!(results[5], Err(AutosarDataError::ParserError { .. })));
+        assert!(matches!(results[6], Err(AutosarDataError::IoErrorRead { .. })));
+        assert!(matches!(
+            results[7],
+            Err(AutosarDataError::DuplicateFilenameError { .. })
+        ));
+        assert_eq!(model.files().count(), 5);
+        let file_order: Vec<PathBuf> = model.files().map(|f| f.filename()).collect();
+        assert_eq!(file_order, filenames[0..5]);
+
+        // loading an empty list of files does nothing
+        let results = model.load_files::<PathBuf>(&[], true);
+        assert!(results.is_empty());
+    }
+
     #[test]
     fn data_merge() {
         const FILEBUF1: &[u8] = r#"<?xml version="1.0" encoding="utf-8"?>
@@ -1879,6 +2809,90 @@ mod test {
     }
 
     #[test]
+    fn write_modified() {
+        let dir = tempdir().unwrap();
+        let filename1 = dir.path().join("file1.arxml");
+        let filename2 = dir.path().join("file2.arxml");
+
+        let model = AutosarModel::new();
+        let file1 = model.create_file(&filename1, AutosarVersion::LATEST).unwrap();
+        let file2 = model.create_file(&filename2, AutosarVersion::LATEST).unwrap();
+        let package1 = model
+            .root_element()
+            .create_sub_element(ElementName::ArPackages)
+            .and_then(|ap| ap.create_named_sub_element(ElementName::ArPackage, "Pkg1"))
+            .unwrap();
+        package1.remove_from_file(&file2).unwrap();
+        // new files are always modified
+        assert!(file1.is_modified());
+        assert!(file2.is_modified());
+        model.write_modified().unwrap();
+        assert!(filename1.exists());
+        assert!(filename2.exists());
+        assert!(!file1.is_modified());
+        assert!(!file2.is_modified());
+
+        // a change inside Pkg1 only modifies file1
+        std::fs::remove_file(&filename1).unwrap();
+        std::fs::remove_file(&filename2).unwrap();
+        package1
+            .create_sub_element(ElementName::Elements)
+            .and_then(|elements| elements.create_named_sub_element(ElementName::System, "System"))
+            .unwrap();
+        assert!(file1.is_modified());
+        assert!(!file2.is_modified());
+        model.write_modified().unwrap();
+        assert!(filename1.exists());
+        assert!(!filename2.exists());
+        assert!(!file1.is_modified());
+
+        // loaded files are not modified
+        let model = AutosarModel::new();
+        let (file1, _) = model.load_file(&filename1, true).unwrap();
+        assert!(!file1.is_modified());
+        let package1 = model.get_element_by_path("/Pkg1").unwrap();
+        package1.set_item_name("Renamed").unwrap();
+        assert!(file1.is_modified());
+
+        // undoing a transaction modifies the files again
+        model.write_modified().unwrap();
+        let transaction = model.begin_transaction();
+        package1
+            .get_sub_element(ElementName::Elements)
+            .and_then(|elements| elements.get_sub_element(ElementName::System))
+            .unwrap()
+            .set_item_name("System2")
+            .unwrap();
+        transaction.commit();
+        model.write_modified().unwrap();
+        assert!(!file1.is_modified());
+        assert!(model.undo().unwrap());
+        assert!(file1.is_modified());
+
+        // the modified files can be written with custom formatting
+        let options = SerializeOptions {
+            indentation: Indentation::Tabs,
+            ..Default::default()
+        };
+        model.write_modified_with_options(&options).unwrap();
+        assert!(!file1.is_modified());
+        assert!(
+            std::fs::read_to_string(&filename1)
+                .unwrap()
+                .contains("\n\t<AR-PACKAGES>")
+        );
+
+        // a failed write keeps the flag
+        let model = AutosarModel::new();
+        let file = model
+            .create_file("nonexistent/dir/some_file.arxml", AutosarVersion::LATEST)
+            .unwrap();
+        assert!(model.write_modified().is_err());
+        assert!(file.is_modified());
+    }
+
+    #[test]
+    #[allow(clippy::mutable_key_type)]
     fn traits() {
         // AutosarModel: Debug, Clone, Hash
         let model = AutosarModel::new();
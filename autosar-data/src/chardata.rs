@@ -2,7 +2,7 @@ use std::borrow::Cow;
 use std::fmt::Display;
 use std::str::FromStr;
 
-use super::{AutosarVersion, CharacterData, CharacterDataSpec, EnumItem};
+use super::{AutosarVersion, CharacterData, CharacterDataSpec, EnumItem, SerializeOutput};
 
 impl CharacterData {
     pub(crate) fn check_value(value: &CharacterData, spec: &CharacterDataSpec, file_version: AutosarVersion) -> bool {
@@ -113,7 +113,34 @@ impl CharacterData {
         None
     }
 
-    pub(crate) fn serialize_internal(&self, outstring: &mut String) {
+    /// create a placeholder value that is valid according to the spec
+    ///
+    /// Returns None if no valid value could be found, e.g. if an enum has no items in the given version.
+    pub(crate) fn placeholder(character_data_spec: &CharacterDataSpec, version: AutosarVersion) -> Option<Self> {
+        match character_data_spec {
+            CharacterDataSpec::Enum { items } => items
+                .iter()
+                .find(|(_, version_mask)| version.compatible(*version_mask))
+                .map(|(item, _)| CharacterData::Enum(*item)),
+            CharacterDataSpec::Pattern {
+                check_fn,
+                regex,
+                max_length,
+            } => {
+                let value = regex_sample(regex)?;
+                if value.len() <= max_length.unwrap_or(usize::MAX) && check_fn(value.as_bytes()) {
+                    Some(CharacterData::String(value))
+                } else {
+                    None
+                }
+            }
+            CharacterDataSpec::String { .. } => Some(CharacterData::String(String::new())),
+            CharacterDataSpec::UnsignedInteger => Some(CharacterData::UnsignedInteger(0)),
+            CharacterDataSpec::Float => Some(CharacterData::Float(0.0)),
+        }
+    }
+
+    pub(crate) fn serialize_internal<O: SerializeOutput>(&self, outstring: &mut O) {
         match self {
             CharacterData::Enum(enumval) => outstring.push_str(enumval.to_str()),
             CharacterData::String(strval) => outstring.push_str(&escape_text(strval)),
@@ -403,6 +430,139 @@ fn escape_text<'a>(input: &'a str) -> Cow<'a, str> {
     }
 }
 
+// create a short string that matches the regex
+//
+// The regex is parsed as far as needed: the first alternative is used everywhere, each character class
+// is replaced by its first character and each quantifier by its minimum count.
+fn regex_sample(regex: &str) -> Option<String> {
+    let chars: Vec<char> = regex.chars().collect();
+    let mut pos = 0;
+    let sample = regex_sample_alternatives(&chars, &mut pos)?;
+    (pos == chars.len()).then_some(sample)
+}
+
+fn regex_sample_alternatives(chars: &[char], pos: &mut usize) -> Option<String> {
+    let sample = regex_sample_sequence(chars, pos)?;
+    while chars.get(*pos) == Some(&'|') {
+        *pos += 1;
+        regex_sample_sequence(chars, pos)?;
+    }
+    Some(sample)
+}
+
+fn regex_sample_sequence(chars: &[char], pos: &mut usize) -> Option<String> {
+    let mut sample = String::new();
+    while let Some(&c) = chars.get(*pos) {
+        *pos += 1;
+        let atom = match c {
+            '|' | ')' => {
+                *pos -= 1;
+                break;
+            }
+            '(' => {
+                if chars[*pos..].starts_with(&['?', ':']) {
+                    *pos += 2;
+                }
+                let group = regex_sample_alternatives(chars, pos)?;
+                if chars.get(*pos) != Some(&')') {
+                    return None;
+                }
+                *pos += 1;
+                group
+            }
+            '[' => regex_sample_class(chars, pos)?.to_string(),
+            '\\' => {
+                let escaped = *chars.get(*pos)?;
+                *pos += 1;
+                regex_sample_escape(escaped).to_string()
+            }
+            '^' | '$' => String::new(),
+            '.' => String::from("a"),
+            _ => c.to_string(),
+        };
+
+        let count = match chars.get(*pos) {
+            Some('*' | '?') => {
+                *pos += 1;
+                0
+            }
+            Some('+') => {
+                *pos += 1;
+                1
+            }
+            Some('{') => {
+                let end = *pos + chars[*pos..].iter().position(|c| *c == '}')?;
+                let quantifier: String = chars[*pos + 1..end].iter().collect();
+                *pos = end + 1;
+                quantifier.split(',').next()?.trim().parse().ok()?
+            }
+            _ => 1,
+        };
+        for _ in 0..count {
+            sample.push_str(&atom);
+        }
+    }
+    Some(sample)
+}
+
+// get a character that matches the character class; pos is directly behind the opening '['
+fn regex_sample_class(chars: &[char], pos: &mut usize) -> Option<char> {
+    let negated = chars.get(*pos) == Some(&'^');
+    if negated {
+        *pos += 1;
+    }
+    let mut members = Vec::new();
+    let mut ranges = Vec::new();
+    let mut first = true;
+    loop {
+        let mut c = *chars.get(*pos)?;
+        *pos += 1;
+        if c == ']' && !first {
+            break;
+        }
+        first = false;
+        if c == '\\' {
+            c = regex_sample_escape(*chars.get(*pos)?);
+            *pos += 1;
+        }
+        if chars.get(*pos) == Some(&'-') && chars.get(*pos + 1).is_some_and(|next| *next != ']') {
+            let mut end = chars[*pos + 1];
+            *pos += 2;
+            if end == '\\' {
+                end = regex_sample_escape(*chars.get(*pos)?);
+                *pos += 1;
+            }
+            ranges.push((c, end));
+        } else {
+            members.push(c);
+        }
+    }
+
+    if negated {
+        "a0A_".chars().find(|candidate| {
+            !members.contains(candidate) && !ranges.iter().any(|(start, end)| (start..=end).contains(&candidate))
+        })
+    } else {
+        members
+            .first()
+            .copied()
+            .or_else(|| ranges.first().map(|(start, _)| *start))
+    }
+}
+
+// get a character that matches an escape sequence
+fn regex_sample_escape(escaped: char) -> char {
+    match escaped {
+        'd' => '0',
+        's' => ' ',
+        'w' | 'i' | 'c' => 'a',
+        'n' => '\n',
+        'r' => '\r',
+        't' => '\t',
+        other => other,
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -841,4 +1001,41 @@ mod test {
         let cdata: CharacterData = true.into();
         assert_eq!(cdata, CharacterData::String("true".to_string()));
     }
+
+    #[test]
+    fn placeholder() {
+        assert_eq!(regex_sample("[1-9][0-9]*|0[xX][0-9a-fA-F]+").unwrap(), "1");
+        assert_eq!(regex_sample(r"([0-9]{4}-[0-9]{2})(T[0-9]{2})?").unwrap(), "0000-00");
+        assert_eq!(regex_sample(r"[^0-9]\.[\-+]").unwrap(), "a.-");
+        assert!(regex_sample("(abc").is_none());
+
+        // a valid placeholder exists for all character data specs of all element types and attributes, in every version
+        let versions: Vec<AutosarVersion> = (0..u32::BITS)
+            .filter_map(|bit| AutosarVersion::from_val(1 << bit))
+            .collect();
+        let mut visited = std::collections::HashSet::new();
+        let mut to_visit = vec![crate::ElementType::ROOT];
+        while let Some(etype) = to_visit.pop() {
+            if !visited.insert(etype) {
+                continue;
+            }
+            let mut specs: Vec<&'static CharacterDataSpec> =
+                etype.attribute_spec_iter().map(|(_, spec, _)| spec).collect();
+            specs.extend(etype.chardata_spec());
+            for spec in specs {
+                for version in &versions {
+                    if let CharacterDataSpec::Enum { items } = spec
+                        && !items.iter().any(|(_, version_mask)| version.compatible(*version_mask))
+                    {
+                        continue;
+                    }
+                    // every pattern must have a placeholder, since there is no other way to create a valid value
+                    let value = CharacterData::placeholder(spec, *version);
+                    assert!(value.is_some(), "no placeholder for {spec:?}");
+                    assert!(CharacterData::check_value(&value.unwrap(), spec, *version), "{spec:?}");
+                }
+            }
+            to_visit.extend(etype.sub_element_spec_iter().map(|(_, sub_type, ..)| sub_type));
+        }
+    }
 }
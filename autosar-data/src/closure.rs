@@ -0,0 +1,258 @@
+use crate::*;
+use fxhash::FxHashSet;
+
+/// create a new model that contains the roots, all elements they refer to directly or indirectly, and the enclosing packages
+#[allow(clippy::mutable_key_type)] // Element uses the pointer for hashing, so the key does not change
+pub(crate) fn extract_closure(
+    model: &AutosarModel,
+    roots: &[Element],
+) -> Result<(AutosarModel, Vec<WeakElement>), AutosarDataError> {
+    // collect the transitive closure of all references, starting from the roots
+    let mut included = FxHashSet::default();
+    let mut unresolved = Vec::new();
+    let mut worklist: Vec<Element> = roots
+        .iter()
+        .filter(|root| root.model().is_ok_and(|root_model| root_model == *model))
+        .cloned()
+        .collect();
+    while let Some(element) = worklist.pop() {
+        if included.contains(&element) {
+            continue;
+        }
+        for (_, sub_element) in element.elements_dfs() {
+            // the PACKAGE-REF of a REFERENCE-BASE only defines a base path, it does not make the package content a dependency
+            if sub_element.is_reference() && !is_reference_base_content(&sub_element) {
+                match sub_element.get_reference_target() {
+                    Ok(target) => worklist.push(target),
+                    Err(_) => unresolved.push(sub_element.clone()),
+                }
+            }
+            included.insert(sub_element);
+        }
+    }
+
+    // the parents of the included elements are kept, but only with the content that is needed to preserve the paths:
+    // their SHORT-NAMEs, and the REFERENCE-BASES of packages, which are needed to resolve relative references
+    let mut skeleton = FxHashSet::default();
+    let mut skeleton_content = Vec::new();
+    for element in &included {
+        let mut current = element.parent()?;
+        while let Some(parent) = current {
+            if included.contains(&parent) || !skeleton.insert(parent.clone()) {
+                break;
+            }
+            skeleton_content.extend(parent.get_sub_element(ElementName::ShortName));
+            if parent.element_name() == ElementName::ArPackage
+                && let Some(reference_bases) = parent.get_sub_element(ElementName::ReferenceBases)
+            {
+                skeleton_content.push(reference_bases);
+            }
+            current = parent.parent()?;
+        }
+    }
+    let closure = included.clone();
+    for element in skeleton_content {
+        included.extend(element.elements_dfs().map(|(_, sub_element)| sub_element));
+    }
+
+    // remove everything else from a copy of the model. Both models have the same structure, so their elements can be matched in order
+    let copy = model.duplicate()?;
+    let unresolved: FxHashSet<Element> = unresolved.into_iter().collect();
+    let mut unresolved_copies = Vec::new();
+    let mut removed = Vec::new();
+    let mut used_files = FxHashSet::default();
+    for ((_, orig_element), (_, copy_element)) in std::iter::zip(model.elements_dfs(), copy.elements_dfs()) {
+        if unresolved.contains(&orig_element) {
+            unresolved_copies.push(copy_element.downgrade());
+        }
+        let orig_parent = orig_element.parent()?;
+        if !included.contains(&orig_element) && !skeleton.contains(&orig_element) {
+            if orig_parent.is_some_and(|parent| skeleton.contains(&parent)) {
+                removed.push(copy_element);
+            }
+        } else if closure.contains(&orig_element) && !orig_parent.is_some_and(|parent| closure.contains(&parent)) {
+            // only files that contain some of the extracted elements are kept
+            used_files.extend(copy_element.file_membership()?.1);
+        }
+    }
+    for element in removed {
+        if let Some(parent) = element.parent()? {
+            parent.remove_sub_element(element)?;
+        }
+    }
+    let unused_files: Vec<ArxmlFile> = copy
+        .files()
+        .filter(|file| !used_files.contains(&file.downgrade()))
+        .collect();
+    for file in unused_files {
+        copy.remove_file(&file);
+    }
+
+    Ok((copy, unresolved_copies))
+}
+
+fn is_reference_base_content(element: &Element) -> bool {
+    element
+        .parent()
+        .ok()
+        .flatten()
+        .is_some_and(|parent| parent.element_name() == ElementName::ReferenceBase)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn create_model() -> (AutosarModel, ArxmlFile) {
+        let model = AutosarModel::new();
+        let file = model.create_file("main.arxml", AutosarVersion::LATEST).unwrap();
+        let other_file = model.create_file("other.arxml", AutosarVersion::LATEST).unwrap();
+        let packages = model
+            .root_element()
+            .create_sub_element(ElementName::ArPackages)
+            .unwrap();
+
+        let signals = packages
+            .create_named_sub_element(ElementName::ArPackage, "Signals")
+            .unwrap();
+        let signal_elements = signals.create_sub_element(ElementName::Elements).unwrap();
+        let system_signal = signal_elements
+            .create_named_sub_element(ElementName::SystemSignal, "SysSig")
+            .unwrap();
+        signal_elements
+            .create_named_sub_element(ElementName::SystemSignal, "Unused")
+            .unwrap();
+        let signal = signal_elements
+            .create_named_sub_element(ElementName::ISignal, "Sig")
+            .unwrap();
+        signal
+            .create_sub_element(ElementName::SystemSignalRef)
+            .and_then(|signal_ref| signal_ref.set_reference_target(&system_signal))
+            .unwrap();
+
+        // the PDU refers to the signal with a relative reference
+        let pdus = packages
+            .create_named_sub_element(ElementName::ArPackage, "Pdus")
+            .unwrap();
+        let reference_base = pdus
+            .create_sub_element(ElementName::ReferenceBases)
+            .and_then(|bases| bases.create_sub_element(ElementName::ReferenceBase))
+            .unwrap();
+        reference_base
+            .create_sub_element(ElementName::ShortLabel)
+            .and_then(|label| label.set_character_data("signals"))
+            .unwrap();
+        reference_base
+            .create_sub_element(ElementName::PackageRef)
+            .and_then(|package_ref| package_ref.set_reference_target(&signals))
+            .unwrap();
+        let mappings = pdus
+            .create_sub_element(ElementName::Elements)
+            .and_then(|elements| elements.create_named_sub_element(ElementName::ISignalIPdu, "Pdu"))
+            .and_then(|pdu| pdu.create_sub_element(ElementName::ISignalToPduMappings))
+            .unwrap();
+        mappings
+            .create_named_sub_element(ElementName::ISignalToIPduMapping, "Mapping")
+            .and_then(|mapping| mapping.create_sub_element(ElementName::ISignalRef))
+            .and_then(|signal_ref| signal_ref.set_relative_reference_target(&signal, "signals"))
+            .unwrap();
+        let broken_ref = mappings
+            .create_named_sub_element(ElementName::ISignalToIPduMapping, "Broken")
+            .and_then(|mapping| mapping.create_sub_element(ElementName::ISignalRef))
+            .unwrap();
+        broken_ref
+            .set_attribute(AttributeName::Dest, EnumItem::ISignal)
+            .unwrap();
+        broken_ref.set_character_data("/Signals/Missing").unwrap();
+
+        // a package in a second file, which is not referenced
+        signals.remove_from_file(&other_file).unwrap();
+        pdus.remove_from_file(&other_file).unwrap();
+        let other = packages
+            .create_named_sub_element(ElementName::ArPackage, "Other")
+            .unwrap();
+        other.remove_from_file(&file).unwrap();
+        other
+            .create_sub_element(ElementName::Elements)
+            .and_then(|elements| elements.create_named_sub_element(ElementName::CanFrame, "Frame"))
+            .unwrap();
+
+        (model, file)
+    }
+
+    #[test]
+    fn extract() {
+        let (model, _) = create_model();
+        let pdu = model.get_element_by_path("/Pdus/Pdu").unwrap();
+        let (extracted, unresolved) = model.extract_closure(&[pdu]).unwrap();
+
+        let mut paths: Vec<String> = extracted.identifiable_elements().map(|(path, _)| path).collect();
+        paths.sort();
+        assert_eq!(
+            paths,
+            vec![
+                "/Pdus",
+                "/Pdus/Pdu",
+                "/Pdus/Pdu/Broken",
+                "/Pdus/Pdu/Mapping",
+                "/Signals",
+                "/Signals/Sig",
+                "/Signals/SysSig"
+            ]
+        );
+        // the relative reference still works in the extracted model
+        let signal_ref = extracted
+            .get_element_by_path("/Pdus/Pdu/Mapping")
+            .and_then(|mapping| mapping.get_sub_element(ElementName::ISignalRef))
+            .unwrap();
+        assert_eq!(
+            signal_ref.get_reference_target().unwrap(),
+            extracted.get_element_by_path("/Signals/Sig").unwrap()
+        );
+        // the broken reference is reported
+        let broken_ref = extracted
+            .get_element_by_path("/Pdus/Pdu/Broken")
+            .and_then(|mapping| mapping.get_sub_element(ElementName::ISignalRef))
+            .unwrap();
+        assert_eq!(unresolved, vec![broken_ref.downgrade()]);
+        // the file that only contains unrelated elements is not part of the extracted model
+        assert_eq!(extracted.files().count(), 1);
+        assert_eq!(
+            extracted.files().next().unwrap().filename(),
+            PathBuf::from("main.arxml")
+        );
+
+        // the original model is unchanged
+        assert!(model.get_element_by_path("/Signals/Unused").is_some());
+        assert!(model.get_element_by_path("/Other/Frame").is_some());
+        assert_eq!(model.files().count(), 2);
+    }
+
+    #[test]
+    fn extract_special_cases() {
+        let (model, _) = create_model();
+        // extracting the root element copies everything
+        let (extracted, unresolved) = model.extract_closure(&[model.root_element()]).unwrap();
+        assert_eq!(
+            extracted.identifiable_elements().count(),
+            model.identifiable_elements().count()
+        );
+        assert_eq!(extracted.files().count(), 2);
+        assert_eq!(unresolved.len(), 1);
+
+        // nested and duplicate roots are handled
+        let signals = model.get_element_by_path("/Signals").unwrap();
+        let signal = model.get_element_by_path("/Signals/Sig").unwrap();
+        let (extracted, unresolved) = model.extract_closure(&[signal.clone(), signals, signal]).unwrap();
+        assert_eq!(extracted.identifiable_elements().count(), 4);
+        assert!(unresolved.is_empty());
+
+        // elements of other models are ignored
+        let (other_model, _) = create_model();
+        let (extracted, _) = model
+            .extract_closure(&[other_model.get_element_by_path("/Signals").unwrap()])
+            .unwrap();
+        assert_eq!(extracted.files().count(), 0);
+        assert_eq!(extracted.identifiable_elements().count(), 0);
+    }
+}
@@ -0,0 +1,267 @@
+use crate::*;
+use std::str::FromStr;
+
+/// The position of the cursor in an element, for which [`Element::complete`] lists completions
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompletionContext {
+    /// A new sub element is being inserted into the element
+    SubElement,
+    /// The name of an attribute is being typed in the start tag of the element
+    AttributeName,
+    /// The value of the attribute is being typed
+    AttributeValue(AttributeName),
+    /// The character data of the element is being typed
+    CharacterData,
+}
+
+/// A single completion, as returned by [`Element::complete`]
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub enum Completion {
+    /// A sub element that is valid in the element
+    SubElement(ValidSubElementInfo),
+    /// An attribute that is valid in the element, and that is not present yet
+    Attribute {
+        /// name of the attribute
+        attribute: AttributeName,
+        /// is the attribute required by the specification
+        required: bool,
+    },
+    /// An enum item that is valid as an attribute value or as character data in the current version
+    EnumItem(EnumItem),
+    /// An identifiable element that the reference may refer to
+    ReferenceTarget {
+        /// the Autosar path of the target element
+        path: String,
+        /// the value of the DEST attribute for a reference to this target
+        dest: EnumItem,
+    },
+}
+
+impl Completion {
+    /// Get the text that is inserted for this completion
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use autosar_data::*;
+    /// let completion = Completion::EnumItem(EnumItem::ISignalIPdu);
+    /// assert_eq!(completion.text(), "I-SIGNAL-I-PDU");
+    /// ```
+    #[must_use]
+    pub fn text(&self) -> String {
+        match self {
+            Completion::SubElement(info) => info.element_name.to_string(),
+            Completion::Attribute { attribute, .. } => attribute.to_string(),
+            Completion::EnumItem(item) => item.to_string(),
+            Completion::ReferenceTarget { path, .. } => path.clone(),
+        }
+    }
+}
+
+/// list the completions for the context in the element; only completions whose text starts with the prefix are included
+pub(crate) fn complete(
+    element: &Element,
+    context: CompletionContext,
+    prefix: &str,
+) -> Result<Vec<Completion>, AutosarDataError> {
+    let version = element.min_version()?;
+    let elemtype = element.element_type();
+
+    let completions = match context {
+        CompletionContext::SubElement => element
+            .list_valid_sub_elements()
+            .into_iter()
+            .map(Completion::SubElement)
+            .collect(),
+        CompletionContext::AttributeName => elemtype
+            .attribute_spec_iter()
+            .filter(|(attribute, ..)| {
+                element.attribute_value(*attribute).is_none()
+                    && elemtype
+                        .find_attribute_spec(*attribute)
+                        .is_some_and(|spec| version.compatible(spec.version))
+            })
+            .map(|(attribute, _, required)| Completion::Attribute { attribute, required })
+            .collect(),
+        CompletionContext::AttributeValue(attribute) => elemtype
+            .find_attribute_spec(attribute)
+            .filter(|spec| version.compatible(spec.version))
+            .map(|spec| enum_completions(spec.spec, version))
+            .unwrap_or_default(),
+        CompletionContext::CharacterData => {
+            if elemtype.is_ref() {
+                reference_completions(element, elemtype, version)?
+            } else if elemtype.content_mode() == ContentMode::Characters
+                && let Some(spec) = elemtype.chardata_spec()
+            {
+                enum_completions(spec, version)
+            } else {
+                Vec::new()
+            }
+        }
+    };
+
+    Ok(completions
+        .into_iter()
+        .filter(|completion| completion.text().starts_with(prefix))
+        .collect())
+}
+
+// all enum items of the spec that are valid in the version
+fn enum_completions(spec: &CharacterDataSpec, version: AutosarVersion) -> Vec<Completion> {
+    if let CharacterDataSpec::Enum { items } = spec {
+        items
+            .iter()
+            .filter(|(_, version_mask)| version.compatible(*version_mask))
+            .map(|(item, _)| Completion::EnumItem(*item))
+            .collect()
+    } else {
+        Vec::new()
+    }
+}
+
+// all identifiable elements that the reference can refer to.
+// If the DEST attribute of the reference is already set, then only targets that match the DEST value are included
+fn reference_completions(
+    element: &Element,
+    elemtype: ElementType,
+    version: AutosarVersion,
+) -> Result<Vec<Completion>, AutosarDataError> {
+    let model = element.model()?;
+    let current_dest = element
+        .attribute_value(AttributeName::Dest)
+        .and_then(|dest| dest.enum_value());
+    let dest_items = elemtype
+        .find_attribute_spec(AttributeName::Dest)
+        .map(|spec| enum_completions(spec.spec, version))
+        .unwrap_or_default();
+
+    let mut completions: Vec<Completion> = model
+        .identifiable_elements()
+        .filter_map(|(path, weak_target)| {
+            let target = weak_target.upgrade()?;
+            let target_type = target.element_type();
+            let base_dest = elemtype.reference_dest_value(&target_type)?;
+            if current_dest.is_some_and(|dest| !target_type.verify_reference_dest(dest)) {
+                return None;
+            }
+            // like in set_reference_target, the name of the target element is preferred as the DEST value
+            let dest = EnumItem::from_str(target.element_name().to_str())
+                .ok()
+                .filter(|item| dest_items.contains(&Completion::EnumItem(*item)))
+                .unwrap_or(base_dest);
+            Some(Completion::ReferenceTarget { path, dest })
+        })
+        .collect();
+    completions.sort_by_key(Completion::text);
+    Ok(completions)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+
+    #[test]
+    fn complete() {
+        let model = AutosarModel::new();
+        model.create_file("test.arxml", AutosarVersion::Autosar_00050).unwrap();
+        let elements = model
+            .root_element()
+            .create_sub_element(ElementName::ArPackages)
+            .and_then(|packages| packages.create_named_sub_element(ElementName::ArPackage, "Pkg"))
+            .and_then(|pkg| pkg.create_sub_element(ElementName::Elements))
+            .unwrap();
+        let pdu = elements
+            .create_named_sub_element(ElementName::ISignalIPdu, "Pdu")
+            .unwrap();
+        elements
+            .create_named_sub_element(ElementName::ISignal, "Signal")
+            .unwrap();
+        let reference = elements
+            .create_named_sub_element(ElementName::System, "System")
+            .and_then(|system| system.create_sub_element(ElementName::FibexElements))
+            .and_then(|fibex| fibex.create_sub_element(ElementName::FibexElementRefConditional))
+            .and_then(|conditional| conditional.create_sub_element(ElementName::FibexElementRef))
+            .unwrap();
+
+        // sub elements
+        let completions = elements.complete(CompletionContext::SubElement, "I-SIGNAL-I-").unwrap();
+        assert!(completions.iter().any(|completion| matches!(
+            completion,
+            Completion::SubElement(ValidSubElementInfo {
+                element_name: ElementName::ISignalIPdu,
+                ..
+            })
+        )));
+        assert!(
+            completions
+                .iter()
+                .all(|completion| completion.text().starts_with("I-SIGNAL-I-"))
+        );
+
+        // attribute names: existing attributes are not suggested
+        let completions = pdu.complete(CompletionContext::AttributeName, "").unwrap();
+        assert!(completions.contains(&Completion::Attribute {
+            attribute: AttributeName::Uuid,
+            required: false
+        }));
+        pdu.set_attribute(AttributeName::Uuid, "1234").unwrap();
+        let completions = pdu.complete(CompletionContext::AttributeName, "").unwrap();
+        assert!(!completions.iter().any(|completion| completion.text() == "UUID"));
+
+        // attribute values: the DEST values of the reference
+        let completions = reference
+            .complete(CompletionContext::AttributeValue(AttributeName::Dest), "I-SIGNAL")
+            .unwrap();
+        assert!(completions.contains(&Completion::EnumItem(EnumItem::ISignalIPdu)));
+        assert!(completions.contains(&Completion::EnumItem(EnumItem::ISignal)));
+        let completions = reference
+            .complete(CompletionContext::AttributeValue(AttributeName::Uuid), "")
+            .unwrap();
+        assert!(completions.is_empty());
+
+        // reference targets: the SYSTEM is not a FIBEX-ELEMENT, so it is not a valid target
+        let completions = reference.complete(CompletionContext::CharacterData, "/Pkg/").unwrap();
+        assert_eq!(
+            completions,
+            vec![
+                Completion::ReferenceTarget {
+                    path: "/Pkg/Pdu".to_string(),
+                    dest: EnumItem::ISignalIPdu
+                },
+                Completion::ReferenceTarget {
+                    path: "/Pkg/Signal".to_string(),
+                    dest: EnumItem::ISignal
+                },
+            ]
+        );
+        reference
+            .set_attribute(AttributeName::Dest, EnumItem::ISignalIPdu)
+            .unwrap();
+        let completions = reference.complete(CompletionContext::CharacterData, "").unwrap();
+        assert_eq!(
+            completions,
+            vec![Completion::ReferenceTarget {
+                path: "/Pkg/Pdu".to_string(),
+                dest: EnumItem::ISignalIPdu
+            }]
+        );
+
+        // enum items in character data
+        let packing = pdu
+            .create_sub_element(ElementName::ISignalToPduMappings)
+            .and_then(|mappings| mappings.create_named_sub_element(ElementName::ISignalToIPduMapping, "Mapping"))
+            .and_then(|mapping| mapping.create_sub_element(ElementName::PackingByteOrder))
+            .unwrap();
+        let completions = packing.complete(CompletionContext::CharacterData, "MOST").unwrap();
+        assert_eq!(
+            completions,
+            vec![
+                Completion::EnumItem(EnumItem::MostSignificantByteFirst),
+                Completion::EnumItem(EnumItem::MostSignificantByteLast)
+            ]
+        );
+        assert!(pdu.complete(CompletionContext::CharacterData, "").unwrap().is_empty());
+    }
+}
@@ -0,0 +1,830 @@
+//! Structural comparison of two models or element subtrees
+//!
+//! A text diff of two arxml files becomes useless as soon as a tool reorders the elements in one of them.
+//! The functions in this module compare the element hierarchy instead:
+//!
+//! - identifiable elements are matched by their name, so that elements with the same Autosar path correspond to each other
+//! - unnamed elements are matched using the same keys that are used by [`Element::cmp`]: the element name, the
+//!   content of the INDEX and DEFINITION-REF sub elements, and the DEST attribute.
+//!
+//! Changes in the order of the sub elements are not reported, unless the parent element is ordered
+//! (see [`ElementType::is_ordered`](autosar_data_specification::ElementType::is_ordered)).
+//! Comments and processing instructions are not part of the data, so changes to them are not reported either.
+//!
+//! # Example
+//!
+//! ```
+//! # use autosar_data::*;
+//! # fn main() -> Result<(), AutosarDataError> {
+//! let model_a = AutosarModel::new();
+//! model_a.create_file("a.arxml", AutosarVersion::LATEST)?;
+//! let packages_a = model_a.root_element().create_sub_element(ElementName::ArPackages)?;
+//! packages_a.create_named_sub_element(ElementName::ArPackage, "Pkg")?;
+//!
+//! let model_b = AutosarModel::new();
+//! model_b.create_file("b.arxml", AutosarVersion::LATEST)?;
+//! let packages_b = model_b.root_element().create_sub_element(ElementName::ArPackages)?;
+//! packages_b.create_named_sub_element(ElementName::ArPackage, "Pkg")?;
+//! let new_pkg = packages_b.create_named_sub_element(ElementName::ArPackage, "Pkg2")?;
+//!
+//! let differences = diff::compare_models(&model_a, &model_b);
+//! assert_eq!(differences, vec![diff::Difference::ElementAdded { new: new_pkg }]);
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::patch::Patch;
+use crate::*;
+use fxhash::FxHasher;
+use std::collections::VecDeque;
+use std::hash::{Hash, Hasher};
+
+/// A single difference between two element hierarchies
+///
+/// All variants refer to the elements that are being compared: `old` is always an element
+/// of the old model (or subtree), and `new` is always an element of the new model.
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub enum Difference {
+    /// The element only exists in the new model
+    ElementAdded {
+        /// the added element
+        new: Element,
+    },
+    /// The element only exists in the old model
+    ElementRemoved {
+        /// the removed element
+        old: Element,
+    },
+    /// The element exists in both models, but it has been moved
+    ///
+    /// An identifiable element is moved if it has a different parent in the new model, so that its Autosar path has changed.
+    /// Elements inside an ordered parent are also reported as moved if their position relative to the other sub elements changed.
+    ElementMoved {
+        /// the element in the old model
+        old: Element,
+        /// the element in the new model
+        new: Element,
+    },
+    /// The character data of the element has changed
+    ///
+    /// For elements with mixed content the values contain the concatenation of all character data content items.
+    CharacterDataChanged {
+        /// the element in the old model
+        old: Element,
+        /// the element in the new model
+        new: Element,
+        /// the character data in the old model
+        old_value: Option<CharacterData>,
+        /// the character data in the new model
+        new_value: Option<CharacterData>,
+    },
+    /// An attribute was added to the element
+    AttributeAdded {
+        /// the element in the old model
+        old: Element,
+        /// the element in the new model
+        new: Element,
+        /// name of the added attribute
+        attrname: AttributeName,
+        /// value of the added attribute
+        value: CharacterData,
+    },
+    /// An attribute was removed from the element
+    AttributeRemoved {
+        /// the element in the old model
+        old: Element,
+        /// the element in the new model
+        new: Element,
+        /// name of the removed attribute
+        attrname: AttributeName,
+        /// value of the removed attribute
+        value: CharacterData,
+    },
+    /// The value of an attribute has changed
+    AttributeChanged {
+        /// the element in the old model
+        old: Element,
+        /// the element in the new model
+        new: Element,
+        /// name of the attribute
+        attrname: AttributeName,
+        /// value of the attribute in the old model
+        old_value: CharacterData,
+        /// value of the attribute in the new model
+        new_value: CharacterData,
+    },
+}
+
+/// Compare the content of two models
+///
+/// The root elements of both models are compared with [`compare_elements`].
+/// Files are not considered, so two models that contain the same elements split across different files are equal.
+///
+/// # Example
+///
+/// ```
+/// # use autosar_data::*;
+/// # let model_a = AutosarModel::new();
+/// # model_a.create_file("a.arxml", AutosarVersion::LATEST).unwrap();
+/// # let model_b = AutosarModel::new();
+/// # model_b.create_file("b.arxml", AutosarVersion::LATEST).unwrap();
+/// let differences = diff::compare_models(&model_a, &model_b);
+/// assert!(differences.is_empty());
+/// ```
+#[must_use]
+pub fn compare_models(old: &AutosarModel, new: &AutosarModel) -> Vec<Difference> {
+    compare_elements(&old.root_element(), &new.root_element())
+}
+
+/// Compare two element subtrees
+///
+/// The two elements are assumed to correspond to each other, and their attributes, character data and sub elements are compared.
+/// Identifiable elements that were removed in one place and added in another are reported as [`Difference::ElementMoved`], if
+/// the match is unique. This includes identifiable elements inside of added or removed subtrees; apart from that the sub elements
+/// of an added or removed element are not reported separately.
+///
+/// # Example
+///
+/// ```
+/// # use autosar_data::*;
+/// # let model = AutosarModel::new();
+/// # model.create_file("a.arxml", AutosarVersion::LATEST).unwrap();
+/// # let packages = model.root_element().create_sub_element(ElementName::ArPackages).unwrap();
+/// let pkg_a = packages.create_named_sub_element(ElementName::ArPackage, "A").unwrap();
+/// let pkg_b = packages.create_named_sub_element(ElementName::ArPackage, "B").unwrap();
+/// pkg_b.set_attribute(AttributeName::Uuid, "1234").unwrap();
+/// let differences = diff::compare_elements(&pkg_a, &pkg_b);
+/// assert_eq!(differences.len(), 2); // the SHORT-NAME differs and the UUID attribute was added
+/// ```
+#[must_use]
+pub fn compare_elements(old: &Element, new: &Element) -> Vec<Difference> {
+    compare_with_counterparts(old, new).0
+}
+
+/// Create a [`Patch`] that transforms the old model into the new model
+///
+/// The patch contains the differences found by [`compare_models`] as a sequence of operations. It can be applied
+/// to the old model, or to any other model with similar content, using [`AutosarModel::apply_patch`].
+///
+/// # Example
+///
+/// ```
+/// # use autosar_data::*;
+/// # fn main() -> Result<(), AutosarDataError> {
+/// let model_a = AutosarModel::new();
+/// model_a.create_file("a.arxml", AutosarVersion::LATEST)?;
+/// let model_b = AutosarModel::new();
+/// model_b.create_file("b.arxml", AutosarVersion::LATEST)?;
+/// let packages = model_b.root_element().create_sub_element(ElementName::ArPackages)?;
+/// packages.create_named_sub_element(ElementName::ArPackage, "Pkg")?;
+///
+/// let patch = diff::create_patch(&model_a, &model_b)?;
+/// model_a.apply_patch(&patch)?;
+/// assert!(model_a.get_element_by_path("/Pkg").is_some());
+/// # Ok(())
+/// # }
+/// ```
+///
+/// # Errors
+///
+///  - [`AutosarDataError::ItemDeleted`]: An element was deleted while the patch was being created
+pub fn create_patch(old: &AutosarModel, new: &AutosarModel) -> Result<Patch, AutosarDataError> {
+    let (differences, counterparts) = compare_with_counterparts(&old.root_element(), &new.root_element());
+    Patch::from_differences(&differences, &counterparts)
+}
+
+// compare two elements, and also return the mapping from elements of the new model to their counterparts in the old model
+#[allow(clippy::mutable_key_type)] // WeakElement uses the pointer for hashing, so the key does not change
+pub(crate) fn compare_with_counterparts(
+    old: &Element,
+    new: &Element,
+) -> (Vec<Difference>, FxHashMap<WeakElement, Element>) {
+    let mut differ = Differ::default();
+    differ.compare(old, new);
+    differ.detect_moves();
+    (differ.result, differ.counterparts)
+}
+
+#[derive(Default)]
+struct Differ {
+    result: Vec<Difference>,
+    // maps each element of the new model that has a counterpart in the old model to that counterpart
+    counterparts: FxHashMap<WeakElement, Element>,
+}
+
+/// key used to match sub elements of the old and new element
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum MatchKey {
+    Identifiable(ElementName, String),
+    Unnamed {
+        element_name: ElementName,
+        index: Option<u64>,
+        definition_ref: Option<String>,
+        dest: Option<EnumItem>,
+    },
+}
+
+impl Differ {
+    fn compare(&mut self, old: &Element, new: &Element) {
+        self.counterparts.insert(new.downgrade(), old.clone());
+        self.compare_attributes(old, new);
+
+        if old.content_type() != ContentType::Elements || new.content_type() != ContentType::Elements {
+            let old_value = text_content(old);
+            let new_value = text_content(new);
+            if old_value != new_value {
+                self.result.push(Difference::CharacterDataChanged {
+                    old: old.clone(),
+                    new: new.clone(),
+                    old_value,
+                    new_value,
+                });
+            }
+        }
+
+        self.compare_sub_elements(old, new);
+    }
+
+    fn compare_attributes(&mut self, old: &Element, new: &Element) {
+        for Attribute { attrname, content } in old.attributes() {
+            match new.attribute_value(attrname) {
+                Some(new_value) if new_value != content => self.result.push(Difference::AttributeChanged {
+                    old: old.clone(),
+                    new: new.clone(),
+                    attrname,
+                    old_value: content,
+                    new_value,
+                }),
+                Some(_) => {}
+                None => self.result.push(Difference::AttributeRemoved {
+                    old: old.clone(),
+                    new: new.clone(),
+                    attrname,
+                    value: content,
+                }),
+            }
+        }
+        for Attribute { attrname, content } in new.attributes() {
+            if old.attribute_value(attrname).is_none() {
+                self.result.push(Difference::AttributeAdded {
+                    old: old.clone(),
+                    new: new.clone(),
+                    attrname,
+                    value: content,
+                });
+            }
+        }
+    }
+
+    fn compare_sub_elements(&mut self, old: &Element, new: &Element) {
+        let old_children: Vec<Element> = old.sub_elements().collect();
+        let new_children: Vec<Element> = new.sub_elements().collect();
+        let old_keys: Vec<MatchKey> = old_children.iter().map(match_key).collect();
+        let new_keys: Vec<MatchKey> = new_children.iter().map(match_key).collect();
+
+        let mut old_matches: Vec<Option<usize>> = vec![None; old_children.len()];
+        let mut new_used = vec![false; new_children.len()];
+
+        // first pass: unnamed elements are only matched if their content is identical.
+        // This prevents spurious changes if one of several otherwise indistinguishable elements is removed.
+        let mut fingerprints: FxHashMap<(&MatchKey, u64), VecDeque<usize>> = FxHashMap::default();
+        for (idx, key) in new_keys.iter().enumerate() {
+            if matches!(key, MatchKey::Unnamed { .. }) {
+                let fingerprint = fingerprint(&new_children[idx]);
+                fingerprints.entry((key, fingerprint)).or_default().push_back(idx);
+            }
+        }
+        for (idx, key) in old_keys.iter().enumerate() {
+            if matches!(key, MatchKey::Unnamed { .. })
+                && let Some(candidates) = fingerprints.get_mut(&(key, fingerprint(&old_children[idx])))
+                && let Some(new_idx) = candidates.pop_front()
+            {
+                old_matches[idx] = Some(new_idx);
+                new_used[new_idx] = true;
+            }
+        }
+
+        // second pass: match all remaining elements by key, in the order in which they occur
+        let mut by_key: FxHashMap<&MatchKey, VecDeque<usize>> = FxHashMap::default();
+        for (idx, key) in new_keys.iter().enumerate() {
+            if !new_used[idx] {
+                by_key.entry(key).or_default().push_back(idx);
+            }
+        }
+        for (idx, key) in old_keys.iter().enumerate() {
+            if old_matches[idx].is_none()
+                && let Some(new_idx) = by_key.get_mut(key).and_then(VecDeque::pop_front)
+            {
+                old_matches[idx] = Some(new_idx);
+                new_used[new_idx] = true;
+            }
+        }
+
+        // in ordered elements, the sub elements that are not part of the longest common ordering have been moved
+        let mut moved = vec![false; old_children.len()];
+        if old.element_type().is_ordered() || new.element_type().is_ordered() {
+            let matched: Vec<usize> = (0..old_children.len())
+                .filter(|idx| old_matches[*idx].is_some())
+                .collect();
+            let sequence: Vec<usize> = matched.iter().filter_map(|idx| old_matches[*idx]).collect();
+            let in_sequence = longest_increasing_subsequence(&sequence);
+            for (pos, idx) in matched.iter().enumerate() {
+                moved[*idx] = !in_sequence[pos];
+            }
+        }
+
+        for (idx, old_child) in old_children.iter().enumerate() {
+            if let Some(new_idx) = old_matches[idx] {
+                let new_child = &new_children[new_idx];
+                if moved[idx] {
+                    self.result.push(Difference::ElementMoved {
+                        old: old_child.clone(),
+                        new: new_child.clone(),
+                    });
+                }
+                self.compare(old_child, new_child);
+            } else {
+                self.result.push(Difference::ElementRemoved { old: old_child.clone() });
+            }
+        }
+        for (idx, new_child) in new_children.iter().enumerate() {
+            if !new_used[idx] {
+                self.result.push(Difference::ElementAdded { new: new_child.clone() });
+            }
+        }
+    }
+
+    // identifiable elements that were removed in one place and added in another place have been moved
+    // This also applies to identifiable elements inside of removed or added subtrees, e.g. when an element is moved to a new package.
+    #[allow(clippy::mutable_key_type)] // Element uses the pointer for hashing, so the key does not change
+    fn detect_moves(&mut self) {
+        let mut moved_old = HashSet::new();
+        let mut moved_new = HashSet::new();
+        loop {
+            let mut removed: FxHashMap<MatchKey, Vec<(usize, Element)>> = FxHashMap::default();
+            let mut added: FxHashMap<MatchKey, Vec<(usize, Element)>> = FxHashMap::default();
+            for difference in &self.result {
+                let (candidates, element) = match difference {
+                    Difference::ElementRemoved { old } => (&mut removed, old),
+                    Difference::ElementAdded { new } => (&mut added, new),
+                    _ => continue,
+                };
+                for (depth, sub_element) in element.elements_dfs() {
+                    if sub_element.is_identifiable() {
+                        candidates
+                            .entry(match_key(&sub_element))
+                            .or_default()
+                            .push((depth, sub_element));
+                    }
+                }
+            }
+
+            // only unique matches are considered, and the matches are processed from the top of the hierarchy
+            let mut pairs: Vec<(usize, Element, Element)> = removed
+                .into_iter()
+                .filter_map(|(key, mut old)| match added.get_mut(&key) {
+                    Some(new) if old.len() == 1 && new.len() == 1 => {
+                        let (depth, old) = old.pop()?;
+                        let (_, new) = new.pop()?;
+                        Some((depth, old, new))
+                    }
+                    _ => None,
+                })
+                .filter(|(_, old, new)| !moved_old.contains(old) && !moved_new.contains(new))
+                .collect();
+            pairs.sort_by_key(|(depth, _, _)| *depth);
+
+            let mut found = Vec::new();
+            for (_, old, new) in pairs {
+                // elements inside of a moved element are compared as part of the moved element
+                if is_inside(&old, &moved_old) || is_inside(&new, &moved_new) {
+                    continue;
+                }
+                moved_old.insert(old.clone());
+                moved_new.insert(new.clone());
+                found.push((old, new));
+            }
+            if found.is_empty() {
+                break;
+            }
+
+            // the positions of the removed elements are indexed, so that each of them can be replaced by a move
+            let removed_positions: FxHashMap<Element, usize> = self
+                .result
+                .iter()
+                .enumerate()
+                .filter_map(|(pos, difference)| match difference {
+                    Difference::ElementRemoved { old } => Some((old.clone(), pos)),
+                    _ => None,
+                })
+                .collect();
+            let mut added_moved = HashSet::new();
+            for (old, new) in found {
+                let moved = Difference::ElementMoved {
+                    old: old.clone(),
+                    new: new.clone(),
+                };
+                if let Some(pos) = removed_positions.get(&old) {
+                    self.result[*pos] = moved;
+                } else {
+                    self.result.push(moved);
+                }
+                self.compare(&old, &new);
+                added_moved.insert(new);
+            }
+            self.result.retain(
+                |difference| !matches!(difference, Difference::ElementAdded { new } if added_moved.contains(new)),
+            );
+        }
+    }
+}
+
+// check if the element is one of the given elements, or a sub element of one of them
+#[allow(clippy::mutable_key_type)] // Element uses the pointer for hashing, so the key does not change
+fn is_inside(element: &Element, ancestors: &HashSet<Element>) -> bool {
+    let mut current = Some(element.clone());
+    while let Some(elem) = current {
+        if ancestors.contains(&elem) {
+            return true;
+        }
+        current = elem.parent().ok().flatten();
+    }
+    false
+}
+
+fn match_key(element: &Element) -> MatchKey {
+    let element_name = element.element_name();
+    if let Some(name) = element.item_name() {
+        MatchKey::Identifiable(element_name, name)
+    } else {
+        let (index, definition_ref, dest) = element_keys(element);
+        MatchKey::Unnamed {
+            element_name,
+            index,
+            definition_ref,
+            dest,
+        }
+    }
+}
+
+// get the keys that identify an unnamed element among its siblings: the INDEX, the DEFINITION-REF and the DEST attribute
+pub(crate) fn element_keys(element: &Element) -> (Option<u64>, Option<String>, Option<EnumItem>) {
+    let index = element
+        .get_sub_element(ElementName::Index)
+        .and_then(|indexelem| indexelem.character_data())
+        .and_then(|cdata| cdata.parse_integer::<u64>());
+    let definition_ref = element
+        .get_sub_element(ElementName::DefinitionRef)
+        .and_then(|defref| defref.character_data())
+        .and_then(|cdata| cdata.string_value());
+    let dest = element
+        .attribute_value(AttributeName::Dest)
+        .and_then(|cdata| cdata.enum_value());
+    (index, definition_ref, dest)
+}
+
+// get the character data of an element; the content items of elements with mixed content are concatenated
+pub(crate) fn text_content(element: &Element) -> Option<CharacterData> {
+    let mut items: Vec<CharacterData> = element.content().filter_map(|item| item.unwrap_cdata()).collect();
+    match items.len() {
+        0 => None,
+        1 => items.pop(),
+        _ => Some(CharacterData::String(
+            items.iter().map(ToString::to_string).collect::<Vec<_>>().concat(),
+        )),
+    }
+}
+
+// calculate a hash of the complete content of an element
+fn fingerprint(element: &Element) -> u64 {
+    let mut hasher = FxHasher::default();
+    hash_content(element, &mut hasher);
+    hasher.finish()
+}
+
+fn hash_content(element: &Element, hasher: &mut FxHasher) {
+    element.element_name().hash(hasher);
+    for Attribute { attrname, content } in element.attributes() {
+        attrname.hash(hasher);
+        content.to_string().hash(hasher);
+    }
+    for item in element.content() {
+        match item {
+            ElementContent::Element(sub_element) => hash_content(&sub_element, hasher),
+            ElementContent::CharacterData(cdata) => cdata.to_string().hash(hasher),
+            // comments and processing instructions are not part of the data
+            ElementContent::Comment(_) | ElementContent::ProcessingInstruction(_) => {}
+        }
+    }
+}
+
+// find the longest increasing subsequence of the input; the result marks the members of the subsequence
+fn longest_increasing_subsequence(sequence: &[usize]) -> Vec<bool> {
+    // tails[len] is the position of the smallest value that ends an increasing subsequence of length len + 1
+    let mut tails: Vec<usize> = Vec::new();
+    let mut predecessors: Vec<Option<usize>> = vec![None; sequence.len()];
+    for (pos, value) in sequence.iter().enumerate() {
+        let len = tails.partition_point(|tail_pos| sequence[*tail_pos] < *value);
+        predecessors[pos] = if len > 0 { Some(tails[len - 1]) } else { None };
+        if len == tails.len() {
+            tails.push(pos);
+        } else {
+            tails[len] = pos;
+        }
+    }
+
+    let mut result = vec![false; sequence.len()];
+    let mut current = tails.last().copied();
+    while let Some(pos) = current {
+        result[pos] = true;
+        current = predecessors[pos];
+    }
+    result
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn create_model(version: AutosarVersion) -> (AutosarModel, Element) {
+        let model = AutosarModel::new();
+        model.create_file("test.arxml", version).unwrap();
+        let packages = model
+            .root_element()
+            .create_sub_element(ElementName::ArPackages)
+            .unwrap();
+        (model, packages)
+    }
+
+    #[test]
+    fn identical_models() {
+        let (model_a, packages_a) = create_model(AutosarVersion::LATEST);
+        let (model_b, packages_b) = create_model(AutosarVersion::LATEST);
+        assert!(compare_models(&model_a, &model_b).is_empty());
+
+        // the order of elements in unordered parents does not matter
+        packages_a
+            .create_named_sub_element(ElementName::ArPackage, "A")
+            .unwrap();
+        packages_a
+            .create_named_sub_element(ElementName::ArPackage, "B")
+            .unwrap();
+        packages_b
+            .create_named_sub_element(ElementName::ArPackage, "B")
+            .unwrap();
+        packages_b
+            .create_named_sub_element(ElementName::ArPackage, "A")
+            .unwrap();
+        assert!(compare_models(&model_a, &model_b).is_empty());
+        assert!(compare_models(&model_a, &model_a).is_empty());
+    }
+
+    #[test]
+    fn added_removed_changed() {
+        let (model_a, packages_a) = create_model(AutosarVersion::LATEST);
+        let (model_b, packages_b) = create_model(AutosarVersion::LATEST);
+        let pkg_a = packages_a
+            .create_named_sub_element(ElementName::ArPackage, "Pkg")
+            .unwrap();
+        let removed = packages_a
+            .create_named_sub_element(ElementName::ArPackage, "Removed")
+            .unwrap();
+        let pkg_b = packages_b
+            .create_named_sub_element(ElementName::ArPackage, "Pkg")
+            .unwrap();
+        let added = packages_b
+            .create_named_sub_element(ElementName::ArPackage, "Added")
+            .unwrap();
+
+        pkg_a.set_attribute(AttributeName::Uuid, "uuid-1").unwrap();
+        pkg_b.set_attribute(AttributeName::Uuid, "uuid-2").unwrap();
+        pkg_a.set_attribute(AttributeName::T, "2024-01-01").unwrap();
+        pkg_b.set_attribute(AttributeName::S, "something").unwrap();
+
+        let elements_a = pkg_a.create_sub_element(ElementName::Elements).unwrap();
+        let elements_b = pkg_b.create_sub_element(ElementName::Elements).unwrap();
+        let signal_a = elements_a
+            .create_named_sub_element(ElementName::ISignal, "Signal")
+            .unwrap();
+        let signal_b = elements_b
+            .create_named_sub_element(ElementName::ISignal, "Signal")
+            .unwrap();
+        let length_a = signal_a.create_sub_element(ElementName::Length).unwrap();
+        length_a.set_character_data("8").unwrap();
+        let length_b = signal_b.create_sub_element(ElementName::Length).unwrap();
+        length_b.set_character_data("16").unwrap();
+
+        let differences = compare_models(&model_a, &model_b);
+        assert_eq!(differences.len(), 6);
+        assert!(differences.contains(&Difference::ElementRemoved { old: removed }));
+        assert!(differences.contains(&Difference::ElementAdded { new: added }));
+        assert!(differences.contains(&Difference::AttributeChanged {
+            old: pkg_a.clone(),
+            new: pkg_b.clone(),
+            attrname: AttributeName::Uuid,
+            old_value: CharacterData::String("uuid-1".to_string()),
+            new_value: CharacterData::String("uuid-2".to_string()),
+        }));
+        assert!(differences.contains(&Difference::AttributeRemoved {
+            old: pkg_a.clone(),
+            new: pkg_b.clone(),
+            attrname: AttributeName::T,
+            value: CharacterData::String("2024-01-01".to_string()),
+        }));
+        assert!(differences.contains(&Difference::AttributeAdded {
+            old: pkg_a.clone(),
+            new: pkg_b.clone(),
+            attrname: AttributeName::S,
+            value: CharacterData::String("something".to_string()),
+        }));
+        assert!(differences.contains(&Difference::CharacterDataChanged {
+            old: length_a,
+            new: length_b,
+            old_value: Some(CharacterData::String("8".to_string())),
+            new_value: Some(CharacterData::String("16".to_string())),
+        }));
+    }
+
+    #[test]
+    fn moved_identifiable() {
+        let (model_a, packages_a) = create_model(AutosarVersion::LATEST);
+        let (model_b, packages_b) = create_model(AutosarVersion::LATEST);
+        let elements_a = packages_a
+            .create_named_sub_element(ElementName::ArPackage, "Pkg1")
+            .and_then(|pkg| pkg.create_sub_element(ElementName::Elements))
+            .unwrap();
+        packages_a
+            .create_named_sub_element(ElementName::ArPackage, "Pkg2")
+            .unwrap();
+        packages_b
+            .create_named_sub_element(ElementName::ArPackage, "Pkg1")
+            .unwrap();
+        let elements_b = packages_b
+            .create_named_sub_element(ElementName::ArPackage, "Pkg2")
+            .and_then(|pkg| pkg.create_sub_element(ElementName::Elements))
+            .unwrap();
+        let signal_a = elements_a
+            .create_named_sub_element(ElementName::ISignal, "Signal")
+            .unwrap();
+        let signal_b = elements_b
+            .create_named_sub_element(ElementName::ISignal, "Signal")
+            .unwrap();
+        signal_a.set_attribute(AttributeName::Uuid, "uuid-1").unwrap();
+        signal_b.set_attribute(AttributeName::Uuid, "uuid-2").unwrap();
+
+        let differences = compare_models(&model_a, &model_b);
+        assert_eq!(differences.len(), 4);
+        // the ELEMENTS of Pkg1 were removed, and the ELEMENTS of Pkg2 were added
+        assert!(differences.contains(&Difference::ElementRemoved { old: elements_a }));
+        assert!(differences.contains(&Difference::ElementAdded { new: elements_b }));
+        // but the signal itself was moved and modified
+        assert!(differences.contains(&Difference::ElementMoved {
+            old: signal_a.clone(),
+            new: signal_b.clone()
+        }));
+        assert!(differences.contains(&Difference::AttributeChanged {
+            old: signal_a,
+            new: signal_b,
+            attrname: AttributeName::Uuid,
+            old_value: CharacterData::String("uuid-1".to_string()),
+            new_value: CharacterData::String("uuid-2".to_string()),
+        }));
+    }
+
+    #[test]
+    fn ordered_elements() {
+        let (model_a, packages_a) = create_model(AutosarVersion::LATEST);
+        let (model_b, packages_b) = create_model(AutosarVersion::LATEST);
+        let arguments_a = packages_a
+            .create_named_sub_element(ElementName::ArPackage, "Pkg")
+            .and_then(|pkg| pkg.create_sub_element(ElementName::Elements))
+            .and_then(|elements| elements.create_named_sub_element(ElementName::BswModuleEntry, "Entry"))
+            .and_then(|entry| entry.create_sub_element(ElementName::Arguments))
+            .unwrap();
+        let arguments_b = packages_b
+            .create_named_sub_element(ElementName::ArPackage, "Pkg")
+            .and_then(|pkg| pkg.create_sub_element(ElementName::Elements))
+            .and_then(|elements| elements.create_named_sub_element(ElementName::BswModuleEntry, "Entry"))
+            .and_then(|entry| entry.create_sub_element(ElementName::Arguments))
+            .unwrap();
+        let names_a = ["arg1", "arg2", "arg3", "arg4"];
+        let names_b = ["arg2", "arg3", "arg1", "arg4"];
+        let args_a: Vec<Element> = names_a
+            .iter()
+            .map(|name| {
+                arguments_a
+                    .create_named_sub_element(ElementName::SwServiceArg, name)
+                    .unwrap()
+            })
+            .collect();
+        let args_b: Vec<Element> = names_b
+            .iter()
+            .map(|name| {
+                arguments_b
+                    .create_named_sub_element(ElementName::SwServiceArg, name)
+                    .unwrap()
+            })
+            .collect();
+
+        // only arg1 was moved, the relative order of the other arguments is unchanged
+        let differences = compare_models(&model_a, &model_b);
+        assert_eq!(
+            differences,
+            vec![Difference::ElementMoved {
+                old: args_a[0].clone(),
+                new: args_b[2].clone()
+            }]
+        );
+    }
+
+    #[test]
+    fn unnamed_elements() {
+        let (model_a, packages_a) = create_model(AutosarVersion::LATEST);
+        let (model_b, packages_b) = create_model(AutosarVersion::LATEST);
+        let system_a = packages_a
+            .create_named_sub_element(ElementName::ArPackage, "Pkg")
+            .and_then(|pkg| pkg.create_sub_element(ElementName::Elements))
+            .and_then(|elements| elements.create_named_sub_element(ElementName::System, "System"))
+            .unwrap();
+        let system_b = packages_b
+            .create_named_sub_element(ElementName::ArPackage, "Pkg")
+            .and_then(|pkg| pkg.create_sub_element(ElementName::Elements))
+            .and_then(|elements| elements.create_named_sub_element(ElementName::System, "System"))
+            .unwrap();
+        let fibex_a = system_a.create_sub_element(ElementName::FibexElements).unwrap();
+        let fibex_b = system_b.create_sub_element(ElementName::FibexElements).unwrap();
+
+        let create_ref = |parent: &Element, target: &str| {
+            let conditional = parent
+                .create_sub_element(ElementName::FibexElementRefConditional)
+                .unwrap();
+            let fibex_ref = conditional.create_sub_element(ElementName::FibexElementRef).unwrap();
+            fibex_ref.set_attribute(AttributeName::Dest, EnumItem::ISignal).unwrap();
+            fibex_ref.set_character_data(target).unwrap();
+            (conditional, fibex_ref)
+        };
+        create_ref(&fibex_a, "/Pkg/A");
+        let (removed, _) = create_ref(&fibex_a, "/Pkg/B");
+        create_ref(&fibex_a, "/Pkg/C");
+        create_ref(&fibex_b, "/Pkg/C");
+        create_ref(&fibex_b, "/Pkg/A");
+
+        // the identical references are matched regardless of their position, so only the conditional containing B is removed
+        let differences = compare_models(&model_a, &model_b);
+        assert_eq!(differences, vec![Difference::ElementRemoved { old: removed.clone() }]);
+
+        // a reference that cannot be matched by its content is matched by its key
+        fibex_a.remove_sub_element(removed).unwrap();
+        let (_, ref_d_a) = create_ref(&fibex_a, "/Pkg/D");
+        let (_, ref_d_b) = create_ref(&fibex_b, "/Pkg/E");
+        let differences = compare_elements(&fibex_a, &fibex_b);
+        assert_eq!(
+            differences,
+            vec![Difference::CharacterDataChanged {
+                old: ref_d_a.clone(),
+                new: ref_d_b.clone(),
+                old_value: Some(CharacterData::String("/Pkg/D".to_string())),
+                new_value: Some(CharacterData::String("/Pkg/E".to_string())),
+            }]
+        );
+
+        // the DEST attribute is part of the key: the references no longer match
+        ref_d_b
+            .set_attribute(AttributeName::Dest, EnumItem::ISignalIPdu)
+            .unwrap();
+        let differences = compare_elements(&fibex_a, &fibex_b);
+        assert_eq!(
+            differences,
+            vec![
+                Difference::ElementRemoved { old: ref_d_a },
+                Difference::ElementAdded { new: ref_d_b },
+            ]
+        );
+    }
+
+    #[test]
+    fn lis() {
+        assert_eq!(longest_increasing_subsequence(&[]), Vec::<bool>::new());
+        assert_eq!(longest_increasing_subsequence(&[0, 1, 2]), vec![true, true, true]);
+        assert_eq!(
+            longest_increasing_subsequence(&[1, 2, 0, 3]),
+            vec![true, true, false, true]
+        );
+        assert_eq!(
+            longest_increasing_subsequence(&[3, 0, 1, 2]),
+            vec![false, true, true, true]
+        );
+    }
+
+    #[test]
+    fn traits() {
+        let (model, _) = create_model(AutosarVersion::LATEST);
+        let difference = Difference::ElementAdded {
+            new: model.root_element(),
+        };
+        let difference2 = difference.clone();
+        assert_eq!(difference, difference2);
+        assert!(!format!("{difference:?}").is_empty());
+    }
+}
@@ -3,6 +3,8 @@ use std::hash::Hash;
 use std::str::FromStr;
 
 use super::*;
+use crate::lossless::{ElementText, OriginalText};
+use autosar_data_specification::ElementMultiplicity;
 
 impl Element {
     /// Get the parent element of the current element
@@ -72,7 +74,7 @@ impl Element {
     }
 
     pub(crate) fn set_parent(&self, new_parent: ElementOrModel) {
-        self.0.write().set_parent(new_parent);
+        self.write_lock().set_parent(new_parent);
     }
 
     /// Get the [`ElementName`] of the element
@@ -175,7 +177,22 @@ impl Element {
         }
         let model = self.model()?;
         let version = self.min_version()?;
-        self.0.write().set_item_name(new_name, &model, version)
+        let old_name = self.item_name();
+        let result = self.write_lock().set_item_name(new_name, &model, version);
+        if result.is_ok()
+            && let Some(old_name) = old_name
+            && old_name != new_name
+        {
+            self.mark_files_modified();
+            model.queue_event(|| ModelEvent::ElementRenamed {
+                element: self.clone(),
+                old_name,
+                new_name: new_name.to_string(),
+            });
+        }
+        // references to the element may have been updated even if renaming failed later on
+        model.dispatch_events();
+        result
     }
 
     /// Returns true if the element is identifiable
@@ -394,10 +411,11 @@ impl Element {
     ///  - [`AutosarDataError::NoFilesInModel`]: The operation cannot be completed because the model does not contain any files
     pub fn create_sub_element(&self, element_name: ElementName) -> Result<Element, AutosarDataError> {
         let version = self.min_version()?;
-        self.0
-            .try_write()
+        let result = self
+            .try_write_lock()
             .ok_or(AutosarDataError::ParentElementLocked)?
-            .create_sub_element(self.downgrade(), element_name, version)
+            .create_sub_element(self.downgrade(), element_name, version);
+        self.notify_created(result)
     }
 
     /// Create a sub element at the specified insertion position
@@ -406,6 +424,8 @@ impl Element {
     /// It is not possible to create named sub elements with this function; use `create_named_sub_element_at`() for that instead.
     ///
     /// The specified insertion position will be compared to the range of valid insertion positions; if it falls outside that range then the function fails.
+    /// The position is an index into the content of this element (see [`Element::content`]), so character data, comments
+    /// and processing instructions are counted as well as sub elements.
     ///
     /// # Example
     ///
@@ -436,9 +456,10 @@ impl Element {
         position: usize,
     ) -> Result<Element, AutosarDataError> {
         let version = self.min_version()?;
-        self.0
-            .write()
-            .create_sub_element_at(self.downgrade(), element_name, position, version)
+        let result = self
+            .write_lock()
+            .create_sub_element_at(self.downgrade(), element_name, position, version);
+        self.notify_created(result)
     }
 
     /// Create a named/identifiable sub element at a suitable insertion position
@@ -477,15 +498,17 @@ impl Element {
     ) -> Result<Element, AutosarDataError> {
         let model = self.model()?;
         let version = self.min_version()?;
-        self.0
-            .write()
-            .create_named_sub_element(self.downgrade(), element_name, item_name, &model, version)
+        let result =
+            self.write_lock()
+                .create_named_sub_element(self.downgrade(), element_name, item_name, &model, version);
+        self.notify_created(result)
     }
 
     /// Create a named/identifiable sub element at the specified insertion position
     ///
     /// The given `ElementName` must be allowed on a sub element in this element, taking into account any sub elements that may already exist.
     /// The specified insertion position will be compared to the range of valid insertion positions; if it falls outside that range then the function fails.
+    /// Like in `create_sub_element_at`(), the position is an index into the content of this element, including comments.
     ///
     /// This method can only be used to create identifiable sub elements.
     ///
@@ -521,9 +544,15 @@ impl Element {
     ) -> Result<Element, AutosarDataError> {
         let model = self.model()?;
         let version = self.min_version()?;
-        self.0
-            .write()
-            .create_named_sub_element_at(self.downgrade(), element_name, item_name, position, &model, version)
+        let result = self.write_lock().create_named_sub_element_at(
+            self.downgrade(),
+            element_name,
+            item_name,
+            position,
+            &model,
+            version,
+        );
+        self.notify_created(result)
     }
 
     /// Create a deep copy of the given element and insert it as a sub-element
@@ -575,14 +604,24 @@ impl Element {
         }
         let model = self.model()?;
         let version = self.min_version()?;
-        self.0
-            .write()
-            .create_copied_sub_element(self.downgrade(), other, &model, version)
+        // the comment of the other element is read first, since its parent may be this element
+        let comment = other.comment();
+        let result = self
+            .write_lock()
+            .create_copied_sub_element(self.downgrade(), other, &model, version);
+        if let Ok(copy) = &result
+            && comment.is_some()
+        {
+            copy.set_comment(comment);
+        }
+        self.notify_created(result)
     }
 
     /// Create a deep copy of the given element and insert it as a sub-element at the given position
     ///
     /// The other element must be a permissible sub-element in this element and not conflict with any existing sub element.
+    /// The position is an index into the content of this element, see [`Element::content`]. If the other element has a
+    /// comment, then the copied comment is inserted at this position and the copy follows it.
     /// The other element can originate from any loaded [`AutosarModel`], it does not have to originate from the same model or file as the current element.
     ///
     /// The [`AutosarVersion`] of the other element might differ from the version of the current file;
@@ -630,9 +669,31 @@ impl Element {
         }
         let model = self.model()?;
         let version = self.min_version()?;
-        self.0
-            .write()
-            .create_copied_sub_element_at(self.downgrade(), other, position, &model, version)
+        // the comment of the other element is read first, since its parent may be this element
+        let comment = other.comment();
+        let result = self
+            .write_lock()
+            .create_copied_sub_element_at(self.downgrade(), other, position, &model, version);
+        if let Ok(copy) = &result
+            && comment.is_some()
+        {
+            copy.set_comment(comment);
+        }
+        self.notify_created(result)
+    }
+
+    // notify the observers of the model about a newly created sub element
+    fn notify_created(&self, result: Result<Element, AutosarDataError>) -> Result<Element, AutosarDataError> {
+        if let Ok(element) = &result
+            && let Ok(model) = self.model()
+        {
+            element.mark_files_modified();
+            model.notify_observers(|| ModelEvent::ElementCreated {
+                element: element.clone(),
+                parent: self.clone(),
+            });
+        }
+        result
     }
 
     /// Take an `element` from it's current location and place it in this element as a sub element
@@ -682,15 +743,23 @@ impl Element {
                 version_new: version_src,
             });
         }
-        self.0
-            .write()
-            .move_element_here(self.downgrade(), move_element, &model, &model_src, version)
+        let old_parent = move_element.parent()?;
+        let result = self
+            .write_lock()
+            .move_element_here(self.downgrade(), move_element, &model, &model_src, version);
+        // moving an element into its current parent without a position does not change anything
+        let old_parent = old_parent.filter(|parent| parent != self);
+        self.notify_moved(&result, old_parent, &model, &model_src);
+        result
     }
 
     /// Take an `element` from it's current location and place it at the given position in this element as a sub element
     ///
     /// The moved element can be taken from anywhere - even from a different arxml document that is not part of the same `AutosarModel`
     ///
+    /// The position is an index into the content of this element, see [`Element::content`]. The comment of the moved
+    /// element is moved with it and is placed directly in front of it.
+    ///
     /// Restrictions:
     /// 1) The element must have a compatible element type. If it could not have been created here, then it can't be moved either.
     /// 2) The origin document of the element must have exactly the same `AutosarVersion` as the destination.
@@ -735,9 +804,43 @@ impl Element {
                 version_new: version_src,
             });
         }
-        self.0
-            .write()
-            .move_element_here_at(self.downgrade(), move_element, position, &model, &model_src, version)
+        let old_parent = move_element.parent()?;
+        let result = self.write_lock().move_element_here_at(
+            self.downgrade(),
+            move_element,
+            position,
+            &model,
+            &model_src,
+            version,
+        );
+        self.notify_moved(&result, old_parent, &model, &model_src);
+        result
+    }
+
+    // notify the observers of both models about a moved element, and deliver the events that were queued during the move
+    fn notify_moved(
+        &self,
+        result: &Result<Element, AutosarDataError>,
+        old_parent: Option<Element>,
+        model: &AutosarModel,
+        model_src: &AutosarModel,
+    ) {
+        if let Ok(element) = result
+            && let Some(old_parent) = old_parent
+        {
+            element.mark_files_modified();
+            old_parent.mark_files_modified();
+            let make_event = || ModelEvent::ElementMoved {
+                element: element.clone(),
+                old_parent: old_parent.clone(),
+                new_parent: self.clone(),
+            };
+            if model_src != model {
+                model_src.notify_observers(make_event);
+            }
+            model.queue_event(make_event);
+        }
+        model.dispatch_events();
     }
 
     /// Remove the sub element `sub_element`
@@ -769,7 +872,18 @@ impl Element {
     ///  - [`AutosarDataError::ShortNameRemovalForbidden`]: It is not permitted to remove the SHORT-NAME of identifiable elements since this would result in invalid data
     pub fn remove_sub_element(&self, sub_element: Element) -> Result<(), AutosarDataError> {
         let model = self.model()?;
-        self.0.write().remove_sub_element(sub_element, &model)
+        self.write_lock().remove_sub_element(sub_element.clone(), &model)?;
+        // a removed element no longer inherits the files of its parent, but it may have been restricted to some of them
+        if sub_element.file_membership_local().is_empty() {
+            self.mark_files_modified();
+        } else {
+            sub_element.mark_files_modified();
+        }
+        model.notify_observers(|| ModelEvent::ElementRemoved {
+            element: sub_element,
+            parent: self.clone(),
+        });
+        Ok(())
     }
 
     /// Remove a sub element identified by an ElementName
@@ -908,7 +1022,7 @@ impl Element {
         };
 
         let version = self.min_version()?;
-        let mut element = self.0.write();
+        let mut element = self.write_lock();
         // set the DEST attribute first - this could fail if the target element has the wrong type
         if element
             .set_attribute_internal(AttributeName::Dest, CharacterData::Enum(enum_item), version)
@@ -920,12 +1034,12 @@ impl Element {
         let opt_old_ref = element.character_data().and_then(|cdata| cdata.string_value());
 
         // if this reference previously referenced some other element, update
-        if let Some(old_ref) = opt_old_ref {
+        if let Some(old_ref) = &opt_old_ref {
             let old_base = element
                 .attribute_value(AttributeName::Base)
                 .and_then(|cdata| cdata.string_value());
             model.fix_reference_origins(
-                &old_ref,
+                old_ref,
                 &target_string,
                 old_base.as_deref(),
                 base_label,
@@ -957,7 +1071,7 @@ impl Element {
         }
 
         // do the update
-        element.set_character_data(CharacterData::String(target_string), version)?;
+        element.set_character_data(CharacterData::String(target_string.clone()), version)?;
         if let Some(base_label) = base_label {
             element.set_attribute_internal(
                 AttributeName::Base,
@@ -968,6 +1082,14 @@ impl Element {
             // ensure BASE does not remain when retargeting from a relative reference to an absolute reference
             element.remove_attribute(AttributeName::Base);
         }
+        drop(element);
+
+        self.mark_files_modified();
+        model.notify_observers(|| ModelEvent::ReferenceRetargeted {
+            element: self.clone(),
+            old_target: opt_old_ref,
+            new_target: Some(target_string),
+        });
 
         Ok(())
     }
@@ -1075,6 +1197,7 @@ impl Element {
     ///  - [`AutosarDataError::ParentElementLocked`]: a parent element was locked and did not become available after waiting briefly.
     ///    The operation was aborted to avoid a deadlock, but can be retried.
     ///  - [`AutosarDataError::IncorrectContentType`]: Cannot set character data on an element which does not contain character data
+    ///  - [`AutosarDataError::DuplicateItemName`]: The first name set in a SHORT-NAME is already used by another element
     pub fn set_character_data<T: Into<CharacterData>>(&self, value: T) -> Result<(), AutosarDataError> {
         let chardata: CharacterData = value.into();
         self.set_character_data_internal(chardata)
@@ -1101,12 +1224,25 @@ impl Element {
             if compatible_value {
                 // if this is a SHORT-NAME element a whole lot of handling is needed in order to unbreak all the cross references
                 let mut prev_path = None;
-                if self.element_name() == ElementName::ShortName {
-                    // this SHORT-NAME element might be newly created, in which case there is no previous path
-                    if self.character_data().is_some()
-                        && let Some(parent) = self.parent()?
-                    {
+                let mut old_name = None;
+                let mut first_name_path = None;
+                if self.element_name() == ElementName::ShortName
+                    && let Some(parent) = self.parent()?
+                {
+                    if let Some(cdata) = self.character_data() {
                         prev_path = Some(parent.path()?);
+                        old_name = cdata.string_value();
+                    } else if parent.is_identifiable() {
+                        // this SHORT-NAME element is newly created, so the parent becomes identifiable with this name
+                        let named_parent_path = parent.named_parent()?.map(|named| named.path()).transpose()?;
+                        let new_path = format!("{}/{chardata}", named_parent_path.unwrap_or_default());
+                        if model.get_element_by_path(&new_path).is_some() {
+                            return Err(AutosarDataError::DuplicateItemName {
+                                element: parent.element_name(),
+                                item_name: chardata.to_string(),
+                            });
+                        }
+                        first_name_path = Some((new_path, parent));
                     }
                 };
 
@@ -1125,17 +1261,21 @@ impl Element {
 
                 // update the character data
                 {
-                    let mut element = self.0.write();
-                    element.content.clear();
-                    element.content.push(ElementContent::CharacterData(chardata));
+                    let mut element = self.write_lock();
+                    element.replace_content_with_chardata(chardata);
+                    element.mark_modified();
                 }
 
                 // short-name: make sure the hashmap in the top-level AutosarModel is updated so that this element can still be found
+                let mut renamed_parent = None;
                 if let Some(prev_path) = prev_path
                     && let Some(parent) = self.parent()?
                 {
                     let new_path = parent.path()?;
                     model.fix_identifiables(&prev_path, &new_path);
+                    renamed_parent = Some(parent);
+                } else if let Some((new_path, parent)) = first_name_path {
+                    model.add_identifiable(new_path, parent.downgrade());
                 }
 
                 // reference: update the references hashmap in the top-level AutosarModel
@@ -1145,9 +1285,9 @@ impl Element {
                     let base = self
                         .attribute_value(AttributeName::Base)
                         .and_then(|cdata| cdata.string_value());
-                    if let Some(old_refval) = old_refval {
+                    if let Some(old_refval) = &old_refval {
                         model.fix_reference_origins(
-                            &old_refval,
+                            old_refval,
                             &refval,
                             base.as_deref(),
                             base.as_deref(),
@@ -1156,6 +1296,11 @@ impl Element {
                     } else {
                         model.add_reference_origin(&refval, base.as_deref(), self.downgrade());
                     }
+                    model.queue_event(|| ModelEvent::ReferenceRetargeted {
+                        element: self.clone(),
+                        old_target: old_refval,
+                        new_target: Some(refval),
+                    });
                 } else if self.element_name() == ElementName::ShortLabel
                     && let Ok(Some(parent)) = self.parent()
                     && parent.element_name() == ElementName::ReferenceBase
@@ -1176,6 +1321,19 @@ impl Element {
                     }
                 }
 
+                if let Some(parent) = renamed_parent {
+                    let new_name = self.character_data().and_then(|cdata| cdata.string_value());
+                    model.queue_event(|| ModelEvent::ElementRenamed {
+                        element: parent,
+                        old_name: old_name.unwrap_or_default(),
+                        new_name: new_name.unwrap_or_default(),
+                    });
+                } else if !elemtype.is_ref() {
+                    model.queue_event(|| ModelEvent::CharacterDataChanged { element: self.clone() });
+                }
+                self.mark_files_modified();
+                model.dispatch_events();
+
                 return Ok(());
             }
         }
@@ -1219,17 +1377,30 @@ impl Element {
             if self.element_name() == ElementName::ShortName {
                 Err(AutosarDataError::ShortNameRemovalForbidden)
             } else {
-                if self.character_data().is_some() {
-                    if self.is_reference() {
-                        let model = self.model()?;
-                        if let Some(CharacterData::String(reference)) = self.character_data() {
-                            let base = self
-                                .attribute_value(AttributeName::Base)
-                                .and_then(|cdata| cdata.string_value());
-                            model.remove_reference_origin(&reference, base.as_deref(), self.downgrade());
-                        }
+                if let Some(cdata) = self.character_data() {
+                    let model = self.model()?;
+                    let is_reference = self.is_reference();
+                    if is_reference && let CharacterData::String(reference) = &cdata {
+                        let base = self
+                            .attribute_value(AttributeName::Base)
+                            .and_then(|cdata| cdata.string_value());
+                        model.remove_reference_origin(reference, base.as_deref(), self.downgrade());
+                    }
+                    {
+                        let mut element = self.write_lock();
+                        element.content.retain(|item| item.is_markup());
+                        element.mark_modified();
+                    }
+                    self.mark_files_modified();
+                    if is_reference {
+                        model.notify_observers(|| ModelEvent::ReferenceRetargeted {
+                            element: self.clone(),
+                            old_target: cdata.string_value(),
+                            new_target: None,
+                        });
+                    } else {
+                        model.notify_observers(|| ModelEvent::CharacterDataChanged { element: self.clone() });
                     }
-                    self.0.write().content.clear();
                 }
                 Ok(())
             }
@@ -1245,6 +1416,9 @@ impl Element {
     /// This method only applies to elements which contain mixed data, i.e. `element.content_type`() == Mixed.
     /// Use `create_sub_element_at` to add an element instead of a character data item
     ///
+    /// The position is an index into the content of this element, which also contains sub elements, comments and
+    /// processing instructions.
+    ///
     /// # Example
     ///
     /// ```
@@ -1267,28 +1441,36 @@ impl Element {
     ///  - [`AutosarDataError::IncorrectContentType`] the element `content_type` is not Mixed
     ///  - [`AutosarDataError::InvalidPosition`] the position is not valid
     pub fn insert_character_content_item(&self, chardata: &str, position: usize) -> Result<(), AutosarDataError> {
-        let mut element = self.0.write();
-        if let ContentMode::Mixed = element.elemtype.content_mode() {
-            if position <= element.content.len() {
-                element.content.insert(
-                    position,
-                    ElementContent::CharacterData(CharacterData::String(chardata.to_owned())),
-                );
-                Ok(())
+        let model = self.model()?;
+        {
+            let mut element = self.write_lock();
+            if let ContentMode::Mixed = element.elemtype.content_mode() {
+                if position <= element.content.len() {
+                    element.content.insert(
+                        position,
+                        ElementContent::CharacterData(CharacterData::String(chardata.to_owned())),
+                    );
+                    element.mark_modified();
+                } else {
+                    return Err(AutosarDataError::InvalidPosition);
+                }
             } else {
-                Err(AutosarDataError::InvalidPosition)
+                return Err(AutosarDataError::IncorrectContentType {
+                    element: element.element_name(),
+                });
             }
-        } else {
-            Err(AutosarDataError::IncorrectContentType {
-                element: element.element_name(),
-            })
         }
+        self.mark_files_modified();
+        model.notify_observers(|| ModelEvent::CharacterDataChanged { element: self.clone() });
+        Ok(())
     }
 
     /// Remove a character data item from the content of this element
     ///
     /// This method only applies to elements which contain mixed data, i.e. `element.content_type` == Mixed
     ///
+    /// The position is an index into the content of this element, see [`Element::content`].
+    ///
     /// # Example
     ///
     /// ```
@@ -1311,20 +1493,210 @@ impl Element {
     ///  - [`AutosarDataError::IncorrectContentType`] the element `content_type` is not Mixed
     ///  - [`AutosarDataError::InvalidPosition`] the position is not valid
     pub fn remove_character_content_item(&self, position: usize) -> Result<(), AutosarDataError> {
-        let mut element = self.0.write();
-        if let ContentMode::Mixed = element.elemtype.content_mode() {
-            if position < element.content.len()
-                && let ElementContent::CharacterData(_) = element.content[position]
-            {
-                element.content.remove(position);
-                return Ok(());
+        let model = self.model()?;
+        {
+            let mut element = self.write_lock();
+            if let ContentMode::Mixed = element.elemtype.content_mode() {
+                if position < element.content.len()
+                    && let ElementContent::CharacterData(_) = element.content[position]
+                {
+                    element.content.remove(position);
+                    element.mark_modified();
+                } else {
+                    return Err(AutosarDataError::InvalidPosition);
+                }
+            } else {
+                return Err(AutosarDataError::IncorrectContentType {
+                    element: element.element_name(),
+                });
             }
-            Err(AutosarDataError::InvalidPosition)
-        } else {
-            Err(AutosarDataError::IncorrectContentType {
-                element: element.element_name(),
-            })
         }
+        self.mark_files_modified();
+        model.notify_observers(|| ModelEvent::CharacterDataChanged { element: self.clone() });
+        Ok(())
+    }
+
+    /// Insert a comment into the content of this element
+    ///
+    /// Comments can be inserted in any element. The comment is written at the given position in the content,
+    /// i.e. before the content item that is currently at this position. A comment that is directly followed by a
+    /// sub element would become the comment of that element when the file is loaded again, see [`Element::comment`].
+    ///
+    /// If the comment contains "--", then this is replaced with "__", because "--" is forbidden inside XML comments.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use autosar_data::*;
+    /// # fn main() -> Result<(), AutosarDataError> {
+    /// # let model = AutosarModel::new();
+    /// # let file = model.create_file("test", AutosarVersion::Autosar_00050).unwrap();
+    /// # let pkg = model.root_element().create_sub_element(ElementName::ArPackages)
+    /// #   .and_then(|e| e.create_named_sub_element(ElementName::ArPackage, "Pkg"))?;
+    /// // add a comment after the SHORT-NAME of the package
+    /// pkg.insert_comment("end of the package", 1)?;
+    /// assert_eq!(pkg.comments(), vec![(1, "end of the package".to_string())]);
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    ///  - [`AutosarDataError::InvalidPosition`] the position is not valid
+    pub fn insert_comment(&self, comment: &str, position: usize) -> Result<(), AutosarDataError> {
+        // make sure the comment we store never contains "--" as this is forbidden by the w3 xml specification
+        let comment = comment.replace("--", "__");
+        self.insert_markup(ElementContent::Comment(comment), position)
+    }
+
+    /// Insert a processing instruction into the content of this element
+    ///
+    /// The instruction is the text between `<?` and `?>`, i.e. the target followed by the instruction data.
+    /// It is inserted at the given position in the content, like a comment inserted by [`Element::insert_comment`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use autosar_data::*;
+    /// # fn main() -> Result<(), AutosarDataError> {
+    /// # let model = AutosarModel::new();
+    /// # let file = model.create_file("test", AutosarVersion::Autosar_00050).unwrap();
+    /// # let pkg = model.root_element().create_sub_element(ElementName::ArPackages)
+    /// #   .and_then(|e| e.create_named_sub_element(ElementName::ArPackage, "Pkg"))?;
+    /// pkg.insert_processing_instruction("tool-info generated", 1)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    ///  - [`AutosarDataError::InvalidProcessingInstruction`] the instruction is empty, contains '>', or its target is "xml"
+    ///  - [`AutosarDataError::InvalidPosition`] the position is not valid
+    pub fn insert_processing_instruction(&self, instruction: &str, position: usize) -> Result<(), AutosarDataError> {
+        let target = instruction.split_ascii_whitespace().next().unwrap_or_default();
+        if target.is_empty() || target.eq_ignore_ascii_case("xml") || instruction.contains('>') {
+            return Err(AutosarDataError::InvalidProcessingInstruction);
+        }
+        self.insert_markup(ElementContent::ProcessingInstruction(instruction.to_owned()), position)
+    }
+
+    fn insert_markup(&self, item: ElementContent, position: usize) -> Result<(), AutosarDataError> {
+        {
+            let mut element = self.write_lock();
+            if position > element.content.len() {
+                return Err(AutosarDataError::InvalidPosition);
+            }
+            element.content.insert(position, item);
+            element.mark_modified();
+        }
+        self.notify_markup_changed();
+        Ok(())
+    }
+
+    /// Remove a comment from the content of this element
+    ///
+    /// The position is an index into the content of this element, as returned by [`Element::comments`].
+    /// This also removes the comment of the following sub element, see [`Element::comment`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use autosar_data::*;
+    /// # fn main() -> Result<(), AutosarDataError> {
+    /// # let model = AutosarModel::new();
+    /// # let file = model.create_file("test", AutosarVersion::Autosar_00050).unwrap();
+    /// # let pkg = model.root_element().create_sub_element(ElementName::ArPackages)
+    /// #   .and_then(|e| e.create_named_sub_element(ElementName::ArPackage, "Pkg"))?;
+    /// pkg.insert_comment("comment", 1)?;
+    /// pkg.remove_comment(1)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    ///  - [`AutosarDataError::InvalidPosition`] there is no comment at this position
+    pub fn remove_comment(&self, position: usize) -> Result<(), AutosarDataError> {
+        self.remove_markup(position, |item| matches!(item, ElementContent::Comment(_)))
+    }
+
+    /// Remove a processing instruction from the content of this element
+    ///
+    /// The position is an index into the content of this element, see [`Element::content`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use autosar_data::*;
+    /// # fn main() -> Result<(), AutosarDataError> {
+    /// # let model = AutosarModel::new();
+    /// # let file = model.create_file("test", AutosarVersion::Autosar_00050).unwrap();
+    /// # let pkg = model.root_element().create_sub_element(ElementName::ArPackages)
+    /// #   .and_then(|e| e.create_named_sub_element(ElementName::ArPackage, "Pkg"))?;
+    /// pkg.insert_processing_instruction("tool-info", 1)?;
+    /// pkg.remove_processing_instruction(1)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    ///  - [`AutosarDataError::InvalidPosition`] there is no processing instruction at this position
+    pub fn remove_processing_instruction(&self, position: usize) -> Result<(), AutosarDataError> {
+        self.remove_markup(position, |item| {
+            matches!(item, ElementContent::ProcessingInstruction(_))
+        })
+    }
+
+    fn remove_markup(&self, position: usize, is_kind: fn(&ElementContent) -> bool) -> Result<(), AutosarDataError> {
+        {
+            let mut element = self.write_lock();
+            if !element.content.get(position).is_some_and(is_kind) {
+                return Err(AutosarDataError::InvalidPosition);
+            }
+            element.content.remove(position);
+            element.mark_modified();
+        }
+        self.notify_markup_changed();
+        Ok(())
+    }
+
+    /// report a change of a comment or processing instruction to the observers of the model
+    fn notify_markup_changed(&self) {
+        self.mark_files_modified();
+        if let Ok(model) = self.model() {
+            model.notify_observers(|| ModelEvent::MarkupChanged { element: self.clone() });
+        }
+    }
+
+    /// Get the comments in the content of this element, together with their positions
+    ///
+    /// The comment in front of this element is part of the content of its parent, so it is not included here.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use autosar_data::*;
+    /// # fn main() -> Result<(), AutosarDataError> {
+    /// # let model = AutosarModel::new();
+    /// # let file = model.create_file("test", AutosarVersion::Autosar_00050).unwrap();
+    /// # let pkg = model.root_element().create_sub_element(ElementName::ArPackages)
+    /// #   .and_then(|e| e.create_named_sub_element(ElementName::ArPackage, "Pkg"))?;
+    /// for (position, comment) in pkg.comments() {
+    ///     println!("{position}: {comment}");
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[must_use]
+    pub fn comments(&self) -> Vec<(usize, String)> {
+        self.0
+            .read()
+            .content
+            .iter()
+            .enumerate()
+            .filter_map(|(position, item)| item.unwrap_comment().map(|comment| (position, comment)))
+            .collect()
     }
 
     /// returns the number of content items in this element
@@ -1375,7 +1747,8 @@ impl Element {
 
     /// Create an iterator over all of the content of this element
     ///
-    /// The iterator can return both sub elements and character data, wrapped as `ElementContent::Element` and `ElementContent::CharacterData`
+    /// as well as all comments and processing instructions
+    /// as well as comments and processing instructions which are not attached to a sub element
     ///
     /// This method is intended to be used with elements that contain mixed content.
     ///
@@ -1390,6 +1763,10 @@ impl Element {
     ///     match content_item {
     ///         ElementContent::CharacterData(data) => {},
     ///         ElementContent::Element(element) => {},
+    ///         ElementContent::Comment(comment) => {},
+    ///         ElementContent::ProcessingInstruction(instruction) => {},
+    ///         // ElementContent is non-exhaustive, so further kinds of content can be added
+    ///         _ => {},
     ///     }
     /// }
     /// ```
@@ -1424,6 +1801,9 @@ impl Element {
     /// None may be returned if the element has been deleted, or for the root element (AUTOSAR) which has no parent.
     /// The returned position can be used with `get_sub_element_at()`.
     ///
+    /// The position is the index of the element in the content of its parent (see [`Element::content`]), not its
+    /// index among the sub elements: comments, processing instructions and character data in front of it are counted.
+    ///
     /// # Example
     ///
     /// ```
@@ -1441,12 +1821,7 @@ impl Element {
     #[must_use]
     pub fn position(&self) -> Option<usize> {
         if let Ok(Some(parent)) = self.parent() {
-            parent
-                .0
-                .read()
-                .content
-                .iter()
-                .position(|ec| matches!(ec, ElementContent::Element(elem) if elem == self))
+            parent.0.read().sub_element_position(self)
         } else {
             None
         }
@@ -1503,7 +1878,9 @@ impl Element {
 
     /// Get the sub element at the given position.
     ///
-    /// Returns None if no such element exists.
+    /// The position is an index into the content of this element (see [`Element::content`]), which also contains
+    /// comments, processing instructions and character data.
+    /// Returns None if no such element exists, or if the content item at this position is not a sub element.
     ///
     /// # Example
     ///
@@ -1558,7 +1935,7 @@ impl Element {
     ///  - [`AutosarDataError::NoFilesInModel`]: The operation cannot be completed because the model does not contain any files
     pub fn get_or_create_sub_element(&self, name: ElementName) -> Result<Element, AutosarDataError> {
         let version = self.min_version()?;
-        let mut locked_elem = self.0.try_write().ok_or(AutosarDataError::ParentElementLocked)?;
+        let mut locked_elem = self.try_write_lock().ok_or(AutosarDataError::ParentElementLocked)?;
         for item in &locked_elem.content {
             if let ElementContent::Element(subelem) = item
                 && subelem.element_name() == name
@@ -1566,7 +1943,9 @@ impl Element {
                 return Ok(subelem.clone());
             }
         }
-        locked_elem.create_sub_element(self.downgrade(), name, version)
+        let result = locked_elem.create_sub_element(self.downgrade(), name, version);
+        drop(locked_elem);
+        self.notify_created(result)
     }
     /// Get or create a named sub element
     ///
@@ -1605,7 +1984,7 @@ impl Element {
     ) -> Result<Element, AutosarDataError> {
         let model = self.model()?;
         let version = self.min_version()?;
-        let mut locked_elem = self.0.try_write().ok_or(AutosarDataError::ParentElementLocked)?;
+        let mut locked_elem = self.try_write_lock().ok_or(AutosarDataError::ParentElementLocked)?;
         for item in &locked_elem.content {
             if let ElementContent::Element(subelem) = item
                 && subelem.element_name() == element_name
@@ -1614,7 +1993,217 @@ impl Element {
                 return Ok(subelem.clone());
             }
         }
-        locked_elem.create_named_sub_element(self.downgrade(), element_name, item_name, &model, version)
+        let result = locked_elem.create_named_sub_element(self.downgrade(), element_name, item_name, &model, version);
+        drop(locked_elem);
+        self.notify_created(result)
+    }
+
+    /// Create all attributes and sub elements of this element that are required by the specification
+    ///
+    /// The specification of the file version is used to find the required attributes and sub elements. Missing ones are
+    /// created with placeholder values that are valid according to the specification, and then the same is done recursively
+    /// for all sub elements, including the ones that already existed.
+    ///
+    /// Identifiable elements get a unique name which is derived from the element name, e.g. `ISignalIPdu`.
+    /// Other elements with character content get the first valid value, e.g. the first enum item of the version,
+    /// an empty string or zero. This also applies to references, so the references must be set to their targets afterwards.
+    ///
+    /// Most sub elements are optional in the Autosar specification, so in practice this mainly creates missing SHORT-NAMEs,
+    /// sets required attributes like DEST in references, and sets the character data of empty elements.
+    /// If one of several alternative sub elements is required, then none of them is created, since only the caller can choose.
+    /// The changes are made in a transaction: if an error occurs, then all of them are reverted.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use autosar_data::*;
+    /// # fn main() -> Result<(), AutosarDataError> {
+    /// # let model = AutosarModel::new();
+    /// # model.create_file("test", AutosarVersion::Autosar_00050)?;
+    /// # let elements = model.root_element().create_sub_element(ElementName::ArPackages)?
+    /// #   .create_named_sub_element(ElementName::ArPackage, "Pkg")?
+    /// #   .create_sub_element(ElementName::Elements)?;
+    /// let reference = elements
+    ///     .create_named_sub_element(ElementName::System, "System")?
+    ///     .create_sub_element(ElementName::FibexElements)?
+    ///     .create_sub_element(ElementName::FibexElementRefConditional)?
+    ///     .create_sub_element(ElementName::FibexElementRef)?;
+    /// reference.fill_required()?;
+    /// // the required DEST attribute has been set
+    /// assert!(reference.attribute_value(AttributeName::Dest).is_some());
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    ///  - [`AutosarDataError::ItemDeleted`]: The current element is in the deleted state and will be freed once the last reference is dropped
+    ///  - [`AutosarDataError::ParentElementLocked`]: a parent element was locked and did not become available after waiting briefly.
+    ///    The operation was aborted to avoid a deadlock, but can be retried.
+    ///  - [`AutosarDataError::NoFilesInModel`]: The operation cannot be completed because the model does not contain any files
+    ///  - [`AutosarDataError::ElementInsertionConflict`]: A required sub element conflicts with an existing sub element.
+    pub fn fill_required(&self) -> Result<(), AutosarDataError> {
+        let model = self.model()?;
+        let version = self.min_version()?;
+        let transaction = model.begin_transaction();
+        match self.fill_required_internal(&model, version) {
+            Ok(()) => {
+                transaction.commit();
+                Ok(())
+            }
+            Err(err) => {
+                transaction.rollback();
+                Err(err)
+            }
+        }
+    }
+
+    fn fill_required_internal(&self, model: &AutosarModel, version: AutosarVersion) -> Result<(), AutosarDataError> {
+        let elemtype = self.element_type();
+        for (attrname, spec, required) in elemtype.attribute_spec_iter() {
+            if required
+                && self.attribute_value(attrname).is_none()
+                && elemtype
+                    .find_attribute_spec(attrname)
+                    .is_some_and(|attr_spec| version.compatible(attr_spec.version))
+                && let Some(value) = CharacterData::placeholder(spec, version)
+            {
+                self.set_attribute(attrname, value)?;
+            }
+        }
+
+        match elemtype.content_mode() {
+            ContentMode::Characters => {
+                if self.character_data().is_none()
+                    && let Some(spec) = elemtype.chardata_spec()
+                    && let Some(value) = CharacterData::placeholder(spec, version)
+                {
+                    self.set_character_data(value)?;
+                }
+            }
+            ContentMode::Sequence => {
+                for (name, sub_elemtype) in validation::required_sub_elements(elemtype, version) {
+                    if self.get_sub_element(name).is_some() {
+                        continue;
+                    }
+                    if name == ElementName::ShortName {
+                        let parent_path = self.named_parent()?.map(|parent| parent.path()).transpose()?;
+                        let item_name = placeholder_item_name(model, parent_path.as_deref(), self.element_name());
+                        self.create_sub_element(name)?.set_character_data(item_name)?;
+                    } else if sub_elemtype.is_named_in_version(version) {
+                        let parent = if self.is_identifiable() {
+                            Some(self.clone())
+                        } else {
+                            self.named_parent()?
+                        };
+                        let parent_path = parent.map(|parent| parent.path()).transpose()?;
+                        let item_name = placeholder_item_name(model, parent_path.as_deref(), name);
+                        self.create_named_sub_element(name, &item_name)?;
+                    } else {
+                        self.create_sub_element(name)?;
+                    }
+                }
+            }
+            ContentMode::Choice | ContentMode::Bag | ContentMode::Mixed => {}
+        }
+
+        let sub_elements: Vec<Element> = self.sub_elements().collect();
+        for sub_element in sub_elements {
+            sub_element.fill_required_internal(model, version)?;
+        }
+        Ok(())
+    }
+
+    /// Check this element and all of its sub elements against the Autosar specification
+    ///
+    /// The element is checked using the `AutosarVersion` of each file that contains it.
+    /// See [`AutosarModel::validate`] for details.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use autosar_data::*;
+    /// # fn main() -> Result<(), AutosarDataError> {
+    /// # let model = AutosarModel::new();
+    /// # model.create_file("filename", AutosarVersion::LATEST)?;
+    /// # let element = model.root_element();
+    /// let diagnostics = element.validate()?;
+    /// assert!(diagnostics.is_empty());
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    ///  - [`AutosarDataError::ItemDeleted`]: The current element is in the deleted state and will be freed once the last reference is dropped
+    ///  - [`AutosarDataError::ParentElementLocked`]: a parent element was locked and did not become available after waiting briefly.
+    ///    The operation was aborted to avoid a deadlock, but can be retried.
+    ///  - [`AutosarDataError::NoFilesInModel`]: The operation cannot be completed because the model does not contain any files
+    pub fn validate(&self) -> Result<Vec<ValidationDiagnostic>, AutosarDataError> {
+        validation::validate_element(self)
+    }
+
+    /// List the completions for a cursor position in this element
+    ///
+    /// The `context` describes what is being edited, and only completions whose text starts with `prefix` are returned.
+    /// The results depend on the context:
+    /// - `SubElement`: all sub elements that are valid in the element, see [`Element::list_valid_sub_elements`]
+    /// - `AttributeName`: all attributes that are valid in the version of the file, and are not present yet
+    /// - `AttributeValue`: all enum items of the attribute that are valid in the version of the file, e.g. the DEST values of a reference
+    /// - `CharacterData`: for references, all identifiable elements that can be the target of the reference.
+    ///   If the DEST attribute is set, then only targets with a matching DEST value are included. For other elements
+    ///   all enum items that are valid in the version of the file.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use autosar_data::*;
+    /// # fn main() -> Result<(), AutosarDataError> {
+    /// # let model = AutosarModel::new();
+    /// # model.create_file("test", AutosarVersion::Autosar_00050)?;
+    /// let packages = model.root_element().create_sub_element(ElementName::ArPackages)?;
+    /// for completion in packages.complete(CompletionContext::SubElement, "AR-")? {
+    ///     println!("{}", completion.text());
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    ///  - [`AutosarDataError::ItemDeleted`]: The current element is in the deleted state and will be freed once the last reference is dropped
+    ///  - [`AutosarDataError::ParentElementLocked`]: a parent element was locked and did not become available after waiting briefly.
+    ///    The operation was aborted to avoid a deadlock, but can be retried.
+    ///  - [`AutosarDataError::NoFilesInModel`]: The operation cannot be completed because the model does not contain any files
+    pub fn complete(&self, context: CompletionContext, prefix: &str) -> Result<Vec<Completion>, AutosarDataError> {
+        completion::complete(self, context, prefix)
+    }
+
+    /// Find all elements that match a query, starting from this element
+    ///
+    /// See [`Query`] for a description of the query syntax. Relative queries are evaluated starting from this element,
+    /// while absolute queries start from the root element of the model.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use autosar_data::*;
+    /// # fn main() -> Result<(), AutosarDataError> {
+    /// # let model = AutosarModel::new();
+    /// # model.create_file("filename", AutosarVersion::LATEST)?;
+    /// # let element = model.root_element();
+    /// for package in element.query("AR-PACKAGES/AR-PACKAGE")? {
+    ///     // [...]
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    ///  - [`AutosarDataError::InvalidQuery`]: The query could not be parsed
+    pub fn query(&self, query: &str) -> Result<QueryIterator, AutosarDataError> {
+        Ok(Query::new(query)?.evaluate_from(self))
     }
 
     /// Create a depth first iterator over this element and all of its sub elements
@@ -1733,9 +2322,11 @@ impl Element {
         let version = self.min_version()?;
         let (old_base, reference_value) = self.base_attribute_info(attrname);
 
-        self.0.write().set_attribute_internal(attrname, value.into(), version)?;
+        self.write_lock()
+            .set_attribute_internal(attrname, value.into(), version)?;
 
         self.base_attribute_fixup(attrname, old_base, reference_value);
+        self.notify_attribute_changed(attrname);
 
         Ok(())
     }
@@ -1765,10 +2356,11 @@ impl Element {
         let version = self.min_version()?;
         let (old_base, reference_value) = self.base_attribute_info(attrname);
 
-        self.0.write().set_attribute_string(attrname, stringvalue, version)?;
+        self.write_lock().set_attribute_string(attrname, stringvalue, version)?;
 
         // post-change fixup for BASE attribute changes only
         self.base_attribute_fixup(attrname, old_base, reference_value);
+        self.notify_attribute_changed(attrname);
 
         Ok(())
     }
@@ -1791,15 +2383,27 @@ impl Element {
     pub fn remove_attribute(&self, attrname: AttributeName) -> bool {
         let (old_base, reference_value) = self.base_attribute_info(attrname);
 
-        let removed = self.0.write().remove_attribute(attrname);
+        let removed = self.write_lock().remove_attribute(attrname);
 
         if removed {
             // fix the cache of reference origins if a BASE attribute was removed
             self.base_attribute_fixup(attrname, old_base, reference_value);
+            self.notify_attribute_changed(attrname);
         }
         removed
     }
 
+    /// report a change of an attribute to the observers of the model
+    fn notify_attribute_changed(&self, attrname: AttributeName) {
+        self.mark_files_modified();
+        if let Ok(model) = self.model() {
+            model.notify_observers(|| ModelEvent::AttributeChanged {
+                element: self.clone(),
+                attribute: attrname,
+            });
+        }
+    }
+
     /// helper function to get the old BASE attribute value and the reference value before changing or removing the attribute
     fn base_attribute_info(&self, attrname: AttributeName) -> (Option<String>, Option<String>) {
         if !self.is_reference() || attrname != AttributeName::Base {
@@ -1848,7 +2452,8 @@ impl Element {
     /// element.sort();
     /// ```
     pub fn sort(&self) {
-        self.0.write().sort();
+        self.write_lock().sort();
+        self.mark_files_modified();
     }
 
     /// Serialize the element and all of its content to a string
@@ -1873,58 +2478,94 @@ impl Element {
         outstring
     }
 
-    pub(crate) fn serialize_internal(
+    pub(crate) fn serialize_internal<O: SerializeOutput>(
         &self,
-        outstring: &mut String,
+        outstring: &mut O,
         indent: usize,
         inline: bool,
         for_file: &Option<WeakArxmlFile>,
     ) {
-        let element = self.0.read();
-        let element_name = element.elemname.to_str();
+        self.serialize_formatted(outstring, indent, inline, for_file, &SerializeOptions::default(), None);
+    }
 
-        if let Some(comment) = &self.0.read().comment {
-            // put the comment on a separate line
+    pub(crate) fn serialize_formatted<O: SerializeOutput>(
+        &self,
+        outstring: &mut O,
+        indent: usize,
+        inline: bool,
+        for_file: &Option<WeakArxmlFile>,
+        options: &SerializeOptions,
+        original: Option<&OriginalText>,
+    ) {
+        // the comment of the root element is not part of the content of another element, so it is written here
+        let is_root = matches!(self.0.read().parent, ElementOrModel::Model(_));
+        if is_root && let Some(comment) = self.comment() {
             if !inline {
-                Self::serialize_newline_indent(outstring, indent);
+                options.write_newline_indent(outstring, indent);
             }
-            outstring.push_str("<!--");
-            outstring.push_str(comment);
-            outstring.push_str("-->");
+            ElementContent::Comment(comment).serialize_text(outstring);
         }
 
         // write the opening tag on a new line and indent it
         if !inline {
-            Self::serialize_newline_indent(outstring, indent);
+            options.write_newline_indent(outstring, indent);
+        }
+
+        self.serialize_body(outstring, indent, for_file, options, original);
+    }
+
+    // serialize the element from its opening tag to its closing tag
+    pub(crate) fn serialize_body<O: SerializeOutput>(
+        &self,
+        outstring: &mut O,
+        indent: usize,
+        for_file: &Option<WeakArxmlFile>,
+        options: &SerializeOptions,
+        original: Option<&OriginalText>,
+    ) {
+        if let Some(original) = original
+            && self.serialize_original(outstring, indent, for_file, options, original)
+        {
+            return;
         }
 
+        let element = self.0.read();
+        let element_name = element.elemname.to_str();
+
         if !element.content.is_empty() {
             outstring.push('<');
             outstring.push_str(element_name);
-            self.serialize_attributes(outstring);
+            self.serialize_attributes(outstring, options);
             outstring.push('>');
 
             match self.content_type() {
                 ContentType::Elements => {
-                    // serialize each sub-element
-                    for subelem in self.sub_elements() {
-                        if for_file.is_none()
-                            || subelem.0.read().file_membership.is_empty()
-                            || subelem.0.read().file_membership.contains(for_file.as_ref().unwrap())
-                        {
-                            subelem.serialize_internal(outstring, indent + 1, false, for_file);
+                    // serialize each sub-element, and put comments and processing instructions on separate lines
+                    for (position, item) in element.content.iter().enumerate() {
+                        if !item_in_file(&element.content, position, for_file) {
+                            continue;
+                        }
+                        match item {
+                            ElementContent::Element(subelem) => {
+                                subelem.serialize_formatted(outstring, indent + 1, false, for_file, options, original);
+                            }
+                            ElementContent::Comment(_) | ElementContent::ProcessingInstruction(_) => {
+                                options.write_newline_indent(outstring, indent + 1);
+                                item.serialize_text(outstring);
+                            }
+                            ElementContent::CharacterData(_) => {}
                         }
                     }
                     // put the closing tag on a new line and indent it
-                    Self::serialize_newline_indent(outstring, indent);
+                    options.write_newline_indent(outstring, indent);
                     outstring.push_str("</");
                     outstring.push_str(element_name);
                     outstring.push('>');
                 }
                 ContentType::CharacterData => {
                     // write the character data on the same line as the opening tag
-                    if let Some(ElementContent::CharacterData(chardata)) = element.content.first() {
-                        chardata.serialize_internal(outstring);
+                    for item in &element.content {
+                        item.serialize_text(outstring);
                     }
 
                     // write the closing tag on the same line
@@ -1933,19 +2574,15 @@ impl Element {
                     outstring.push('>');
                 }
                 ContentType::Mixed => {
-                    for item in self.content() {
+                    for (position, item) in element.content.iter().enumerate() {
+                        if !item_in_file(&element.content, position, for_file) {
+                            continue;
+                        }
                         match item {
                             ElementContent::Element(subelem) => {
-                                if for_file.is_none()
-                                    || subelem.0.read().file_membership.is_empty()
-                                    || subelem.0.read().file_membership.contains(for_file.as_ref().unwrap())
-                                {
-                                    subelem.serialize_internal(outstring, indent + 1, true, for_file);
-                                }
-                            }
-                            ElementContent::CharacterData(chardata) => {
-                                chardata.serialize_internal(outstring);
+                                subelem.serialize_formatted(outstring, indent + 1, true, for_file, options, original);
                             }
+                            _ => item.serialize_text(outstring),
                         }
                     }
                     // write the closing tag on the same line
@@ -1957,28 +2594,98 @@ impl Element {
         } else {
             outstring.push('<');
             outstring.push_str(element_name);
-            self.serialize_attributes(outstring);
-            outstring.push('/');
-            outstring.push('>');
+            self.serialize_attributes(outstring, options);
+            match options.empty_elements {
+                EmptyElementStyle::SelfClosing => outstring.push_str("/>"),
+                EmptyElementStyle::StartEndTags => {
+                    outstring.push_str("></");
+                    outstring.push_str(element_name);
+                    outstring.push('>');
+                }
+            }
         }
     }
 
-    fn serialize_newline_indent(outstring: &mut String, indent: usize) {
-        outstring.push('\n');
-        for _ in 0..indent {
-            outstring.push_str("  ");
+    // get the content items that belong to the file, if they still correspond to the recorded text of the element.
+    // Other files can add items to the element, and items can be added to or removed from the file after loading
+    fn original_content(
+        &self,
+        for_file: &Option<WeakArxmlFile>,
+        element_text: &ElementText,
+    ) -> Option<Vec<ElementContent>> {
+        let element = self.0.read();
+        let content: Vec<ElementContent> = element
+            .content
+            .iter()
+            .enumerate()
+            .filter(|(position, _)| item_in_file(&element.content, *position, for_file))
+            .map(|(_, item)| item.clone())
+            .collect();
+        let matches_recorded = content.len() == element_text.content.len()
+            && content
+                .iter()
+                .zip(&element_text.content)
+                .all(|(item, (_, item_text))| matches!(item, ElementContent::Element(_)) == item_text.is_none());
+        matches_recorded.then_some(content)
+    }
+
+    // serialize an unmodified element using the text it had when it was loaded
+    // Returns false if the original text can't be used, because the element or its content in this file has changed
+    fn serialize_original<O: SerializeOutput>(
+        &self,
+        outstring: &mut O,
+        indent: usize,
+        for_file: &Option<WeakArxmlFile>,
+        options: &SerializeOptions,
+        original: &OriginalText,
+    ) -> bool {
+        let Some(element_text) = original.element_text(self) else {
+            return false;
+        };
+        let Some(content) = self.original_content(for_file, element_text) else {
+            return false;
+        };
+
+        outstring.push_str(original.text(&element_text.start_tag));
+        for (item, (gap, item_text)) in content.iter().zip(&element_text.content) {
+            outstring.push_str(original.text(gap));
+            match item {
+                ElementContent::Element(subelem) => {
+                    subelem.serialize_body(outstring, indent + 1, for_file, options, Some(original));
+                }
+                _ => {
+                    if let Some(item_text) = item_text {
+                        outstring.push_str(original.text(item_text));
+                    } else {
+                        item.serialize_text(outstring);
+                    }
+                }
+            }
         }
+        outstring.push_str(original.text(&element_text.end_gap));
+        outstring.push_str(original.text(&element_text.end_tag));
+        true
+    }
+
+    // check if the element is part of the file that is being serialized
+    fn is_in_file(&self, for_file: &Option<WeakArxmlFile>) -> bool {
+        let element = self.0.read();
+        for_file
+            .as_ref()
+            .is_none_or(|file| element.file_membership.is_empty() || element.file_membership.contains(file))
     }
 
-    fn serialize_attributes(&self, outstring: &mut String) {
+    fn serialize_attributes<O: SerializeOutput>(&self, outstring: &mut O, options: &SerializeOptions) {
         let element = self.0.read();
         if !element.attributes.is_empty() {
+            let quote = options.quote_char();
             for attribute in &element.attributes {
                 outstring.push(' ');
                 outstring.push_str(attribute.attrname.to_str());
-                outstring.push_str("=\"");
+                outstring.push('=');
+                outstring.push(quote);
                 attribute.content.serialize_internal(outstring);
-                outstring.push('"');
+                outstring.push(quote);
             }
         }
     }
@@ -2168,6 +2875,83 @@ impl Element {
         self.0.read().file_membership.clone()
     }
 
+    /// find the sub element that occupies the same place as `other`, which is an element of a different parent
+    ///
+    /// Identifiable elements are matched by their element name and item name. Other elements are matched by their
+    /// element name if only one of them can exist. Elements that can occur any number of times are never matched.
+    pub(crate) fn find_corresponding_sub_element(&self, other: &Element) -> Result<Option<Element>, AutosarDataError> {
+        let element_name = other.element_name();
+        if let Some(item_name) = other.item_name() {
+            return Ok(self.sub_elements().find(|sub_element| {
+                sub_element.element_name() == element_name && sub_element.item_name().as_ref() == Some(&item_name)
+            }));
+        }
+
+        if self.sub_element_is_repeatable(element_name)? {
+            Ok(None)
+        } else {
+            // only one sub element of this kind can exist
+            Ok(self.get_sub_element(element_name))
+        }
+    }
+
+    /// check if a sub element with the given name can occur any number of times in this element
+    pub(crate) fn sub_element_is_repeatable(&self, element_name: ElementName) -> Result<bool, AutosarDataError> {
+        let element_type = self.element_type();
+        let version = self.min_version()?;
+        let multiplicity = element_type
+            .find_sub_element(element_name, version as u32)
+            .and_then(|(_, indices)| element_type.get_sub_element_multiplicity(&indices));
+        Ok(multiplicity == Some(ElementMultiplicity::Any))
+    }
+
+    /// check if this element is a sub element of the ancestor, at any depth
+    pub(crate) fn is_sub_element_of(&self, ancestor: &Element) -> bool {
+        let mut current = self.parent().ok().flatten();
+        while let Some(elem) = current {
+            if elem == *ancestor {
+                return true;
+            }
+            current = elem.parent().ok().flatten();
+        }
+        false
+    }
+
+    /// mark all files that contain this element as modified
+    pub(crate) fn mark_files_modified(&self) {
+        if let Ok((_, files)) = self.file_membership() {
+            for file in files.iter().filter_map(WeakArxmlFile::upgrade) {
+                file.0.write().modified = true;
+            }
+        }
+    }
+
+    /// lock the element for writing, and record its state in the active transactions
+    ///
+    /// All modifications of the element must use this function, so that they can be reverted by a transaction.
+    pub(crate) fn write_lock(&self) -> parking_lot::RwLockWriteGuard<'_, ElementRaw> {
+        let element_locked = self.0.write();
+        transaction::record_element(self, &element_locked);
+        element_locked
+    }
+
+    /// try to lock the element for writing without blocking, see [`Element::write_lock`]
+    pub(crate) fn try_write_lock(&self) -> Option<parking_lot::RwLockWriteGuard<'_, ElementRaw>> {
+        let element_locked = self.0.try_write()?;
+        transaction::record_element(self, &element_locked);
+        Some(element_locked)
+    }
+
+    /// try to lock the element for writing until the timeout expires, see [`Element::write_lock`]
+    pub(crate) fn try_write_lock_for(
+        &self,
+        timeout: std::time::Duration,
+    ) -> Option<parking_lot::RwLockWriteGuard<'_, ElementRaw>> {
+        let element_locked = self.0.try_write_for(timeout)?;
+        transaction::record_element(self, &element_locked);
+        Some(element_locked)
+    }
+
     /// set the file membership of an element
     ///
     /// The passed set acts as a restriction of the file membership of the parent element.
@@ -2185,7 +2969,7 @@ impl Element {
             .map_or(u32::MAX, |p| p.element_type().splittable());
         // can always reset the membership to empty = inherited; otherwise the parent must be splittable
         if file_membership.is_empty() || parent_splittable != 0 {
-            self.0.write().file_membership = file_membership;
+            self.write_lock().file_membership = file_membership;
         }
     }
 
@@ -2223,12 +3007,17 @@ impl Element {
                 if !current_fileset.contains(&weak_file) {
                     let mut updated_fileset = current_fileset;
                     updated_fileset.insert(weak_file);
-                    self.0.write().file_membership = updated_fileset;
+                    self.write_lock().file_membership = updated_fileset;
 
                     // recursively continue with the parent
                     if let Some(parent) = self.parent()? {
+                        // the parent now has a different set of sub elements in the file
+                        parent.write_lock().mark_modified();
                         parent.add_to_file_restricted(file)?;
                     }
+                    self.mark_files_modified();
+                    file.model()?
+                        .notify_observers(|| ModelEvent::FileMembershipChanged { element: self.clone() });
                 }
                 Ok(())
             } else {
@@ -2251,7 +3040,7 @@ impl Element {
             // which does not include the new file
             if self.element_type().splittable() != 0 {
                 for se in self.sub_elements() {
-                    if let Some(mut subelem) = se.0.try_write()
+                    if let Some(mut subelem) = se.try_write_lock()
                         && subelem.file_membership.is_empty()
                     {
                         subelem.file_membership.clone_from(&current_fileset);
@@ -2264,11 +3053,12 @@ impl Element {
             // if the parent is splittable, or if the current element already has a fileset, then that fileset should be updated
             let parent_splittable = self.parent()?.is_none_or(|p| p.element_type().splittable() != 0);
             if parent_splittable || local {
-                self.0.write().file_membership = extended_fileset;
+                self.write_lock().file_membership = extended_fileset;
             }
 
             // recursively continue with the parent
             if let Some(parent) = self.parent()? {
+                parent.write_lock().mark_modified();
                 parent.add_to_file_restricted(file)?;
             }
         }
@@ -2312,22 +3102,29 @@ impl Element {
                 // current_fileset is the set of files which contain the current element
                 let (_, current_fileset) = self.file_membership()?;
                 let mut restricted_fileset = current_fileset;
-                restricted_fileset.remove(&weak_file);
-                if restricted_fileset.is_empty() {
-                    // the element will no longer be part of any file, so try to delete it
-                    if let Some(parent) = self.parent()? {
+                let changed = restricted_fileset.remove(&weak_file);
+                if let Some(parent) = self.parent()? {
+                    if restricted_fileset.is_empty() {
+                        // the element will no longer be part of any file, so try to delete it
                         let _ = parent.remove_sub_element(self.to_owned());
+                    } else if changed {
+                        // the parent now has a different set of sub elements in the file
+                        parent.write_lock().mark_modified();
                     }
                 }
                 // this works even if the element was just removed
-                self.0.write().file_membership = restricted_fileset;
+                self.write_lock().file_membership = restricted_fileset;
 
                 // update all sub elements with non-default file_membership
                 let mut to_delete = Vec::new();
                 for (_, subelem) in self.elements_dfs() {
                     // only need to care about those where file_membership is not empty. All other inherit from their parent
                     if !subelem.0.read().file_membership.is_empty() {
-                        subelem.0.write().file_membership.remove(&weak_file);
+                        if subelem.write_lock().file_membership.remove(&weak_file)
+                            && let Ok(Some(parent)) = subelem.parent()
+                        {
+                            parent.write_lock().mark_modified();
+                        }
                         // if the file_membership just went to empty, then subelem should be deleted
                         if subelem.0.read().file_membership.is_empty() {
                             to_delete.push(subelem);
@@ -2341,6 +3138,13 @@ impl Element {
                     }
                 }
 
+                if changed {
+                    // the element is no longer part of the file, so only this file is modified
+                    file.0.write().modified = true;
+                    file.model()?
+                        .notify_observers(|| ModelEvent::FileMembershipChanged { element: self.clone() });
+                }
+
                 Ok(())
             } else {
                 // adding a file from a different model is not permitted
@@ -2395,9 +3199,12 @@ impl Element {
         self.0.read().calc_element_insert_range(element_name, version)
     }
 
-    /// Return the comment attachd to the element (if any)
+    /// Return the comment in front of the element (if any)
     ///
-    /// A comment directly preceding the opening tag is considered to be atached and is returned here.
+    /// All comments are stored as content items, see [`ElementContent::Comment`]. The comment of an element is the
+    /// comment item directly preceding it in the content of its parent. It is removed, moved and copied together with
+    /// the element.
+    /// The comment of the root element is the comment in front of the `<AUTOSAR>` tag.
     ///
     /// In the arxml text:
     /// ```xml
@@ -2417,12 +3224,23 @@ impl Element {
     /// ```
     #[must_use]
     pub fn comment(&self) -> Option<String> {
-        self.0.read().comment.clone()
+        let parent = self.0.read().parent.clone();
+        match parent {
+            ElementOrModel::Element(weak_parent) => {
+                let parent = weak_parent.upgrade()?;
+                let parent_locked = parent.0.read();
+                let position = parent_locked.comment_position(self)?;
+                parent_locked.content[position].unwrap_comment()
+            }
+            ElementOrModel::Model(weak_model) => weak_model.upgrade()?.0.read().root_comment.clone(),
+            ElementOrModel::None => None,
+        }
     }
 
-    /// Set or delete the comment attached to the element
+    /// Set or delete the comment in front of the element
     ///
-    /// Set None to remove the comment.
+    /// Set None to remove the comment. The comment is a content item of the parent element, so it is not
+    /// copied, moved or removed together with the element.
     ///
     /// If the new comment value contains "--", then this is replaced with "__", because "--" is forbidden inside XML comments.
     ///
@@ -2444,7 +3262,71 @@ impl Element {
                 *comment = comment.replace("--", "__");
             }
         }
-        self.0.write().comment = opt_comment;
+        let parent = self.0.read().parent.clone();
+        match parent {
+            ElementOrModel::Element(weak_parent) => {
+                let Some(parent) = weak_parent.upgrade() else {
+                    return;
+                };
+                {
+                    let mut parent_locked = parent.write_lock();
+                    let Some(position) = parent_locked.sub_element_position(self) else {
+                        return;
+                    };
+                    match (parent_locked.comment_position(self), opt_comment) {
+                        (Some(comment_pos), Some(comment)) => {
+                            parent_locked.content[comment_pos] = ElementContent::Comment(comment);
+                        }
+                        (Some(comment_pos), None) => {
+                            parent_locked.content.remove(comment_pos);
+                        }
+                        (None, Some(comment)) => {
+                            parent_locked.content.insert(position, ElementContent::Comment(comment))
+                        }
+                        (None, None) => return,
+                    }
+                    parent_locked.mark_modified();
+                }
+                parent.notify_markup_changed();
+            }
+            ElementOrModel::Model(weak_model) => {
+                if let Some(model) = weak_model.upgrade() {
+                    let mut model_locked = model.0.write();
+                    model_locked.record_file_list();
+                    model_locked.root_comment = opt_comment;
+                }
+                self.notify_markup_changed();
+            }
+            ElementOrModel::None => {}
+        }
+    }
+
+    /// Get the location in the source arxml file where this element was loaded from
+    ///
+    /// The location is only available for elements that were created by the parser while loading a file.
+    /// It is removed when the element is modified, for example by changing its character data,
+    /// attributes or sub elements, since the element then no longer matches the original text.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use autosar_data::*;
+    /// # fn main() -> Result<(), AutosarDataError> {
+    /// # let model = AutosarModel::new();
+    /// # let buffer = br#"<?xml version="1.0" encoding="utf-8"?>
+    /// # <AUTOSAR xsi:schemaLocation="http://autosar.org/schema/r4.0 AUTOSAR_00050.xsd" xmlns="http://autosar.org/schema/r4.0" xmlns:xsi="http://www.w3.org/2001/XMLSchema-instance">
+    /// # <AR-PACKAGES><AR-PACKAGE><SHORT-NAME>Pkg</SHORT-NAME></AR-PACKAGE></AR-PACKAGES></AUTOSAR>"#;
+    /// # model.load_buffer(buffer, "file.arxml", true)?;
+    /// let element = model.get_element_by_path("/Pkg").unwrap();
+    /// if let Some(location) = element.source_location() {
+    ///     println!("{}:{}:{}", location.filename.display(), location.line, location.column);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[must_use]
+    pub fn source_location(&self) -> Option<SourceLocation> {
+        self.0.read().source_location.clone()
     }
 
     /// find the minumum version of all arxml files which contain this element
@@ -2624,6 +3506,19 @@ fn decompose_item_name(name: &str) -> Option<(String, u64)> {
     }
 }
 
+// check if a content item is part of the file that is being serialized.
+// A comment in front of a sub element is only written if the sub element is written
+fn item_in_file(content: &[ElementContent], position: usize, for_file: &Option<WeakArxmlFile>) -> bool {
+    match &content[position] {
+        ElementContent::Element(sub_element) => sub_element.is_in_file(for_file),
+        ElementContent::Comment(_) => !matches!(
+            content.get(position + 1),
+            Some(ElementContent::Element(sub_element)) if !sub_element.is_in_file(for_file)
+        ),
+        _ => true,
+    }
+}
+
 // a helper that provides compact debug output for the content of an element
 struct ElementContentFormatter<'a>(&'a SmallVec<[ElementContent; 4]>);
 impl std::fmt::Debug for ElementContentFormatter<'_> {
@@ -2633,6 +3528,7 @@ impl std::fmt::Debug for ElementContentFormatter<'_> {
             match item {
                 ElementContent::Element(elem) => list_fmt.entry(&elem.element_name()),
                 ElementContent::CharacterData(cdata) => list_fmt.entry(&cdata),
+                ElementContent::Comment(_) | ElementContent::ProcessingInstruction(_) => list_fmt.entry(&item),
             };
         }
         list_fmt.finish()
@@ -2720,7 +3616,7 @@ impl Hash for WeakElement {
 }
 
 impl ElementContent {
-    /// returns the element contained inside this `ElementContent`, or None if the content is `CharacterData`
+    /// returns the element contained inside this `ElementContent`, or None if the content is something else
     #[must_use]
     pub fn unwrap_element(&self) -> Option<Element> {
         if let ElementContent::Element(element) = self {
@@ -2730,7 +3626,7 @@ impl ElementContent {
         }
     }
 
-    /// returns the `CharacterData` inside this `ElementContent`, or None if the content is an Element
+    /// returns the `CharacterData` inside this `ElementContent`, or None if the content is something else
     #[must_use]
     pub fn unwrap_cdata(&self) -> Option<CharacterData> {
         if let ElementContent::CharacterData(cdata) = self {
@@ -2739,6 +3635,42 @@ impl ElementContent {
             None
         }
     }
+
+    /// returns the text of the comment inside this `ElementContent`, or None if the content is something else
+    #[must_use]
+    pub fn unwrap_comment(&self) -> Option<String> {
+        if let ElementContent::Comment(comment) = self {
+            Some(comment.clone())
+        } else {
+            None
+        }
+    }
+
+    /// returns true if the content is a comment or a processing instruction
+    pub(crate) fn is_markup(&self) -> bool {
+        matches!(
+            self,
+            ElementContent::Comment(_) | ElementContent::ProcessingInstruction(_)
+        )
+    }
+
+    // serialize a content item other than a sub element
+    fn serialize_text<O: SerializeOutput>(&self, outstring: &mut O) {
+        match self {
+            ElementContent::Element(_) => {}
+            ElementContent::CharacterData(chardata) => chardata.serialize_internal(outstring),
+            ElementContent::Comment(comment) => {
+                outstring.push_str("<!--");
+                outstring.push_str(comment);
+                outstring.push_str("-->");
+            }
+            ElementContent::ProcessingInstruction(instruction) => {
+                outstring.push_str("<?");
+                outstring.push_str(instruction);
+                outstring.push_str("?>");
+            }
+        }
+    }
 }
 
 // custom debug implementation: skip printing any content, since the content is only "WeakRef(0x...)"
@@ -2758,10 +3690,34 @@ impl std::fmt::Debug for ElementContent {
         match self {
             ElementContent::Element(elem) => elem.fmt(f),
             ElementContent::CharacterData(cdata) => cdata.fmt(f),
+            ElementContent::Comment(comment) => f.write_fmt(format_args!("<!--{comment}-->")),
+            ElementContent::ProcessingInstruction(instruction) => f.write_fmt(format_args!("<?{instruction}?>")),
         }
     }
 }
 
+// derive an item name from the element name, e.g. "I-SIGNAL-I-PDU" -> "ISignalIPdu", which is unique in the parent
+fn placeholder_item_name(model: &AutosarModel, parent_path: Option<&str>, element_name: ElementName) -> String {
+    let base_name: String = element_name
+        .to_str()
+        .split('-')
+        .map(|part| {
+            let mut chars = part.chars();
+            chars.next().map_or_else(String::new, |first| {
+                first.to_ascii_uppercase().to_string() + &chars.as_str().to_ascii_lowercase()
+            })
+        })
+        .collect();
+    let parent_path = parent_path.unwrap_or_default();
+    let mut name = base_name.clone();
+    let mut counter = 1;
+    while model.get_element_by_path(&format!("{parent_path}/{name}")).is_some() {
+        name = format!("{base_name}_{counter}");
+        counter += 1;
+    }
+    name
+}
+
 #[cfg(test)]
 mod test {
     use crate::*;
@@ -4304,6 +5260,232 @@ mod test {
         // remove the comment
         el_autosar.set_comment(None);
         assert!(el_autosar.comment().is_none());
+
+        // the root comment is part of the model, and the comments of other elements are part of their parents
+        let packages = el_autosar.create_sub_element(ElementName::ArPackages).unwrap();
+        let transaction = model.begin_transaction();
+        el_autosar.set_comment(Some("root".to_string()));
+        packages.set_comment(Some("packages".to_string()));
+        assert_eq!(el_autosar.comments(), vec![(0, "packages".to_string())]);
+        transaction.rollback();
+        assert!(el_autosar.comment().is_none());
+        assert!(packages.comment().is_none());
+        assert_eq!(el_autosar.content_item_count(), 1);
+    }
+
+    #[test]
+    fn content_comments() {
+        const FILEBUF: &[u8] = br#"<?xml version="1.0" encoding="utf-8"?>
+<AUTOSAR xsi:schemaLocation="http://autosar.org/schema/r4.0 AUTOSAR_00050.xsd" xmlns="http://autosar.org/schema/r4.0" xmlns:xsi="http://www.w3.org/2001/XMLSchema-instance">
+  <AR-PACKAGES>
+    <!--first--><!--second-->
+    <AR-PACKAGE><SHORT-NAME><!--in name-->Pkg</SHORT-NAME><?tool info?>
+      <ELEMENTS/>
+      <!--trailing-->
+    </AR-PACKAGE>
+  </AR-PACKAGES>
+</AUTOSAR>"#;
+        let model = AutosarModel::new();
+        let (file, _) = model.load_buffer(FILEBUF, "test.arxml", true).unwrap();
+        let ar_packages = model.root_element().get_sub_element(ElementName::ArPackages).unwrap();
+        let pkg = model.get_element_by_path("/Pkg").unwrap();
+        let short_name = pkg.get_sub_element(ElementName::ShortName).unwrap();
+
+        // all comments are content items; the last comment before an element is also returned by comment()
+        assert_eq!(
+            ar_packages.comments(),
+            vec![(0, "first".to_string()), (1, "second".to_string())]
+        );
+        assert_eq!(pkg.comment().unwrap(), "second");
+        assert_eq!(pkg.comments(), vec![(3, "trailing".to_string())]);
+        assert_eq!(
+            pkg.content().nth(1),
+            Some(ElementContent::ProcessingInstruction("tool info".to_string()))
+        );
+        assert_eq!(short_name.comments(), vec![(0, "in name".to_string())]);
+        assert_eq!(short_name.character_data().unwrap().string_value().unwrap(), "Pkg");
+        assert_eq!(pkg.item_name().unwrap(), "Pkg");
+
+        // comments and processing instructions are written at their original positions
+        let text = file.serialize().unwrap();
+        assert!(text.contains("<AR-PACKAGES>\n    <!--first-->\n    <!--second-->\n    <AR-PACKAGE>"));
+        assert!(text.contains("<SHORT-NAME><!--in name-->Pkg</SHORT-NAME>\n      <?tool info?>"));
+        assert!(text.contains("<ELEMENTS/>\n      <!--trailing-->\n    </AR-PACKAGE>"));
+        let model2 = AutosarModel::new();
+        let (file2, _) = model2.load_buffer(text.as_bytes(), "test.arxml", true).unwrap();
+        assert_eq!(file2.serialize().unwrap(), text);
+
+        // setting the character data keeps the comment
+        pkg.set_item_name("Renamed").unwrap();
+        assert_eq!(short_name.comments(), vec![(0, "in name".to_string())]);
+        assert_eq!(short_name.character_data().unwrap().string_value().unwrap(), "Renamed");
+
+        // insert and remove comments and processing instructions
+        pkg.insert_comment("new--comment", 4).unwrap();
+        assert_eq!(pkg.comments().last().unwrap(), &(4, "new__comment".to_string()));
+        assert!(matches!(
+            pkg.insert_comment("comment", 10),
+            Err(AutosarDataError::InvalidPosition)
+        ));
+        assert!(matches!(pkg.remove_comment(1), Err(AutosarDataError::InvalidPosition)));
+        pkg.remove_comment(4).unwrap();
+        pkg.remove_processing_instruction(1).unwrap();
+        assert_eq!(pkg.content_item_count(), 3);
+        for invalid in ["", "xml version", "a>b"] {
+            assert!(matches!(
+                pkg.insert_processing_instruction(invalid, 0),
+                Err(AutosarDataError::InvalidProcessingInstruction)
+            ));
+        }
+        pkg.insert_processing_instruction("tool other", 0).unwrap();
+        // elements created after the comments are inserted in a valid position
+        pkg.create_sub_element(ElementName::Category).unwrap();
+        assert!(pkg.validate().unwrap().is_empty());
+
+        // sorting keeps comments in front of the element that follows them
+        pkg.sort();
+        let content: Vec<ElementContent> = pkg.content().collect();
+        assert!(matches!(&content[0], ElementContent::ProcessingInstruction(_)));
+        assert_eq!(
+            content[1].unwrap_element().unwrap().element_name(),
+            ElementName::ShortName
+        );
+        assert_eq!(content.last().unwrap().unwrap_comment().unwrap(), "trailing");
+    }
+
+    #[test]
+    fn comment_follows_element() {
+        let model = AutosarModel::new();
+        model.create_file("test", AutosarVersion::LATEST).unwrap();
+        let elements = model
+            .root_element()
+            .create_sub_element(ElementName::ArPackages)
+            .and_then(|pkgs| pkgs.create_named_sub_element(ElementName::ArPackage, "Pkg"))
+            .and_then(|pkg| pkg.create_sub_element(ElementName::Elements))
+            .unwrap();
+        let other_elements = model
+            .root_element()
+            .get_sub_element(ElementName::ArPackages)
+            .and_then(|pkgs| pkgs.create_named_sub_element(ElementName::ArPackage, "Other").ok())
+            .and_then(|pkg| pkg.create_sub_element(ElementName::Elements).ok())
+            .unwrap();
+        let sys1 = elements.create_named_sub_element(ElementName::System, "Sys1").unwrap();
+        let sys2 = elements.create_named_sub_element(ElementName::System, "Sys2").unwrap();
+        let sys3 = elements.create_named_sub_element(ElementName::System, "Sys3").unwrap();
+        sys1.set_comment(Some("comment 1".to_string()));
+        sys2.set_comment(Some("comment 2".to_string()));
+
+        // moving an element within its parent also moves its comment
+        elements.move_element_here_at(&sys1, 4).unwrap();
+        assert_eq!(sys1.comment().unwrap(), "comment 1");
+        assert_eq!(sys2.comment().unwrap(), "comment 2");
+        assert!(sys3.comment().is_none());
+        assert_eq!(sys1.position(), Some(4));
+        elements.move_element_here_at(&sys1, 0).unwrap();
+        assert_eq!(sys1.position(), Some(1));
+        assert_eq!(sys1.comment().unwrap(), "comment 1");
+        assert_eq!(elements.content_item_count(), 5);
+
+        // copying an element copies its comment
+        let copy = other_elements.create_copied_sub_element(&sys2).unwrap();
+        assert_eq!(copy.comment().unwrap(), "comment 2");
+        let copy = elements.create_copied_sub_element(&sys2).unwrap();
+        assert_eq!(copy.comment().unwrap(), "comment 2");
+        assert_eq!(elements.content_item_count(), 7);
+
+        // moving an element to a different parent moves its comment
+        other_elements.move_element_here(&sys1).unwrap();
+        assert_eq!(sys1.comment().unwrap(), "comment 1");
+        assert_eq!(elements.comments().len(), 2);
+        assert_eq!(other_elements.comments().len(), 2);
+
+        // removing an element removes its comment, so that it does not become the comment of the next element
+        elements.remove_sub_element(sys2).unwrap();
+        assert!(sys3.comment().is_none());
+        assert_eq!(elements.comments().len(), 1);
+
+        // a move between models takes the comment along
+        let model2 = AutosarModel::new();
+        model2.create_file("test", AutosarVersion::LATEST).unwrap();
+        let elements2 = model2
+            .root_element()
+            .create_sub_element(ElementName::ArPackages)
+            .and_then(|pkgs| pkgs.create_named_sub_element(ElementName::ArPackage, "Pkg"))
+            .and_then(|pkg| pkg.create_sub_element(ElementName::Elements))
+            .unwrap();
+        elements2.move_element_here(&sys1).unwrap();
+        assert_eq!(sys1.comment().unwrap(), "comment 1");
+        assert_eq!(other_elements.comments().len(), 1);
+    }
+
+    #[test]
+    fn source_location() {
+        const FILEBUF1: &[u8] = br#"<?xml version="1.0" encoding="utf-8"?>
+<AUTOSAR xsi:schemaLocation="http://autosar.org/schema/r4.0 AUTOSAR_00050.xsd" xmlns="http://autosar.org/schema/r4.0" xmlns:xsi="http://www.w3.org/2001/XMLSchema-instance">
+  <AR-PACKAGES>
+    <AR-PACKAGE><SHORT-NAME>Pkg</SHORT-NAME>
+      <ELEMENTS>
+        <SYSTEM><SHORT-NAME>System</SHORT-NAME><PNC-VECTOR-LENGTH>4</PNC-VECTOR-LENGTH></SYSTEM>
+      </ELEMENTS>
+    </AR-PACKAGE>
+  </AR-PACKAGES>
+</AUTOSAR>"#;
+        const FILEBUF2: &[u8] = br#"<?xml version="1.0" encoding="utf-8"?>
+<AUTOSAR xsi:schemaLocation="http://autosar.org/schema/r4.0 AUTOSAR_00050.xsd" xmlns="http://autosar.org/schema/r4.0" xmlns:xsi="http://www.w3.org/2001/XMLSchema-instance">
+  <AR-PACKAGES>
+    <AR-PACKAGE><SHORT-NAME>Pkg</SHORT-NAME>
+      <ELEMENTS>
+        <SYSTEM><SHORT-NAME>System2</SHORT-NAME></SYSTEM>
+      </ELEMENTS>
+    </AR-PACKAGE>
+  </AR-PACKAGES>
+</AUTOSAR>"#;
+        let model = AutosarModel::new();
+        model.load_buffer(FILEBUF1, "file1.arxml", true).unwrap();
+        model.load_buffer(FILEBUF2, "file2.arxml", true).unwrap();
+
+        let root_loc = model.root_element().source_location().unwrap();
+        assert_eq!(&*root_loc.filename, Path::new("file1.arxml"));
+        assert_eq!((root_loc.line, root_loc.column), (2, 1));
+
+        let pkg = model.get_element_by_path("/Pkg").unwrap();
+        let pkg_loc = pkg.source_location().unwrap();
+        assert_eq!((pkg_loc.line, pkg_loc.column), (4, 5));
+
+        let system = model.get_element_by_path("/Pkg/System").unwrap();
+        let pnc_vector_length = system.get_sub_element(ElementName::PncVectorLength).unwrap();
+        let pvl_loc = pnc_vector_length.source_location().unwrap();
+        assert_eq!(&*pvl_loc.filename, Path::new("file1.arxml"));
+        assert_eq!((pvl_loc.line, pvl_loc.column), (6, 48));
+
+        // elements that were added by merging the second file keep their location in that file
+        let system2 = model.get_element_by_path("/Pkg/System2").unwrap();
+        let system2_loc = system2.source_location().unwrap();
+        assert_eq!(&*system2_loc.filename, Path::new("file2.arxml"));
+        assert_eq!((system2_loc.line, system2_loc.column), (6, 9));
+
+        // modifying an element removes its location
+        pnc_vector_length.set_character_data(5).unwrap();
+        assert!(pnc_vector_length.source_location().is_none());
+        system2.set_attribute_string(AttributeName::Uuid, "abc").unwrap();
+        assert!(system2.source_location().is_none());
+        system.create_sub_element(ElementName::Desc).unwrap();
+        assert!(system.source_location().is_none());
+        // the comment of an element is part of the content of its parent
+        pkg.set_comment(Some("comment".to_string()));
+        assert!(pkg.source_location().is_some());
+        assert!(pkg.parent().unwrap().unwrap().source_location().is_none());
+        // unmodified elements keep their location
+        assert!(model.root_element().source_location().is_some());
+
+        // newly created elements don't have a location
+        let new_pkg = model
+            .root_element()
+            .get_sub_element(ElementName::ArPackages)
+            .unwrap()
+            .create_named_sub_element(ElementName::ArPackage, "NewPkg")
+            .unwrap();
+        assert!(new_pkg.source_location().is_none());
     }
 
     #[test]
@@ -4322,6 +5504,7 @@ mod test {
     }
 
     #[test]
+    #[allow(clippy::mutable_key_type)]
     fn traits() {
         let model = AutosarModel::new();
         model.create_file("test", AutosarVersion::LATEST).unwrap();
@@ -4701,6 +5884,81 @@ mod test {
         assert_eq!(model.0.read().reference_bases.len(), 0);
     }
 
+    #[test]
+    fn copy_reference_bases() {
+        let model = AutosarModel::new();
+        model.create_file("test", AutosarVersion::LATEST).unwrap();
+        let package = model
+            .root_element()
+            .create_sub_element(ElementName::ArPackages)
+            .and_then(|packages| packages.create_named_sub_element(ElementName::ArPackage, "Pkg"))
+            .unwrap();
+        let sub_package = package
+            .create_sub_element(ElementName::ArPackages)
+            .and_then(|packages| packages.create_named_sub_element(ElementName::ArPackage, "Sub"))
+            .unwrap();
+        let ecu = sub_package
+            .create_sub_element(ElementName::Elements)
+            .and_then(|elements| elements.create_named_sub_element(ElementName::EcuInstance, "Ecu"))
+            .unwrap();
+        let reference_base = package
+            .create_sub_element(ElementName::ReferenceBases)
+            .and_then(|bases| bases.create_sub_element(ElementName::ReferenceBase))
+            .unwrap();
+        reference_base
+            .create_sub_element(ElementName::ShortLabel)
+            .and_then(|label| label.set_character_data("Base"))
+            .unwrap();
+        reference_base
+            .create_sub_element(ElementName::PackageRef)
+            .and_then(|package_ref| package_ref.set_reference_target(&sub_package))
+            .unwrap();
+        let reference = package
+            .create_sub_element(ElementName::Elements)
+            .and_then(|elements| elements.create_named_sub_element(ElementName::System, "System"))
+            .and_then(|system| system.create_sub_element(ElementName::FibexElements))
+            .and_then(|fibex| fibex.create_sub_element(ElementName::FibexElementRefConditional))
+            .and_then(|conditional| conditional.create_sub_element(ElementName::FibexElementRef))
+            .unwrap();
+        reference.set_relative_reference_target(&ecu, "Base").unwrap();
+        assert_eq!(reference.get_reference_target().unwrap(), ecu);
+
+        // the REFERENCE-BASE of a copied package is registered in the destination model
+        let model2 = AutosarModel::new();
+        model2.create_file("test2", AutosarVersion::LATEST).unwrap();
+        let copy = model2
+            .root_element()
+            .create_sub_element(ElementName::ArPackages)
+            .and_then(|packages| packages.create_copied_sub_element(&package))
+            .unwrap();
+        assert_eq!(model2.0.read().reference_bases.len(), 1);
+        let copied_reference = model2
+            .get_element_by_path("/Pkg/System")
+            .and_then(|system| system.get_sub_element(ElementName::FibexElements))
+            .and_then(|fibex| fibex.get_sub_element(ElementName::FibexElementRefConditional))
+            .and_then(|conditional| conditional.get_sub_element(ElementName::FibexElementRef))
+            .unwrap();
+        assert_eq!(
+            copied_reference.get_reference_target().unwrap(),
+            model2.get_element_by_path("/Pkg/Sub/Ecu").unwrap()
+        );
+        assert_eq!(copy.path().unwrap(), "/Pkg");
+
+        // the same applies to a duplicated model
+        let model3 = model.duplicate().unwrap();
+        assert_eq!(model3.0.read().reference_bases.len(), 1);
+        let duplicated_reference = model3
+            .get_element_by_path("/Pkg/System")
+            .and_then(|system| system.get_sub_element(ElementName::FibexElements))
+            .and_then(|fibex| fibex.get_sub_element(ElementName::FibexElementRefConditional))
+            .and_then(|conditional| conditional.get_sub_element(ElementName::FibexElementRef))
+            .unwrap();
+        assert_eq!(
+            duplicated_reference.get_reference_target().unwrap(),
+            model3.get_element_by_path("/Pkg/Sub/Ecu").unwrap()
+        );
+    }
+
     #[test]
     fn rename_reference_base() {
         let model = AutosarModel::new();
@@ -4833,4 +6091,98 @@ mod test {
         let ref_origin = ref_elem.downgrade();
         assert!(origins.iter().any(|(elem, base)| *elem == ref_origin && base == "B"));
     }
+
+    #[test]
+    fn fill_required() {
+        let model = AutosarModel::new();
+        model.create_file("test.arxml", AutosarVersion::Autosar_00050).unwrap();
+        let elements = model
+            .root_element()
+            .create_sub_element(ElementName::ArPackages)
+            .and_then(|packages| packages.create_named_sub_element(ElementName::ArPackage, "Pkg"))
+            .and_then(|pkg| pkg.create_sub_element(ElementName::Elements))
+            .unwrap();
+
+        // required attributes and character data are created with placeholder values
+        let conditional = elements
+            .create_named_sub_element(ElementName::System, "System")
+            .and_then(|system| system.create_sub_element(ElementName::FibexElements))
+            .and_then(|fibex| fibex.create_sub_element(ElementName::FibexElementRefConditional))
+            .unwrap();
+        let reference = conditional.create_sub_element(ElementName::FibexElementRef).unwrap();
+        conditional.fill_required().unwrap();
+        assert!(reference.character_data().is_some());
+        assert!(reference.attribute_value(AttributeName::Dest).is_some());
+
+        // an identifiable element without SHORT-NAME gets a unique name
+        let model2 = AutosarModel::new();
+        let buffer = br#"<?xml version="1.0" encoding="utf-8"?>
+        <AUTOSAR xsi:schemaLocation="http://autosar.org/schema/r4.0 AUTOSAR_00050.xsd" xmlns="http://autosar.org/schema/r4.0" xmlns:xsi="http://www.w3.org/2001/XMLSchema-instance">
+        <AR-PACKAGES><AR-PACKAGE><SHORT-NAME>Pkg</SHORT-NAME><ELEMENTS>
+            <I-SIGNAL-I-PDU><SHORT-NAME>ISignalIPdu</SHORT-NAME></I-SIGNAL-I-PDU>
+            <I-SIGNAL-I-PDU></I-SIGNAL-I-PDU>
+            <I-SIGNAL-I-PDU></I-SIGNAL-I-PDU>
+        </ELEMENTS></AR-PACKAGE></AR-PACKAGES></AUTOSAR>"#;
+        model2.load_buffer(buffer, "test.arxml", false).unwrap();
+        let pdu = model2
+            .get_element_by_path("/Pkg")
+            .and_then(|pkg| pkg.get_sub_element(ElementName::Elements))
+            .and_then(|elements| elements.get_sub_element_at(1))
+            .unwrap();
+        assert!(pdu.item_name().is_none());
+        pdu.fill_required().unwrap();
+        assert_eq!(pdu.item_name().unwrap(), "ISignalIPdu_1");
+        assert_eq!(model2.get_element_by_path("/Pkg/ISignalIPdu_1").unwrap(), pdu);
+
+        // all changes are made in one transaction, which can be undone in one step
+        model2.undo().unwrap();
+        assert!(pdu.item_name().is_none());
+        assert!(model2.get_element_by_path("/Pkg/ISignalIPdu_1").is_none());
+        model2.redo().unwrap();
+        assert_eq!(model2.get_element_by_path("/Pkg/ISignalIPdu_1").unwrap(), pdu);
+
+        // setting the first name of an element registers it, if the name is not used yet
+        let pdu_unnamed = pdu.parent().unwrap().unwrap().get_sub_element_at(2).unwrap();
+        let short_name = pdu_unnamed.create_sub_element(ElementName::ShortName).unwrap();
+        let result = short_name.set_character_data("ISignalIPdu");
+        assert!(matches!(result, Err(AutosarDataError::DuplicateItemName { .. })));
+        assert!(short_name.character_data().is_none());
+        short_name.set_character_data("Manual").unwrap();
+        assert_eq!(model2.get_element_by_path("/Pkg/Manual").unwrap(), pdu_unnamed);
+
+        // existing content is not changed
+        let pdu2 = elements
+            .create_named_sub_element(ElementName::ISignalIPdu, "Pdu")
+            .unwrap();
+        pdu2.create_sub_element(ElementName::Length)
+            .and_then(|length| length.set_character_data("8"))
+            .unwrap();
+        let empty_length = elements
+            .create_named_sub_element(ElementName::ISignalIPdu, "Pdu3")
+            .and_then(|pdu| pdu.create_sub_element(ElementName::Length))
+            .unwrap();
+        pdu2.fill_required().unwrap();
+        assert_eq!(pdu2.item_name().unwrap(), "Pdu");
+        assert_eq!(
+            pdu2.get_sub_element(ElementName::Length).unwrap().character_data(),
+            Some(CharacterData::String("8".to_string()))
+        );
+        empty_length.fill_required().unwrap();
+        assert!(empty_length.character_data().is_some());
+
+        // every element that can be created in a package is valid after filling it
+        let version = AutosarVersion::Autosar_00050;
+        for (name, etype, version_mask, _) in elements.element_type().sub_element_spec_iter() {
+            if !version.compatible(version_mask) {
+                continue;
+            }
+            let element = if etype.is_named_in_version(version) {
+                elements.create_named_sub_element(name, &format!("Element{}", elements.sub_elements().count()))
+            } else {
+                elements.create_sub_element(name)
+            };
+            element.unwrap().fill_required().unwrap();
+        }
+        assert_eq!(model.validate(), Vec::new());
+    }
 }
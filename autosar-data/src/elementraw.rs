@@ -12,7 +12,7 @@ use std::sync::Arc;
 
 use crate::{
     Attribute, AutosarDataError, AutosarModel, CharacterData, Element, ElementContent, ElementOrModel, ElementRaw,
-    WeakElement,
+    ModelEvent, WeakElement,
 };
 
 /// `ElementRaw` provides the internal implementation of (almost) all Element operations
@@ -31,6 +31,12 @@ use crate::{
 /// To avoid this, parent element locks can only be acquired with `try_lock`(). If the lock is not acquired within a
 /// reasonable time (10ms is used here), then the operation aborts with a `ParentElementLocked` error.
 impl ElementRaw {
+    /// record that the element no longer matches the text it was loaded from
+    pub(crate) fn mark_modified(&mut self) {
+        self.source_location = None;
+        self.modified = true;
+    }
+
     /// get the parent element of the current element
     pub(crate) fn parent(&self) -> Result<Option<Element>, AutosarDataError> {
         match &self.parent {
@@ -58,7 +64,7 @@ impl ElementRaw {
         // is this element named in any autosar version? - If it's not named here then we'll simply fail in the next step
         if self.elemtype.is_named() {
             // if an item is named, then the SHORT-NAME sub element that contains the name is always the first sub element
-            if let Some(ElementContent::Element(subelem)) = self.content.first() {
+            if let Some(subelem) = self.first_sub_element() {
                 // why use try_lock? It is possible that we're calling path() while already holding the lock on subelem.
                 // In this case path() descends to the parent, then calls item_name() and would deadlock.
                 // This case happens when subelem is *not* a ShortName but elemtype.is_named() returns true because there
@@ -96,9 +102,11 @@ impl ElementRaw {
                 });
             }
 
+            // references that were updated to the new path; the observers are notified once the model is no longer locked
+            let mut retargeted = Vec::new();
             // if an item is named, then the SHORT-NAME sub element that contains the name is always the first sub element
-            if let Some(ElementContent::Element(subelem_wrapped)) = self.content.first() {
-                let mut subelem = subelem_wrapped.0.write();
+            if let Some(subelem_wrapped) = self.first_sub_element() {
+                let mut subelem = subelem_wrapped.write_lock();
                 if subelem.element_name() == ElementName::ShortName {
                     subelem.set_character_data(new_name.to_owned(), version)?;
                     model.fix_identifiables(&old_path, &new_path);
@@ -118,10 +126,13 @@ impl ElementRaw {
 
                                 for weak_ref_elem in &reflist {
                                     if let Some(ref_elem) = weak_ref_elem.upgrade() {
-                                        let mut ref_elem_locked = ref_elem.0.write();
+                                        let mut ref_elem_locked = ref_elem.write_lock();
                                         // can't use .set_character_data() here, because the model is locked
-                                        ref_elem_locked.content[0] =
-                                            ElementContent::CharacterData(CharacterData::String(refpath_new.clone()));
+                                        ref_elem_locked
+                                            .replace_content_with_chardata(CharacterData::String(refpath_new.clone()));
+                                        ref_elem_locked.mark_modified();
+                                        drop(ref_elem_locked);
+                                        retargeted.push((ref_elem, refpath.clone(), refpath_new.clone()));
                                     }
                                 }
                                 model_locked.reference_origins.insert(refpath_new, reflist);
@@ -130,6 +141,14 @@ impl ElementRaw {
                     }
                 }
             }
+            for (element, old_target, new_target) in retargeted {
+                model.queue_modified(element.clone());
+                model.queue_event(|| ModelEvent::ReferenceRetargeted {
+                    element,
+                    old_target: Some(old_target),
+                    new_target: Some(new_target),
+                });
+            }
 
             Ok(())
         } else {
@@ -144,7 +163,7 @@ impl ElementRaw {
         // is this element named in any autosar version? - If it's not named here then we'll simply fail in the next step
         if self.elemtype.is_named() {
             // if an item is named, then the SHORT-NAME sub element that contains the name is always the first sub element
-            if let Some(ElementContent::Element(subelem)) = self.content.first()
+            if let Some(subelem) = self.first_sub_element()
                 && subelem.element_name() == ElementName::ShortName
             {
                 return true;
@@ -308,11 +327,13 @@ impl ElementRaw {
                 content: smallvec![],
                 attributes: smallvec![],
                 file_membership: HashSet::with_capacity(0),
-                comment: None,
+                source_location: None,
+                modified: false,
             }
             .wrap();
             self.content
                 .insert(position, ElementContent::Element(sub_element.clone()));
+            self.mark_modified();
             Ok(sub_element)
         }
     }
@@ -407,18 +428,22 @@ impl ElementRaw {
                 content: smallvec![],
                 attributes: smallvec![],
                 file_membership: HashSet::with_capacity(0),
-                comment: None,
+                source_location: None,
+                modified: false,
             }
             .wrap();
             self.content
                 .insert(position, ElementContent::Element(sub_element.clone()));
+            self.mark_modified();
             // create a SHORT-NAME for the sub element
-            let shortname_element =
-                sub_element
-                    .0
-                    .write()
-                    .create_sub_element(sub_element.downgrade(), ElementName::ShortName, version)?;
-            let _ = shortname_element.0.write().set_character_data(item_name_cdata, version);
+            let shortname_element = sub_element.write_lock().create_sub_element(
+                sub_element.downgrade(),
+                ElementName::ShortName,
+                version,
+            )?;
+            let _ = shortname_element
+                .write_lock()
+                .set_character_data(item_name_cdata, version);
             model.add_identifiable(path, sub_element.downgrade());
             Ok(sub_element)
         } else {
@@ -524,9 +549,30 @@ impl ElementRaw {
                     .and_then(|cdata| cdata.string_value());
                 model.add_reference_origin(&reference, base.as_deref(), sub_elem.downgrade());
             }
+            // add all reference bases to the reference_bases cache
+            // a REFERENCE-BASE is not identifiable, so the last identifiable element in path_parts is the owning package
+            if sub_elem.element_name() == ElementName::ReferenceBase
+                && let Some(label) = sub_elem
+                    .get_sub_element(ElementName::ShortLabel)
+                    .and_then(|e| e.character_data())
+                    .and_then(|cdata| cdata.string_value())
+                && let Some(package_ref) = sub_elem.get_sub_element(ElementName::PackageRef)
+                && let Some(package_ref_val) = package_ref.character_data().and_then(|cdata| cdata.string_value())
+            {
+                let base_attr = package_ref
+                    .attribute_value(AttributeName::Base)
+                    .and_then(|cdata| cdata.string_value());
+                let owner_package_path = path_parts
+                    .iter()
+                    .filter_map(std::clone::Clone::clone)
+                    .collect::<Vec<String>>()
+                    .join("/");
+                model.fix_reference_base(None, label, package_ref_val, base_attr, owner_package_path);
+            }
         }
 
         self.content.insert(position, ElementContent::Element(newelem.clone()));
+        self.mark_modified();
 
         Ok(newelem)
     }
@@ -540,12 +586,13 @@ impl ElementRaw {
             attributes: SmallVec::with_capacity(self.attributes.len()),
             parent: ElementOrModel::None,
             file_membership: HashSet::with_capacity(0),
-            comment: self.comment.clone(),
+            source_location: None,
+            modified: false,
         }
         .wrap();
 
         {
-            let mut copy = copy_wrapped.0.write();
+            let mut copy = copy_wrapped.write_lock();
             // copy all the attributes
             for attribute in &self.attributes {
                 // get the specification of the attribute
@@ -575,7 +622,7 @@ impl ElementRaw {
                 }
             }
 
-            // copy all content: sub elements and text items
+            // copy all content: sub elements, text items, comments and processing instructions
             for content_item in &self.content {
                 match content_item {
                     ElementContent::Element(sub_elem) => {
@@ -587,12 +634,14 @@ impl ElementRaw {
                             .is_some()
                             && let Ok(copied_sub_elem) = sub_elem.0.read().deep_copy(target_version)
                         {
-                            copied_sub_elem.0.write().parent = ElementOrModel::Element(copy_wrapped.downgrade());
+                            copied_sub_elem.write_lock().parent = ElementOrModel::Element(copy_wrapped.downgrade());
                             copy.content.push(ElementContent::Element(copied_sub_elem));
                         }
                     }
-                    ElementContent::CharacterData(cdata) => {
-                        copy.content.push(ElementContent::CharacterData(cdata.clone()));
+                    ElementContent::CharacterData(_)
+                    | ElementContent::Comment(_)
+                    | ElementContent::ProcessingInstruction(_) => {
+                        copy.content.push(content_item.clone());
                     }
                 }
             }
@@ -619,13 +668,11 @@ impl ElementRaw {
         if counter > 1 {
             // set the name directly by modifying the character content of the short name element
             // note: the method set_character_data is not suitable here, because it updates the identifiables hashmap
-            if let Some(ElementContent::Element(short_name_elem)) = self.content.first() {
-                // the SHORT-NAME at index 0 is guaranteed to exist, because the earlier is_identifiable check succeeded
-                let mut sn_element = short_name_elem.0.write();
-                sn_element.content.clear();
-                sn_element
-                    .content
-                    .push(ElementContent::CharacterData(CharacterData::String(name.clone())));
+            if let Some(short_name_elem) = self.first_sub_element() {
+                // the SHORT-NAME is guaranteed to be the first sub element, because the earlier is_identifiable check succeeded
+                let mut sn_element = short_name_elem.write_lock();
+                sn_element.replace_content_with_chardata(CharacterData::String(name.clone()));
+                sn_element.mark_modified();
             }
         }
 
@@ -725,13 +772,21 @@ impl ElementRaw {
                 })
                 .unwrap();
 
-            if current_position < position {
-                // the first element in the subslice is moved to the last position by rotate_left
-                self.content[current_position..=position].rotate_left(1);
+            // the comment in front of the element is moved together with it
+            let start = if self.comment_position(move_element).is_some() {
+                current_position - 1
             } else {
-                // the last element in the subslice is moved to the first position by rotate_right
-                self.content[position..=current_position].rotate_right(1);
+                current_position
+            };
+            let count = current_position - start + 1;
+            if current_position < position {
+                // the first items in the subslice are moved to the last positions by rotate_left
+                self.content[start..=position].rotate_left(count);
+            } else if position < start {
+                // the last items in the subslice are moved to the first positions by rotate_right
+                self.content[position..=current_position].rotate_right(count);
             }
+            self.mark_modified();
 
             Ok(move_element.clone())
         } else {
@@ -782,11 +837,10 @@ impl ElementRaw {
         let dest_path_prefix = self.path_unchecked()?;
 
         // limit the lifetime of the lock on src_parent
-        {
+        let comment = {
             // lock the source parent element and remove the move_element from its content list
             let mut src_parent_locked = src_parent
-                .0
-                .try_write_for(Duration::from_millis(10))
+                .try_write_lock_for(Duration::from_millis(10))
                 .ok_or(AutosarDataError::ParentElementLocked)?;
             let idx = src_parent_locked
                 .content
@@ -799,11 +853,13 @@ impl ElementRaw {
                     }
                 })
                 .unwrap();
-            src_parent_locked.content.remove(idx);
-        }
+            let comment = src_parent_locked.take_sub_element_at(idx);
+            src_parent_locked.mark_modified();
+            comment
+        };
 
         // set the parent of the new element to the current element
-        let mut move_element_locked = move_element.0.write();
+        let mut move_element_locked = move_element.write_lock();
         move_element_locked.parent = ElementOrModel::Element(self_weak);
         let dest_path = if move_element_locked.is_identifiable() {
             let new_name = move_element_locked.make_unique_item_name(model, &dest_path_prefix)?;
@@ -828,6 +884,7 @@ impl ElementRaw {
         }
 
         // the move_element was moved within this autosar model, so we can update all other references pointing to it
+        let mut retargeted = Vec::new();
         let mut model_locked = model.0.write();
         for orig_ref in &original_paths {
             if let Some(suffix) = orig_ref.strip_prefix(&src_path_prefix) {
@@ -837,17 +894,27 @@ impl ElementRaw {
                     let refstr = format!("{dest_path}{suffix}");
                     for ref_element_weak in &ref_elements {
                         if let Some(ref_element) = ref_element_weak.upgrade() {
-                            ref_element.0.write().set_character_data(refstr.clone(), version)?;
+                            ref_element.write_lock().set_character_data(refstr.clone(), version)?;
+                            retargeted.push((ref_element, orig_ref.clone(), refstr.clone()));
                         }
                     }
                     model_locked.reference_origins.insert(refstr, ref_elements);
                 }
             }
         }
+        drop(model_locked);
+        for (element, old_target, new_target) in retargeted {
+            model.queue_modified(element.clone());
+            model.queue_event(|| ModelEvent::ReferenceRetargeted {
+                element,
+                old_target: Some(old_target),
+                new_target: Some(new_target),
+            });
+        }
 
-        // insert move_element
-        self.content
-            .insert(position, ElementContent::Element(move_element.clone()));
+        // insert move_element, together with the comment it had in its previous location
+        self.insert_sub_element_at(position, move_element, comment);
+        self.mark_modified();
 
         Ok(move_element.clone())
     }
@@ -890,11 +957,10 @@ impl ElementRaw {
             .collect();
 
         // limit the lifetime of the mutex on src_parent
-        {
+        let comment = {
             // lock the parent of the new element and remove it from the parent's content list
             let mut src_parent_locked = src_parent
-                .0
-                .try_write_for(Duration::from_millis(10))
+                .try_write_lock_for(Duration::from_millis(10))
                 .ok_or(AutosarDataError::ParentElementLocked)?;
             let idx = src_parent_locked
                 .content
@@ -907,8 +973,10 @@ impl ElementRaw {
                     }
                 })
                 .unwrap();
-            src_parent_locked.content.remove(idx);
-        }
+            let comment = src_parent_locked.take_sub_element_at(idx);
+            src_parent_locked.mark_modified();
+            comment
+        };
 
         // remove all cached references for elements under move_element - they all become invalid as a result of moving it
         for path in original_paths.keys() {
@@ -923,7 +991,7 @@ impl ElementRaw {
         }
 
         // set the parent of the new element to the current element
-        let mut move_element_locked = move_element.0.write();
+        let mut move_element_locked = move_element.write_lock();
         move_element_locked.parent = ElementOrModel::Element(self_weak);
         let dest_path = if move_element_locked.is_identifiable() {
             let new_name = move_element_locked.make_unique_item_name(model, &dest_path_prefix)?;
@@ -947,7 +1015,13 @@ impl ElementRaw {
                 let mut refstr = old_ref.clone();
                 if let Some(suffix) = old_ref.strip_prefix(&src_path_prefix) {
                     refstr = format!("{dest_path}{suffix}");
-                    ref_element.0.write().set_character_data(refstr.clone(), version)?;
+                    ref_element.write_lock().set_character_data(refstr.clone(), version)?;
+                    model.queue_modified(ref_element.clone());
+                    model.queue_event(|| ModelEvent::ReferenceRetargeted {
+                        element: ref_element.clone(),
+                        old_target: Some(old_ref.clone()),
+                        new_target: Some(refstr.clone()),
+                    });
                 }
                 let base = ref_element
                     .attribute_value(AttributeName::Base)
@@ -956,9 +1030,9 @@ impl ElementRaw {
             }
         }
 
-        // insert move_element
-        self.content
-            .insert(position, ElementContent::Element(move_element.clone()));
+        // insert move_element, together with the comment it had in its previous location
+        self.insert_sub_element_at(position, move_element, comment);
+        self.mark_modified();
 
         Ok(move_element.clone())
     }
@@ -1090,7 +1164,7 @@ impl ElementRaw {
         model: &AutosarModel,
     ) -> Result<(), AutosarDataError> {
         let path = Cow::from(self.path_unchecked()?);
-        let mut sub_element_locked = sub_element.0.write();
+        let mut sub_element_locked = sub_element.write_lock();
         // find the position of sub_element in the parent element first to verify that sub_element actually *is* a sub element
         let pos = self
             .content
@@ -1119,7 +1193,9 @@ impl ElementRaw {
             self.remove_incomplete_reference_base(model, &sub_element_locked);
         }
         sub_element_locked.remove_internal(sub_element.downgrade(), model, path);
-        self.content.remove(pos);
+        // the comment of the sub element is removed with it, so that it does not become the comment of the next element
+        self.take_sub_element_at(pos);
+        self.mark_modified();
         Ok(())
     }
 
@@ -1148,8 +1224,7 @@ impl ElementRaw {
         for item in &self.content {
             if let ElementContent::Element(sub_element) = item {
                 sub_element
-                    .0
-                    .write()
+                    .write_lock()
                     .remove_internal(sub_element.downgrade(), model, Cow::from(path.as_ref()));
             }
         }
@@ -1177,16 +1252,13 @@ impl ElementRaw {
         version: AutosarVersion,
     ) -> Result<(), AutosarDataError> {
         if (self.elemtype.content_mode() == ContentMode::Characters
-            || (self.elemtype.content_mode() == ContentMode::Mixed && self.content.len() <= 1))
+            || (self.elemtype.content_mode() == ContentMode::Mixed && self.data_items().count() <= 1))
             && let Some(cdata_spec) = self.elemtype.chardata_spec()
             && CharacterData::check_value(&chardata, cdata_spec, version)
         {
             // update the character data
-            if self.content.is_empty() {
-                self.content.push(ElementContent::CharacterData(chardata));
-            } else {
-                self.content[0] = ElementContent::CharacterData(chardata);
-            }
+            self.replace_content_with_chardata(chardata);
+            self.mark_modified();
             return Ok(());
         }
         Err(AutosarDataError::IncorrectContentType {
@@ -1199,16 +1271,79 @@ impl ElementRaw {
     /// This method only applies to elements which contain character data, i.e. `element.content_type` == `CharacterData`,
     /// or elements with `element.content_type` == Mixed, but which only contain a single `CharacterData` item
     pub(crate) fn character_data(&self) -> Option<CharacterData> {
-        if self.content.len() == 1
-            && (self.elemtype.content_mode() == ContentMode::Characters
-                || self.elemtype.content_mode() == ContentMode::Mixed)
-            && let Some(ElementContent::CharacterData(cdata)) = self.content.first()
+        let mut data_items = self.data_items();
+        if (self.elemtype.content_mode() == ContentMode::Characters
+            || self.elemtype.content_mode() == ContentMode::Mixed)
+            && let (Some(ElementContent::CharacterData(cdata)), None) = (data_items.next(), data_items.next())
         {
             return Some(cdata.clone());
         }
         None
     }
 
+    // iterate over the sub elements and character data, skipping comments and processing instructions
+    fn data_items(&self) -> impl Iterator<Item = &ElementContent> {
+        self.content.iter().filter(|item| !item.is_markup())
+    }
+
+    // get the first sub element; it may be preceded by comments and processing instructions
+    fn first_sub_element(&self) -> Option<&Element> {
+        self.content.iter().find_map(|item| match item {
+            ElementContent::Element(sub_element) => Some(sub_element),
+            _ => None,
+        })
+    }
+
+    /// replace all character data and sub elements by the new character data
+    ///
+    /// Comments and processing instructions are kept in place.
+    pub(crate) fn replace_content_with_chardata(&mut self, chardata: CharacterData) {
+        // all items before this position are comments or processing instructions, so they are not moved by retain()
+        let position = self
+            .content
+            .iter()
+            .position(|item| !item.is_markup())
+            .unwrap_or(self.content.len());
+        self.content.retain(|item| item.is_markup());
+        self.content.insert(position, ElementContent::CharacterData(chardata));
+        self.mark_modified();
+    }
+
+    /// get the position of the sub element in the content of this element
+    pub(crate) fn sub_element_position(&self, sub_element: &Element) -> Option<usize> {
+        self.content
+            .iter()
+            .position(|item| matches!(item, ElementContent::Element(elem) if elem == sub_element))
+    }
+
+    /// get the position of the comment item directly in front of the sub element
+    pub(crate) fn comment_position(&self, sub_element: &Element) -> Option<usize> {
+        let comment_pos = self.sub_element_position(sub_element)?.checked_sub(1)?;
+        matches!(self.content[comment_pos], ElementContent::Comment(_)).then_some(comment_pos)
+    }
+
+    /// remove the sub element at the given position from the content, together with its comment
+    ///
+    /// The comment is returned, so that it can be inserted again in front of the element if it is moved.
+    fn take_sub_element_at(&mut self, position: usize) -> Option<String> {
+        self.content.remove(position);
+        let comment_pos = position.checked_sub(1)?;
+        if matches!(self.content[comment_pos], ElementContent::Comment(_)) {
+            self.content.remove(comment_pos).unwrap_comment()
+        } else {
+            None
+        }
+    }
+
+    /// insert a sub element at the given position in the content, preceded by its comment
+    fn insert_sub_element_at(&mut self, position: usize, sub_element: &Element, comment: Option<String>) {
+        self.content
+            .insert(position, ElementContent::Element(sub_element.clone()));
+        if let Some(comment) = comment {
+            self.content.insert(position, ElementContent::Comment(comment));
+        }
+    }
+
     /// get a single attribute by name
     pub(crate) fn attribute_value(&self, attrname: AttributeName) -> Option<CharacterData> {
         if let Some(attr) = self.attributes.iter().find(|attr| attr.attrname == attrname) {
@@ -1236,6 +1371,7 @@ impl ElementRaw {
                         content: value,
                     });
                 }
+                self.mark_modified();
                 Ok(())
             } else {
                 Err(AutosarDataError::InvalidAttributeValue)
@@ -1265,6 +1401,7 @@ impl ElementRaw {
                         content: value,
                     });
                 }
+                self.mark_modified();
                 Ok(())
             } else {
                 Err(AutosarDataError::InvalidAttributeValue)
@@ -1284,6 +1421,7 @@ impl ElementRaw {
                     // the attribute can only be removed if it is optional
                     if !required {
                         self.attributes.remove(idx);
+                        self.mark_modified();
                         return true;
                     }
                 }
@@ -1300,34 +1438,41 @@ impl ElementRaw {
                 let len = self.content.len();
                 if !self.elemtype.is_ordered() && len > 1 {
                     // remove all child elements from this element and sort them
-                    let mut sorting_vec: Vec<(Vec<usize>, Element)> = Vec::with_capacity(len);
+                    // comments and processing instructions stay in front of the element that follows them
+                    let mut sorting_vec: Vec<(Vec<usize>, Element, Vec<ElementContent>)> = Vec::with_capacity(len);
+                    let mut markup = Vec::new();
                     for ec_elem in &self.content {
                         if let ElementContent::Element(elem) = ec_elem {
                             // descend into the element and sort it before doing anything else with it
-                            elem.sort();
+                            elem.write_lock().sort();
                             let (_, elem_indices) =
                                 self.elemtype.find_sub_element(elem.element_name(), u32::MAX).unwrap();
-                            sorting_vec.push((elem_indices, elem.clone()));
+                            sorting_vec.push((elem_indices, elem.clone(), std::mem::take(&mut markup)));
+                        } else {
+                            // Sequence, Choice and Bag do not have character content, so this is a comment or processing instruction
+                            markup.push(ec_elem.clone());
                         }
-                        // Sequence, Choice and Bag do not have character content, so else {} is not needed
                     }
 
                     // sort the elements, first by their indices, then by their content
-                    sorting_vec.sort_by(|(elem_indices_a, elem_a), (elem_indices_b, elem_b)| {
+                    sorting_vec.sort_by(|(elem_indices_a, elem_a, _), (elem_indices_b, elem_b, _)| {
                         elem_indices_a.cmp(elem_indices_b).then(elem_a.cmp(elem_b))
                     });
 
                     self.content.clear();
-                    // put the sorted elements back
-                    for (_, elem) in sorting_vec {
+                    // put the sorted elements back, followed by any trailing comments and processing instructions
+                    for (_, elem, leading_markup) in sorting_vec {
+                        self.content.extend(leading_markup);
                         self.content.push(ElementContent::Element(elem));
                     }
+                    self.content.extend(markup);
+                    self.mark_modified();
                 } else {
                     // 0 or 1 content items -or- the element is ordered and sorting it is forbidden.
                     // in either case we need to descend into the child element(s)
                     for ec in &self.content {
                         if let ElementContent::Element(elem) = ec {
-                            elem.sort();
+                            elem.write_lock().sort();
                         }
                     }
                 }
@@ -0,0 +1,320 @@
+//! Crash-safe writing of several arxml files
+//!
+//! All files are serialized to temporary files in the directories of their targets before any existing file is touched.
+//! Each temporary file is then renamed over its target, after a backup of the original file was created as a hard link
+//! or a copy. This way the target path always refers to a complete file. If a rename fails, the backups are renamed
+//! back into place, so the original files are either all replaced or all left untouched.
+use crate::*;
+use std::fs::OpenOptions;
+use std::io::ErrorKind;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+// the number of attempts to find an unused name for a temporary file or a backup
+const MAX_ATTEMPTS: usize = 100;
+
+// makes the names of temporary files unique within the process
+static SIBLING_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+/// the state of one file during the write operation
+struct PendingFile {
+    target: PathBuf,
+    temporary: PathBuf,
+    // the path of the backup of the original file, if the target existed
+    backup: Option<PathBuf>,
+    // true if the temporary file was renamed to the target path
+    replaced: bool,
+}
+
+/// write all files atomically
+pub(crate) fn write_files(files: &[ArxmlFile], options: &SerializeOptions) -> Result<(), AutosarDataError> {
+    let mut pending = Vec::with_capacity(files.len());
+    for file in files {
+        match write_temporary(file, options) {
+            Ok(temporary) => pending.push(PendingFile {
+                target: file.filename(),
+                temporary,
+                backup: None,
+                replaced: false,
+            }),
+            Err(error) => {
+                rollback(&pending);
+                return Err(error);
+            }
+        }
+    }
+
+    for idx in 0..pending.len() {
+        if let Err(ioerror) = replace_file(&mut pending[idx]) {
+            let filename = pending[idx].target.clone();
+            rollback(&pending);
+            return Err(AutosarDataError::IoErrorWrite { filename, ioerror });
+        }
+    }
+
+    // the backups are no longer needed. Failing to remove one is not an error, since all files were written
+    for backup in pending.iter().filter_map(|pending_file| pending_file.backup.as_ref()) {
+        let _ = std::fs::remove_file(backup);
+    }
+    Ok(())
+}
+
+/// get a unique path in the same directory as the target file, so that renaming it does not cross file systems
+fn sibling_path(target: &Path, extension: &str) -> PathBuf {
+    let name = target.file_name().unwrap_or_default().to_string_lossy();
+    let counter = SIBLING_COUNTER.fetch_add(1, Ordering::Relaxed);
+    target.with_file_name(format!(".{name}.{}.{counter}.{extension}", std::process::id()))
+}
+
+/// create a new item next to the target file
+///
+/// `create` must fail with `ErrorKind::AlreadyExists` if the path is in use, e.g. by a file that was left behind by a
+/// crashed process. In this case another path is tried.
+fn create_sibling(
+    target: &Path,
+    extension: &str,
+    mut create: impl FnMut(&Path) -> std::io::Result<()>,
+) -> std::io::Result<PathBuf> {
+    let mut attempt = 1;
+    loop {
+        let path = sibling_path(target, extension);
+        match create(&path) {
+            Ok(()) => return Ok(path),
+            Err(error) if error.kind() == ErrorKind::AlreadyExists && attempt < MAX_ATTEMPTS => attempt += 1,
+            Err(error) => return Err(error),
+        }
+    }
+}
+
+/// serialize the file to a new temporary file and flush it to disk
+fn write_temporary(file: &ArxmlFile, options: &SerializeOptions) -> Result<PathBuf, AutosarDataError> {
+    let filename = file.filename();
+    let mut result = Ok(());
+    let create_result = create_sibling(&filename, "tmp", |temporary| {
+        let mut output = OpenOptions::new().write(true).create_new(true).open(temporary)?;
+        result = file.write_to_with_options(&mut output, options).and_then(|()| {
+            output.sync_all().map_err(|ioerror| AutosarDataError::IoErrorWrite {
+                filename: filename.clone(),
+                ioerror,
+            })
+        });
+        if result.is_err() {
+            drop(output);
+            let _ = std::fs::remove_file(temporary);
+        }
+        Ok(())
+    });
+    let temporary = create_result.map_err(|ioerror| AutosarDataError::IoErrorWrite {
+        filename: filename.clone(),
+        ioerror,
+    })?;
+    result.map(|()| temporary)
+}
+
+/// keep a backup of the original file, and rename the temporary file over the target path
+///
+/// The temporary file gets the permissions of the original file. After the rename, the directory is synced,
+/// so that the new directory entry is on disk as well.
+fn replace_file(pending_file: &mut PendingFile) -> std::io::Result<()> {
+    match std::fs::metadata(&pending_file.target) {
+        Ok(metadata) => {
+            let backup = create_sibling(&pending_file.target, "bak", |backup| {
+                create_backup(&pending_file.target, backup)
+            })?;
+            pending_file.backup = Some(backup);
+            std::fs::set_permissions(&pending_file.temporary, metadata.permissions())?;
+        }
+        Err(error) if error.kind() == ErrorKind::NotFound => {}
+        Err(error) => return Err(error),
+    }
+    std::fs::rename(&pending_file.temporary, &pending_file.target)?;
+    pending_file.replaced = true;
+    sync_directory(&pending_file.target)
+}
+
+/// create a hard link to the original file, or a copy if the file system does not support hard links
+fn create_backup(target: &Path, backup: &Path) -> std::io::Result<()> {
+    match std::fs::hard_link(target, backup) {
+        Ok(()) => Ok(()),
+        Err(error) if error.kind() == ErrorKind::AlreadyExists => Err(error),
+        Err(_) => {
+            let mut input = std::fs::File::open(target)?;
+            let mut output = OpenOptions::new().write(true).create_new(true).open(backup)?;
+            std::io::copy(&mut input, &mut output)?;
+            output.sync_all()
+        }
+    }
+}
+
+/// flush the directory entries of the directory containing the path to disk
+#[cfg(unix)]
+fn sync_directory(path: &Path) -> std::io::Result<()> {
+    match path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => std::fs::File::open(dir)?.sync_all(),
+        _ => std::fs::File::open(".")?.sync_all(),
+    }
+}
+
+/// directories can't be opened as files on this platform, so the directory entries are flushed by the OS
+#[cfg(not(unix))]
+fn sync_directory(_path: &Path) -> std::io::Result<()> {
+    Ok(())
+}
+
+/// restore the original files and remove all temporary files and backups
+fn rollback(pending: &[PendingFile]) {
+    for pending_file in pending.iter().rev() {
+        match (&pending_file.backup, pending_file.replaced) {
+            // renaming the backup over the target restores the original file in a single step
+            (Some(backup), true) => {
+                let _ = std::fs::rename(backup, &pending_file.target);
+            }
+            (None, true) => {
+                let _ = std::fs::remove_file(&pending_file.target);
+            }
+            (backup, false) => {
+                let _ = std::fs::remove_file(&pending_file.temporary);
+                if let Some(backup) = backup {
+                    let _ = std::fs::remove_file(backup);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn dir_entries(dir: &Path) -> Vec<String> {
+        let mut entries: Vec<String> = std::fs::read_dir(dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().to_string_lossy().to_string())
+            .collect();
+        entries.sort();
+        entries
+    }
+
+    #[test]
+    fn replace_files() {
+        let dir = tempdir().unwrap();
+        let filename1 = dir.path().join("file1.arxml");
+        let filename2 = dir.path().join("file2.arxml");
+        std::fs::write(&filename1, "original").unwrap();
+
+        let model = AutosarModel::new();
+        let file1 = model.create_file(&filename1, AutosarVersion::LATEST).unwrap();
+        let file2 = model.create_file(&filename2, AutosarVersion::LATEST).unwrap();
+        write_files(&[file1.clone(), file2.clone()], &SerializeOptions::default()).unwrap();
+
+        // the existing file was replaced, the new file was created, and no temporary files are left
+        assert_eq!(std::fs::read_to_string(&filename1).unwrap(), file1.serialize().unwrap());
+        assert_eq!(std::fs::read_to_string(&filename2).unwrap(), file2.serialize().unwrap());
+        assert_eq!(dir_entries(dir.path()), vec!["file1.arxml", "file2.arxml"]);
+    }
+
+    #[test]
+    fn failed_write() {
+        let dir = tempdir().unwrap();
+        let filename1 = dir.path().join("file1.arxml");
+        let filename2 = dir.path().join("nonexistent").join("file2.arxml");
+        std::fs::write(&filename1, "original").unwrap();
+
+        let model = AutosarModel::new();
+        let file1 = model.create_file(&filename1, AutosarVersion::LATEST).unwrap();
+        let file2 = model.create_file(&filename2, AutosarVersion::LATEST).unwrap();
+        let result = write_files(&[file1, file2], &SerializeOptions::default());
+        let Err(AutosarDataError::IoErrorWrite { filename, .. }) = result else {
+            panic!("unexpected result: {result:?}");
+        };
+        assert_eq!(filename, filename2);
+
+        // the original file is untouched and the temporary file of file1 was removed
+        assert_eq!(std::fs::read_to_string(&filename1).unwrap(), "original");
+        assert_eq!(dir_entries(dir.path()), vec!["file1.arxml"]);
+    }
+
+    #[test]
+    fn failed_rename() {
+        let dir = tempdir().unwrap();
+        let filename1 = dir.path().join("file1.arxml");
+        let filename2 = dir.path().join("file2.arxml");
+        std::fs::write(&filename1, "original").unwrap();
+
+        let model = AutosarModel::new();
+        let file1 = model.create_file(&filename1, AutosarVersion::LATEST).unwrap();
+        let file2 = model.create_file(&filename2, AutosarVersion::LATEST).unwrap();
+        let mut pending: Vec<PendingFile> = [&file1, &file2]
+            .iter()
+            .map(|file| PendingFile {
+                target: file.filename(),
+                temporary: write_temporary(file, &SerializeOptions::default()).unwrap(),
+                backup: None,
+                replaced: false,
+            })
+            .collect();
+        replace_file(&mut pending[0]).unwrap();
+        assert_eq!(std::fs::read_to_string(&filename1).unwrap(), file1.serialize().unwrap());
+        // the backup of the original file exists next to the replaced file
+        assert_eq!(
+            std::fs::read_to_string(pending[0].backup.as_ref().unwrap()).unwrap(),
+            "original"
+        );
+
+        // the second rename fails because its temporary file is missing
+        std::fs::remove_file(&pending[1].temporary).unwrap();
+        assert!(replace_file(&mut pending[1]).is_err());
+        rollback(&pending);
+        assert_eq!(std::fs::read_to_string(&filename1).unwrap(), "original");
+        assert_eq!(dir_entries(dir.path()), vec!["file1.arxml"]);
+    }
+
+    #[test]
+    fn stale_files() {
+        let dir = tempdir().unwrap();
+        let filename = dir.path().join("file.arxml");
+        // the names of temporary files are unique, and names that are in use are skipped
+        let first = sibling_path(&filename, "tmp");
+        assert_ne!(first, sibling_path(&filename, "tmp"));
+        let mut attempts = 0;
+        let path = create_sibling(&filename, "tmp", |_| {
+            attempts += 1;
+            if attempts < 3 {
+                Err(std::io::Error::from(ErrorKind::AlreadyExists))
+            } else {
+                Ok(())
+            }
+        })
+        .unwrap();
+        assert_eq!(attempts, 3);
+        assert_ne!(path, first);
+
+        // a temporary file that was left behind by a crashed process does not prevent writing
+        let model = AutosarModel::new();
+        let file = model.create_file(&filename, AutosarVersion::LATEST).unwrap();
+        let stale_name = format!(
+            ".file.arxml.{}.{}.tmp",
+            std::process::id(),
+            SIBLING_COUNTER.load(Ordering::Relaxed)
+        );
+        std::fs::write(dir.path().join(&stale_name), "stale").unwrap();
+        write_files(std::slice::from_ref(&file), &SerializeOptions::default()).unwrap();
+        assert_eq!(std::fs::read_to_string(&filename).unwrap(), file.serialize().unwrap());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn keep_permissions() {
+        use std::os::unix::fs::PermissionsExt;
+        let dir = tempdir().unwrap();
+        let filename = dir.path().join("file.arxml");
+        std::fs::write(&filename, "original").unwrap();
+        std::fs::set_permissions(&filename, std::fs::Permissions::from_mode(0o640)).unwrap();
+
+        let model = AutosarModel::new();
+        let file = model.create_file(&filename, AutosarVersion::LATEST).unwrap();
+        write_files(&[file], &SerializeOptions::default()).unwrap();
+        let mode = std::fs::metadata(&filename).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o640);
+    }
+}
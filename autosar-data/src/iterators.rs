@@ -265,6 +265,35 @@ impl Iterator for IdentifiablesIterator {
 
 impl FusedIterator for IdentifiablesIterator {}
 
+#[doc(hidden)]
+pub struct QueryIterator {
+    elements: std::vec::IntoIter<Element>,
+}
+
+impl QueryIterator {
+    pub(crate) fn new(elements: Vec<Element>) -> Self {
+        Self {
+            elements: elements.into_iter(),
+        }
+    }
+}
+
+impl Iterator for QueryIterator {
+    type Item = Element;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.elements.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.elements.size_hint()
+    }
+}
+
+impl ExactSizeIterator for QueryIterator {}
+
+impl FusedIterator for QueryIterator {}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -317,7 +346,8 @@ mod test {
             attributes: SmallVec::new(),
             content: SmallVec::new(),
             file_membership: HashSet::with_capacity(0),
-            comment: None,
+            source_location: None,
+            modified: false,
         }
         .wrap();
         let sub_element = ElementRaw {
@@ -330,7 +360,8 @@ mod test {
                 ElementContent::Element(sub_sub_element.clone())
             ],
             file_membership: HashSet::with_capacity(0),
-            comment: None,
+            source_location: None,
+            modified: false,
         }
         .wrap();
         let element = ElementRaw {
@@ -343,7 +374,8 @@ mod test {
                 ElementContent::Element(sub_element.clone())
             ],
             file_membership: HashSet::with_capacity(0),
-            comment: None,
+            source_location: None,
+            modified: false,
         }
         .wrap();
         let dfs_iter = element.elements_dfs();
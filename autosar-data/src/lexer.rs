@@ -34,6 +34,7 @@ pub(crate) enum ArxmlEvent<'a> {
     EndElement(&'a [u8]),
     Characters(&'a [u8]),
     Comment(&'a [u8]),
+    ProcessingInstruction(&'a [u8]),
     EndOfFile,
 }
 
@@ -41,7 +42,11 @@ pub(crate) struct ArxmlLexer<'a> {
     buffer: &'a [u8],
     bufpos: usize,
     line: usize,
+    line_start: usize,
+    column_cache: (usize, usize),
+    element_position: (usize, usize),
     deferred_end: Option<(usize, usize)>,
+    token_start: usize,
     sourcefile: PathBuf,
 }
 
@@ -57,7 +62,11 @@ impl<'a> ArxmlLexer<'a> {
             buffer,
             bufpos,
             line: 1,
+            line_start: bufpos,
+            column_cache: (bufpos, 1),
+            element_position: (1, 1),
             deferred_end: None,
+            token_start: bufpos,
             sourcefile: name,
         }
     }
@@ -74,6 +83,7 @@ impl<'a> ArxmlLexer<'a> {
                 all_whitespace = false;
             } else if self.buffer[endpos] == b'\n' {
                 self.line += 1;
+                self.line_start = endpos + 1;
             }
             endpos += 1;
         }
@@ -107,7 +117,8 @@ impl<'a> ArxmlLexer<'a> {
             self.deferred_end = Some((self.bufpos + 1, self.bufpos + 1 + elemname.len()));
         }
 
-        self.line += count_lines(text);
+        self.element_position = (self.line, self.column(self.bufpos));
+        self.count_lines(self.bufpos + 1, endpos);
         self.bufpos = endpos + 1;
         ArxmlEvent::BeginElement(elemname, attributes)
     }
@@ -123,16 +134,17 @@ impl<'a> ArxmlLexer<'a> {
         ArxmlEvent::EndElement(text)
     }
 
-    fn read_xml_header(&mut self, endpos: usize) -> Option<Result<ArxmlEvent<'a>, AutosarDataError>> {
+    fn read_processing_instruction(&mut self, endpos: usize) -> Result<ArxmlEvent<'a>, AutosarDataError> {
         debug_assert!(self.bufpos < self.buffer.len());
         debug_assert!(endpos > self.bufpos + 1);
         debug_assert!(self.buffer[self.bufpos] == b'<');
 
         if self.buffer[endpos - 1] != b'?' {
-            return Some(Err(self.error(ArxmlLexerError::InvalidProcessingInstruction)));
+            return Err(self.error(ArxmlLexerError::InvalidProcessingInstruction));
         }
 
-        let text = &self.buffer[self.bufpos + 2..endpos - 1];
+        let startpos = self.bufpos + 2;
+        let text = &self.buffer[startpos..endpos - 1];
         self.bufpos = endpos + 1;
 
         let text_trimmed = text.trim_ascii();
@@ -189,15 +201,15 @@ impl<'a> ArxmlLexer<'a> {
                 || ver != b"1.0"
                 || (encoding != b"utf-8" && encoding != b"UTF-8" && encoding != b"utf8" && encoding != b"UTF8")
             {
-                Some(Err(self.error(ArxmlLexerError::InvalidXmlHeader)))
+                Err(self.error(ArxmlLexerError::InvalidXmlHeader))
             } else {
-                Some(Ok(ArxmlEvent::ArxmlHeader(standalone)))
+                Ok(ArxmlEvent::ArxmlHeader(standalone))
             }
         } else {
-            None
+            Ok(ArxmlEvent::ProcessingInstruction(text))
         };
 
-        self.line += count_lines(text);
+        self.count_lines(startpos, endpos);
         result
     }
 
@@ -216,20 +228,66 @@ impl<'a> ArxmlLexer<'a> {
                 source: ArxmlLexerError::InvalidComment,
             });
         }
-        self.line += count_lines(text);
+        self.count_lines(startpos, endpos);
         let comment = &self.buffer[startpos + 4..endpos - 2];
         Ok(ArxmlEvent::Comment(comment))
     }
-}
 
-impl ArxmlLexer<'_> {
-    pub(crate) fn next<'a>(&'a mut self) -> Result<(usize, ArxmlEvent<'a>), AutosarDataError> {
+    // count the lines in the given range of the buffer, and keep track of the start of the current line
+    fn count_lines(&mut self, startpos: usize, endpos: usize) {
+        for (idx, c) in self.buffer[startpos..endpos].iter().enumerate() {
+            if *c == b'\n' {
+                self.line += 1;
+                self.line_start = startpos + idx + 1;
+            }
+        }
+    }
+
+    // get the column of a position in the buffer on the current line
+    // The column counts utf-8 characters and starts at 1. Since positions are requested in increasing order,
+    // counting can continue from the previously calculated position, unless a new line was started since then.
+    fn column(&mut self, pos: usize) -> usize {
+        let (start, column) = if self.column_cache.0 >= self.line_start {
+            self.column_cache
+        } else {
+            (self.line_start, 1)
+        };
+        let column = column
+            + self.buffer[start..pos]
+                .iter()
+                .filter(|c| (**c & 0b1100_0000) != 0b1000_0000)
+                .count();
+        self.column_cache = (pos, column);
+        column
+    }
+
+    /// get the line and column of the most recent `BeginElement` event
+    pub(crate) fn element_position(&self) -> (usize, usize) {
+        self.element_position
+    }
+
+    /// get the position in the buffer where the most recent event started
+    ///
+    /// Whitespace and processing instructions that were skipped before the event are not included.
+    /// The `EndElement` event of an `<element/>` is empty, so it starts at the current position.
+    pub(crate) fn token_start(&self) -> usize {
+        self.token_start
+    }
+
+    /// get the position in the buffer after the most recent event
+    pub(crate) fn position(&self) -> usize {
+        self.bufpos
+    }
+
+    pub(crate) fn next(&mut self) -> Result<(usize, ArxmlEvent<'a>), AutosarDataError> {
         // if an <element/> was found, then a BeginElement event is returned first, and the EndElement is deferred and must be returned next
         if let Some((startpos, endpos)) = self.deferred_end {
             self.deferred_end = None;
+            self.token_start = self.bufpos;
             Ok((self.line, ArxmlEvent::EndElement(&self.buffer[startpos..endpos])))
         } else {
             loop {
+                self.token_start = self.bufpos;
                 if self.bufpos == self.buffer.len() {
                     break Ok((self.line, ArxmlEvent::EndOfFile));
                 } else if self.buffer[self.bufpos] == b'<' {
@@ -254,11 +312,8 @@ impl ArxmlLexer<'_> {
                         }
                         b'?' => {
                             // second char is '?' -> xml header or processing instruction
-                            // processing instructions are ignored, read_xml_header returns None
-                            if let Some(result) = self.read_xml_header(endpos) {
-                                let value = result?;
-                                return Ok((self.line, value));
-                            }
+                            let value = self.read_processing_instruction(endpos)?;
+                            return Ok((self.line, value));
                         }
                         b'!' => {
                             // second char is '!' -> parse a comment
@@ -289,9 +344,7 @@ impl ArxmlLexer<'_> {
                         return Ok((self.line, ArxmlEvent::Characters(text)));
                     }
                 }
-                // loop if:
-                // - a processing instruction was ignored
-                // - empty character data found (whitespace only)
+                // loop if empty character data was found (whitespace only)
             }
         }
     }
@@ -305,10 +358,6 @@ impl ArxmlLexer<'_> {
     }
 }
 
-fn count_lines(text: &[u8]) -> usize {
-    text.iter().filter(|c| **c == b'\n').count()
-}
-
 #[cfg(test)]
 mod test {
     use super::*;
@@ -335,6 +384,31 @@ mod test {
         assert!(matches!(lexer.next(), Ok((_, ArxmlEvent::ArxmlHeader(None)))));
     }
 
+    #[test]
+    fn element_position() {
+        let data =
+            "<?xml version=\"1.0\" encoding=\"utf-8\"?>\n<a>\n  <b\n x=\"1\"/><c>ä</c>  <d>\n</d></a>".as_bytes();
+        let mut lexer = ArxmlLexer::new(data, PathBuf::from("(buffer)"));
+        let mut positions = Vec::new();
+        loop {
+            match lexer.next() {
+                Ok((_, ArxmlEvent::BeginElement(elem, _))) => positions.push((elem, lexer.element_position())),
+                Ok((_, ArxmlEvent::EndOfFile)) => break,
+                Ok(_) => {}
+                Err(err) => panic!("{err}"),
+            }
+        }
+        assert_eq!(
+            positions,
+            vec![
+                (&b"a"[..], (2, 1)),
+                (&b"b"[..], (3, 3)),
+                (&b"c"[..], (4, 9)),
+                (&b"d"[..], (4, 19))
+            ]
+        );
+    }
+
     #[test]
     fn test_incomplete_data() {
         let data = b"<element";
@@ -364,6 +438,17 @@ mod test {
         );
     }
 
+    #[test]
+    fn test_processing_instruction() {
+        let data = b"<element><?tool some data?></element>";
+        let mut lexer = ArxmlLexer::new(data, PathBuf::from("(buffer)"));
+        assert!(lexer.next().is_ok());
+        assert!(
+            matches!(lexer.next(), Ok((_, ArxmlEvent::ProcessingInstruction(instruction))) if instruction == b"tool some data")
+        );
+        assert!(matches!(lexer.next(), Ok((_, ArxmlEvent::EndElement(_)))));
+    }
+
     #[test]
     fn test_comment() {
         let data = b"<!-- foo--><element>";
@@ -82,25 +82,49 @@ use indexmap::IndexMap;
 pub use iterators::*;
 use parking_lot::RwLock;
 use parser::ArxmlParser;
+use serialize::SerializeOutput;
 use smallvec::SmallVec;
 use std::collections::HashSet;
 use std::path::{Path, PathBuf};
 use std::sync::{Arc, Weak};
 use std::{fs::File, io::Read};
 use thiserror::Error;
+pub use transaction::Transaction;
 
 mod arxmlfile;
 mod autosarmodel;
 mod chardata;
+mod closure;
+mod completion;
+pub mod diff;
 mod element;
 mod elementraw;
+mod filewriter;
 mod iterators;
 mod lexer;
+mod lossless;
+pub mod merge;
+mod migration;
+mod observer;
 mod parser;
+pub mod patch;
+mod query;
+mod reload;
+mod serialize;
+mod transaction;
+#[cfg(feature = "typed")]
+pub mod typed;
+mod validation;
 
 // allow public access to the error sub-types
+pub use completion::{Completion, CompletionContext};
 pub use lexer::ArxmlLexerError;
+pub use migration::MigrationChange;
+pub use observer::{ModelEvent, ModelObserver, ObserverId};
 pub use parser::ArxmlParserError;
+pub use query::{Query, QueryError};
+pub use serialize::{EmptyElementStyle, Indentation, LineEnding, QuoteStyle, SerializeOptions, XmlDeclaration};
+pub use validation::{ValidationDiagnostic, ValidationErrorKind};
 
 // reexport some of the info from the specification
 pub use autosar_data_specification::AttributeName;
@@ -110,7 +134,7 @@ pub use autosar_data_specification::EnumItem;
 
 type FxIndexMap<K, V> = IndexMap<K, V, FxBuildHasher>;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub(crate) struct ReferenceBaseInfo {
     pub(crate) owner_package_path: String,
     pub(crate) package_ref: String,
@@ -125,11 +149,20 @@ pub(crate) struct ReferenceBaseInfo {
 /// It stores the association between elements and files.
 /// In addition, this top-level structure provides caching of Autosar paths, to allow quick resolution of cross-references.
 #[derive(Clone)]
-pub struct AutosarModel(Arc<RwLock<AutosarModelRaw>>);
+pub struct AutosarModel(Arc<AutosarModelData>);
 
 // Weak reference to an instance of AutosarModel
 #[derive(Clone)]
-pub(crate) struct WeakAutosarModel(Weak<RwLock<AutosarModelRaw>>);
+pub(crate) struct WeakAutosarModel(Weak<AutosarModelData>);
+
+// The data of a model, which is shared by all of its handles
+//
+// The journal of the active transactions is not part of the locked data, because elements are recorded in it while
+// the model may be locked. Access to the locked data works through `Deref`.
+pub(crate) struct AutosarModelData {
+    raw: RwLock<AutosarModelRaw>,
+    journal: transaction::Journal,
+}
 
 /// The inner autosar data model (unlocked)
 ///
@@ -139,14 +172,20 @@ pub(crate) struct WeakAutosarModel(Weak<RwLock<AutosarModelRaw>>);
 pub(crate) struct AutosarModelRaw {
     root_element: Element,
     files: Vec<ArxmlFile>,
+    /// `root_comment` is the comment before the root element, which is not part of the content of any element
+    root_comment: Option<String>,
     /// `identifiables` is a `HashMap` of all named elements, needed to resolve references without doing a full search.
-    identifiables: FxIndexMap<String, WeakElement>,
+    identifiables: transaction::TrackedIndex<FxIndexMap<String, WeakElement>>,
     /// `reference_origins` is a `HashMap` of all referencing elements.
-    reference_origins: FxHashMap<String, Vec<WeakElement>>,
+    reference_origins: transaction::TrackedIndex<FxHashMap<String, Vec<WeakElement>>>,
     /// `relative_reference_origins` is a `HashMap` of all referencing elements with relative paths.
-    relative_reference_origins: FxHashMap<String, Vec<(WeakElement, String)>>, // Relative path -> [(referencing element, base label)]*
+    relative_reference_origins: transaction::TrackedIndex<FxHashMap<String, Vec<(WeakElement, String)>>>, // Relative path -> [(referencing element, base label)]*
     /// `reference_bases` stores all REFERENCE-BASE declarations indexed by short label.
-    reference_bases: FxHashMap<String, Vec<ReferenceBaseInfo>>,
+    reference_bases: transaction::TrackedIndex<FxHashMap<String, Vec<ReferenceBaseInfo>>>,
+    /// `history` contains the undo and redo steps recorded by transactions
+    history: transaction::ModelHistory,
+    /// `observers` contains the registered observers and the events that have not been delivered to them yet
+    observers: observer::ObserverList,
 }
 
 /// The error type `AutosarDataError` wraps all errors that can be generated anywhere in the crate
@@ -339,6 +378,13 @@ pub enum AutosarDataError {
     #[error("The given value is not valid for this attribute")]
     InvalidAttributeValue,
 
+    /// The attribute is required and cannot be removed
+    #[error("The attribute {attrname} is required and cannot be removed")]
+    AttributeRequired {
+        /// name of the required attribute
+        attrname: AttributeName,
+    },
+
     /// The file is from a different model and may not be used in this operation
     #[error("The file is from a different model and may not be used in this operation")]
     InvalidFile,
@@ -363,6 +409,43 @@ pub enum AutosarDataError {
         "Modifying the fileset of this element is not allowed, because the parent of the element is not marked as splittable"
     )]
     FilesetModificationForbidden,
+
+    /// A transaction is in progress, so the undo history cannot be used
+    #[error("The operation cannot be completed because a transaction is in progress")]
+    TransactionInProgress,
+
+    /// The model was modified outside of a transaction, so the recorded history no longer applies to it
+    #[error("The model was modified outside of a transaction; the undo history has been discarded")]
+    HistoryConflict,
+
+    /// The patch could not be applied, because some of its operations conflict with the content of the model
+    #[error("The patch could not be applied: {} operations conflict with the model", .conflicts.len())]
+    PatchConflict {
+        /// all conflicts that were found
+        conflicts: Vec<patch::PatchConflict>,
+    },
+
+    /// The query could not be parsed
+    #[error("Invalid query '{}': {}", .query, .source)]
+    InvalidQuery {
+        /// The query string
+        query: String,
+        /// The underlying `QueryError`
+        source: QueryError,
+    },
+
+    /// The element does not have the expected element name
+    #[error("Expected an element {}, but found {}", .expected, .element)]
+    UnexpectedElement {
+        /// The expected element name
+        expected: ElementName,
+        /// The name of the element that was found
+        element: ElementName,
+    },
+
+    /// The processing instruction is not valid
+    #[error("The processing instruction is not valid")]
+    InvalidProcessingInstruction,
 }
 
 /// An Autosar arxml file
@@ -381,6 +464,8 @@ pub(crate) struct ArxmlFileRaw {
     model: WeakAutosarModel,
     pub(crate) filename: PathBuf,
     pub(crate) xml_standalone: Option<bool>, // preserve the xml standalone attribute
+    pub(crate) modified: bool,               // the content changed since the file was loaded or written
+    pub(crate) original_text: Option<Arc<lossless::OriginalText>>, // only present if the file was loaded in lossless mode
 }
 
 /// An arxml element
@@ -406,7 +491,64 @@ pub(crate) struct ElementRaw {
     pub(crate) content: SmallVec<[ElementContent; 4]>,
     pub(crate) attributes: SmallVec<[Attribute; 1]>,
     pub(crate) file_membership: HashSet<WeakArxmlFile>,
-    pub(crate) comment: Option<String>,
+    pub(crate) source_location: Option<SourceLocation>,
+    pub(crate) modified: bool, // the element changed since it was loaded, so its original text can't be used
+}
+
+/// The location in an arxml file where an element was loaded from
+///
+/// The line and column refer to the `<` of the opening tag of the element.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct SourceLocation {
+    /// The name of the file that contained the element
+    pub filename: Arc<Path>,
+    /// The line number, starting at 1
+    pub line: usize,
+    /// The column, starting at 1. It counts characters, not bytes.
+    pub column: usize,
+}
+
+/// Options for loading arxml data
+///
+/// # Example
+///
+/// ```
+/// # use autosar_data::*;
+/// # fn main() -> Result<(), AutosarDataError> {
+/// # let buffer = br#"<?xml version="1.0" encoding="utf-8"?>
+/// # <AUTOSAR xsi:schemaLocation="http://autosar.org/schema/r4.0 AUTOSAR_00050.xsd" xmlns="http://autosar.org/schema/r4.0" xmlns:xsi="http://www.w3.org/2001/XMLSchema-instance">
+/// #   <!-- comment --><AR-PACKAGES/></AUTOSAR>"#;
+/// let model = AutosarModel::new();
+/// let options = LoadOptions {
+///     lossless: true,
+///     ..Default::default()
+/// };
+/// let (file, _) = model.load_buffer_with_options(buffer, "file.arxml", &options)?;
+/// assert_eq!(file.serialize()?.as_bytes(), buffer);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LoadOptions {
+    /// toggle strict parsing. Some parsing errors are recoverable and can be issued as warnings.
+    pub strict: bool,
+    /// keep the original text of the file, so that elements which are not modified are serialized exactly as they were loaded.
+    ///
+    /// This preserves whitespace, the order and quoting of attributes, comments, processing instructions and the spelling of entities.
+    /// Elements that are modified are formatted according to the [`SerializeOptions`].
+    ///
+    /// The complete text of each file is kept in memory while the file is part of the model, together with the location
+    /// of each element in the text. This roughly doubles the memory needed for a loaded file.
+    pub lossless: bool,
+}
+
+impl Default for LoadOptions {
+    fn default() -> Self {
+        Self {
+            strict: true,
+            lossless: false,
+        }
+    }
 }
 
 /// A single attribute of an arxml element
@@ -421,12 +563,19 @@ pub struct Attribute {
 /// One content item inside an arxml element
 ///
 /// Elements may contain other elements, character data, or a mixture of both, depending on their type.
+/// Comments and processing instructions can appear in the content of any element.
+/// A comment that precedes a sub element is a separate content item, which is also returned by [`Element::comment`].
 #[derive(Clone, PartialEq, Eq, PartialOrd, Ord)]
+#[non_exhaustive]
 pub enum ElementContent {
     /// A sub element
     Element(Element),
     /// Character data
     CharacterData(CharacterData),
+    /// A comment, without the surrounding `<!--` and `-->`
+    Comment(String),
+    /// A processing instruction, without the surrounding `<?` and `?>`
+    ProcessingInstruction(String),
 }
 
 /// The enum `CharacterData` provides typed access to the content of elements and attributes
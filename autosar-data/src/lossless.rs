@@ -0,0 +1,297 @@
+//! The original text of files that were loaded in lossless mode
+//!
+//! In lossless mode the parser records the exact text of each element: its start tag, the text between its content
+//! items, the raw text of its character data, comments and processing instructions, and its end tag. Whitespace is
+//! part of the text between the content items.
+//!
+//! The serializer uses the recorded text for elements that were not modified since they were loaded.
+//! Every modification of the attributes or the content of an element sets its `modified` flag, and so does a change of
+//! the files that its sub elements belong to. The recorded text is also ignored if the content items of the element that
+//! belong to the serialized file don't match the recorded items.
+//!
+//! The complete text of the file is kept for as long as the file is part of the model, so lossless mode is opt-in
+//! via [`LoadOptions::lossless`](crate::LoadOptions::lossless).
+use crate::*;
+use std::ops::Range;
+
+/// The original text of a file, and the location of each element in that text
+pub(crate) struct OriginalText {
+    text: String,
+    // everything before the <AUTOSAR> element, including the xml header
+    pub(crate) prolog: Range<usize>,
+    // everything after the </AUTOSAR> element
+    pub(crate) epilog: Range<usize>,
+    pub(crate) standalone: Option<bool>,
+    // the comment before the <AUTOSAR> element, which is part of the prolog
+    pub(crate) root_comment: Option<String>,
+    elements: FxHashMap<WeakElement, ElementText>,
+}
+
+/// The original text of a single element, as ranges in the text of the file
+pub(crate) struct ElementText {
+    pub(crate) start_tag: Range<usize>,
+    // for each content item: the text before it, and the raw text of all items except sub elements
+    pub(crate) content: Vec<(Range<usize>, Option<Range<usize>>)>,
+    // the text between the last content item and the end tag
+    pub(crate) end_gap: Range<usize>,
+    // empty if the element was written as <element/>
+    pub(crate) end_tag: Range<usize>,
+}
+
+/// Records the text of the content items of an element while it is parsed
+pub(crate) struct ContentTextRecorder {
+    content: Vec<(Range<usize>, Option<Range<usize>>)>,
+    // the end of the previous content item, or of the start tag
+    last_end: usize,
+}
+
+impl OriginalText {
+    /// create the original text from the buffer of the parser; returns None if the buffer is not valid utf-8
+    pub(crate) fn new(
+        buffer: &[u8],
+        prolog: Range<usize>,
+        epilog: Range<usize>,
+        standalone: Option<bool>,
+        root_comment: Option<String>,
+        elements: FxHashMap<WeakElement, ElementText>,
+    ) -> Option<Self> {
+        let text = String::from_utf8(buffer.to_vec()).ok()?;
+        Some(Self {
+            text,
+            prolog,
+            epilog,
+            standalone,
+            root_comment,
+            elements,
+        })
+    }
+
+    pub(crate) fn text(&self, range: &Range<usize>) -> &str {
+        &self.text[range.clone()]
+    }
+
+    /// get the original text of the element, if it is unmodified
+    pub(crate) fn element_text(&self, element: &Element) -> Option<&ElementText> {
+        let element_text = self.elements.get(&element.downgrade())?;
+        (!element.0.read().modified).then_some(element_text)
+    }
+}
+
+impl ContentTextRecorder {
+    pub(crate) fn new(start_tag_end: usize) -> Self {
+        Self {
+            content: Vec::new(),
+            last_end: start_tag_end,
+        }
+    }
+
+    /// record a sub element, whose text is recorded separately
+    pub(crate) fn sub_element(&mut self, start: usize, end: usize) {
+        self.content.push((self.last_end..start, None));
+        self.last_end = end;
+    }
+
+    /// record an item whose text is written unchanged: character data, a comment or a processing instruction
+    pub(crate) fn text_item(&mut self, range: Range<usize>) {
+        self.content.push((self.last_end..range.start, Some(range.clone())));
+        self.last_end = range.end;
+    }
+
+    pub(crate) fn finish(self, start_tag: Range<usize>, end_tag: Range<usize>) -> ElementText {
+        ElementText {
+            start_tag,
+            content: self.content,
+            end_gap: self.last_end..end_tag.start,
+            end_tag,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+
+    const FILE_TEXT: &str = "\u{feff}<?xml version='1.0' encoding='UTF-8'?>
+<!--root comment-->
+<AUTOSAR xsi:schemaLocation=\"http://autosar.org/schema/r4.0 AUTOSAR_00050.xsd\" xmlns:xsi=\"http://www.w3.org/2001/XMLSchema-instance\" xmlns=\"http://autosar.org/schema/r4.0\">
+\t<AR-PACKAGES>
+\t\t<!-- package comment -->
+\t\t<AR-PACKAGE><SHORT-NAME>Pkg</SHORT-NAME>
+      <ELEMENTS>
+        <SYSTEM UUID='1234'><SHORT-NAME>System</SHORT-NAME><DESC><L-2 L=\"EN\">a &amp; b <BR/>text</L-2></DESC></SYSTEM>
+        <ECU-INSTANCE   ><SHORT-NAME>Ecu</SHORT-NAME></ECU-INSTANCE>
+        <!-- end of elements -->
+      </ELEMENTS>
+\t\t</AR-PACKAGE>
+\t\t<?tool info?>
+\t</AR-PACKAGES>
+</AUTOSAR>
+";
+
+    fn load(lossless: bool) -> (AutosarModel, ArxmlFile) {
+        let model = AutosarModel::new();
+        let options = LoadOptions {
+            lossless,
+            ..Default::default()
+        };
+        let (file, _) = model
+            .load_buffer_with_options(FILE_TEXT.as_bytes(), "test.arxml", &options)
+            .unwrap();
+        (model, file)
+    }
+
+    #[test]
+    fn unmodified_file() {
+        let (_model, file) = load(true);
+        assert_eq!(file.serialize().unwrap(), FILE_TEXT);
+
+        // the serialize options have no effect on the original text
+        let options = SerializeOptions {
+            line_ending: LineEnding::CrLf,
+            ..Default::default()
+        };
+        assert_eq!(file.serialize_with_options(&options).unwrap(), FILE_TEXT);
+    }
+
+    #[test]
+    fn modified_elements() {
+        let (model, file) = load(true);
+        let ecu = model.get_element_by_path("/Pkg/Ecu").unwrap();
+        ecu.create_sub_element(ElementName::Category)
+            .unwrap()
+            .set_character_data("CAT")
+            .unwrap();
+        let text = file.serialize().unwrap();
+        // the modified element is regenerated, everything else is unchanged
+        let (before, after) = FILE_TEXT.split_once("<ECU-INSTANCE   >").unwrap();
+        let (_, after) = after.split_once("</ECU-INSTANCE>").unwrap();
+        assert!(text.starts_with(before));
+        assert!(text.ends_with(after));
+        assert!(text.contains("<ECU-INSTANCE>\n          <SHORT-NAME>Ecu</SHORT-NAME>"));
+        assert!(text.contains("<CATEGORY>CAT</CATEGORY>"));
+
+        // a removed element is omitted, the text around it is kept
+        let elements = ecu.parent().unwrap().unwrap();
+        elements.remove_sub_element(ecu).unwrap();
+        let text = file.serialize().unwrap();
+        assert!(!text.contains("ECU-INSTANCE"));
+        assert!(text.contains("<SYSTEM UUID='1234'><SHORT-NAME>System</SHORT-NAME><DESC><L-2 L=\"EN\">a &amp; b <BR/>text</L-2></DESC></SYSTEM>"));
+
+        // the reloaded text contains the same model
+        let model2 = AutosarModel::new();
+        model2.load_buffer(text.as_bytes(), "test.arxml", true).unwrap();
+        assert!(model2.get_element_by_path("/Pkg/System").is_some());
+        assert!(model2.get_element_by_path("/Pkg/Ecu").is_none());
+    }
+
+    #[test]
+    fn modified_comments() {
+        let (model, file) = load(true);
+        let package = model.get_element_by_path("/Pkg").unwrap();
+        package.set_comment(Some("new comment".to_string()));
+        let text = file.serialize().unwrap();
+        assert!(!text.contains("package comment"));
+        assert!(text.contains("<!--new comment-->"));
+        // the comment is part of the AR-PACKAGES element, which is regenerated. The package keeps its original text
+        assert!(text.contains("<!--new comment-->\n    <AR-PACKAGE><SHORT-NAME>Pkg</SHORT-NAME>\n      <ELEMENTS>"));
+        assert!(text.contains("<ECU-INSTANCE   >"));
+
+        // changing the root comment regenerates the xml header
+        model.root_element().set_comment(None);
+        let text = file.serialize().unwrap();
+        assert!(!text.contains("root comment"));
+        assert!(text.starts_with("<?xml version=\"1.0\" encoding=\"utf-8\"?>\n<AUTOSAR"));
+        assert!(text.contains("<!--new comment-->"));
+    }
+
+    #[test]
+    fn modified_content_comments() {
+        let (model, file) = load(true);
+        let elements = model
+            .get_element_by_path("/Pkg")
+            .unwrap()
+            .get_sub_element(ElementName::Elements)
+            .unwrap();
+        elements.remove_comment(2).unwrap();
+        elements.insert_comment("new", 0).unwrap();
+        let text = file.serialize().unwrap();
+        // only the ELEMENTS element is regenerated, its sub elements keep their original text
+        assert!(text.contains(
+            "      <ELEMENTS>\n        <!--new-->\n        <SYSTEM UUID='1234'><SHORT-NAME>System</SHORT-NAME>"
+        ));
+        assert!(text.contains(
+            "<ECU-INSTANCE   ><SHORT-NAME>Ecu</SHORT-NAME></ECU-INSTANCE>\n      </ELEMENTS>\n\t\t</AR-PACKAGE>"
+        ));
+        assert!(!text.contains("end of elements"));
+        assert!(text.contains("\t\t<?tool info?>"));
+    }
+
+    #[test]
+    fn modified_flag() {
+        let (model, file) = load(true);
+        let ecu = model.get_element_by_path("/Pkg/Ecu").unwrap();
+        // the element is modified even though its attributes and content are the same as before
+        ecu.set_attribute(AttributeName::Uuid, "5678").unwrap();
+        assert!(ecu.remove_attribute(AttributeName::Uuid));
+        assert!(file.serialize().unwrap().contains("<ECU-INSTANCE>"));
+
+        // reverting a modification restores the flag, so the original text is used again
+        let (model, file) = load(true);
+        let system = model.get_element_by_path("/Pkg/System").unwrap();
+        let transaction = model.begin_transaction();
+        system.set_attribute(AttributeName::Uuid, "5678").unwrap();
+        transaction.rollback();
+        assert_eq!(file.serialize().unwrap(), FILE_TEXT);
+    }
+
+    #[test]
+    fn multiple_files() {
+        let file_text = |system: &str| {
+            format!(
+                r#"<?xml version="1.0" encoding="utf-8"?>
+<AUTOSAR xsi:schemaLocation="http://autosar.org/schema/r4.0 AUTOSAR_00050.xsd" xmlns="http://autosar.org/schema/r4.0" xmlns:xsi="http://www.w3.org/2001/XMLSchema-instance">
+  <AR-PACKAGES><AR-PACKAGE><SHORT-NAME>Pkg</SHORT-NAME>
+    <ELEMENTS><SYSTEM><SHORT-NAME>{system}</SHORT-NAME></SYSTEM></ELEMENTS>
+  </AR-PACKAGE></AR-PACKAGES>
+</AUTOSAR>
+"#
+            )
+        };
+        let model = AutosarModel::new();
+        let options = LoadOptions {
+            lossless: true,
+            ..Default::default()
+        };
+        let (file_a, _) = model
+            .load_buffer_with_options(file_text("X").as_bytes(), "a.arxml", &options)
+            .unwrap();
+        let (file_b, _) = model
+            .load_buffer_with_options(file_text("Y").as_bytes(), "b.arxml", &options)
+            .unwrap();
+        let text_a = file_a.serialize().unwrap();
+        assert!(text_a.contains("<SHORT-NAME>X</SHORT-NAME>"));
+        assert!(!text_a.contains("<SHORT-NAME>Y</SHORT-NAME>"));
+        assert!(file_b.serialize().unwrap().contains("<SHORT-NAME>Y</SHORT-NAME>"));
+
+        // the system from b.arxml is added to a.arxml, so the ELEMENTS of a.arxml no longer match their original text
+        let system_y = model.get_element_by_path("/Pkg/Y").unwrap();
+        system_y.add_to_file(&file_a).unwrap();
+        let text_a = file_a.serialize().unwrap();
+        assert!(text_a.contains("<SHORT-NAME>X</SHORT-NAME>"));
+        assert!(text_a.contains("<SHORT-NAME>Y</SHORT-NAME>"));
+
+        // removing it from a.arxml again omits it
+        system_y.remove_from_file(&file_a).unwrap();
+        let text_a = file_a.serialize().unwrap();
+        assert!(!text_a.contains("<SHORT-NAME>Y</SHORT-NAME>"));
+        assert!(file_b.serialize().unwrap().contains("<SHORT-NAME>Y</SHORT-NAME>"));
+    }
+
+    #[test]
+    fn not_lossless() {
+        let (_model, file) = load(false);
+        let text = file.serialize().unwrap();
+        assert_ne!(text, FILE_TEXT);
+        assert!(text.contains("<ECU-INSTANCE>"));
+    }
+}
@@ -0,0 +1,872 @@
+//! Three-way merge of models
+//!
+//! [`merge_models`] combines the changes that were made in two models (`ours` and `theirs`) since they were
+//! created from a common `base`. The changes in `theirs` are applied to `ours`, unless they conflict with the changes in `ours`.
+//!
+//! Corresponding elements are found in the same way as in [`diff::compare_models`]:
+//! identifiable elements are matched by their Autosar path, and unnamed elements by their keys.
+//! Changes to different elements are merged automatically. The specification is used to decide if two sub elements
+//! that were added by both sides can coexist: sub elements which can occur any number of times are combined, unless their
+//! parent is not splittable and both sides added elements with the same keys. Sub elements that can only occur once cannot be combined.
+//! Both sides can also not modify the sub elements of the same ordered element, since there is no reliable way to combine
+//! the resulting orderings.
+//!
+//! # Example
+//!
+//! ```
+//! # use autosar_data::*;
+//! # fn main() -> Result<(), AutosarDataError> {
+//! # fn create_model() -> Result<(AutosarModel, Element), AutosarDataError> {
+//! #     let model = AutosarModel::new();
+//! #     model.create_file("file.arxml", AutosarVersion::LATEST)?;
+//! #     let packages = model.root_element().create_sub_element(ElementName::ArPackages)?;
+//! #     Ok((model, packages))
+//! # }
+//! let (base, _) = create_model()?;
+//! let (ours, ours_packages) = create_model()?;
+//! let (theirs, theirs_packages) = create_model()?;
+//! ours_packages.create_named_sub_element(ElementName::ArPackage, "Ours")?;
+//! theirs_packages.create_named_sub_element(ElementName::ArPackage, "Theirs")?;
+//!
+//! let conflicts = merge::merge_models(&base, &ours, &theirs)?;
+//! assert!(conflicts.is_empty());
+//! assert!(ours.get_element_by_path("/Theirs").is_some());
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::diff::{Difference, compare_elements, compare_with_counterparts, element_keys, text_content};
+use crate::patch::{ElementData, ElementLocator, set_mixed_text};
+use crate::*;
+use fxhash::FxHashSet;
+
+/// A conflict between the changes in `ours` and `theirs`
+///
+/// Where a conflict occurs, the content of `ours` is kept unchanged.
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub enum MergeConflict {
+    /// Both sides changed the character data of the element to different values
+    CharacterData {
+        /// the element in the base model
+        base: Element,
+        /// the element in our model
+        ours: Element,
+        /// the element in their model
+        theirs: Element,
+    },
+    /// Both sides changed the value of the attribute to different values
+    Attribute {
+        /// the element in the base model
+        base: Element,
+        /// the element in our model
+        ours: Element,
+        /// the element in their model
+        theirs: Element,
+        /// the name of the attribute
+        attrname: AttributeName,
+    },
+    /// Their side removed the attribute, but it is required in our model and cannot be removed
+    AttributeNotRemovable {
+        /// the element in the base model
+        base: Element,
+        /// the element in our model
+        ours: Element,
+        /// the element in their model
+        theirs: Element,
+        /// the name of the attribute
+        attrname: AttributeName,
+    },
+    /// Both sides added an element in the same place, but with different content
+    ElementAdded {
+        /// the element in our model
+        ours: Element,
+        /// the element in their model
+        theirs: Element,
+    },
+    /// One side removed an element, while the other side modified it
+    RemovedModified {
+        /// the removed element in the base model
+        base: Element,
+        /// the element in our model, if it was not removed in our model
+        ours: Option<Element>,
+        /// the modified element in their model, if it was not removed in their model
+        theirs: Option<Element>,
+    },
+    /// Both sides moved the element to different places
+    ElementMoved {
+        /// the element in the base model
+        base: Element,
+        /// the element in our model
+        ours: Element,
+        /// the element in their model
+        theirs: Element,
+    },
+    /// Both sides changed the sub elements of an ordered element
+    OrderedContent {
+        /// the ordered element in the base model
+        base: Element,
+        /// the ordered element in our model
+        ours: Element,
+        /// the ordered element in their model
+        theirs: Element,
+    },
+}
+
+/// Merge the changes between `base` and `theirs` into `ours`
+///
+/// All changes that were made in `theirs` since `base` are applied to `ours`, unless they conflict with changes in `ours`.
+/// The merge is performed as one transaction, so it becomes a single step in the undo history of `ours`.
+/// If a transaction is active in `ours`, then the changes of the merge become part of that transaction instead.
+///
+/// Returns the list of conflicts. If the list is empty, then `ours` now contains the changes of both sides.
+///
+/// # Example
+///
+/// ```
+/// # use autosar_data::*;
+/// # fn main() -> Result<(), AutosarDataError> {
+/// # let base = AutosarModel::new();
+/// # base.create_file("file.arxml", AutosarVersion::LATEST)?;
+/// # let ours = AutosarModel::new();
+/// # ours.create_file("file.arxml", AutosarVersion::LATEST)?;
+/// # let theirs = AutosarModel::new();
+/// # theirs.create_file("file.arxml", AutosarVersion::LATEST)?;
+/// let conflicts = merge::merge_models(&base, &ours, &theirs)?;
+/// for conflict in conflicts {
+///     println!("conflict: {conflict:?}");
+/// }
+/// # Ok(())
+/// # }
+/// ```
+///
+/// # Errors
+///
+///  - Any error that is returned while modifying `ours`. In this case `ours` is not changed.
+pub fn merge_models(
+    base: &AutosarModel,
+    ours: &AutosarModel,
+    theirs: &AutosarModel,
+) -> Result<Vec<MergeConflict>, AutosarDataError> {
+    let mut merger = Merger::new(base, ours, theirs);
+    let transaction = ours.begin_transaction();
+    match merger.merge() {
+        Ok(()) => {
+            transaction.commit();
+            Ok(merger.conflicts)
+        }
+        Err(err) => {
+            transaction.rollback();
+            Err(err)
+        }
+    }
+}
+
+// All maps and sets use the elements of the base model as keys, unless noted otherwise
+#[allow(clippy::mutable_key_type)] // WeakElement uses the pointer for hashing, so the key does not change
+struct Merger {
+    ours_model: AutosarModel,
+    theirs_differences: Vec<Difference>,
+    // maps elements of their model to the base model
+    theirs_counterparts: FxHashMap<WeakElement, Element>,
+    base_to_theirs: FxHashMap<WeakElement, Element>,
+    // maps elements of our model to the base model
+    ours_counterparts: FxHashMap<WeakElement, Element>,
+    base_to_ours: FxHashMap<WeakElement, Element>,
+    // elements whose character data was changed in our model
+    ours_text: FxHashSet<WeakElement>,
+    // attributes that were changed in our model
+    ours_attributes: FxHashSet<(WeakElement, AttributeName)>,
+    // elements that were removed in our model
+    ours_removed: FxHashSet<WeakElement>,
+    // elements that were moved in our model, together with their new version
+    ours_moved: FxHashMap<WeakElement, Element>,
+    // elements that contain any change in our model
+    ours_touched: FxHashSet<WeakElement>,
+    // elements whose list of sub elements was changed in our model
+    ours_children_changed: FxHashSet<WeakElement>,
+    conflicts: Vec<MergeConflict>,
+}
+
+impl Merger {
+    #[allow(clippy::mutable_key_type)] // WeakElement uses the pointer for hashing, so the key does not change
+    fn new(base: &AutosarModel, ours: &AutosarModel, theirs: &AutosarModel) -> Self {
+        let (ours_differences, ours_counterparts) =
+            compare_with_counterparts(&base.root_element(), &ours.root_element());
+        let (theirs_differences, theirs_counterparts) =
+            compare_with_counterparts(&base.root_element(), &theirs.root_element());
+        let invert = |counterparts: &FxHashMap<WeakElement, Element>| {
+            counterparts
+                .iter()
+                .filter_map(|(new, old)| Some((old.downgrade(), new.upgrade()?)))
+                .collect::<FxHashMap<_, _>>()
+        };
+
+        let mut merger = Self {
+            ours_model: ours.clone(),
+            base_to_theirs: invert(&theirs_counterparts),
+            base_to_ours: invert(&ours_counterparts),
+            theirs_differences,
+            theirs_counterparts,
+            ours_counterparts,
+            ours_text: FxHashSet::default(),
+            ours_attributes: FxHashSet::default(),
+            ours_removed: FxHashSet::default(),
+            ours_moved: FxHashMap::default(),
+            ours_touched: FxHashSet::default(),
+            ours_children_changed: FxHashSet::default(),
+            conflicts: Vec::new(),
+        };
+
+        for difference in ours_differences {
+            match difference {
+                Difference::CharacterDataChanged { old, .. } => {
+                    merger.ours_text.insert(old.downgrade());
+                    merger.touch(&old);
+                }
+                Difference::AttributeAdded { old, attrname, .. }
+                | Difference::AttributeRemoved { old, attrname, .. }
+                | Difference::AttributeChanged { old, attrname, .. } => {
+                    merger.ours_attributes.insert((old.downgrade(), attrname));
+                    merger.touch(&old);
+                }
+                Difference::ElementRemoved { old } => {
+                    merger.ours_removed.insert(old.downgrade());
+                    merger.touch(&old);
+                    merger.children_changed(old.parent().ok().flatten());
+                }
+                Difference::ElementAdded { new } => {
+                    let base_parent = merger.ours_base_parent(&new);
+                    merger.children_changed(base_parent);
+                }
+                Difference::ElementMoved { old, new } => {
+                    merger.touch(&old);
+                    merger.children_changed(old.parent().ok().flatten());
+                    let base_parent = merger.ours_base_parent(&new);
+                    merger.children_changed(base_parent);
+                    merger.ours_moved.insert(old.downgrade(), new);
+                }
+            }
+        }
+
+        merger
+    }
+
+    // record that an element of the base model and all of its parents contain changes in our model
+    fn touch(&mut self, element: &Element) {
+        let mut current = Some(element.clone());
+        while let Some(elem) = current {
+            if !self.ours_touched.insert(elem.downgrade()) {
+                // the parents of this element have already been recorded
+                break;
+            }
+            current = elem.parent().ok().flatten();
+        }
+    }
+
+    fn children_changed(&mut self, base_parent: Option<Element>) {
+        if let Some(parent) = base_parent {
+            self.touch(&parent);
+            self.ours_children_changed.insert(parent.downgrade());
+        }
+    }
+
+    // get the base model counterpart of the parent of an element in our model
+    fn ours_base_parent(&self, element: &Element) -> Option<Element> {
+        let parent = element.parent().ok().flatten()?;
+        self.ours_counterparts.get(&parent.downgrade()).cloned()
+    }
+
+    fn merge(&mut self) -> Result<(), AutosarDataError> {
+        let differences = std::mem::take(&mut self.theirs_differences);
+        let moved_base: Vec<Element> = differences
+            .iter()
+            .filter_map(|difference| match difference {
+                Difference::ElementMoved { old, .. } => Some(old.clone()),
+                _ => None,
+            })
+            .collect();
+        let moved_new: Vec<Element> = differences
+            .iter()
+            .filter_map(|difference| match difference {
+                Difference::ElementMoved { new, .. } => Some(new.clone()),
+                _ => None,
+            })
+            .collect();
+
+        let mut late_removes = Vec::new();
+        for difference in &differences {
+            match difference {
+                Difference::CharacterDataChanged { old, new, .. } => self.change_character_data(old, new)?,
+                Difference::AttributeAdded { old, new, attrname, .. }
+                | Difference::AttributeRemoved { old, new, attrname, .. }
+                | Difference::AttributeChanged { old, new, attrname, .. } => {
+                    self.change_attribute(old, new, *attrname)?;
+                }
+                Difference::ElementRemoved { old } => {
+                    // elements that contain a moved element can only be removed after the move
+                    if moved_base.iter().any(|moved| moved.is_sub_element_of(old)) {
+                        late_removes.push(old.clone());
+                    } else {
+                        self.remove_element(old)?;
+                    }
+                }
+                Difference::ElementAdded { new } => self.add_element(new, &moved_new)?,
+                // moves are performed after all elements were added, since they may target a new element
+                Difference::ElementMoved { .. } => {}
+            }
+        }
+        for difference in &differences {
+            if let Difference::ElementMoved { old, new } = difference {
+                self.move_element(old, new)?;
+            }
+        }
+        for old in late_removes {
+            self.remove_element(&old)?;
+        }
+
+        Ok(())
+    }
+
+    fn change_character_data(&mut self, base: &Element, theirs: &Element) -> Result<(), AutosarDataError> {
+        let Some(ours) = self.ours_element(base, theirs) else {
+            return Ok(());
+        };
+        let theirs_value = text_content(theirs);
+        if self.ours_text.contains(&base.downgrade()) {
+            if text_content(&ours) != theirs_value {
+                self.add_conflict(MergeConflict::CharacterData {
+                    base: base.clone(),
+                    ours,
+                    theirs: theirs.clone(),
+                });
+            }
+            return Ok(());
+        }
+
+        if ours.content_type() == ContentType::Mixed {
+            set_mixed_text(&ours, theirs_value.map(|cdata| cdata.to_string()).as_deref())
+        } else if let Some(cdata) = theirs_value {
+            ours.set_character_data(cdata)
+        } else {
+            ours.remove_character_data()
+        }
+    }
+
+    fn change_attribute(
+        &mut self,
+        base: &Element,
+        theirs: &Element,
+        attrname: AttributeName,
+    ) -> Result<(), AutosarDataError> {
+        let Some(ours) = self.ours_element(base, theirs) else {
+            return Ok(());
+        };
+        let theirs_value = theirs.attribute_value(attrname);
+        if self.ours_attributes.contains(&(base.downgrade(), attrname)) {
+            if ours.attribute_value(attrname) != theirs_value {
+                self.add_conflict(MergeConflict::Attribute {
+                    base: base.clone(),
+                    ours,
+                    theirs: theirs.clone(),
+                    attrname,
+                });
+            }
+            return Ok(());
+        }
+
+        if let Some(value) = theirs_value {
+            ours.set_attribute(attrname, value)
+        } else {
+            // remove_attribute also returns false if the attribute does not exist
+            if !ours.remove_attribute(attrname) && ours.attribute_value(attrname).is_some() {
+                self.add_conflict(MergeConflict::AttributeNotRemovable {
+                    base: base.clone(),
+                    ours,
+                    theirs: theirs.clone(),
+                    attrname,
+                });
+            }
+            Ok(())
+        }
+    }
+
+    fn remove_element(&mut self, base: &Element) -> Result<(), AutosarDataError> {
+        let ours = self.base_to_ours.get(&base.downgrade()).cloned();
+        if self.ours_touched.contains(&base.downgrade()) {
+            // removing the element is only possible if it was not changed in our model, or if it was also removed
+            if !self.ours_removed.contains(&base.downgrade()) {
+                self.add_conflict(MergeConflict::RemovedModified {
+                    base: base.clone(),
+                    ours,
+                    theirs: None,
+                });
+            }
+            return Ok(());
+        }
+        // no counterpart: a parent element was already removed in our model
+        let Some(ours) = ours else {
+            return Ok(());
+        };
+        if let Some(base_parent) = base.parent()?
+            && !self.check_ordered(&base_parent)
+        {
+            return Ok(());
+        }
+
+        if let Some(ours_parent) = ours.parent()? {
+            ours_parent.remove_sub_element(ours)?;
+        }
+        Ok(())
+    }
+
+    fn add_element(&mut self, theirs: &Element, moved_new: &[Element]) -> Result<(), AutosarDataError> {
+        let Some(base_parent) = theirs
+            .parent()?
+            .and_then(|parent| self.theirs_counterparts.get(&parent.downgrade()).cloned())
+        else {
+            return Ok(());
+        };
+        let Some(ours_parent) = self.ours_element(&base_parent, theirs) else {
+            return Ok(());
+        };
+        if !self.check_ordered(&base_parent) {
+            return Ok(());
+        }
+
+        if let Some(existing) = self.find_existing(&ours_parent, theirs)? {
+            if !compare_elements(&existing, theirs).is_empty() {
+                self.add_conflict(MergeConflict::ElementAdded {
+                    ours: existing,
+                    theirs: theirs.clone(),
+                });
+            }
+            return Ok(());
+        }
+
+        let position = self.ordered_position(&ours_parent, theirs)?;
+        // elements that were moved into the new element by theirs are moved separately
+        ElementData::from_element_excluding(theirs, moved_new).create_in(&ours_parent, position)?;
+        Ok(())
+    }
+
+    // find an element in our model that occupies the same place as an element that was added in their model
+    fn find_existing(&self, ours_parent: &Element, theirs: &Element) -> Result<Option<Element>, AutosarDataError> {
+        let element_name = theirs.element_name();
+        if theirs.item_name().is_some() || !ours_parent.sub_element_is_repeatable(element_name)? {
+            return ours_parent.find_corresponding_sub_element(theirs);
+        }
+
+        // elements which can occur any number of times are combined, but identical additions on both sides are only kept once.
+        // In elements that are not splittable, elements with the same keys that were added on both sides conflict.
+        let keys = element_keys(theirs);
+        let mut candidates = ours_parent.sub_elements().filter(|sub_element| {
+            sub_element.element_name() == element_name
+                && !self.ours_counterparts.contains_key(&sub_element.downgrade())
+                && element_keys(sub_element) == keys
+        });
+        let version = ours_parent.min_version()?;
+        if ours_parent.element_type().splittable_in(version) {
+            Ok(candidates.find(|candidate| compare_elements(candidate, theirs).is_empty()))
+        } else {
+            let candidates: Vec<Element> = candidates.collect();
+            Ok(candidates
+                .iter()
+                .find(|candidate| compare_elements(candidate, theirs).is_empty())
+                .or(candidates.first())
+                .cloned())
+        }
+    }
+
+    fn move_element(&mut self, base: &Element, theirs: &Element) -> Result<(), AutosarDataError> {
+        let Some(ours) = self.ours_element(base, theirs) else {
+            return Ok(());
+        };
+        let Some(theirs_parent) = theirs.parent()? else {
+            return Ok(());
+        };
+        let base_parent = self.theirs_counterparts.get(&theirs_parent.downgrade()).cloned();
+        let ours_parent = if let Some(base_parent) = &base_parent {
+            self.ours_element(base_parent, theirs)
+        } else {
+            // the new parent was added in their model, and it should now also exist in our model
+            ElementLocator::from_element(&theirs_parent)?.resolve(&self.ours_model)
+        };
+        let Some(ours_parent) = ours_parent else {
+            return Ok(());
+        };
+
+        if let Some(ours_moved) = self.ours_moved.get(&base.downgrade()).cloned() {
+            // both sides moved the element
+            let ours_location = ElementLocator::from_element(&ours_parent)?;
+            let ours_moved_location = ours_moved
+                .parent()?
+                .map(|parent| ElementLocator::from_element(&parent))
+                .transpose()?;
+            if Some(ours_location) != ours_moved_location || ours_moved.position() != theirs.position() {
+                self.add_conflict(MergeConflict::ElementMoved {
+                    base: base.clone(),
+                    ours: ours_moved,
+                    theirs: theirs.clone(),
+                });
+            }
+            return Ok(());
+        }
+        if let Some(old_base_parent) = base.parent()?
+            && !self.check_ordered(&old_base_parent)
+        {
+            return Ok(());
+        }
+        if let Some(base_parent) = &base_parent
+            && !self.check_ordered(base_parent)
+        {
+            return Ok(());
+        }
+
+        if let Some(position) = self.ordered_position(&ours_parent, theirs)? {
+            ours_parent.move_element_here_at(&ours, position)?;
+        } else {
+            ours_parent.move_element_here(&ours)?;
+        }
+        Ok(())
+    }
+
+    // get the counterpart of a base element in our model. If it doesn't exist, then our model removed it, which conflicts with their change
+    fn ours_element(&mut self, base: &Element, theirs: &Element) -> Option<Element> {
+        if let Some(ours) = self.base_to_ours.get(&base.downgrade()) {
+            return Some(ours.clone());
+        }
+
+        // find the element that was removed in our model: either the element itself or one of its parents
+        let mut removed = base.clone();
+        let mut current = Some(base.clone());
+        while let Some(elem) = current {
+            if self.ours_removed.contains(&elem.downgrade()) {
+                removed = elem;
+                break;
+            }
+            current = elem.parent().ok().flatten();
+        }
+        let theirs = self
+            .base_to_theirs
+            .get(&removed.downgrade())
+            .cloned()
+            .or(Some(theirs.clone()));
+        self.add_conflict(MergeConflict::RemovedModified {
+            base: removed,
+            ours: None,
+            theirs,
+        });
+        None
+    }
+
+    // the sub elements of an ordered element can only be changed by one side
+    fn check_ordered(&mut self, base_parent: &Element) -> bool {
+        if !base_parent.element_type().is_ordered() || !self.ours_children_changed.contains(&base_parent.downgrade()) {
+            return true;
+        }
+        if let (Some(ours), Some(theirs)) = (
+            self.base_to_ours.get(&base_parent.downgrade()).cloned(),
+            self.base_to_theirs.get(&base_parent.downgrade()).cloned(),
+        ) {
+            self.add_conflict(MergeConflict::OrderedContent {
+                base: base_parent.clone(),
+                ours,
+                theirs,
+            });
+        }
+        false
+    }
+
+    // the position of an element is only relevant if its parent is ordered
+    fn ordered_position(&self, ours_parent: &Element, theirs: &Element) -> Result<Option<usize>, AutosarDataError> {
+        if ours_parent.element_type().is_ordered() {
+            let max_position = ours_parent.content_item_count();
+            Ok(theirs.position().map(|position| position.min(max_position)))
+        } else {
+            Ok(None)
+        }
+    }
+
+    fn add_conflict(&mut self, conflict: MergeConflict) {
+        if !self.conflicts.contains(&conflict) {
+            self.conflicts.push(conflict);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    // create a model with some content; all three models of a merge start out with the same content
+    fn create_model() -> AutosarModel {
+        let model = AutosarModel::new();
+        model.create_file("test.arxml", AutosarVersion::LATEST).unwrap();
+        let elements = model
+            .root_element()
+            .create_sub_element(ElementName::ArPackages)
+            .and_then(|packages| packages.create_named_sub_element(ElementName::ArPackage, "Pkg"))
+            .and_then(|pkg| pkg.create_sub_element(ElementName::Elements))
+            .unwrap();
+        for name in ["Signal1", "Signal2", "Signal3"] {
+            elements
+                .create_named_sub_element(ElementName::ISignal, name)
+                .and_then(|signal| signal.create_sub_element(ElementName::Length))
+                .and_then(|length| length.set_character_data("8"))
+                .unwrap();
+        }
+        let arguments = elements
+            .create_named_sub_element(ElementName::BswModuleEntry, "Entry")
+            .and_then(|entry| entry.create_sub_element(ElementName::Arguments))
+            .unwrap();
+        for name in ["arg1", "arg2"] {
+            arguments
+                .create_named_sub_element(ElementName::SwServiceArg, name)
+                .unwrap();
+        }
+        model
+    }
+
+    fn get(model: &AutosarModel, path: &str) -> Element {
+        model.get_element_by_path(path).unwrap()
+    }
+
+    fn set_length(model: &AutosarModel, path: &str, value: &str) {
+        get(model, path)
+            .get_sub_element(ElementName::Length)
+            .unwrap()
+            .set_character_data(value)
+            .unwrap();
+    }
+
+    #[test]
+    fn merge_without_conflicts() {
+        let base = create_model();
+        let ours = create_model();
+        let theirs = create_model();
+
+        // ours: change Signal1, add Signal4, remove Signal3
+        set_length(&ours, "/Pkg/Signal1", "16");
+        let elements = get(&ours, "/Pkg").get_sub_element(ElementName::Elements).unwrap();
+        elements
+            .create_named_sub_element(ElementName::ISignal, "Signal4")
+            .unwrap();
+        elements.remove_sub_element(get(&ours, "/Pkg/Signal3")).unwrap();
+
+        // theirs: change Signal2, add Signal5 and an attribute, add arg3
+        set_length(&theirs, "/Pkg/Signal2", "32");
+        let elements = get(&theirs, "/Pkg").get_sub_element(ElementName::Elements).unwrap();
+        elements
+            .create_named_sub_element(ElementName::ISignal, "Signal5")
+            .unwrap();
+        get(&theirs, "/Pkg").set_attribute(AttributeName::Uuid, "1234").unwrap();
+        get(&theirs, "/Pkg/Entry/arg2")
+            .parent()
+            .unwrap()
+            .unwrap()
+            .create_named_sub_element(ElementName::SwServiceArg, "arg3")
+            .unwrap();
+
+        let conflicts = merge_models(&base, &ours, &theirs).unwrap();
+        assert!(conflicts.is_empty());
+
+        // build the expected result
+        let expected = create_model();
+        set_length(&expected, "/Pkg/Signal1", "16");
+        set_length(&expected, "/Pkg/Signal2", "32");
+        let elements = get(&expected, "/Pkg").get_sub_element(ElementName::Elements).unwrap();
+        elements
+            .create_named_sub_element(ElementName::ISignal, "Signal4")
+            .unwrap();
+        elements
+            .create_named_sub_element(ElementName::ISignal, "Signal5")
+            .unwrap();
+        elements.remove_sub_element(get(&expected, "/Pkg/Signal3")).unwrap();
+        get(&expected, "/Pkg")
+            .set_attribute(AttributeName::Uuid, "1234")
+            .unwrap();
+        get(&expected, "/Pkg/Entry/arg2")
+            .parent()
+            .unwrap()
+            .unwrap()
+            .create_named_sub_element(ElementName::SwServiceArg, "arg3")
+            .unwrap();
+        assert_eq!(diff::compare_models(&ours, &expected), vec![]);
+
+        // the merge is one step in the undo history
+        assert!(ours.can_undo());
+        ours.undo().unwrap();
+        assert!(ours.get_element_by_path("/Pkg/Signal5").is_none());
+    }
+
+    #[test]
+    fn identical_changes() {
+        let base = create_model();
+        let ours = create_model();
+        let theirs = create_model();
+        for model in [&ours, &theirs] {
+            set_length(model, "/Pkg/Signal1", "16");
+            let elements = get(model, "/Pkg").get_sub_element(ElementName::Elements).unwrap();
+            elements
+                .create_named_sub_element(ElementName::ISignal, "Signal4")
+                .unwrap();
+            elements.remove_sub_element(get(model, "/Pkg/Signal3")).unwrap();
+        }
+        let conflicts = merge_models(&base, &ours, &theirs).unwrap();
+        assert!(conflicts.is_empty());
+        assert!(diff::compare_models(&ours, &theirs).is_empty());
+    }
+
+    #[test]
+    fn conflicts() {
+        let base = create_model();
+        let ours = create_model();
+        let theirs = create_model();
+
+        // both change Signal1
+        set_length(&ours, "/Pkg/Signal1", "16");
+        set_length(&theirs, "/Pkg/Signal1", "32");
+        // theirs removes Signal2, which ours modified
+        set_length(&ours, "/Pkg/Signal2", "16");
+        let elements = get(&theirs, "/Pkg").get_sub_element(ElementName::Elements).unwrap();
+        elements.remove_sub_element(get(&theirs, "/Pkg/Signal2")).unwrap();
+        // ours removes Signal3, which theirs modified
+        let elements = get(&ours, "/Pkg").get_sub_element(ElementName::Elements).unwrap();
+        elements.remove_sub_element(get(&ours, "/Pkg/Signal3")).unwrap();
+        set_length(&theirs, "/Pkg/Signal3", "16");
+        // both add Signal4 with different content
+        for (model, value) in [(&ours, "1"), (&theirs, "2")] {
+            get(model, "/Pkg")
+                .get_sub_element(ElementName::Elements)
+                .and_then(|elements| elements.create_named_sub_element(ElementName::ISignal, "Signal4").ok())
+                .and_then(|signal| signal.create_sub_element(ElementName::Length).ok())
+                .unwrap()
+                .set_character_data(value)
+                .unwrap();
+        }
+        // both change the order of the arguments
+        for (model, name) in [(&ours, "arg3"), (&theirs, "arg4")] {
+            get(model, "/Pkg/Entry/arg1")
+                .parent()
+                .unwrap()
+                .unwrap()
+                .create_named_sub_element(ElementName::SwServiceArg, name)
+                .unwrap();
+        }
+        // both change the same attribute
+        get(&ours, "/Pkg").set_attribute(AttributeName::Uuid, "1").unwrap();
+        get(&theirs, "/Pkg").set_attribute(AttributeName::Uuid, "2").unwrap();
+
+        let conflicts = merge_models(&base, &ours, &theirs).unwrap();
+        assert_eq!(conflicts.len(), 6);
+        assert!(
+            conflicts.contains(&MergeConflict::CharacterData {
+                base: get(&base, "/Pkg/Signal1").get_sub_element(ElementName::Length).unwrap(),
+                ours: get(&ours, "/Pkg/Signal1").get_sub_element(ElementName::Length).unwrap(),
+                theirs: get(&theirs, "/Pkg/Signal1")
+                    .get_sub_element(ElementName::Length)
+                    .unwrap(),
+            })
+        );
+        assert!(conflicts.contains(&MergeConflict::RemovedModified {
+            base: get(&base, "/Pkg/Signal2"),
+            ours: Some(get(&ours, "/Pkg/Signal2")),
+            theirs: None,
+        }));
+        assert!(conflicts.contains(&MergeConflict::RemovedModified {
+            base: get(&base, "/Pkg/Signal3"),
+            ours: None,
+            theirs: Some(get(&theirs, "/Pkg/Signal3")),
+        }));
+        assert!(conflicts.contains(&MergeConflict::ElementAdded {
+            ours: get(&ours, "/Pkg/Signal4"),
+            theirs: get(&theirs, "/Pkg/Signal4"),
+        }));
+        let arguments = |model: &AutosarModel| get(model, "/Pkg/Entry/arg1").parent().unwrap().unwrap();
+        assert!(conflicts.contains(&MergeConflict::OrderedContent {
+            base: arguments(&base),
+            ours: arguments(&ours),
+            theirs: arguments(&theirs),
+        }));
+        assert!(conflicts.contains(&MergeConflict::Attribute {
+            base: get(&base, "/Pkg"),
+            ours: get(&ours, "/Pkg"),
+            theirs: get(&theirs, "/Pkg"),
+            attrname: AttributeName::Uuid,
+        }));
+
+        // our side of each conflict is kept
+        let length = get(&ours, "/Pkg/Signal1").get_sub_element(ElementName::Length).unwrap();
+        assert_eq!(length.character_data().unwrap().to_string(), "16");
+        assert!(ours.get_element_by_path("/Pkg/Signal2").is_some());
+        assert!(ours.get_element_by_path("/Pkg/Entry/arg4").is_none());
+    }
+
+    #[test]
+    fn required_attribute() {
+        let base = create_model();
+        let ours = create_model();
+        let theirs = create_model();
+        // the API refuses to remove a required attribute, so it is removed directly
+        theirs
+            .root_element()
+            .0
+            .write()
+            .attributes
+            .retain(|attribute| attribute.attrname != AttributeName::xsiSchemalocation);
+
+        let conflicts = merge_models(&base, &ours, &theirs).unwrap();
+        assert_eq!(
+            conflicts,
+            vec![MergeConflict::AttributeNotRemovable {
+                base: base.root_element(),
+                ours: ours.root_element(),
+                theirs: theirs.root_element(),
+                attrname: AttributeName::xsiSchemalocation,
+            }]
+        );
+        assert!(
+            ours.root_element()
+                .attribute_value(AttributeName::xsiSchemalocation)
+                .is_some()
+        );
+    }
+
+    #[test]
+    fn moved_elements() {
+        let base = create_model();
+        let ours = create_model();
+        let theirs = create_model();
+
+        // theirs moves Signal1 into a new package, and ours modifies it
+        let new_elements = get(&theirs, "/Pkg")
+            .parent()
+            .unwrap()
+            .unwrap()
+            .create_named_sub_element(ElementName::ArPackage, "NewPkg")
+            .and_then(|pkg| pkg.create_sub_element(ElementName::Elements))
+            .unwrap();
+        new_elements.move_element_here(&get(&theirs, "/Pkg/Signal1")).unwrap();
+        set_length(&ours, "/Pkg/Signal1", "16");
+
+        let conflicts = merge_models(&base, &ours, &theirs).unwrap();
+        assert!(conflicts.is_empty());
+        assert!(ours.get_element_by_path("/Pkg/Signal1").is_none());
+        let length = get(&ours, "/NewPkg/Signal1")
+            .get_sub_element(ElementName::Length)
+            .unwrap();
+        assert_eq!(length.character_data().unwrap().to_string(), "16");
+    }
+
+    #[test]
+    fn traits() {
+        let model = create_model();
+        let conflict = MergeConflict::ElementAdded {
+            ours: model.root_element(),
+            theirs: model.root_element(),
+        };
+        assert_eq!(conflict.clone(), conflict);
+        assert!(!format!("{conflict:?}").is_empty());
+    }
+}
@@ -0,0 +1,585 @@
+use crate::*;
+
+/// A change that was made by [`ArxmlFile::migrate_version`] in order to make the content of the file compatible with the new version
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub enum MigrationChange {
+    /// The element was renamed, because its name is different in the new version
+    ElementRenamed {
+        /// The renamed element
+        element: Element,
+        /// The name of the element in the old version
+        old_name: ElementName,
+        /// The name of the element in the new version
+        new_name: ElementName,
+    },
+    /// The element was removed, because it cannot be represented in the new version
+    ElementRemoved {
+        /// The parent of the removed element
+        parent: Element,
+        /// The name of the removed element
+        element_name: ElementName,
+        /// The xml path of the removed element, see [`Element::xml_path`]
+        path: String,
+    },
+    /// The attribute was renamed, because its name is different in the new version
+    AttributeRenamed {
+        /// The element that contains the attribute
+        element: Element,
+        /// The name of the attribute in the old version
+        old_name: AttributeName,
+        /// The name of the attribute in the new version
+        new_name: AttributeName,
+    },
+    /// The attribute was removed, because it cannot be represented in the new version
+    AttributeRemoved {
+        /// The element that contained the attribute
+        element: Element,
+        /// The removed attribute
+        attribute: AttributeName,
+        /// The value of the removed attribute
+        value: CharacterData,
+    },
+    /// The value of the attribute was converted, e.g. because an enum item was renamed
+    AttributeChanged {
+        /// The element that contains the attribute
+        element: Element,
+        /// The changed attribute
+        attribute: AttributeName,
+        /// The value in the old version
+        old_value: CharacterData,
+        /// The value in the new version
+        new_value: CharacterData,
+    },
+    /// The character data of the element was converted, e.g. because an enum item was renamed
+    CharacterDataChanged {
+        /// The changed element
+        element: Element,
+        /// The value in the old version
+        old_value: CharacterData,
+        /// The value in the new version
+        new_value: CharacterData,
+    },
+}
+
+struct Migrator {
+    model: AutosarModel,
+    file: WeakArxmlFile,
+    old_version: AutosarVersion,
+    version: AutosarVersion,
+    changes: Vec<MigrationChange>,
+}
+
+/// migrate the content of the file to a new version, and set the version of the file
+pub(crate) fn migrate_file(
+    file: &ArxmlFile,
+    version: AutosarVersion,
+) -> Result<Vec<MigrationChange>, AutosarDataError> {
+    let model = file.model()?;
+    let old_version = file.version();
+    if old_version == version {
+        return Ok(Vec::new());
+    }
+
+    // if the migration fails, then the transaction is dropped and all changes are reverted
+    let transaction = model.begin_transaction();
+    // the version is changed first, so that all modifications are checked against the new version
+    file.write_lock().version = version;
+
+    let mut migrator = Migrator {
+        model: model.clone(),
+        file: file.downgrade(),
+        old_version,
+        version,
+        changes: Vec::new(),
+    };
+    migrator.migrate_element(&model.root_element(), ElementType::ROOT)?;
+
+    let (compat_errors, _) = file.check_version_compatibility(version);
+    if !compat_errors.is_empty() {
+        transaction.rollback();
+        return Err(AutosarDataError::VersionIncompatibleData { version });
+    }
+    transaction.commit();
+    file.0.write().modified = true;
+    model.notify_observers(|| ModelEvent::ModelReset);
+
+    Ok(migrator.changes)
+}
+
+impl Migrator {
+    // migrate the element and its sub elements. Returns false if the element must be removed
+    fn migrate_element(&mut self, element: &Element, elemtype: ElementType) -> Result<bool, AutosarDataError> {
+        // the element type can depend on the version, e.g. if the ordering or the type of the character data was changed
+        if element.elemtype() != elemtype {
+            element.write_lock().elemtype = elemtype;
+        }
+
+        if !self.migrate_attributes(element, elemtype)? || !self.migrate_character_data(element, elemtype)? {
+            return Ok(false);
+        }
+
+        let sub_elements: Vec<Element> = element
+            .sub_elements()
+            .filter(|sub_element| self.is_member(sub_element))
+            .collect();
+        for sub_element in sub_elements {
+            let keep = if let Some((sub_elemtype, _)) =
+                elemtype.find_sub_element(sub_element.element_name(), self.version as u32)
+            {
+                self.migrate_element(&sub_element, sub_elemtype)?
+            } else if let Some((new_name, sub_elemtype)) = self
+                .find_renamed_element(elemtype, &sub_element)
+                .filter(|_| !self.is_shared(&sub_element))
+            {
+                self.rename_element(&sub_element, new_name, sub_elemtype)?;
+                self.migrate_element(&sub_element, sub_elemtype)?
+            } else {
+                false
+            };
+
+            if !keep {
+                let element_name = sub_element.element_name();
+                let path = sub_element.xml_path();
+                // elements that are shared with other files are only removed from the migrated file
+                if self.is_shared(&sub_element)
+                    && let Some(file) = self.file.upgrade()
+                {
+                    sub_element.remove_from_file(&file)?;
+                } else {
+                    element.remove_sub_element(sub_element)?;
+                }
+                self.changes.push(MigrationChange::ElementRemoved {
+                    parent: element.clone(),
+                    element_name,
+                    path,
+                });
+            }
+        }
+
+        Ok(true)
+    }
+
+    // only elements that are part of the migrated file are modified
+    fn is_member(&self, element: &Element) -> bool {
+        let membership = element.file_membership_local();
+        membership.is_empty() || membership.contains(&self.file)
+    }
+
+    fn is_shared(&self, element: &Element) -> bool {
+        element.file_membership_local().len() > 1
+    }
+
+    // migrate the attributes. Returns false if a required attribute cannot be converted and the element must be removed
+    fn migrate_attributes(&mut self, element: &Element, elemtype: ElementType) -> Result<bool, AutosarDataError> {
+        let attributes: Vec<Attribute> = element.attributes().collect();
+        for Attribute { attrname, content } in attributes {
+            match elemtype.find_attribute_spec(attrname) {
+                Some(AttributeSpec {
+                    spec,
+                    version,
+                    required,
+                }) if self.version.compatible(version) => {
+                    if !CharacterData::check_value(&content, spec, self.version) {
+                        if let Some(new_value) = self.convert_value(&content, spec) {
+                            element.set_attribute(attrname, new_value.clone())?;
+                            self.changes.push(MigrationChange::AttributeChanged {
+                                element: element.clone(),
+                                attribute: attrname,
+                                old_value: content,
+                                new_value,
+                            });
+                        } else if required {
+                            return Ok(false);
+                        } else {
+                            self.remove_attribute(element, attrname, content);
+                        }
+                    }
+                }
+                old_spec => {
+                    if let Some(new_name) =
+                        old_spec.and_then(|spec| self.find_renamed_attribute(element, attrname, spec))
+                    {
+                        if let Some(attribute) = element
+                            .write_lock()
+                            .attributes
+                            .iter_mut()
+                            .find(|attribute| attribute.attrname == attrname)
+                        {
+                            attribute.attrname = new_name;
+                        }
+                        self.changes.push(MigrationChange::AttributeRenamed {
+                            element: element.clone(),
+                            old_name: attrname,
+                            new_name,
+                        });
+                    } else {
+                        self.remove_attribute(element, attrname, content);
+                    }
+                }
+            }
+        }
+        Ok(true)
+    }
+
+    fn remove_attribute(&mut self, element: &Element, attribute: AttributeName, value: CharacterData) {
+        // remove_attribute() refuses to remove required attributes, but an attribute that does not exist in the new version must always be removed
+        if !element.remove_attribute(attribute) {
+            let mut element_locked = element.write_lock();
+            element_locked.attributes.retain(|attr| attr.attrname != attribute);
+            element_locked.mark_modified();
+        }
+        self.changes.push(MigrationChange::AttributeRemoved {
+            element: element.clone(),
+            attribute,
+            value,
+        });
+    }
+
+    // migrate the character data. Returns false if the character data cannot be converted and the element must be removed
+    fn migrate_character_data(&mut self, element: &Element, elemtype: ElementType) -> Result<bool, AutosarDataError> {
+        // elements with mixed content always contain strings, which need no conversion
+        if elemtype.content_mode() == ContentMode::Characters
+            && let Some(spec) = elemtype.chardata_spec()
+            && let Some(old_value) = element.character_data()
+            && !CharacterData::check_value(&old_value, spec, self.version)
+        {
+            let Some(new_value) = self.convert_value(&old_value, spec) else {
+                return Ok(false);
+            };
+            element.set_character_data(new_value.clone())?;
+            self.changes.push(MigrationChange::CharacterDataChanged {
+                element: element.clone(),
+                old_value,
+                new_value,
+            });
+        }
+        Ok(true)
+    }
+
+    // convert a value so that it matches the spec in the new version
+    fn convert_value(&self, value: &CharacterData, spec: &CharacterDataSpec) -> Option<CharacterData> {
+        if let (CharacterData::Enum(item), CharacterDataSpec::Enum { items }) = (value, spec) {
+            // enum items that were renamed usually differ only in the spelling, e.g. "-" vs. "_"
+            let normalized = normalize_enum_item(*item);
+            let mut candidates = items
+                .iter()
+                .filter(|(candidate, version_mask)| {
+                    self.version.compatible(*version_mask) && normalize_enum_item(*candidate) == normalized
+                })
+                .map(|(candidate, _)| *candidate);
+            return match (candidates.next(), candidates.next()) {
+                (Some(candidate), None) => Some(CharacterData::Enum(candidate)),
+                _ => None,
+            };
+        }
+        let converted = CharacterData::parse(&value.to_string(), spec, self.version)?;
+        CharacterData::check_value(&converted, spec, self.version).then_some(converted)
+    }
+
+    // find the name of an element in the new version, based on the old name and the data type of the element.
+    // An element was renamed if the parent contains an element with the same data type which only exists in the new version,
+    // and whose name is more similar to the old name than all others
+    fn find_renamed_element(&self, parent_type: ElementType, element: &Element) -> Option<(ElementName, ElementType)> {
+        let old_name = element.element_name();
+        let old_type = element.element_type();
+        let candidates = parent_type
+            .sub_element_spec_iter()
+            .filter(|(name, elemtype, version_mask, _)| {
+                *name != old_name
+                    && elemtype.same_datatype(&old_type)
+                    && self.version.compatible(*version_mask)
+                    && !self.old_version.compatible(*version_mask)
+            })
+            .map(|(name, elemtype, ..)| (name, elemtype));
+        best_match(candidates, &old_name.to_string(), |(name, _)| name.to_string())
+    }
+
+    fn rename_element(
+        &mut self,
+        element: &Element,
+        new_name: ElementName,
+        new_type: ElementType,
+    ) -> Result<(), AutosarDataError> {
+        let old_name = element.element_name();
+        {
+            let mut element_locked = element.write_lock();
+            element_locked.elemname = new_name;
+            element_locked.elemtype = new_type;
+            element_locked.mark_modified();
+        }
+        self.changes.push(MigrationChange::ElementRenamed {
+            element: element.clone(),
+            old_name,
+            new_name,
+        });
+
+        // references to the renamed element might need a different DEST value
+        if let Ok(path) = element.path() {
+            for referrer in self
+                .model
+                .get_references_to(&path)
+                .iter()
+                .filter_map(WeakElement::upgrade)
+            {
+                if let Some(CharacterData::Enum(old_dest)) = referrer.attribute_value(AttributeName::Dest)
+                    && !new_type.verify_reference_dest(old_dest)
+                    && let Some(new_dest) = referrer.element_type().reference_dest_value(&new_type)
+                {
+                    referrer.set_attribute(AttributeName::Dest, new_dest)?;
+                    self.changes.push(MigrationChange::AttributeChanged {
+                        element: referrer,
+                        attribute: AttributeName::Dest,
+                        old_value: CharacterData::Enum(old_dest),
+                        new_value: CharacterData::Enum(new_dest),
+                    });
+                }
+            }
+        }
+        Ok(())
+    }
+
+    // find the name of an attribute in the new version. This works in the same way as for elements
+    fn find_renamed_attribute(
+        &self,
+        element: &Element,
+        old_name: AttributeName,
+        old_spec: AttributeSpec,
+    ) -> Option<AttributeName> {
+        let elemtype = element.element_type();
+        let candidates = elemtype.attribute_spec_iter().filter(|(name, spec, _)| {
+            *name != old_name
+                && std::ptr::eq(*spec, old_spec.spec)
+                && element.attribute_value(*name).is_none()
+                && elemtype
+                    .find_attribute_spec(*name)
+                    .is_some_and(|AttributeSpec { version, .. }| {
+                        self.version.compatible(version) && !self.old_version.compatible(version)
+                    })
+        });
+        best_match(candidates, &old_name.to_string(), |(name, ..)| name.to_string()).map(|(name, ..)| name)
+    }
+}
+
+// select the candidate whose name shares the most words with the old name. There is no match if several candidates are equally good.
+fn best_match<T>(candidates: impl Iterator<Item = T>, old_name: &str, get_name: impl Fn(&T) -> String) -> Option<T> {
+    let old_words: Vec<&str> = old_name.split('-').collect();
+    let mut best: Option<(usize, T)> = None;
+    let mut ambiguous = false;
+    for candidate in candidates {
+        let score = get_name(&candidate)
+            .split('-')
+            .filter(|word| old_words.contains(word))
+            .count();
+        if score == 0 {
+            continue;
+        }
+        match &best {
+            Some((best_score, _)) if score < *best_score => {}
+            Some((best_score, _)) if score == *best_score => ambiguous = true,
+            _ => {
+                best = Some((score, candidate));
+                ambiguous = false;
+            }
+        }
+    }
+    if ambiguous {
+        None
+    } else {
+        best.map(|(_, candidate)| candidate)
+    }
+}
+
+fn normalize_enum_item(item: EnumItem) -> String {
+    item.to_string()
+        .chars()
+        .filter(|c| *c != '-' && *c != '_')
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const FILE_4_0_1: &str = r#"<?xml version="1.0" encoding="utf-8"?>
+    <AUTOSAR xsi:schemaLocation="http://autosar.org/schema/r4.0 AUTOSAR_4-0-1.xsd" xmlns="http://autosar.org/schema/r4.0" xmlns:xsi="http://www.w3.org/2001/XMLSchema-instance">
+    <INTRODUCTION>
+        <P>
+            <L-1 L="EN">see <XREF><LABEL></LABEL></XREF></L-1>
+        </P>
+    </INTRODUCTION>
+    <AR-PACKAGES>
+        <AR-PACKAGE>
+            <SHORT-NAME>Pkg</SHORT-NAME>
+        </AR-PACKAGE>
+    </AR-PACKAGES>
+    </AUTOSAR>"#;
+
+    const FILE_00052: &str = r#"<?xml version="1.0" encoding="utf-8"?>
+    <AUTOSAR xsi:schemaLocation="http://autosar.org/schema/r4.0 AUTOSAR_00052.xsd" xmlns="http://autosar.org/schema/r4.0" xmlns:xsi="http://www.w3.org/2001/XMLSchema-instance">
+    <AR-PACKAGES>
+        <AR-PACKAGE>
+            <SHORT-NAME>Pkg</SHORT-NAME>
+            <ELEMENTS>
+                <DIAGNOSTIC-ACCESS-PERMISSION>
+                    <SHORT-NAME>Permission</SHORT-NAME>
+                </DIAGNOSTIC-ACCESS-PERMISSION>
+                <SYSTEM>
+                    <SHORT-NAME>System</SHORT-NAME>
+                    <FIBEX-ELEMENTS>
+                        <FIBEX-ELEMENT-REF-CONDITIONAL>
+                            <FIBEX-ELEMENT-REF DEST="SERVICE-INSTANCE-COLLECTION-SET">/Pkg/Collection</FIBEX-ELEMENT-REF>
+                        </FIBEX-ELEMENT-REF-CONDITIONAL>
+                    </FIBEX-ELEMENTS>
+                </SYSTEM>
+            </ELEMENTS>
+        </AR-PACKAGE>
+    </AR-PACKAGES>
+    </AUTOSAR>"#;
+
+    #[test]
+    fn rename_elements() {
+        let model = AutosarModel::new();
+        let (file, _) = model.load_buffer(FILE_4_0_1.as_bytes(), "test.arxml", true).unwrap();
+        let xref = model
+            .root_element()
+            .elements_dfs()
+            .map(|(_, element)| element)
+            .find(|element| element.element_name() == ElementName::Xref)
+            .unwrap();
+        assert!(xref.get_sub_element(ElementName::Label).is_some());
+        assert!(file.set_version(AutosarVersion::Autosar_00050).is_err());
+
+        let changes = file.migrate_version(AutosarVersion::Autosar_00050).unwrap();
+        let label = xref.get_sub_element(ElementName::Label1).unwrap();
+        assert_eq!(
+            changes,
+            vec![MigrationChange::ElementRenamed {
+                element: label.clone(),
+                old_name: ElementName::Label,
+                new_name: ElementName::Label1,
+            }]
+        );
+        assert_eq!(file.version(), AutosarVersion::Autosar_00050);
+        assert!(model.validate().is_empty());
+
+        // migrate back to the original version
+        let changes = file.migrate_version(AutosarVersion::Autosar_4_0_1).unwrap();
+        assert_eq!(changes.len(), 1);
+        assert_eq!(label.element_name(), ElementName::Label);
+        assert!(model.validate().is_empty());
+
+        // the migration can be undone
+        model.undo().unwrap();
+        assert_eq!(label.element_name(), ElementName::Label1);
+        assert_eq!(file.version(), AutosarVersion::Autosar_00050);
+    }
+
+    #[test]
+    fn remove_elements() {
+        let model = AutosarModel::new();
+        let (file, _) = model.load_buffer(FILE_00052.as_bytes(), "test.arxml", true).unwrap();
+        let system = model.get_element_by_path("/Pkg/System").unwrap();
+
+        let changes = file.migrate_version(AutosarVersion::Autosar_4_0_1).unwrap();
+        assert_eq!(changes.len(), 2);
+        // the reference has a DEST value that does not exist in the old version, so it is removed
+        assert!(matches!(
+            &changes[0],
+            MigrationChange::ElementRemoved {
+                element_name: ElementName::DiagnosticAccessPermission,
+                ..
+            }
+        ));
+        assert!(matches!(
+            &changes[1],
+            MigrationChange::ElementRemoved {
+                element_name: ElementName::FibexElementRef,
+                ..
+            }
+        ));
+        assert!(model.get_element_by_path("/Pkg/Permission").is_none());
+        let conditional = system
+            .get_sub_element(ElementName::FibexElements)
+            .and_then(|fibex| fibex.get_sub_element(ElementName::FibexElementRefConditional))
+            .unwrap();
+        assert_eq!(conditional.sub_elements().count(), 0);
+        assert!(model.validate().is_empty());
+    }
+
+    #[test]
+    fn convert_values() {
+        let model = AutosarModel::new();
+        let file = model.create_file("test.arxml", AutosarVersion::Autosar_00050).unwrap();
+        let migrator = Migrator {
+            model,
+            file: file.downgrade(),
+            old_version: AutosarVersion::Autosar_4_0_1,
+            version: AutosarVersion::Autosar_00050,
+            changes: Vec::new(),
+        };
+        let spec = CharacterDataSpec::Enum {
+            items: &[(EnumItem::ISignal, u32::MAX), (EnumItem::ISignalIPdu, 1)],
+        };
+        assert_eq!(
+            migrator.convert_value(&CharacterData::Enum(EnumItem::ISignal), &spec),
+            Some(CharacterData::Enum(EnumItem::ISignal))
+        );
+        // the only matching item is not allowed in the new version
+        assert_eq!(
+            migrator.convert_value(&CharacterData::Enum(EnumItem::ISignalIPdu), &spec),
+            None
+        );
+        assert_eq!(migrator.convert_value(&CharacterData::Enum(EnumItem::Aa), &spec), None);
+
+        let spec = CharacterDataSpec::UnsignedInteger;
+        assert_eq!(
+            migrator.convert_value(&CharacterData::String("42".to_string()), &spec),
+            Some(CharacterData::UnsignedInteger(42))
+        );
+        assert_eq!(
+            migrator.convert_value(&CharacterData::String("x".to_string()), &spec),
+            None
+        );
+    }
+
+    #[test]
+    fn best_match_selection() {
+        let candidates = ["MIN-DEADLINE", "MAX-DEADLINE", "OTHER"];
+        assert_eq!(
+            best_match(candidates.into_iter(), "DEADLINE-MIN", |c| c.to_string()),
+            Some("MIN-DEADLINE")
+        );
+        assert_eq!(best_match(candidates.into_iter(), "DEADLINE", |c| c.to_string()), None);
+        assert_eq!(best_match(candidates.into_iter(), "UNRELATED", |c| c.to_string()), None);
+        assert_eq!(normalize_enum_item(EnumItem::ISignalIPdu), "isignalipdu");
+    }
+
+    #[test]
+    fn errors() {
+        let model = AutosarModel::new();
+        let file = model.create_file("test.arxml", AutosarVersion::Autosar_00050).unwrap();
+        assert_eq!(file.migrate_version(AutosarVersion::Autosar_00050).unwrap(), Vec::new());
+
+        // a migration inside of a transaction becomes part of the transaction
+        let transaction = model.begin_transaction();
+        file.migrate_version(AutosarVersion::Autosar_00051).unwrap();
+        assert_eq!(file.version(), AutosarVersion::Autosar_00051);
+        transaction.rollback();
+        assert_eq!(file.version(), AutosarVersion::Autosar_00050);
+        assert!(!model.can_undo());
+    }
+
+    #[test]
+    fn traits() {
+        let model = AutosarModel::new();
+        let change = MigrationChange::ElementRenamed {
+            element: model.root_element(),
+            old_name: ElementName::Label,
+            new_name: ElementName::Label1,
+        };
+        assert_eq!(change.clone(), change);
+        assert!(!format!("{change:?}").is_empty());
+    }
+}
@@ -0,0 +1,539 @@
+use crate::*;
+
+/// A modification of an [`AutosarModel`], which is reported to all registered observers
+///
+/// Each modification is reported by a single event, which describes it as specifically as possible:
+/// changing the SHORT-NAME of an element is reported as [`ModelEvent::ElementRenamed`], and changing the value of a
+/// reference is reported as [`ModelEvent::ReferenceRetargeted`], rather than as [`ModelEvent::CharacterDataChanged`].
+///
+/// Elements that are created, copied or removed together with their parent are not reported separately.
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub enum ModelEvent {
+    /// A new element was created or copied into `parent`
+    ElementCreated {
+        /// The new element
+        element: Element,
+        /// The parent of the new element
+        parent: Element,
+    },
+    /// An element was removed from `parent`. The removed element is in the deleted state.
+    ElementRemoved {
+        /// The removed element
+        element: Element,
+        /// The former parent of the removed element
+        parent: Element,
+    },
+    /// An element was moved to a new parent, or to a new position inside of its parent
+    ///
+    /// If the element was moved between two models, then the observers of both models receive this event.
+    ElementMoved {
+        /// The moved element
+        element: Element,
+        /// The parent before the move
+        old_parent: Element,
+        /// The parent after the move
+        new_parent: Element,
+    },
+    /// The SHORT-NAME of an identifiable element was changed
+    ElementRenamed {
+        /// The renamed element
+        element: Element,
+        /// The previous item name
+        old_name: String,
+        /// The new item name
+        new_name: String,
+    },
+    /// The character data of an element was set, removed, or a character content item was inserted or removed
+    CharacterDataChanged {
+        /// The modified element
+        element: Element,
+    },
+    /// An attribute was set or removed
+    AttributeChanged {
+        /// The modified element
+        element: Element,
+        /// The name of the modified attribute
+        attribute: AttributeName,
+    },
+    /// A comment or a processing instruction of an element was inserted, changed or removed
+    MarkupChanged {
+        /// The modified element
+        element: Element,
+    },
+    /// An element was added to a file or removed from a file
+    FileMembershipChanged {
+        /// The modified element
+        element: Element,
+    },
+    /// The target of a reference was changed
+    ///
+    /// This happens when a reference is set directly, and when its target is renamed or moved.
+    ReferenceRetargeted {
+        /// The reference element
+        element: Element,
+        /// The previous value of the reference, if any
+        old_target: Option<String>,
+        /// The new value of the reference, or None if the reference value was removed
+        new_target: Option<String>,
+    },
+    /// Many parts of the model were changed at once
+    ///
+    /// This event is emitted after rolling back a transaction, after [`AutosarModel::undo`] and [`AutosarModel::redo`],
+    /// after reloading a file and after a version migration. Observers should re-read all data of the model that they depend on.
+    ModelReset,
+}
+
+/// An observer receives a [`ModelEvent`] for each modification of an [`AutosarModel`]
+///
+/// Observers are registered with [`AutosarModel::add_observer`]. The trait is implemented for all closures
+/// that take a `&ModelEvent`.
+///
+/// The events are delivered after the modification is complete and all locks have been released, so an observer
+/// may read the model and even modify it. Modifications made by an observer are reported to all observers as well.
+pub trait ModelObserver: Send + Sync {
+    /// Handle a single event
+    fn on_event(&self, event: &ModelEvent);
+}
+
+impl<F: Fn(&ModelEvent) + Send + Sync> ModelObserver for F {
+    fn on_event(&self, event: &ModelEvent) {
+        self(event);
+    }
+}
+
+/// Identifies a registered observer, so that it can be removed with [`AutosarModel::remove_observer`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ObserverId(u64);
+
+/// The registered observers of a model, and the events that have not been delivered yet
+#[derive(Default)]
+pub(crate) struct ObserverList {
+    observers: Vec<(ObserverId, Arc<dyn ModelObserver>)>,
+    next_id: u64,
+    pending_events: Vec<ModelEvent>,
+    dispatching: bool,
+    // elements that were modified while other elements were locked; their files are marked as modified during dispatch
+    modified_elements: Vec<Element>,
+}
+
+type EventBatch = (Vec<ModelEvent>, Vec<Arc<dyn ModelObserver>>);
+
+impl ObserverList {
+    pub(crate) fn add(&mut self, observer: Arc<dyn ModelObserver>) -> ObserverId {
+        let id = ObserverId(self.next_id);
+        self.next_id += 1;
+        self.observers.push((id, observer));
+        id
+    }
+
+    pub(crate) fn remove(&mut self, id: ObserverId) -> bool {
+        let count = self.observers.len();
+        self.observers.retain(|(observer_id, _)| *observer_id != id);
+        count != self.observers.len()
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.observers.is_empty()
+    }
+
+    pub(crate) fn push_event(&mut self, event: ModelEvent) {
+        self.pending_events.push(event);
+    }
+
+    pub(crate) fn push_modified(&mut self, element: Element) {
+        self.modified_elements.push(element);
+    }
+
+    // take the pending events, unless they are already being delivered by another call of dispatch_events
+    fn begin_dispatch(&mut self) -> Option<EventBatch> {
+        if self.dispatching {
+            return None;
+        }
+        let batch = self.take_pending();
+        self.dispatching = batch.is_some();
+        batch
+    }
+
+    fn take_pending(&mut self) -> Option<EventBatch> {
+        if self.pending_events.is_empty() {
+            self.dispatching = false;
+            return None;
+        }
+        let events = std::mem::take(&mut self.pending_events);
+        let observers = self.observers.iter().map(|(_, observer)| observer.clone()).collect();
+        Some((events, observers))
+    }
+}
+
+/// mark the files of the queued elements as modified, and deliver all pending events of the model to its observers
+///
+/// This must be called without holding any locks, since the observers may access the model.
+/// Events that are emitted while the events are being delivered, e.g. because an observer modifies the model, are
+/// delivered by the same loop afterwards. This way all observers see all events in the same order.
+pub(crate) fn dispatch_events(model: &AutosarModel) {
+    let modified_elements = std::mem::take(&mut model.0.write().observers.modified_elements);
+    for element in modified_elements {
+        element.mark_files_modified();
+    }
+
+    let Some(mut batch) = model.0.write().observers.begin_dispatch() else {
+        return;
+    };
+    let _guard = DispatchGuard(model);
+    loop {
+        let (events, observers) = batch;
+        for event in &events {
+            for observer in &observers {
+                observer.on_event(event);
+            }
+        }
+        match model.0.write().observers.take_pending() {
+            Some(next_batch) => batch = next_batch,
+            None => break,
+        }
+    }
+}
+
+// make sure that events can still be delivered after an observer panicked
+struct DispatchGuard<'a>(&'a AutosarModel);
+
+impl Drop for DispatchGuard<'_> {
+    fn drop(&mut self) {
+        if std::thread::panicking() {
+            let mut model = self.0.0.write();
+            model.observers.dispatching = false;
+            model.observers.pending_events.clear();
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use parking_lot::Mutex;
+
+    // register an observer that records all events
+    fn record_events(model: &AutosarModel) -> (ObserverId, Arc<Mutex<Vec<ModelEvent>>>) {
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let events_clone = events.clone();
+        let id = model.add_observer(move |event: &ModelEvent| events_clone.lock().push(event.clone()));
+        (id, events)
+    }
+
+    #[test]
+    fn create_and_remove() {
+        let model = AutosarModel::new();
+        model.create_file("file", AutosarVersion::LATEST).unwrap();
+        let (id, events) = record_events(&model);
+        let root = model.root_element();
+
+        let packages = root.create_sub_element(ElementName::ArPackages).unwrap();
+        let package = packages
+            .create_named_sub_element(ElementName::ArPackage, "Pkg")
+            .unwrap();
+        let copy = packages.create_copied_sub_element(&package).unwrap();
+        // an existing element is not created again
+        packages
+            .get_or_create_named_sub_element(ElementName::ArPackage, "Pkg")
+            .unwrap();
+        packages.remove_sub_element(copy.clone()).unwrap();
+        assert_eq!(
+            *events.lock(),
+            vec![
+                ModelEvent::ElementCreated {
+                    element: packages.clone(),
+                    parent: root.clone()
+                },
+                ModelEvent::ElementCreated {
+                    element: package.clone(),
+                    parent: packages.clone()
+                },
+                ModelEvent::ElementCreated {
+                    element: copy.clone(),
+                    parent: packages.clone()
+                },
+                ModelEvent::ElementRemoved {
+                    element: copy,
+                    parent: packages.clone()
+                },
+            ]
+        );
+
+        // after the observer is removed, no more events are recorded
+        events.lock().clear();
+        assert!(model.remove_observer(id));
+        assert!(!model.remove_observer(id));
+        packages
+            .create_named_sub_element(ElementName::ArPackage, "Pkg2")
+            .unwrap();
+        assert!(events.lock().is_empty());
+    }
+
+    #[test]
+    fn rename_and_references() {
+        let model = AutosarModel::new();
+        model.create_file("file", AutosarVersion::LATEST).unwrap();
+        let packages = model
+            .root_element()
+            .create_sub_element(ElementName::ArPackages)
+            .unwrap();
+        let package = packages
+            .create_named_sub_element(ElementName::ArPackage, "Pkg")
+            .unwrap();
+        let elements = package.create_sub_element(ElementName::Elements).unwrap();
+        let system_signal = elements
+            .create_named_sub_element(ElementName::SystemSignal, "SysSig")
+            .unwrap();
+        let signal_ref = elements
+            .create_named_sub_element(ElementName::ISignal, "Signal")
+            .and_then(|signal| signal.create_sub_element(ElementName::SystemSignalRef))
+            .unwrap();
+        let (_, events) = record_events(&model);
+
+        signal_ref.set_reference_target(&system_signal).unwrap();
+        system_signal.set_item_name("NewSignal").unwrap();
+        system_signal
+            .get_sub_element(ElementName::ShortName)
+            .unwrap()
+            .set_character_data("Signal2")
+            .unwrap();
+        signal_ref.remove_character_data().unwrap();
+        assert_eq!(
+            *events.lock(),
+            vec![
+                ModelEvent::ReferenceRetargeted {
+                    element: signal_ref.clone(),
+                    old_target: None,
+                    new_target: Some("/Pkg/SysSig".to_string())
+                },
+                ModelEvent::ReferenceRetargeted {
+                    element: signal_ref.clone(),
+                    old_target: Some("/Pkg/SysSig".to_string()),
+                    new_target: Some("/Pkg/NewSignal".to_string())
+                },
+                ModelEvent::ElementRenamed {
+                    element: system_signal.clone(),
+                    old_name: "SysSig".to_string(),
+                    new_name: "NewSignal".to_string()
+                },
+                ModelEvent::ElementRenamed {
+                    element: system_signal.clone(),
+                    old_name: "NewSignal".to_string(),
+                    new_name: "Signal2".to_string()
+                },
+                ModelEvent::ReferenceRetargeted {
+                    element: signal_ref.clone(),
+                    old_target: Some("/Pkg/NewSignal".to_string()),
+                    new_target: None
+                },
+            ]
+        );
+
+        // moving the target of a reference retargets the reference
+        events.lock().clear();
+        signal_ref.set_reference_target(&system_signal).unwrap();
+        let package2 = packages
+            .create_named_sub_element(ElementName::ArPackage, "Pkg2")
+            .unwrap();
+        let elements2 = package2.create_sub_element(ElementName::Elements).unwrap();
+        events.lock().clear();
+        elements2.move_element_here(&system_signal).unwrap();
+        assert_eq!(
+            *events.lock(),
+            vec![
+                ModelEvent::ReferenceRetargeted {
+                    element: signal_ref.clone(),
+                    old_target: Some("/Pkg/Signal2".to_string()),
+                    new_target: Some("/Pkg2/Signal2".to_string())
+                },
+                ModelEvent::ElementMoved {
+                    element: system_signal.clone(),
+                    old_parent: elements.clone(),
+                    new_parent: elements2.clone()
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn content_and_attributes() {
+        let model = AutosarModel::new();
+        let file = model.create_file("file", AutosarVersion::LATEST).unwrap();
+        let file2 = model.create_file("file2", AutosarVersion::LATEST).unwrap();
+        let package = model
+            .root_element()
+            .create_sub_element(ElementName::ArPackages)
+            .and_then(|packages| packages.create_named_sub_element(ElementName::ArPackage, "Pkg"))
+            .unwrap();
+        let desc = package
+            .create_sub_element(ElementName::Desc)
+            .and_then(|desc| desc.create_sub_element(ElementName::L2))
+            .unwrap();
+        let (_, events) = record_events(&model);
+
+        desc.insert_character_content_item("text", 0).unwrap();
+        desc.remove_character_content_item(0).unwrap();
+        package.set_attribute(AttributeName::Uuid, "1234").unwrap();
+        assert!(package.remove_attribute(AttributeName::Uuid));
+        assert!(!package.remove_attribute(AttributeName::Uuid));
+        package.remove_from_file(&file2).unwrap();
+        package.add_to_file(&file2).unwrap();
+        assert_eq!(
+            *events.lock(),
+            vec![
+                ModelEvent::CharacterDataChanged { element: desc.clone() },
+                ModelEvent::CharacterDataChanged { element: desc.clone() },
+                ModelEvent::AttributeChanged {
+                    element: package.clone(),
+                    attribute: AttributeName::Uuid
+                },
+                ModelEvent::AttributeChanged {
+                    element: package.clone(),
+                    attribute: AttributeName::Uuid
+                },
+                ModelEvent::FileMembershipChanged {
+                    element: package.clone()
+                },
+                ModelEvent::FileMembershipChanged {
+                    element: package.clone()
+                },
+            ]
+        );
+
+        // removing the element from its last file removes it from the model
+        events.lock().clear();
+        package.remove_from_file(&file2).unwrap();
+        package.remove_from_file(&file).unwrap();
+        let events = events.lock();
+        assert_eq!(events.len(), 3);
+        assert!(matches!(&events[1], ModelEvent::ElementRemoved { element, .. } if *element == package));
+    }
+
+    #[test]
+    fn markup_and_reset() {
+        let model = AutosarModel::new();
+        model.create_file("file", AutosarVersion::LATEST).unwrap();
+        let packages = model
+            .root_element()
+            .create_sub_element(ElementName::ArPackages)
+            .unwrap();
+        let (_, events) = record_events(&model);
+
+        packages.insert_comment("comment", 0).unwrap();
+        packages.remove_comment(0).unwrap();
+        packages.insert_processing_instruction("tool-info", 0).unwrap();
+        packages.remove_processing_instruction(0).unwrap();
+        // the comment of an element is part of the content of its parent
+        packages.set_comment(Some("comment".to_string()));
+        let mut expected = vec![
+            ModelEvent::MarkupChanged {
+                element: packages.clone()
+            };
+            4
+        ];
+        expected.push(ModelEvent::MarkupChanged {
+            element: model.root_element(),
+        });
+        assert_eq!(*events.lock(), expected);
+
+        // reverting changes emits a single ModelReset event
+        let transaction = model.begin_transaction();
+        packages
+            .create_named_sub_element(ElementName::ArPackage, "Pkg")
+            .unwrap();
+        transaction.commit();
+        events.lock().clear();
+        model.undo().unwrap();
+        model.redo().unwrap();
+        let transaction = model.begin_transaction();
+        packages.set_comment(None);
+        transaction.rollback();
+        assert_eq!(
+            *events.lock(),
+            vec![
+                ModelEvent::ModelReset,
+                ModelEvent::ModelReset,
+                ModelEvent::MarkupChanged {
+                    element: model.root_element()
+                },
+                ModelEvent::ModelReset,
+            ]
+        );
+
+        // a rollback without changes does not emit an event
+        events.lock().clear();
+        model.begin_transaction().rollback();
+        assert!(events.lock().is_empty());
+    }
+
+    #[test]
+    fn move_between_models() {
+        let model = AutosarModel::new();
+        model.create_file("file", AutosarVersion::LATEST).unwrap();
+        let packages = model
+            .root_element()
+            .create_sub_element(ElementName::ArPackages)
+            .unwrap();
+        let package = packages
+            .create_named_sub_element(ElementName::ArPackage, "Pkg")
+            .unwrap();
+        let model2 = AutosarModel::new();
+        model2.create_file("file", AutosarVersion::LATEST).unwrap();
+        let packages2 = model2
+            .root_element()
+            .create_sub_element(ElementName::ArPackages)
+            .unwrap();
+        let (_, events) = record_events(&model);
+        let (_, events2) = record_events(&model2);
+
+        packages2.move_element_here(&package).unwrap();
+        let expected = vec![ModelEvent::ElementMoved {
+            element: package.clone(),
+            old_parent: packages.clone(),
+            new_parent: packages2.clone(),
+        }];
+        assert_eq!(*events.lock(), expected);
+        assert_eq!(*events2.lock(), expected);
+    }
+
+    #[test]
+    fn observer_access() {
+        let model = AutosarModel::new();
+        model.create_file("file", AutosarVersion::LATEST).unwrap();
+        let packages = model
+            .root_element()
+            .create_sub_element(ElementName::ArPackages)
+            .unwrap();
+
+        // the observer can read and modify the model while handling an event
+        model.add_observer(|event: &ModelEvent| {
+            if let ModelEvent::ElementCreated { element, .. } = event
+                && element.element_name() == ElementName::ArPackage
+            {
+                let model = element.model().unwrap();
+                assert!(model.get_element_by_path(&element.path().unwrap()).is_some());
+                element.create_sub_element(ElementName::Elements).unwrap();
+            }
+        });
+        let (_, events) = record_events(&model);
+        let package = packages
+            .create_named_sub_element(ElementName::ArPackage, "Pkg")
+            .unwrap();
+        let elements = package.get_sub_element(ElementName::Elements).unwrap();
+        // the events of the observer are delivered after the event that caused them
+        assert_eq!(
+            *events.lock(),
+            vec![
+                ModelEvent::ElementCreated {
+                    element: package.clone(),
+                    parent: packages.clone()
+                },
+                ModelEvent::ElementCreated {
+                    element: elements,
+                    parent: package
+                },
+            ]
+        );
+    }
+}
@@ -5,16 +5,19 @@ use autosar_data_specification::{
 use smallvec::SmallVec;
 use std::borrow::Cow;
 use std::collections::HashSet;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::str::FromStr;
 use std::str::Utf8Error;
+use std::sync::Arc;
 use thiserror::Error;
 
 use crate::lexer::{ArxmlEvent, ArxmlLexer};
+use crate::lossless::{ContentTextRecorder, ElementText, OriginalText};
 use crate::{
     Attribute, AutosarDataError, CharacterData, Element, ElementContent, ElementOrModel, ElementRaw, ReferenceBaseInfo,
-    WeakElement,
+    SourceLocation, WeakElement,
 };
+use fxhash::FxHashMap;
 
 #[derive(Debug, Error, PartialEq)]
 #[non_exhaustive]
@@ -250,6 +253,7 @@ pub enum ArxmlParserError {
 
 pub(crate) struct ArxmlParser<'a> {
     filename: PathBuf,
+    source_file: Arc<Path>,
     line: usize,
     buffer: &'a [u8],
     fileversion: AutosarVersion,
@@ -261,11 +265,17 @@ pub(crate) struct ArxmlParser<'a> {
     pub(crate) reference_bases: Vec<(String, ReferenceBaseInfo)>,
     pub(crate) warnings: Vec<AutosarDataError>,
     standalone: Option<bool>,
+    // the comment before the root element
+    root_comment: Option<String>,
+    // the original text of each element; only recorded in lossless mode
+    element_texts: Option<FxHashMap<WeakElement, ElementText>>,
+    original_text: Option<OriginalText>,
 }
 
 impl<'a> ArxmlParser<'a> {
     pub(crate) fn new(filename: PathBuf, buffer: &'a [u8], strict: bool) -> Self {
         Self {
+            source_file: Arc::from(filename.as_path()),
             filename,
             line: 1,
             buffer,
@@ -278,10 +288,23 @@ impl<'a> ArxmlParser<'a> {
             reference_bases: Vec::new(),
             warnings: Vec::new(),
             standalone: None,
+            root_comment: None,
+            element_texts: None,
+            original_text: None,
         }
     }
 
-    fn next<'b>(&mut self, lexer: &'b mut ArxmlLexer) -> Result<ArxmlEvent<'b>, AutosarDataError> {
+    /// record the original text of the file while parsing, so that unmodified elements can be serialized without changes
+    pub(crate) fn keep_original_text(&mut self) {
+        self.element_texts = Some(FxHashMap::default());
+    }
+
+    /// get the original text that was recorded in lossless mode
+    pub(crate) fn take_original_text(&mut self) -> Option<OriginalText> {
+        self.original_text.take()
+    }
+
+    fn next<'b>(&mut self, lexer: &mut ArxmlLexer<'b>) -> Result<ArxmlEvent<'b>, AutosarDataError> {
         let (line, event) = lexer.next()?;
         self.line = line;
         Ok(event)
@@ -328,16 +351,23 @@ impl<'a> ArxmlParser<'a> {
             return Err(self.error(ArxmlParserError::InvalidArxmlFileHeader));
         }
 
-        let mut stored_comment = None;
         let mut token = self.next(&mut lexer)?;
-        while let ArxmlEvent::Comment(comment_bytes) = token {
-            stored_comment = Some(String::from_utf8_lossy(comment_bytes).into());
+        // processing instructions outside of the root element are skipped
+        loop {
+            match token {
+                ArxmlEvent::Comment(comment_bytes) => {
+                    self.root_comment = Some(String::from_utf8_lossy(comment_bytes).into());
+                }
+                ArxmlEvent::ProcessingInstruction(_) => {}
+                _ => break,
+            }
             token = self.next(&mut lexer)?;
         }
 
         if let ArxmlEvent::BeginElement(elemname, attributes_text) = token
             && let Ok(ElementName::Autosar) = ElementName::from_bytes(elemname)
         {
+            let prolog = 0..lexer.token_start();
             let attributes = self.parse_attribute_text(ElementType::ROOT, attributes_text)?;
             self.parse_file_header(&attributes)?;
 
@@ -348,11 +378,23 @@ impl<'a> ArxmlParser<'a> {
                 content: SmallVec::new(),
                 attributes,
                 file_membership: HashSet::with_capacity(0),
-                comment: stored_comment,
+                source_location: Some(self.source_location(&lexer)),
+                modified: false,
             };
             let path = Cow::from("");
             let autosar_root_element = self.parse_element(new_element, path, &mut lexer)?;
+            let epilog = lexer.position()..self.buffer.len();
             self.verify_end_of_input(&mut lexer)?;
+            if let Some(element_texts) = self.element_texts.take() {
+                self.original_text = OriginalText::new(
+                    self.buffer,
+                    prolog,
+                    epilog,
+                    self.standalone,
+                    self.root_comment.clone(),
+                    element_texts,
+                );
+            }
 
             return Ok(autosar_root_element);
         }
@@ -440,11 +482,25 @@ impl<'a> ArxmlParser<'a> {
         Ok(version)
     }
 
+    /// get the source location of the element that was most recently opened by the lexer
+    fn source_location(&self, lexer: &ArxmlLexer) -> SourceLocation {
+        let (line, column) = lexer.element_position();
+        SourceLocation {
+            filename: self.source_file.clone(),
+            line,
+            column,
+        }
+    }
+
     /// return the standalone attribute from the xml header
     pub(crate) fn get_standalone(&self) -> Option<bool> {
         self.standalone
     }
 
+    pub(crate) fn take_root_comment(&mut self) -> Option<String> {
+        self.root_comment.take()
+    }
+
     /// parse a single element of an arxml file
     fn parse_element(
         &mut self,
@@ -458,7 +514,13 @@ impl<'a> ArxmlParser<'a> {
         let mut elem_idx: Vec<usize> = Vec::new();
         let mut short_name_found = false;
 
-        let mut stored_comment = None;
+        // in lossless mode, the text of the content items is recorded
+        let start_tag = lexer.token_start()..lexer.position();
+        let mut recorder = self
+            .element_texts
+            .is_some()
+            .then(|| ContentTextRecorder::new(lexer.position()));
+
         loop {
             // track the current element name in the parser for error messages - set this in every loop iteration, since it gets overwritten during the recursive calls
             self.current_element = element.elemname;
@@ -483,21 +545,23 @@ impl<'a> ArxmlParser<'a> {
                             content: SmallVec::new(),
                             attributes: self.parse_attribute_text(sub_elemtype, attr_text)?,
                             file_membership: HashSet::with_capacity(0),
-                            comment: stored_comment,
+                            source_location: Some(self.source_location(lexer)),
+                            modified: false,
                         };
+                        let sub_element_start = lexer.token_start();
                         let sub_element = self.parse_element(new_element, Cow::from(path.as_ref()), lexer)?;
-                        stored_comment = None;
+                        if let Some(recorder) = &mut recorder {
+                            recorder.sub_element(sub_element_start, lexer.position());
+                        }
                         // if this sub element was a short name, then Autosar path handling is needed
                         if name == ElementName::ShortName {
                             short_name_found = true;
                             let sub_element_inner = sub_element.0.read();
-                            if let Some(ElementContent::CharacterData(CharacterData::String(name_string))) =
-                                sub_element_inner.content.first()
-                            {
+                            if let Some(CharacterData::String(name_string)) = sub_element_inner.character_data() {
                                 let mut new_path = String::with_capacity(path.len() + name_string.len() + 1);
                                 new_path.push_str(&path);
                                 new_path.push('/');
-                                new_path.push_str(name_string);
+                                new_path.push_str(&name_string);
                                 path = Cow::from(new_path.clone());
                                 self.identifiables.push((new_path, wrapped_element.downgrade()));
                             }
@@ -513,6 +577,13 @@ impl<'a> ArxmlParser<'a> {
                 ArxmlEvent::EndElement(elem_text) => {
                     if let Ok(name) = ElementName::from_bytes(elem_text) {
                         if name == element.elemname {
+                            if let Some(element_texts) = &mut self.element_texts
+                                && let Some(recorder) = recorder
+                            {
+                                let end_tag = lexer.token_start()..lexer.position();
+                                let element_text = recorder.finish(start_tag, end_tag);
+                                element_texts.insert(wrapped_element.downgrade(), element_text);
+                            }
                             break;
                         }
                         return Err(self.error(ArxmlParserError::IncorrectEndElement {
@@ -538,6 +609,9 @@ impl<'a> ArxmlParser<'a> {
                                 .push((refpath.to_owned(), wrapped_element.downgrade(), base));
                         }
                         element.content.push(ElementContent::CharacterData(value));
+                        if let Some(recorder) = &mut recorder {
+                            recorder.text_item(lexer.token_start()..lexer.position());
+                        }
                     } else {
                         self.optional_error(ArxmlParserError::CharacterContentForbidden {
                             element: element.elemname,
@@ -553,7 +627,18 @@ impl<'a> ArxmlParser<'a> {
                     }));
                 }
                 ArxmlEvent::Comment(comment_bytes) => {
-                    stored_comment = Some(String::from_utf8_lossy(comment_bytes).into());
+                    let comment = String::from_utf8_lossy(comment_bytes).into();
+                    element.content.push(ElementContent::Comment(comment));
+                    if let Some(recorder) = &mut recorder {
+                        recorder.text_item(lexer.token_start()..lexer.position());
+                    }
+                }
+                ArxmlEvent::ProcessingInstruction(instruction_bytes) => {
+                    let instruction = String::from_utf8_lossy(instruction_bytes).into();
+                    element.content.push(ElementContent::ProcessingInstruction(instruction));
+                    if let Some(recorder) = &mut recorder {
+                        recorder.text_item(lexer.token_start()..lexer.position());
+                    }
                 }
             }
         }
@@ -996,7 +1081,10 @@ impl<'a> ArxmlParser<'a> {
     }
 
     fn verify_end_of_input(&mut self, lexer: &mut ArxmlLexer) -> Result<(), AutosarDataError> {
-        let (_, next_event) = lexer.next()?;
+        let (_, mut next_event) = lexer.next()?;
+        while let ArxmlEvent::ProcessingInstruction(_) = next_event {
+            (_, next_event) = lexer.next()?;
+        }
         if let ArxmlEvent::EndOfFile = next_event {
             Ok(())
         } else {
@@ -1010,9 +1098,9 @@ impl<'a> ArxmlParser<'a> {
         let mut lexer = ArxmlLexer::new(self.buffer, self.filename.clone());
 
         if let Ok(ArxmlEvent::ArxmlHeader(_)) = self.next(&mut lexer) {
-            // skip any comments
+            // skip any comments and processing instructions
             let mut arxmlevent = self.next(&mut lexer);
-            while let Ok(ArxmlEvent::Comment(..)) = arxmlevent {
+            while let Ok(ArxmlEvent::Comment(..) | ArxmlEvent::ProcessingInstruction(..)) = arxmlevent {
                 arxmlevent = self.next(&mut lexer);
             }
             if let Ok(ArxmlEvent::BeginElement(elemname, attributes_text)) = arxmlevent
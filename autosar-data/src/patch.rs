@@ -0,0 +1,959 @@
+//! Patches that describe modifications of a model
+//!
+//! A [`Patch`] is a list of [`PatchOperation`]s, which add, remove, or move elements, or change character data and attributes.
+//! Patches are usually created from the differences between two models with [`diff::create_patch`],
+//! and they can be applied to a model with [`AutosarModel::apply_patch`].
+//!
+//! The elements that are modified by the operations are identified by an [`ElementLocator`], which does not depend on a
+//! particular model, so a patch can be applied to any model with similar content.
+//!
+//! If the feature `serde` is enabled, then all patch types implement `Serialize` and `Deserialize`.
+//!
+//! # Example
+//!
+//! ```
+//! # use autosar_data::*;
+//! # use autosar_data::patch::*;
+//! # fn main() -> Result<(), AutosarDataError> {
+//! let model = AutosarModel::new();
+//! model.create_file("a.arxml", AutosarVersion::LATEST)?;
+//! let packages = model.root_element().create_sub_element(ElementName::ArPackages)?;
+//! packages.create_named_sub_element(ElementName::ArPackage, "Pkg")?;
+//!
+//! let patch = Patch {
+//!     operations: vec![PatchOperation::SetAttribute {
+//!         element: ElementLocator {
+//!             path: "/Pkg".to_string(),
+//!             sub_elements: vec![],
+//!         },
+//!         attrname: AttributeName::Uuid,
+//!         old_value: None,
+//!         new_value: Some("1234".to_string()),
+//!     }],
+//! };
+//! model.apply_patch(&patch)?;
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::diff::{Difference, element_keys, text_content};
+use crate::*;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// A list of operations that modify a model
+///
+/// The operations are applied in order by [`AutosarModel::apply_patch`].
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Patch {
+    /// the operations of the patch
+    pub operations: Vec<PatchOperation>,
+}
+
+/// A single operation of a [`Patch`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[non_exhaustive]
+pub enum PatchOperation {
+    /// Create a new element with all of its content
+    AddElement {
+        /// the parent of the new element
+        parent: ElementLocator,
+        /// the position of the new element in the parent. If it is `None`, then the first valid position is used
+        position: Option<usize>,
+        /// the content of the new element
+        element: ElementData,
+    },
+    /// Remove an element
+    RemoveElement {
+        /// the element to remove
+        element: ElementLocator,
+    },
+    /// Move an element to a different parent, or to a different position inside its parent
+    MoveElement {
+        /// the element to move
+        element: ElementLocator,
+        /// the new parent of the element
+        new_parent: ElementLocator,
+        /// the new position of the element. If it is `None`, then the first valid position is used
+        position: Option<usize>,
+    },
+    /// Set or remove the character data of an element
+    SetCharacterData {
+        /// the element to modify
+        element: ElementLocator,
+        /// the expected current character data of the element
+        old_value: Option<String>,
+        /// the new character data; `None` removes the character data
+        new_value: Option<String>,
+    },
+    /// Set or remove an attribute
+    SetAttribute {
+        /// the element to modify
+        element: ElementLocator,
+        /// the name of the attribute
+        #[cfg_attr(feature = "serde", serde(with = "serde_name"))]
+        attrname: AttributeName,
+        /// the expected current value of the attribute
+        old_value: Option<String>,
+        /// the new value of the attribute; `None` removes the attribute
+        new_value: Option<String>,
+    },
+}
+
+/// Identifies an element independently of the model that contains it
+///
+/// The element is located using the Autosar path of the element itself or of its closest identifiable parent.
+/// Starting from there, the `sub_elements` lead to the element.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ElementLocator {
+    /// the Autosar path of the element or its closest identifiable parent. An empty path refers to the root element.
+    pub path: String,
+    /// the path from the identifiable element to the located element
+    pub sub_elements: Vec<PathSegment>,
+}
+
+/// One step from an element to one of its unnamed sub elements
+///
+/// The sub element is identified by its name, and by the same keys that are used in [`Element::cmp`]:
+/// the content of its INDEX and DEFINITION-REF sub elements and its DEST attribute.
+/// If there are several sub elements with the same keys, then `occurrence` selects one of them.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct PathSegment {
+    /// the name of the sub element
+    #[cfg_attr(feature = "serde", serde(with = "serde_name"))]
+    pub element_name: ElementName,
+    /// the content of the INDEX sub element
+    pub index: Option<u64>,
+    /// the content of the DEFINITION-REF sub element
+    pub definition_ref: Option<String>,
+    /// the value of the DEST attribute
+    pub dest: Option<String>,
+    /// the number of preceding sub elements with the same name and keys
+    pub occurrence: usize,
+}
+
+/// The complete content of an element that is created by [`PatchOperation::AddElement`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ElementData {
+    /// the name of the element
+    #[cfg_attr(feature = "serde", serde(with = "serde_name"))]
+    pub element_name: ElementName,
+    /// the attributes of the element
+    pub attributes: Vec<AttributeData>,
+    /// the content of the element
+    pub content: Vec<ContentData>,
+}
+
+/// An attribute inside of [`ElementData`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct AttributeData {
+    /// the name of the attribute
+    #[cfg_attr(feature = "serde", serde(with = "serde_name"))]
+    pub attrname: AttributeName,
+    /// the value of the attribute
+    pub value: String,
+}
+
+/// A content item inside of [`ElementData`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum ContentData {
+    /// a sub element
+    Element(ElementData),
+    /// character data
+    CharacterData(String),
+    /// a comment, without the surrounding `<!--` and `-->`
+    Comment(String),
+    /// a processing instruction, without the surrounding `<?` and `?>`
+    ProcessingInstruction(String),
+}
+
+/// A conflict between an operation of a patch and the content of the model
+#[derive(Debug)]
+pub struct PatchConflict {
+    /// the index of the operation in [`Patch::operations`]
+    pub operation: usize,
+    /// the reason why the operation could not be applied
+    pub kind: PatchConflictKind,
+}
+
+/// The reason for a [`PatchConflict`]
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum PatchConflictKind {
+    /// The element does not exist in the model
+    ElementNotFound(ElementLocator),
+    /// The character data of the element does not have the expected value
+    CharacterDataMismatch {
+        /// the value that was expected by the patch
+        expected: Option<String>,
+        /// the value in the model
+        found: Option<String>,
+    },
+    /// The attribute does not have the expected value
+    AttributeMismatch {
+        /// the name of the attribute
+        attrname: AttributeName,
+        /// the value that was expected by the patch
+        expected: Option<String>,
+        /// the value in the model
+        found: Option<String>,
+    },
+    /// The attribute is required by the specification and cannot be removed
+    AttributeNotRemovable(AttributeName),
+    /// The value is not valid character data for the element
+    InvalidValue(String),
+    /// The root element cannot be removed or moved
+    RootElement,
+    /// The modification of the model failed
+    ModelError(AutosarDataError),
+}
+
+impl From<AutosarDataError> for PatchConflictKind {
+    fn from(err: AutosarDataError) -> Self {
+        PatchConflictKind::ModelError(err)
+    }
+}
+
+impl Patch {
+    /// Check if the patch contains any operations
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use autosar_data::patch::Patch;
+    /// let patch = Patch::default();
+    /// assert!(patch.is_empty());
+    /// ```
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.operations.is_empty()
+    }
+
+    // convert the differences found by diff::create_patch into a patch
+    // The counterparts map elements of the new model to the corresponding elements of the old model; these are the
+    // elements that will be modified when the patch is applied.
+    #[allow(clippy::mutable_key_type)] // WeakElement uses the pointer for hashing, so the key does not change
+    pub(crate) fn from_differences(
+        differences: &[Difference],
+        counterparts: &FxHashMap<WeakElement, Element>,
+    ) -> Result<Self, AutosarDataError> {
+        let target_locator = |element: &Element| match counterparts.get(&element.downgrade()) {
+            Some(old_element) => ElementLocator::from_element(old_element),
+            None => ElementLocator::from_element(element),
+        };
+        let moved_old: Vec<Element> = differences
+            .iter()
+            .filter_map(|difference| match difference {
+                Difference::ElementMoved { old, .. } => Some(old.clone()),
+                _ => None,
+            })
+            .collect();
+        let moved_new: Vec<Element> = differences
+            .iter()
+            .filter_map(|difference| match difference {
+                Difference::ElementMoved { new, .. } => Some(new.clone()),
+                _ => None,
+            })
+            .collect();
+
+        let mut changes = Vec::new();
+        let mut removes = Vec::new();
+        let mut adds = Vec::new();
+        let mut moves = Vec::new();
+        let mut late_removes = Vec::new();
+        for difference in differences {
+            match difference {
+                Difference::ElementAdded { new } => {
+                    let parent = new.parent()?.ok_or(AutosarDataError::ItemDeleted)?;
+                    adds.push(PatchOperation::AddElement {
+                        parent: target_locator(&parent)?,
+                        position: ordered_position(&parent, new),
+                        element: ElementData::from_element_excluding(new, &moved_new),
+                    });
+                }
+                Difference::ElementRemoved { old } => {
+                    let operation = PatchOperation::RemoveElement {
+                        element: ElementLocator::from_element(old)?,
+                    };
+                    // elements that contain a moved element can only be removed after the move
+                    if moved_old.iter().any(|moved| moved.is_sub_element_of(old)) {
+                        late_removes.push(operation);
+                    } else {
+                        removes.push(operation);
+                    }
+                }
+                Difference::ElementMoved { old, new } => {
+                    let parent = new.parent()?.ok_or(AutosarDataError::ItemDeleted)?;
+                    let position = ordered_position(&parent, new);
+                    moves.push((
+                        position,
+                        PatchOperation::MoveElement {
+                            element: ElementLocator::from_element(old)?,
+                            new_parent: target_locator(&parent)?,
+                            position,
+                        },
+                    ));
+                }
+                Difference::CharacterDataChanged {
+                    old,
+                    old_value,
+                    new_value,
+                    ..
+                } => changes.push(PatchOperation::SetCharacterData {
+                    element: ElementLocator::from_element(old)?,
+                    old_value: old_value.as_ref().map(ToString::to_string),
+                    new_value: new_value.as_ref().map(ToString::to_string),
+                }),
+                Difference::AttributeAdded {
+                    old, attrname, value, ..
+                } => changes.push(PatchOperation::SetAttribute {
+                    element: ElementLocator::from_element(old)?,
+                    attrname: *attrname,
+                    old_value: None,
+                    new_value: Some(value.to_string()),
+                }),
+                Difference::AttributeRemoved {
+                    old, attrname, value, ..
+                } => changes.push(PatchOperation::SetAttribute {
+                    element: ElementLocator::from_element(old)?,
+                    attrname: *attrname,
+                    old_value: Some(value.to_string()),
+                    new_value: None,
+                }),
+                Difference::AttributeChanged {
+                    old,
+                    attrname,
+                    old_value,
+                    new_value,
+                    ..
+                } => changes.push(PatchOperation::SetAttribute {
+                    element: ElementLocator::from_element(old)?,
+                    attrname: *attrname,
+                    old_value: Some(old_value.to_string()),
+                    new_value: Some(new_value.to_string()),
+                }),
+            }
+        }
+
+        // Changes are applied first, while all elements are still in their original locations.
+        // Elements are removed in reverse order, so that the locators of the remaining elements stay valid.
+        // Moves inside of ordered parents are performed in order of their target positions.
+        removes.reverse();
+        late_removes.reverse();
+        moves.sort_by_key(|(position, _)| *position);
+        let mut operations = changes;
+        operations.append(&mut removes);
+        operations.append(&mut adds);
+        operations.extend(moves.into_iter().map(|(_, operation)| operation));
+        operations.append(&mut late_removes);
+
+        Ok(Self { operations })
+    }
+}
+
+// the position of an element is only relevant if its parent is ordered
+fn ordered_position(parent: &Element, element: &Element) -> Option<usize> {
+    if parent.element_type().is_ordered() {
+        element.position()
+    } else {
+        None
+    }
+}
+
+impl PatchOperation {
+    fn apply(&self, model: &AutosarModel) -> Result<(), PatchConflictKind> {
+        match self {
+            PatchOperation::AddElement {
+                parent,
+                position,
+                element,
+            } => {
+                let parent = parent.resolve_or_conflict(model)?;
+                element.create_in(&parent, *position)?;
+            }
+            PatchOperation::RemoveElement { element } => {
+                let element = element.resolve_or_conflict(model)?;
+                let parent = element.parent()?.ok_or(PatchConflictKind::RootElement)?;
+                parent.remove_sub_element(element)?;
+            }
+            PatchOperation::MoveElement {
+                element,
+                new_parent,
+                position,
+            } => {
+                let element = element.resolve_or_conflict(model)?;
+                let new_parent = new_parent.resolve_or_conflict(model)?;
+                if element.parent()?.is_none() {
+                    return Err(PatchConflictKind::RootElement);
+                }
+                if let Some(position) = position {
+                    new_parent.move_element_here_at(&element, *position)?;
+                } else {
+                    new_parent.move_element_here(&element)?;
+                }
+            }
+            PatchOperation::SetCharacterData {
+                element,
+                old_value,
+                new_value,
+            } => {
+                let element = element.resolve_or_conflict(model)?;
+                let found = text_content(&element).map(|cdata| cdata.to_string());
+                if found != *old_value {
+                    return Err(PatchConflictKind::CharacterDataMismatch {
+                        expected: old_value.clone(),
+                        found,
+                    });
+                }
+                set_text_content(&element, new_value.as_deref())?;
+            }
+            PatchOperation::SetAttribute {
+                element,
+                attrname,
+                old_value,
+                new_value,
+            } => {
+                let element = element.resolve_or_conflict(model)?;
+                let found = element.attribute_value(*attrname).map(|cdata| cdata.to_string());
+                if found != *old_value {
+                    return Err(PatchConflictKind::AttributeMismatch {
+                        attrname: *attrname,
+                        expected: old_value.clone(),
+                        found,
+                    });
+                }
+                if let Some(value) = new_value {
+                    element.set_attribute_string(*attrname, value)?;
+                } else if found.is_some() && !element.remove_attribute(*attrname) {
+                    return Err(PatchConflictKind::AttributeNotRemovable(*attrname));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+// apply all operations of the patch, and collect the conflicts
+pub(crate) fn apply(model: &AutosarModel, patch: &Patch) -> Vec<PatchConflict> {
+    patch
+        .operations
+        .iter()
+        .enumerate()
+        .filter_map(|(operation, op)| op.apply(model).err().map(|kind| PatchConflict { operation, kind }))
+        .collect()
+}
+
+// replace the character data of an element. For elements with mixed content all character data items are replaced.
+fn set_text_content(element: &Element, value: Option<&str>) -> Result<(), PatchConflictKind> {
+    if element.content_type() == ContentType::Mixed {
+        set_mixed_text(element, value)?;
+    } else if let Some(text) = value {
+        let cdata = parse_value(element, text).ok_or_else(|| PatchConflictKind::InvalidValue(text.to_string()))?;
+        element.set_character_data(cdata)?;
+    } else {
+        element.remove_character_data()?;
+    }
+    Ok(())
+}
+
+// replace all character data items of an element with mixed content by a single item
+pub(crate) fn set_mixed_text(element: &Element, value: Option<&str>) -> Result<(), AutosarDataError> {
+    let cdata_positions: Vec<usize> = element
+        .content()
+        .enumerate()
+        .filter(|(_, item)| matches!(item, ElementContent::CharacterData(_)))
+        .map(|(position, _)| position)
+        .collect();
+    for position in cdata_positions.into_iter().rev() {
+        element.remove_character_content_item(position)?;
+    }
+    if let Some(text) = value {
+        element.insert_character_content_item(text, 0)?;
+    }
+    Ok(())
+}
+
+// parse a string into character data according to the specification of the element
+fn parse_value(element: &Element, text: &str) -> Option<CharacterData> {
+    let spec = element.element_type().chardata_spec()?;
+    let version = element.min_version().ok()?;
+    CharacterData::parse(text, spec, version)
+}
+
+impl ElementLocator {
+    /// Create an `ElementLocator` for an element
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use autosar_data::*;
+    /// # use autosar_data::patch::*;
+    /// # fn main() -> Result<(), AutosarDataError> {
+    /// # let model = AutosarModel::new();
+    /// # model.create_file("a.arxml", AutosarVersion::LATEST)?;
+    /// let packages = model.root_element().create_sub_element(ElementName::ArPackages)?;
+    /// let package = packages.create_named_sub_element(ElementName::ArPackage, "Pkg")?;
+    /// let elements = package.create_sub_element(ElementName::Elements)?;
+    /// let locator = ElementLocator::from_element(&elements)?;
+    /// assert_eq!(locator.path, "/Pkg");
+    /// assert_eq!(locator.sub_elements.len(), 1);
+    /// assert_eq!(locator.resolve(&model), Some(elements));
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    ///  - [`AutosarDataError::ItemDeleted`]: The element is not part of a model
+    pub fn from_element(element: &Element) -> Result<Self, AutosarDataError> {
+        let mut sub_elements = Vec::new();
+        let mut current = element.clone();
+        let path = loop {
+            if current.is_identifiable() {
+                break current.path()?;
+            }
+            let Some(parent) = current.parent()? else {
+                break String::new();
+            };
+            let mut segment = PathSegment::from_element(&current);
+            segment.occurrence = parent
+                .sub_elements()
+                .take_while(|sub_element| *sub_element != current)
+                .filter(|sub_element| segment.matches(sub_element))
+                .count();
+            sub_elements.push(segment);
+            current = parent;
+        };
+        sub_elements.reverse();
+
+        Ok(Self { path, sub_elements })
+    }
+
+    /// Find the element in the given model
+    ///
+    /// Returns `None` if the model does not contain a matching element.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use autosar_data::*;
+    /// # use autosar_data::patch::*;
+    /// # let model = AutosarModel::new();
+    /// # model.create_file("a.arxml", AutosarVersion::LATEST).unwrap();
+    /// let locator = ElementLocator {
+    ///     path: String::new(),
+    ///     sub_elements: vec![],
+    /// };
+    /// assert_eq!(locator.resolve(&model), Some(model.root_element()));
+    /// ```
+    #[must_use]
+    pub fn resolve(&self, model: &AutosarModel) -> Option<Element> {
+        let mut element = if self.path.is_empty() {
+            model.root_element()
+        } else {
+            model.get_element_by_path(&self.path)?
+        };
+        for segment in &self.sub_elements {
+            element = element
+                .sub_elements()
+                .filter(|sub_element| segment.matches(sub_element))
+                .nth(segment.occurrence)?;
+        }
+        Some(element)
+    }
+
+    fn resolve_or_conflict(&self, model: &AutosarModel) -> Result<Element, PatchConflictKind> {
+        self.resolve(model)
+            .ok_or_else(|| PatchConflictKind::ElementNotFound(self.clone()))
+    }
+}
+
+impl PathSegment {
+    fn from_element(element: &Element) -> Self {
+        let (index, definition_ref, dest) = element_keys(element);
+        Self {
+            element_name: element.element_name(),
+            index,
+            definition_ref,
+            dest: dest.map(|dest| dest.to_string()),
+            occurrence: 0,
+        }
+    }
+
+    fn matches(&self, element: &Element) -> bool {
+        if element.element_name() != self.element_name {
+            return false;
+        }
+        let (index, definition_ref, dest) = element_keys(element);
+        index == self.index && definition_ref == self.definition_ref && dest.map(|dest| dest.to_string()) == self.dest
+    }
+}
+
+impl ElementData {
+    /// Capture the content of an element
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use autosar_data::*;
+    /// # use autosar_data::patch::*;
+    /// # let model = AutosarModel::new();
+    /// # model.create_file("a.arxml", AutosarVersion::LATEST).unwrap();
+    /// let data = ElementData::from_element(&model.root_element());
+    /// assert_eq!(data.element_name, ElementName::Autosar);
+    /// ```
+    #[must_use]
+    pub fn from_element(element: &Element) -> Self {
+        Self::from_element_excluding(element, &[])
+    }
+
+    // capture the content of an element, but leave out the excluded sub elements
+    pub(crate) fn from_element_excluding(element: &Element, excluded: &[Element]) -> Self {
+        let attributes = element
+            .attributes()
+            .map(|attribute| AttributeData {
+                attrname: attribute.attrname,
+                value: attribute.content.to_string(),
+            })
+            .collect();
+        let content = element
+            .content()
+            .filter_map(|item| match item {
+                ElementContent::Element(sub_element) => (!excluded.contains(&sub_element))
+                    .then(|| ContentData::Element(Self::from_element_excluding(&sub_element, excluded))),
+                ElementContent::CharacterData(cdata) => Some(ContentData::CharacterData(cdata.to_string())),
+                ElementContent::Comment(comment) => Some(ContentData::Comment(comment)),
+                ElementContent::ProcessingInstruction(instruction) => {
+                    Some(ContentData::ProcessingInstruction(instruction))
+                }
+            })
+            .collect();
+        Self {
+            element_name: element.element_name(),
+            attributes,
+            content,
+        }
+    }
+
+    // create an element from the data as a sub element of parent
+    pub(crate) fn create_in(&self, parent: &Element, position: Option<usize>) -> Result<Element, AutosarDataError> {
+        let short_name = self.content.iter().find_map(|item| match item {
+            ContentData::Element(sub_element) if sub_element.element_name == ElementName::ShortName => {
+                sub_element.text()
+            }
+            _ => None,
+        });
+        let element = match (short_name, position) {
+            (Some(name), Some(position)) => parent.create_named_sub_element_at(self.element_name, name, position)?,
+            (Some(name), None) => parent.create_named_sub_element(self.element_name, name)?,
+            (None, Some(position)) => parent.create_sub_element_at(self.element_name, position)?,
+            (None, None) => parent.create_sub_element(self.element_name)?,
+        };
+
+        for attribute in &self.attributes {
+            element.set_attribute_string(attribute.attrname, &attribute.value)?;
+        }
+        for item in &self.content {
+            match item {
+                ContentData::Element(sub_element) => {
+                    // the SHORT-NAME was already created together with the element
+                    if sub_element.element_name == ElementName::ShortName
+                        && let Some(short_name_elem) = element.get_sub_element(ElementName::ShortName)
+                    {
+                        for attribute in &sub_element.attributes {
+                            short_name_elem.set_attribute_string(attribute.attrname, &attribute.value)?;
+                        }
+                    } else {
+                        sub_element.create_in(&element, None)?;
+                    }
+                }
+                ContentData::CharacterData(text) => {
+                    if element.content_type() == ContentType::Mixed {
+                        element.insert_character_content_item(text, element.content_item_count())?;
+                    } else {
+                        let cdata = parse_value(&element, text).ok_or(AutosarDataError::IncorrectContentType {
+                            element: element.element_name(),
+                        })?;
+                        element.set_character_data(cdata)?;
+                    }
+                }
+                // markup is added after the content that was created so far
+                ContentData::Comment(comment) => element.insert_comment(comment, element.content_item_count())?,
+                ContentData::ProcessingInstruction(instruction) => {
+                    element.insert_processing_instruction(instruction, element.content_item_count())?;
+                }
+            }
+        }
+
+        Ok(element)
+    }
+
+    fn text(&self) -> Option<&str> {
+        self.content.iter().find_map(|item| match item {
+            ContentData::CharacterData(text) => Some(text.as_str()),
+            _ => None,
+        })
+    }
+}
+
+// serialize ElementName and AttributeName as strings
+#[cfg(feature = "serde")]
+mod serde_name {
+    use serde::{Deserialize, Deserializer, Serializer, de::Error};
+    use std::fmt::Display;
+    use std::str::FromStr;
+
+    pub(super) fn serialize<S: Serializer, T: Display>(value: &T, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(value)
+    }
+
+    pub(super) fn deserialize<'de, D: Deserializer<'de>, T: FromStr>(deserializer: D) -> Result<T, D::Error> {
+        let text = String::deserialize(deserializer)?;
+        T::from_str(&text).map_err(|_| D::Error::custom(format!("invalid name {text}")))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn create_model() -> (AutosarModel, Element) {
+        let model = AutosarModel::new();
+        model.create_file("test.arxml", AutosarVersion::LATEST).unwrap();
+        let packages = model
+            .root_element()
+            .create_sub_element(ElementName::ArPackages)
+            .unwrap();
+        (model, packages)
+    }
+
+    fn build_content(packages: &Element, signal_package: &str, length: &str, targets: &[&str]) {
+        let pkg = packages
+            .create_named_sub_element(ElementName::ArPackage, "Pkg")
+            .unwrap();
+        pkg.set_attribute(AttributeName::Uuid, length).unwrap();
+        let elements = pkg.create_sub_element(ElementName::Elements).unwrap();
+        let system = elements
+            .create_named_sub_element(ElementName::System, "System")
+            .unwrap();
+        let fibex_elements = system.create_sub_element(ElementName::FibexElements).unwrap();
+        for target in targets {
+            let fibex_ref = fibex_elements
+                .create_sub_element(ElementName::FibexElementRefConditional)
+                .and_then(|conditional| conditional.create_sub_element(ElementName::FibexElementRef))
+                .unwrap();
+            fibex_ref.set_attribute(AttributeName::Dest, EnumItem::ISignal).unwrap();
+            fibex_ref.set_character_data(*target).unwrap();
+        }
+
+        let signal_pkg = packages
+            .get_or_create_named_sub_element(ElementName::ArPackage, signal_package)
+            .unwrap();
+        let signal = signal_pkg
+            .get_or_create_sub_element(ElementName::Elements)
+            .and_then(|elements| elements.create_named_sub_element(ElementName::ISignal, "Signal"))
+            .unwrap();
+        signal
+            .create_sub_element(ElementName::Length)
+            .and_then(|elem| elem.set_character_data(length))
+            .unwrap();
+    }
+
+    #[test]
+    fn patch_roundtrip() {
+        let (model_a, packages_a) = create_model();
+        build_content(&packages_a, "Signals", "8", &["/Signals/A", "/Signals/B", "/Signals/C"]);
+        let (model_b, packages_b) = create_model();
+        build_content(
+            &packages_b,
+            "OtherSignals",
+            "16",
+            &["/Signals/C", "/Signals/A", "/Signals/D"],
+        );
+
+        let patch = diff::create_patch(&model_a, &model_b).unwrap();
+        assert!(!patch.is_empty());
+
+        // apply the patch to a copy of model_a
+        let (model_c, packages_c) = create_model();
+        build_content(&packages_c, "Signals", "8", &["/Signals/A", "/Signals/B", "/Signals/C"]);
+        model_c.apply_patch(&patch).unwrap();
+        assert!(diff::compare_models(&model_c, &model_b).is_empty());
+        assert!(model_c.get_element_by_path("/OtherSignals/Signal").is_some());
+        assert!(model_c.get_element_by_path("/Signals/Signal").is_none());
+
+        // the patch is one step in the undo history
+        model_c.undo().unwrap();
+        assert!(diff::compare_models(&model_c, &model_a).is_empty());
+    }
+
+    #[test]
+    fn conflicts() {
+        let (model_a, packages_a) = create_model();
+        build_content(&packages_a, "Signals", "8", &["/Signals/A"]);
+        let (model_b, packages_b) = create_model();
+        build_content(&packages_b, "Signals", "16", &["/Signals/B"]);
+        let patch = diff::create_patch(&model_a, &model_b).unwrap();
+
+        // model_c differs from model_a: the patch cannot be applied
+        let (model_c, packages_c) = create_model();
+        build_content(&packages_c, "Signals", "32", &["/Signals/A"]);
+        let result = model_c.apply_patch(&patch);
+        let Err(AutosarDataError::PatchConflict { conflicts }) = result else {
+            panic!("expected a conflict, got {result:?}");
+        };
+        assert_eq!(conflicts.len(), 2);
+        assert!(conflicts.iter().any(|conflict| matches!(
+            &conflict.kind,
+            PatchConflictKind::AttributeMismatch { found: Some(found), .. } if found == "32"
+        )));
+        assert!(conflicts.iter().any(|conflict| matches!(
+            &conflict.kind,
+            PatchConflictKind::CharacterDataMismatch { found: Some(found), .. } if found == "32"
+        )));
+        // all or nothing: nothing was changed
+        let (model_d, packages_d) = create_model();
+        build_content(&packages_d, "Signals", "32", &["/Signals/A"]);
+        assert!(diff::compare_models(&model_c, &model_d).is_empty());
+        assert!(!model_c.can_undo());
+
+        // missing elements are conflicts
+        let patch = Patch {
+            operations: vec![PatchOperation::RemoveElement {
+                element: ElementLocator {
+                    path: "/Missing".to_string(),
+                    sub_elements: vec![],
+                },
+            }],
+        };
+        let result = model_c.apply_patch(&patch);
+        let Err(AutosarDataError::PatchConflict { conflicts }) = result else {
+            panic!("expected a conflict, got {result:?}");
+        };
+        assert!(matches!(conflicts[0].kind, PatchConflictKind::ElementNotFound(_)));
+        assert_eq!(conflicts[0].operation, 0);
+
+        // the root element cannot be removed
+        let patch = Patch {
+            operations: vec![PatchOperation::RemoveElement {
+                element: ElementLocator {
+                    path: String::new(),
+                    sub_elements: vec![],
+                },
+            }],
+        };
+        let result = model_c.apply_patch(&patch);
+        let Err(AutosarDataError::PatchConflict { conflicts }) = result else {
+            panic!("expected a conflict, got {result:?}");
+        };
+        assert!(matches!(conflicts[0].kind, PatchConflictKind::RootElement));
+
+        // required attributes cannot be removed
+        let schema_location = model_c
+            .root_element()
+            .attribute_value(AttributeName::xsiSchemalocation)
+            .map(|value| value.to_string());
+        let patch = Patch {
+            operations: vec![PatchOperation::SetAttribute {
+                element: ElementLocator {
+                    path: String::new(),
+                    sub_elements: vec![],
+                },
+                attrname: AttributeName::xsiSchemalocation,
+                old_value: schema_location,
+                new_value: None,
+            }],
+        };
+        let result = model_c.apply_patch(&patch);
+        let Err(AutosarDataError::PatchConflict { conflicts }) = result else {
+            panic!("expected a conflict, got {result:?}");
+        };
+        assert!(matches!(
+            conflicts[0].kind,
+            PatchConflictKind::AttributeNotRemovable(AttributeName::xsiSchemalocation)
+        ));
+    }
+
+    #[test]
+    fn locator() {
+        let (model, packages) = create_model();
+        build_content(&packages, "Signals", "8", &["/Signals/A", "/Signals/B"]);
+        let fibex_elements = model
+            .get_element_by_path("/Pkg/System")
+            .and_then(|system| system.get_sub_element(ElementName::FibexElements))
+            .unwrap();
+        let second_ref = fibex_elements
+            .get_sub_element_at(1)
+            .and_then(|conditional| conditional.get_sub_element(ElementName::FibexElementRef))
+            .unwrap();
+        let locator = ElementLocator::from_element(&second_ref).unwrap();
+        assert_eq!(locator.path, "/Pkg/System");
+        assert_eq!(locator.sub_elements.len(), 3);
+        assert_eq!(locator.sub_elements[1].occurrence, 1);
+        assert_eq!(locator.sub_elements[2].dest.as_deref(), Some("I-SIGNAL"));
+        assert_eq!(locator.resolve(&model), Some(second_ref));
+
+        let root_locator = ElementLocator::from_element(&model.root_element()).unwrap();
+        assert_eq!(root_locator.path, "");
+        assert!(root_locator.sub_elements.is_empty());
+    }
+
+    #[test]
+    fn element_data_markup() {
+        let (model, packages) = create_model();
+        let pkg = packages
+            .create_named_sub_element(ElementName::ArPackage, "Pkg")
+            .unwrap();
+        pkg.insert_comment("comment", 1).unwrap();
+        pkg.insert_processing_instruction("tool info", 2).unwrap();
+        let data = ElementData::from_element(&pkg);
+        assert_eq!(data.content[1], ContentData::Comment("comment".to_string()));
+        assert_eq!(
+            data.content[2],
+            ContentData::ProcessingInstruction("tool info".to_string())
+        );
+
+        // the markup is recreated together with the element
+        pkg.set_item_name("Old").unwrap();
+        let copy = data.create_in(&packages, None).unwrap();
+        assert_eq!(copy, model.get_element_by_path("/Pkg").unwrap());
+        assert_eq!(copy.comments(), vec![(1, "comment".to_string())]);
+        assert_eq!(
+            copy.content().nth(2),
+            Some(ElementContent::ProcessingInstruction("tool info".to_string()))
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serialize() {
+        let (model_a, _) = create_model();
+        let (model_b, packages_b) = create_model();
+        build_content(&packages_b, "Signals", "8", &["/Signals/A"]);
+        let patch = diff::create_patch(&model_a, &model_b).unwrap();
+        let json = serde_json::to_string(&patch).unwrap();
+        assert!(json.contains("\"AR-PACKAGE\""));
+        let patch2: Patch = serde_json::from_str(&json).unwrap();
+        assert_eq!(patch, patch2);
+    }
+
+    #[test]
+    fn traits() {
+        let patch = Patch::default();
+        let patch2 = patch.clone();
+        assert_eq!(patch, patch2);
+        assert!(!format!("{patch:?}").is_empty());
+        let conflict = PatchConflict {
+            operation: 0,
+            kind: PatchConflictKind::InvalidValue("x".to_string()),
+        };
+        assert!(!format!("{conflict:?}").is_empty());
+    }
+}
@@ -0,0 +1,651 @@
+use crate::*;
+use fxhash::FxHashSet;
+use std::str::FromStr;
+
+/// A compiled query that selects elements from a model
+///
+/// A query consists of steps that are separated by `/`. If the query starts with `/`, then it is evaluated starting
+/// from the root element of the model; otherwise it is evaluated relative to a given element.
+///
+/// Each step is one of:
+///  - `ELEMENT-NAME`: all sub elements with this [`ElementName`]
+///  - `ShortName`: all identifiable elements with this SHORT-NAME, which are found below the current element in the
+///    same way as in an Autosar path. The SHORT-NAME may contain the wildcards `*` (any sequence of characters)
+///    and `?` (any single character).
+///  - `*`: all sub elements. Use `?*` to match any SHORT-NAME instead.
+///  - `**`: the current element and all of its sub elements at any depth
+///
+/// Steps can be followed by one or more predicates in square brackets:
+///  - `[@ATTRIBUTE]`: the attribute exists
+///  - `[@ATTRIBUTE='value']` or `[@ATTRIBUTE!='value']`: the value of the attribute
+///  - `[.='value']` or `[.!='value']`: the character data of the element
+///  - `[ELEMENT-NAME]`: the element has a sub element with this name
+///  - `[ELEMENT-NAME='value']` or `[ELEMENT-NAME!='value']`: the character data of a sub element
+///
+/// Since all element names are upper case, a SHORT-NAME that is identical to an element name can only be matched with a predicate,
+/// e.g. `*[SHORT-NAME='SYSTEM']`.
+///
+/// # Example
+///
+/// ```
+/// # use autosar_data::*;
+/// # fn main() -> Result<(), AutosarDataError> {
+/// # let model = AutosarModel::new();
+/// # model.create_file("filename", AutosarVersion::LATEST)?;
+/// let query = Query::new("/Pkg/**/CAN-CLUSTER/CAN-CLUSTER-VARIANTS/*/BAUDRATE")?;
+/// for baudrate in query.evaluate(&model) {
+///     println!("{:?}", baudrate.character_data());
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Query {
+    absolute: bool,
+    steps: Vec<Step>,
+}
+
+/// An error that occurred while parsing a query
+#[derive(Debug, Error, Eq, PartialEq, Clone)]
+#[non_exhaustive]
+pub enum QueryError {
+    /// The query is empty, or contains an empty step
+    #[error("Empty step at position {position}")]
+    EmptyStep {
+        /// position of the error in the query
+        position: usize,
+    },
+
+    /// The step is neither an element name nor a valid SHORT-NAME
+    #[error("Invalid step '{step}' at position {position}")]
+    InvalidStep {
+        /// the invalid step
+        step: String,
+        /// position of the error in the query
+        position: usize,
+    },
+
+    /// The predicate is not valid
+    #[error("Invalid predicate at position {position}")]
+    InvalidPredicate {
+        /// position of the error in the query
+        position: usize,
+    },
+
+    /// The query ended unexpectedly, e.g. inside of a predicate
+    #[error("Unexpected end of the query")]
+    UnexpectedEnd,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Step {
+    kind: StepKind,
+    predicates: Vec<Predicate>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum StepKind {
+    Descendants,
+    AnyElement,
+    ElementName(ElementName),
+    ShortName(String),
+    ShortNamePattern(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Predicate {
+    Attribute(AttributeName, Option<Comparison>),
+    CharacterData(Option<ElementName>, Option<Comparison>),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Comparison {
+    equal: bool,
+    value: String,
+}
+
+impl Query {
+    /// Parse a query
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use autosar_data::*;
+    /// # fn main() -> Result<(), AutosarDataError> {
+    /// let query = Query::new("/Pkg/*/I-SIGNAL-I-PDU[@UUID]/LENGTH[.='8']")?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    ///  - [`AutosarDataError::InvalidQuery`]: The query could not be parsed
+    pub fn new(query: &str) -> Result<Self, AutosarDataError> {
+        QueryParser::new(query)
+            .parse()
+            .map_err(|source| AutosarDataError::InvalidQuery {
+                query: query.to_string(),
+                source,
+            })
+    }
+
+    /// Evaluate the query on a model
+    ///
+    /// Relative queries are evaluated starting from the root element of the model.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use autosar_data::*;
+    /// # fn main() -> Result<(), AutosarDataError> {
+    /// # let model = AutosarModel::new();
+    /// # model.create_file("filename", AutosarVersion::LATEST)?;
+    /// let query = Query::new("/*Pkg")?;
+    /// let packages: Vec<Element> = query.evaluate(&model).collect();
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[must_use]
+    pub fn evaluate(&self, model: &AutosarModel) -> QueryIterator {
+        self.evaluate_steps(model, model.root_element())
+    }
+
+    /// Evaluate the query starting from an element
+    ///
+    /// Absolute queries are evaluated starting from the root element of the model that contains the element.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use autosar_data::*;
+    /// # fn main() -> Result<(), AutosarDataError> {
+    /// # let model = AutosarModel::new();
+    /// # model.create_file("filename", AutosarVersion::LATEST)?;
+    /// # let element = model.root_element();
+    /// let query = Query::new("AR-PACKAGES/AR-PACKAGE")?;
+    /// let packages: Vec<Element> = query.evaluate_from(&element).collect();
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[must_use]
+    pub fn evaluate_from(&self, element: &Element) -> QueryIterator {
+        let Ok(model) = element.model() else {
+            return QueryIterator::new(Vec::new());
+        };
+        if self.absolute {
+            self.evaluate_steps(&model, model.root_element())
+        } else {
+            self.evaluate_steps(&model, element.clone())
+        }
+    }
+
+    #[allow(clippy::mutable_key_type)] // WeakElement uses the pointer for hashing, so the key does not change
+    fn evaluate_steps(&self, model: &AutosarModel, start: Element) -> QueryIterator {
+        let mut steps = self.steps.as_slice();
+        let mut context = vec![start];
+
+        // absolute queries that start with SHORT-NAME patterns are resolved using the identifiables index of the model
+        if self.absolute {
+            let prefix_len = steps
+                .iter()
+                .take_while(|step| matches!(step.kind, StepKind::ShortName(_) | StepKind::ShortNamePattern(_)))
+                .take_while(|step| step.predicates.is_empty())
+                .count();
+            if steps[..prefix_len]
+                .iter()
+                .any(|step| matches!(step.kind, StepKind::ShortNamePattern(_)))
+            {
+                context = find_identifiables(model, &steps[..prefix_len]);
+                steps = &steps[prefix_len..];
+            }
+        }
+
+        for step in steps {
+            let mut seen = FxHashSet::default();
+            let mut next_context = Vec::new();
+            for element in &context {
+                step.kind.apply(model, element, &mut |found| {
+                    if step.predicates.iter().all(|predicate| predicate.matches(&found))
+                        && seen.insert(found.downgrade())
+                    {
+                        next_context.push(found);
+                    }
+                });
+            }
+            context = next_context;
+        }
+
+        QueryIterator::new(context)
+    }
+}
+
+impl FromStr for Query {
+    type Err = AutosarDataError;
+
+    fn from_str(query: &str) -> Result<Self, Self::Err> {
+        Self::new(query)
+    }
+}
+
+impl StepKind {
+    // call the callback for every element that is selected by this step
+    fn apply(&self, model: &AutosarModel, element: &Element, callback: &mut impl FnMut(Element)) {
+        match self {
+            StepKind::Descendants => element
+                .elements_dfs()
+                .for_each(|(_, sub_element)| callback(sub_element)),
+            StepKind::AnyElement => element.sub_elements().for_each(callback),
+            StepKind::ElementName(element_name) => element
+                .sub_elements()
+                .filter(|sub_element| sub_element.element_name() == *element_name)
+                .for_each(callback),
+            StepKind::ShortName(name) => {
+                // the element can be found directly in the identifiables index if the current element has a path
+                let parent_path = if element.is_identifiable() {
+                    element.path().ok()
+                } else if element.element_name() == ElementName::Autosar {
+                    Some(String::new())
+                } else {
+                    None
+                };
+                if let Some(parent_path) = parent_path {
+                    if let Some(found) = model.get_element_by_path(&format!("{parent_path}/{name}")) {
+                        callback(found);
+                    }
+                } else {
+                    named_children(element, &mut |child| {
+                        if child.item_name().as_deref() == Some(name) {
+                            callback(child);
+                        }
+                    });
+                }
+            }
+            StepKind::ShortNamePattern(pattern) => named_children(element, &mut |child| {
+                if child.item_name().is_some_and(|name| glob_match(pattern, &name)) {
+                    callback(child);
+                }
+            }),
+        }
+    }
+}
+
+impl Predicate {
+    fn matches(&self, element: &Element) -> bool {
+        match self {
+            Predicate::Attribute(attrname, comparison) => element
+                .attribute_value(*attrname)
+                .is_some_and(|value| comparison.as_ref().is_none_or(|cmp| cmp.matches(&value))),
+            Predicate::CharacterData(None, comparison) => element
+                .character_data()
+                .is_some_and(|value| comparison.as_ref().is_none_or(|cmp| cmp.matches(&value))),
+            Predicate::CharacterData(Some(element_name), comparison) => element
+                .sub_elements()
+                .filter(|sub_element| sub_element.element_name() == *element_name)
+                .any(|sub_element| match comparison {
+                    Some(cmp) => sub_element.character_data().is_some_and(|value| cmp.matches(&value)),
+                    None => true,
+                }),
+        }
+    }
+}
+
+impl Comparison {
+    fn matches(&self, value: &CharacterData) -> bool {
+        (value.to_string() == self.value) == self.equal
+    }
+}
+
+// call the callback for all identifiable elements whose named parent is the given element
+fn named_children(element: &Element, callback: &mut impl FnMut(Element)) {
+    for sub_element in element.sub_elements() {
+        if sub_element.is_identifiable() {
+            callback(sub_element);
+        } else {
+            named_children(&sub_element, callback);
+        }
+    }
+}
+
+// find all identifiable elements whose path matches the SHORT-NAME steps
+fn find_identifiables(model: &AutosarModel, steps: &[Step]) -> Vec<Element> {
+    let mut found: Vec<(String, WeakElement)> = model
+        .identifiable_elements()
+        .filter(|(path, _)| {
+            let mut parts = path.split('/').skip(1);
+            steps.iter().all(|step| {
+                parts.next().is_some_and(|part| match &step.kind {
+                    StepKind::ShortName(name) => part == name,
+                    StepKind::ShortNamePattern(pattern) => glob_match(pattern, part),
+                    _ => false,
+                })
+            }) && parts.next().is_none()
+        })
+        .collect();
+    found.sort_by(|(path1, _), (path2, _)| path1.cmp(path2));
+    found.into_iter().filter_map(|(_, weak)| weak.upgrade()).collect()
+}
+
+// match a text against a pattern containing the wildcards '*' and '?'
+fn glob_match(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+    let (mut pat_idx, mut text_idx) = (0, 0);
+    // position of the last '*' in the pattern, and the text position where it started matching
+    let mut backtrack: Option<(usize, usize)> = None;
+    while text_idx < text.len() {
+        if pat_idx < pattern.len() && (pattern[pat_idx] == '?' || pattern[pat_idx] == text[text_idx]) {
+            pat_idx += 1;
+            text_idx += 1;
+        } else if pat_idx < pattern.len() && pattern[pat_idx] == '*' {
+            backtrack = Some((pat_idx, text_idx));
+            pat_idx += 1;
+        } else if let Some((star_idx, star_text_idx)) = backtrack {
+            // let the last '*' match one more character
+            pat_idx = star_idx + 1;
+            text_idx = star_text_idx + 1;
+            backtrack = Some((star_idx, star_text_idx + 1));
+        } else {
+            return false;
+        }
+    }
+    pattern[pat_idx..].iter().all(|c| *c == '*')
+}
+
+struct QueryParser<'a> {
+    query: &'a str,
+    position: usize,
+}
+
+impl<'a> QueryParser<'a> {
+    fn new(query: &'a str) -> Self {
+        Self { query, position: 0 }
+    }
+
+    fn parse(mut self) -> Result<Query, QueryError> {
+        let absolute = self.query.starts_with('/');
+        if absolute {
+            self.position = 1;
+        }
+        let mut steps = Vec::new();
+        loop {
+            steps.push(self.parse_step()?);
+            match self.peek() {
+                Some('/') => self.position += 1,
+                None => break,
+                Some(_) => {
+                    return Err(QueryError::InvalidPredicate {
+                        position: self.position,
+                    });
+                }
+            }
+        }
+        Ok(Query { absolute, steps })
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.query[self.position..].chars().next()
+    }
+
+    // read a name consisting of letters, digits, '-', '_' and wildcards
+    fn read_name(&mut self) -> &'a str {
+        let start = self.position;
+        let len = self.query[start..]
+            .find(|c: char| !(c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '*' | '?')))
+            .unwrap_or(self.query.len() - start);
+        self.position += len;
+        &self.query[start..start + len]
+    }
+
+    fn parse_step(&mut self) -> Result<Step, QueryError> {
+        let start = self.position;
+        let text = self.read_name();
+        let kind = if text.is_empty() {
+            return Err(QueryError::EmptyStep { position: start });
+        } else if text == "**" {
+            StepKind::Descendants
+        } else if text == "*" {
+            StepKind::AnyElement
+        } else if let Ok(element_name) = ElementName::from_str(text) {
+            StepKind::ElementName(element_name)
+        } else if text.contains('-') {
+            return Err(QueryError::InvalidStep {
+                step: text.to_string(),
+                position: start,
+            });
+        } else if text.contains(['*', '?']) {
+            StepKind::ShortNamePattern(text.to_string())
+        } else {
+            StepKind::ShortName(text.to_string())
+        };
+
+        let mut predicates = Vec::new();
+        while self.peek() == Some('[') {
+            self.position += 1;
+            predicates.push(self.parse_predicate()?);
+        }
+        Ok(Step { kind, predicates })
+    }
+
+    fn parse_predicate(&mut self) -> Result<Predicate, QueryError> {
+        let start = self.position;
+        let invalid = QueryError::InvalidPredicate { position: start };
+        let predicate = match self.peek() {
+            Some('@') => {
+                self.position += 1;
+                let attrname = AttributeName::from_str(self.read_name()).map_err(|_| invalid.clone())?;
+                Predicate::Attribute(attrname, self.parse_comparison()?)
+            }
+            Some('.') => {
+                self.position += 1;
+                Predicate::CharacterData(None, self.parse_comparison()?)
+            }
+            Some(_) => {
+                let element_name = ElementName::from_str(self.read_name()).map_err(|_| invalid.clone())?;
+                Predicate::CharacterData(Some(element_name), self.parse_comparison()?)
+            }
+            None => return Err(QueryError::UnexpectedEnd),
+        };
+        match self.peek() {
+            Some(']') => {
+                self.position += 1;
+                Ok(predicate)
+            }
+            Some(_) => Err(invalid),
+            None => Err(QueryError::UnexpectedEnd),
+        }
+    }
+
+    fn parse_comparison(&mut self) -> Result<Option<Comparison>, QueryError> {
+        let rest = &self.query[self.position..];
+        let equal = if rest.starts_with("!=") {
+            self.position += 2;
+            false
+        } else if rest.starts_with('=') {
+            self.position += 1;
+            true
+        } else {
+            return Ok(None);
+        };
+
+        // the value is a string enclosed in single or double quotes
+        let quote = self.peek().ok_or(QueryError::UnexpectedEnd)?;
+        if quote != '\'' && quote != '"' {
+            return Err(QueryError::InvalidPredicate {
+                position: self.position,
+            });
+        }
+        let value_start = self.position + 1;
+        let value_len = self.query[value_start..].find(quote).ok_or(QueryError::UnexpectedEnd)?;
+        self.position = value_start + value_len + 1;
+        Ok(Some(Comparison {
+            equal,
+            value: self.query[value_start..value_start + value_len].to_string(),
+        }))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn create_model() -> AutosarModel {
+        let model = AutosarModel::new();
+        model.create_file("test.arxml", AutosarVersion::LATEST).unwrap();
+        let packages = model
+            .root_element()
+            .create_sub_element(ElementName::ArPackages)
+            .unwrap();
+        let pkg = packages
+            .create_named_sub_element(ElementName::ArPackage, "Pkg")
+            .unwrap();
+        let sub_packages = pkg.create_sub_element(ElementName::ArPackages).unwrap();
+        for (pkg_name, cluster_name, baudrate) in [("Can1", "Cluster1", "500000"), ("Can2", "Cluster2", "250000")] {
+            let cluster = sub_packages
+                .create_named_sub_element(ElementName::ArPackage, pkg_name)
+                .and_then(|pkg| pkg.create_sub_element(ElementName::Elements))
+                .and_then(|elements| elements.create_named_sub_element(ElementName::CanCluster, cluster_name))
+                .unwrap();
+            cluster.set_attribute(AttributeName::Uuid, cluster_name).unwrap();
+            cluster
+                .create_sub_element(ElementName::CanClusterVariants)
+                .and_then(|variants| variants.create_sub_element(ElementName::CanClusterConditional))
+                .and_then(|conditional| conditional.create_sub_element(ElementName::Baudrate))
+                .and_then(|baudrate_elem| baudrate_elem.set_character_data(baudrate))
+                .unwrap();
+        }
+        packages
+            .create_named_sub_element(ElementName::ArPackage, "Other")
+            .unwrap();
+        model
+    }
+
+    fn query_paths(model: &AutosarModel, query: &str) -> Vec<String> {
+        Query::new(query)
+            .unwrap()
+            .evaluate(model)
+            .map(|element| element.path().unwrap_or_else(|_| element.xml_path()))
+            .collect()
+    }
+
+    #[test]
+    fn short_name_steps() {
+        let model = create_model();
+        assert_eq!(query_paths(&model, "/Pkg"), vec!["/Pkg"]);
+        assert_eq!(query_paths(&model, "/Pkg/Can1/Cluster1"), vec!["/Pkg/Can1/Cluster1"]);
+        assert_eq!(query_paths(&model, "/Pkg/Can1/Cluster2"), Vec::<String>::new());
+        // wildcards use the identifiables index
+        assert_eq!(
+            query_paths(&model, "/Pkg/Can?/?*"),
+            vec!["/Pkg/Can1/Cluster1", "/Pkg/Can2/Cluster2"]
+        );
+        assert_eq!(query_paths(&model, "/?*/?*/*2"), vec!["/Pkg/Can2/Cluster2"]);
+        assert_eq!(query_paths(&model, "/O*"), vec!["/Other"]);
+        // short names after other steps
+        assert_eq!(
+            query_paths(&model, "/**/Cluster*"),
+            vec!["/Pkg/Can1/Cluster1", "/Pkg/Can2/Cluster2"]
+        );
+        assert_eq!(query_paths(&model, "AR-PACKAGES/AR-PACKAGE/Can1"), vec!["/Pkg/Can1"]);
+    }
+
+    #[test]
+    fn element_steps() {
+        let model = create_model();
+        let baudrates: Vec<String> = Query::new("/Pkg/**/CAN-CLUSTER/CAN-CLUSTER-VARIANTS/*/BAUDRATE")
+            .unwrap()
+            .evaluate(&model)
+            .map(|element| element.character_data().unwrap().to_string())
+            .collect();
+        assert_eq!(baudrates, vec!["500000", "250000"]);
+
+        assert_eq!(query_paths(&model, "/AR-PACKAGES/AR-PACKAGE").len(), 2);
+        assert_eq!(query_paths(&model, "/*").len(), 1);
+        // ** includes the starting element
+        assert_eq!(query_paths(&model, "/Pkg/**").first().unwrap(), "/Pkg");
+        // relative queries start at the given element
+        let cluster = model.get_element_by_path("/Pkg/Can2/Cluster2").unwrap();
+        let query = Query::new("CAN-CLUSTER-VARIANTS/CAN-CLUSTER-CONDITIONAL/BAUDRATE").unwrap();
+        assert_eq!(query.evaluate_from(&cluster).count(), 1);
+        // absolute queries start at the root element
+        let query = Query::new("/Pkg/?*/?*").unwrap();
+        assert_eq!(query.evaluate_from(&cluster).count(), 2);
+    }
+
+    #[test]
+    fn predicates() {
+        let model = create_model();
+        assert_eq!(
+            query_paths(&model, "/**/CAN-CLUSTER[@UUID='Cluster2']"),
+            vec!["/Pkg/Can2/Cluster2"]
+        );
+        assert_eq!(
+            query_paths(&model, "/**/CAN-CLUSTER[@UUID!='Cluster2']"),
+            vec!["/Pkg/Can1/Cluster1"]
+        );
+        assert_eq!(query_paths(&model, "/**/CAN-CLUSTER[@UUID]").len(), 2);
+        assert_eq!(query_paths(&model, "/**/CAN-CLUSTER[@T]").len(), 0);
+        assert_eq!(
+            query_paths(&model, "/**/AR-PACKAGE[SHORT-NAME='Other']"),
+            vec!["/Other"]
+        );
+        assert_eq!(query_paths(&model, "/**/AR-PACKAGE[ELEMENTS]").len(), 2);
+        assert_eq!(query_paths(&model, "/**/BAUDRATE[.=\"250000\"]").len(), 1);
+        assert_eq!(query_paths(&model, "/**/BAUDRATE[.!='250000']").len(), 1);
+        assert_eq!(
+            query_paths(&model, "/Pkg/?*/?*[@UUID][SHORT-NAME='Cluster1']"),
+            vec!["/Pkg/Can1/Cluster1"]
+        );
+        // predicates on a SHORT-NAME prefix
+        assert_eq!(query_paths(&model, "/Pkg[@UUID]/Can1"), Vec::<String>::new());
+    }
+
+    #[test]
+    fn parse_errors() {
+        let check = |query: &str, expected: QueryError| {
+            let result = Query::new(query);
+            let Err(AutosarDataError::InvalidQuery { source, .. }) = result else {
+                panic!("query {query} should fail, got {result:?}");
+            };
+            assert_eq!(source, expected);
+        };
+        check("", QueryError::EmptyStep { position: 0 });
+        check("/", QueryError::EmptyStep { position: 1 });
+        check("/Pkg//X", QueryError::EmptyStep { position: 5 });
+        check(
+            "/NOT-AN-ELEMENT",
+            QueryError::InvalidStep {
+                step: "NOT-AN-ELEMENT".to_string(),
+                position: 1,
+            },
+        );
+        check("/Pkg[@NOT-AN-ATTRIBUTE]", QueryError::InvalidPredicate { position: 5 });
+        check("/Pkg[.='x'", QueryError::UnexpectedEnd);
+        check("/Pkg[.='x", QueryError::UnexpectedEnd);
+        check("/Pkg[.=x]", QueryError::InvalidPredicate { position: 7 });
+        check("/Pkg[.]x", QueryError::InvalidPredicate { position: 7 });
+        assert!("/Pkg".parse::<Query>().is_ok());
+    }
+
+    #[test]
+    fn glob() {
+        assert!(glob_match("*", ""));
+        assert!(glob_match("*", "abc"));
+        assert!(glob_match("a*c", "abbbc"));
+        assert!(glob_match("a?c", "abc"));
+        assert!(!glob_match("a?c", "ac"));
+        assert!(glob_match("*b*", "abc"));
+        assert!(!glob_match("*x*", "abc"));
+        assert!(glob_match("a*b*c", "aXbYbZc"));
+        assert!(!glob_match("abc", "abcd"));
+    }
+
+    #[test]
+    fn traits() {
+        let query = Query::new("/Pkg").unwrap();
+        assert_eq!(query.clone(), query);
+        assert!(!format!("{query:?}").is_empty());
+        let error = QueryError::UnexpectedEnd;
+        assert_eq!(error.clone(), error);
+        assert!(!format!("{error:?} {error}").is_empty());
+    }
+}
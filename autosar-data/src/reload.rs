@@ -0,0 +1,419 @@
+use crate::diff::{Difference, compare_with_counterparts, text_content};
+use crate::patch::{ElementData, ElementLocator, set_mixed_text};
+use crate::*;
+
+/// reparse the file from disk and apply the differences to the existing model
+///
+/// The content of the file is first extracted from a duplicate of the model, and then compared with the new data.
+/// Unchanged elements keep their identity, since the differences are applied to the existing elements.
+pub(crate) fn reload_file(file: &ArxmlFile, strict: bool) -> Result<Vec<AutosarDataError>, AutosarDataError> {
+    let model = file.model()?;
+    if !model.files().any(|model_file| model_file == *file) {
+        return Err(AutosarDataError::ItemDeleted);
+    }
+    let filename = file.filename();
+    let new_model = AutosarModel::new();
+    let (new_file, warnings) = new_model.load_file(&filename, strict)?;
+
+    // the duplicate only keeps the content of this file. Its elements are matched with the existing elements
+    // by their paths, which are still identical before the other files are removed
+    let copy = model.duplicate()?;
+    let mut copy_to_real = FxHashMap::default();
+    for (_, copy_element) in copy.elements_dfs() {
+        if let Some(real_element) = ElementLocator::from_element(&copy_element)?.resolve(&model) {
+            copy_to_real.insert(copy_element.downgrade(), real_element);
+        }
+    }
+    let other_files: Vec<ArxmlFile> = copy
+        .files()
+        .filter(|copy_file| copy_file.filename() != filename)
+        .collect();
+    for other_file in other_files {
+        copy.remove_file(&other_file);
+    }
+
+    let (differences, counterparts) = compare_with_counterparts(&copy.root_element(), &new_model.root_element());
+    let mut reloader = Reloader {
+        model: model.clone(),
+        file: file.clone(),
+        copy_to_real,
+        counterparts,
+    };
+
+    // if reconciling the model fails, then the transaction is dropped and all changes are reverted
+    let transaction = model.begin_transaction();
+    {
+        let mut file_locked = file.write_lock();
+        file_locked.version = new_file.version();
+        file_locked.xml_standalone = new_file.xml_standalone();
+    }
+    reloader.apply(&differences)?;
+    transaction.commit();
+    // the model now matches the file on disk
+    file.0.write().modified = false;
+    model.notify_observers(|| ModelEvent::ModelReset);
+
+    Ok(warnings)
+}
+
+#[allow(clippy::mutable_key_type)] // WeakElement uses the pointer for hashing, so the key does not change
+struct Reloader {
+    model: AutosarModel,
+    file: ArxmlFile,
+    // maps the elements of the duplicated model to the existing elements
+    copy_to_real: FxHashMap<WeakElement, Element>,
+    // maps the elements of the reloaded data to their counterparts in the duplicated model, or in the existing model
+    counterparts: FxHashMap<WeakElement, Element>,
+}
+
+impl Reloader {
+    fn apply(&mut self, differences: &[Difference]) -> Result<(), AutosarDataError> {
+        let moved_old: Vec<Element> = differences
+            .iter()
+            .filter_map(|difference| match difference {
+                Difference::ElementMoved { old, .. } => Some(old.clone()),
+                _ => None,
+            })
+            .collect();
+        let moved_new: Vec<Element> = differences
+            .iter()
+            .filter_map(|difference| match difference {
+                Difference::ElementMoved { new, .. } => Some(new.clone()),
+                _ => None,
+            })
+            .collect();
+
+        let mut late_removes = Vec::new();
+        for difference in differences {
+            match difference {
+                Difference::CharacterDataChanged { old, new, .. } => {
+                    let element = self.real(old);
+                    let value = text_content(new);
+                    if element.content_type() == ContentType::Mixed {
+                        set_mixed_text(&element, value.map(|cdata| cdata.to_string()).as_deref())?;
+                    } else if let Some(cdata) = value {
+                        element.set_character_data(cdata)?;
+                    } else {
+                        element.remove_character_data()?;
+                    }
+                }
+                Difference::AttributeAdded { old, new, attrname, .. }
+                | Difference::AttributeRemoved { old, new, attrname, .. }
+                | Difference::AttributeChanged { old, new, attrname, .. } => {
+                    let element = self.real(old);
+                    if let Some(value) = new.attribute_value(*attrname) {
+                        element.set_attribute(*attrname, value)?;
+                    } else if !element.remove_attribute(*attrname) && element.attribute_value(*attrname).is_some() {
+                        // the attribute is required, so the model can't match the file
+                        return Err(AutosarDataError::AttributeRequired { attrname: *attrname });
+                    }
+                }
+                Difference::ElementRemoved { old } => {
+                    // elements that contain a moved element can only be removed after the move
+                    if moved_old.iter().any(|moved| moved.is_sub_element_of(old)) {
+                        late_removes.push(old.clone());
+                    } else {
+                        self.remove_element(old)?;
+                    }
+                }
+                Difference::ElementAdded { new } => self.add_element(new, &moved_new)?,
+                // moves are performed after all elements were added, since they may target a new element
+                Difference::ElementMoved { .. } => {}
+            }
+        }
+        for difference in differences {
+            if let Difference::ElementMoved { old, new } = difference {
+                self.move_element(old, new)?;
+            }
+        }
+        for old in late_removes {
+            self.remove_element(&old)?;
+        }
+
+        Ok(())
+    }
+
+    // get the existing element that corresponds to an element of the duplicated model.
+    // Elements that are not part of the duplicated model are already existing elements
+    fn real(&self, old: &Element) -> Element {
+        self.copy_to_real
+            .get(&old.downgrade())
+            .cloned()
+            .unwrap_or_else(|| old.clone())
+    }
+
+    // get the existing element that corresponds to an element of the reloaded data
+    fn real_counterpart(&self, new: &Element) -> Result<Option<Element>, AutosarDataError> {
+        if let Some(old) = self.counterparts.get(&new.downgrade()) {
+            Ok(Some(self.real(old)))
+        } else {
+            // the element was added by the reload
+            Ok(ElementLocator::from_element(new)?.resolve(&self.model))
+        }
+    }
+
+    fn remove_element(&self, old: &Element) -> Result<(), AutosarDataError> {
+        let element = self.real(old);
+        let Some(parent) = element.parent()? else {
+            return Ok(());
+        };
+        if parent.element_type().splittable() != 0 {
+            // the element is deleted if it is not part of any other file
+            element.remove_from_file(&self.file)
+        } else if parent.file_membership()?.1.len() <= 1 {
+            parent.remove_sub_element(element)
+        } else {
+            // the content of elements which are not splittable is shared by all of their files
+            Ok(())
+        }
+    }
+
+    fn add_element(&mut self, new: &Element, moved_new: &[Element]) -> Result<(), AutosarDataError> {
+        let Some(parent) = new
+            .parent()?
+            .map(|parent| self.real_counterpart(&parent))
+            .transpose()?
+            .flatten()
+        else {
+            return Ok(());
+        };
+        let splittable = parent.element_type().splittable() != 0;
+
+        // an element that occupies the same place as the new element may already exist, because it is part of other files
+        if let Some(existing) = parent.find_corresponding_sub_element(new)? {
+            // the element was previously only part of other files. Its content is reconciled with the reloaded data, and
+            // any content that only belongs to the other files is removed from this file
+            if splittable {
+                existing.add_to_file(&self.file)?;
+            }
+            let (differences, counterparts) = compare_with_counterparts(&existing, new);
+            self.counterparts.extend(counterparts);
+            return self.apply(&differences);
+        }
+
+        let position = if parent.element_type().is_ordered() {
+            let max_position = parent.content_item_count();
+            new.position().map(|position| position.min(max_position))
+        } else {
+            None
+        };
+        // elements that were moved into the new element are moved separately
+        let element = ElementData::from_element_excluding(new, moved_new).create_in(&parent, position)?;
+        if splittable && parent.file_membership()?.1.len() > 1 {
+            let mut file_membership = HashSet::new();
+            file_membership.insert(self.file.downgrade());
+            element.set_file_membership(file_membership);
+        }
+        Ok(())
+    }
+
+    fn move_element(&self, old: &Element, new: &Element) -> Result<(), AutosarDataError> {
+        let element = self.real(old);
+        let Some(parent) = new
+            .parent()?
+            .map(|parent| self.real_counterpart(&parent))
+            .transpose()?
+            .flatten()
+        else {
+            return Ok(());
+        };
+        if parent.element_type().is_ordered()
+            && let Some(position) = new.position()
+        {
+            parent.move_element_here_at(&element, position.min(parent.content_item_count()))?;
+        } else if element.parent()?.as_ref() != Some(&parent) {
+            parent.move_element_here(&element)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use tempfile::tempdir;
+
+    const HEADER: &str = r#"<?xml version="1.0" encoding="utf-8"?>
+<AUTOSAR xsi:schemaLocation="http://autosar.org/schema/r4.0 AUTOSAR_00050.xsd" xmlns="http://autosar.org/schema/r4.0" xmlns:xsi="http://www.w3.org/2001/XMLSchema-instance">"#;
+
+    fn arxml(elements: &str) -> String {
+        format!(
+            "{HEADER}<AR-PACKAGES><AR-PACKAGE><SHORT-NAME>Pkg</SHORT-NAME><ELEMENTS>{elements}</ELEMENTS></AR-PACKAGE></AR-PACKAGES></AUTOSAR>"
+        )
+    }
+
+    #[test]
+    fn reload() {
+        let dir = tempdir().unwrap();
+        let filename = dir.path().join("file.arxml");
+        std::fs::write(
+            &filename,
+            arxml(
+                r#"<SYSTEM-SIGNAL><SHORT-NAME>SysSig</SHORT-NAME></SYSTEM-SIGNAL>
+                <SYSTEM-SIGNAL><SHORT-NAME>SysSig2</SHORT-NAME></SYSTEM-SIGNAL>
+                <I-SIGNAL><SHORT-NAME>Sig</SHORT-NAME><SYSTEM-SIGNAL-REF DEST="SYSTEM-SIGNAL">/Pkg/SysSig</SYSTEM-SIGNAL-REF></I-SIGNAL>
+                <SYSTEM><SHORT-NAME>System</SHORT-NAME></SYSTEM>"#,
+            ),
+        )
+        .unwrap();
+        let model = AutosarModel::new();
+        let (file, _) = model.load_file(&filename, true).unwrap();
+        let package = model.get_element_by_path("/Pkg").unwrap();
+        let signal = model.get_element_by_path("/Pkg/Sig").unwrap();
+        let signal_ref = signal.get_sub_element(ElementName::SystemSignalRef).unwrap();
+        let system = model.get_element_by_path("/Pkg/System").unwrap();
+        let sys_signal2 = model.get_element_by_path("/Pkg/SysSig2").unwrap();
+
+        // the file is changed on disk: the reference points to SysSig2, the SYSTEM is removed and a new element is added
+        std::fs::write(
+            &filename,
+            arxml(
+                r#"<SYSTEM-SIGNAL><SHORT-NAME>SysSig</SHORT-NAME></SYSTEM-SIGNAL>
+                <SYSTEM-SIGNAL UUID="1234"><SHORT-NAME>SysSig2</SHORT-NAME></SYSTEM-SIGNAL>
+                <I-SIGNAL><SHORT-NAME>Sig</SHORT-NAME><SYSTEM-SIGNAL-REF DEST="SYSTEM-SIGNAL">/Pkg/SysSig2</SYSTEM-SIGNAL-REF></I-SIGNAL>
+                <CAN-FRAME><SHORT-NAME>Frame</SHORT-NAME></CAN-FRAME>"#,
+            ),
+        )
+        .unwrap();
+        file.0.write().modified = true;
+        let events = std::sync::Arc::new(parking_lot::Mutex::new(Vec::new()));
+        let events_clone = events.clone();
+        model.add_observer(move |event: &ModelEvent| events_clone.lock().push(event.clone()));
+        let warnings = file.reload(true).unwrap();
+        assert!(warnings.is_empty());
+        assert!(!file.is_modified());
+        // observers are told that the model was changed in many places
+        assert_eq!(events.lock().last(), Some(&ModelEvent::ModelReset));
+
+        // unchanged elements keep their identity
+        assert_eq!(model.get_element_by_path("/Pkg").unwrap(), package);
+        assert_eq!(model.get_element_by_path("/Pkg/Sig").unwrap(), signal);
+        assert_eq!(model.get_element_by_path("/Pkg/SysSig2").unwrap(), sys_signal2);
+        assert_eq!(
+            sys_signal2.attribute_value(AttributeName::Uuid),
+            Some(CharacterData::String("1234".to_string()))
+        );
+        // the reference was updated
+        assert_eq!(signal_ref.get_reference_target().unwrap(), sys_signal2);
+        assert_eq!(model.get_references_to("/Pkg/SysSig2"), vec![signal_ref.downgrade()]);
+        // removed and added elements
+        assert!(model.get_element_by_path("/Pkg/System").is_none());
+        assert!(matches!(system.parent(), Err(AutosarDataError::ItemDeleted)));
+        assert!(model.get_element_by_path("/Pkg/Frame").is_some());
+        assert_eq!(file.serialize().unwrap(), {
+            let reloaded = AutosarModel::new();
+            let (reloaded_file, _) = reloaded.load_file(&filename, true).unwrap();
+            reloaded_file.serialize().unwrap()
+        });
+    }
+
+    #[test]
+    fn reload_multiple_files() {
+        let dir = tempdir().unwrap();
+        let filename1 = dir.path().join("file1.arxml");
+        let filename2 = dir.path().join("file2.arxml");
+        std::fs::write(&filename1, arxml("<SYSTEM><SHORT-NAME>System1</SHORT-NAME></SYSTEM>")).unwrap();
+        std::fs::write(&filename2, arxml("<SYSTEM><SHORT-NAME>System2</SHORT-NAME></SYSTEM>")).unwrap();
+        let model = AutosarModel::new();
+        let (file1, _) = model.load_file(&filename1, true).unwrap();
+        let (file2, _) = model.load_file(&filename2, true).unwrap();
+        let package = model.get_element_by_path("/Pkg").unwrap();
+        let system2 = model.get_element_by_path("/Pkg/System2").unwrap();
+
+        // System1 is removed from file1, and System2 is now also part of file1
+        std::fs::write(
+            &filename1,
+            arxml("<SYSTEM><SHORT-NAME>System2</SHORT-NAME></SYSTEM><CAN-FRAME><SHORT-NAME>Frame</SHORT-NAME></CAN-FRAME>"),
+        )
+        .unwrap();
+        file1.reload(true).unwrap();
+
+        assert_eq!(model.get_element_by_path("/Pkg").unwrap(), package);
+        assert!(model.get_element_by_path("/Pkg/System1").is_none());
+        assert_eq!(model.get_element_by_path("/Pkg/System2").unwrap(), system2);
+        let (_, system2_files) = system2.file_membership().unwrap();
+        assert!(system2_files.contains(&file1.downgrade()));
+        assert!(system2_files.contains(&file2.downgrade()));
+        // the new element only belongs to file1
+        let frame = model.get_element_by_path("/Pkg/Frame").unwrap();
+        let (_, frame_files) = frame.file_membership().unwrap();
+        assert_eq!(frame_files.len(), 1);
+        assert!(frame_files.contains(&file1.downgrade()));
+        assert!(!file2.serialize().unwrap().contains("Frame"));
+
+        // System2 is removed from file2, but it remains in the model because it is still part of file1
+        std::fs::write(&filename2, arxml("")).unwrap();
+        file2.reload(true).unwrap();
+        assert_eq!(model.get_element_by_path("/Pkg/System2").unwrap(), system2);
+        let (_, system2_files) = system2.file_membership().unwrap();
+        assert!(!system2_files.contains(&file2.downgrade()));
+        assert!(!file2.serialize().unwrap().contains("System2"));
+    }
+
+    #[test]
+    fn reload_errors() {
+        let dir = tempdir().unwrap();
+        let filename = dir.path().join("file.arxml");
+        std::fs::write(&filename, arxml("<SYSTEM><SHORT-NAME>System</SHORT-NAME></SYSTEM>")).unwrap();
+        let model = AutosarModel::new();
+        let (file, _) = model.load_file(&filename, true).unwrap();
+        let text = file.serialize().unwrap();
+
+        // invalid data does not change the model
+        std::fs::write(&filename, "<AUTOSAR>").unwrap();
+        let result = file.reload(true);
+        assert!(matches!(result, Err(AutosarDataError::ParserError { .. })));
+        assert_eq!(file.serialize().unwrap(), text);
+
+        std::fs::remove_file(&filename).unwrap();
+        let result = file.reload(true);
+        assert!(matches!(result, Err(AutosarDataError::IoErrorRead { .. })));
+
+        // reloading inside of a transaction becomes part of the transaction
+        std::fs::write(&filename, arxml("")).unwrap();
+        let transaction = model.begin_transaction();
+        file.reload(true).unwrap();
+        assert_ne!(file.serialize().unwrap(), text);
+        transaction.rollback();
+        assert_eq!(file.serialize().unwrap(), text);
+
+        // a file that was removed from the model cannot be reloaded
+        model.remove_file(&file);
+        let result = file.reload(true);
+        assert!(matches!(result, Err(AutosarDataError::ItemDeleted)));
+    }
+
+    #[test]
+    fn reload_required_attribute() {
+        let dir = tempdir().unwrap();
+        let filename = dir.path().join("file.arxml");
+        std::fs::write(
+            &filename,
+            arxml(r#"<SYSTEM><SHORT-NAME>System</SHORT-NAME><DESC><L-2 L="EN">text</L-2></DESC></SYSTEM>"#),
+        )
+        .unwrap();
+        let model = AutosarModel::new();
+        let (file, _) = model.load_file(&filename, true).unwrap();
+        let text = file.serialize().unwrap();
+
+        // the L attribute is missing on disk, but it is required and can't be removed from the model
+        std::fs::write(
+            &filename,
+            arxml(
+                r#"<SYSTEM><SHORT-NAME>System</SHORT-NAME><DESC><L-2>text</L-2></DESC></SYSTEM>
+                <CAN-FRAME><SHORT-NAME>Frame</SHORT-NAME></CAN-FRAME>"#,
+            ),
+        )
+        .unwrap();
+        let result = file.reload(false);
+        assert!(matches!(
+            result,
+            Err(AutosarDataError::AttributeRequired {
+                attrname: AttributeName::L
+            })
+        ));
+        // all other changes were rolled back
+        assert_eq!(file.serialize().unwrap(), text);
+        assert!(model.get_element_by_path("/Pkg/Frame").is_none());
+    }
+}
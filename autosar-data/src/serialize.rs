@@ -0,0 +1,299 @@
+//! Formatting options for the serialization of arxml files, and the destinations of the serialized text
+use std::io::Write;
+
+/// Options that control the formatting of serialized arxml files
+///
+/// The default options produce the same output as [`ArxmlFile::serialize`](crate::ArxmlFile::serialize):
+/// two spaces per indentation level, `\n` line endings, the declaration `<?xml version="1.0" encoding="utf-8"?>`,
+/// empty elements as `<X/>` and double quotes around attribute values.
+///
+/// # Example
+///
+/// ```
+/// # use autosar_data::*;
+/// # fn main() -> Result<(), AutosarDataError> {
+/// # let model = AutosarModel::new();
+/// # let file = model.create_file("test", AutosarVersion::Autosar_00050)?;
+/// let options = SerializeOptions {
+///     indentation: Indentation::Tabs,
+///     line_ending: LineEnding::CrLf,
+///     xml_declaration: XmlDeclaration::Uppercase,
+///     ..Default::default()
+/// };
+/// let text = file.serialize_with_options(&options)?;
+/// assert!(text.starts_with("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\r\n<AUTOSAR"));
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct SerializeOptions {
+    /// indentation of nested elements
+    pub indentation: Indentation,
+    /// line ending after each line
+    pub line_ending: LineEnding,
+    /// style of the XML declaration at the start of the file
+    pub xml_declaration: XmlDeclaration,
+    /// format of elements without content
+    pub empty_elements: EmptyElementStyle,
+    /// quotes around attribute values, which also applies to the XML declaration
+    pub attribute_quotes: QuoteStyle,
+    /// omit the standalone attribute from the XML declaration, even if the file has a value for `xml_standalone`
+    pub omit_standalone: bool,
+}
+
+/// Indentation of nested elements
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Indentation {
+    /// indent each level by the given number of spaces
+    Spaces(usize),
+    /// indent each level by one tab
+    Tabs,
+}
+
+impl Default for Indentation {
+    fn default() -> Self {
+        Self::Spaces(2)
+    }
+}
+
+/// Line ending after each line
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LineEnding {
+    /// `\n`
+    #[default]
+    Lf,
+    /// `\r\n`
+    CrLf,
+}
+
+/// Style of the XML declaration at the start of the file
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum XmlDeclaration {
+    /// `<?xml version="1.0" encoding="utf-8"?>`
+    #[default]
+    Lowercase,
+    /// `<?xml version="1.0" encoding="UTF-8"?>`
+    Uppercase,
+    /// no XML declaration; the file starts with the `<AUTOSAR>` element
+    Omit,
+}
+
+/// Format of elements without content
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum EmptyElementStyle {
+    /// `<X/>`
+    #[default]
+    SelfClosing,
+    /// `<X></X>`
+    StartEndTags,
+}
+
+/// Quotes around attribute values
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum QuoteStyle {
+    /// `ATTR="value"`
+    #[default]
+    Double,
+    /// `ATTR='value'`
+    Single,
+}
+
+impl SerializeOptions {
+    pub(crate) fn line_ending_str(&self) -> &'static str {
+        match self.line_ending {
+            LineEnding::Lf => "\n",
+            LineEnding::CrLf => "\r\n",
+        }
+    }
+
+    pub(crate) fn quote_char(&self) -> char {
+        match self.attribute_quotes {
+            QuoteStyle::Double => '"',
+            QuoteStyle::Single => '\'',
+        }
+    }
+
+    pub(crate) fn write_newline_indent<O: SerializeOutput>(&self, output: &mut O, indent: usize) {
+        output.push_str(self.line_ending_str());
+        for _ in 0..indent {
+            match self.indentation {
+                Indentation::Spaces(width) => {
+                    for _ in 0..width {
+                        output.push(' ');
+                    }
+                }
+                Indentation::Tabs => output.push('\t'),
+            }
+        }
+    }
+
+    /// write the XML declaration; returns false if the declaration is omitted
+    pub(crate) fn write_xml_declaration<O: SerializeOutput>(&self, output: &mut O, standalone: Option<bool>) -> bool {
+        let q = self.quote_char();
+        let encoding = match self.xml_declaration {
+            XmlDeclaration::Lowercase => "utf-8",
+            XmlDeclaration::Uppercase => "UTF-8",
+            XmlDeclaration::Omit => return false,
+        };
+        output.push_str(&format!("<?xml version={q}1.0{q} encoding={q}{encoding}{q}"));
+        match standalone {
+            Some(true) if !self.omit_standalone => output.push_str(&format!(" standalone={q}yes{q}")),
+            Some(false) if !self.omit_standalone => output.push_str(&format!(" standalone={q}no{q}")),
+            _ => {}
+        }
+        output.push_str("?>");
+        true
+    }
+}
+
+/// Destination of the serialized arxml text
+///
+/// Serialization either builds a `String`, or it streams the text to an [`std::io::Write`] through an `ArxmlWriter`.
+pub(crate) trait SerializeOutput {
+    fn push_str(&mut self, text: &str);
+
+    fn push(&mut self, c: char) {
+        self.push_str(c.encode_utf8(&mut [0; 4]));
+    }
+}
+
+impl SerializeOutput for String {
+    fn push_str(&mut self, text: &str) {
+        String::push_str(self, text);
+    }
+
+    fn push(&mut self, c: char) {
+        String::push(self, c);
+    }
+}
+
+/// Streams serialized text to an `io::Write`
+///
+/// Serialization itself cannot fail, so the first io error is stored and reported by `finish()`.
+pub(crate) struct ArxmlWriter<W: Write> {
+    writer: std::io::BufWriter<W>,
+    error: Option<std::io::Error>,
+}
+
+impl<W: Write> ArxmlWriter<W> {
+    pub(crate) fn new(writer: W) -> Self {
+        Self {
+            writer: std::io::BufWriter::with_capacity(64 * 1024, writer),
+            error: None,
+        }
+    }
+
+    pub(crate) fn finish(mut self) -> std::io::Result<()> {
+        match self.error.take() {
+            Some(error) => Err(error),
+            None => self.writer.flush(),
+        }
+    }
+}
+
+impl<W: Write> SerializeOutput for ArxmlWriter<W> {
+    fn push_str(&mut self, text: &str) {
+        if self.error.is_none()
+            && let Err(error) = self.writer.write_all(text.as_bytes())
+        {
+            self.error = Some(error);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{AutosarModel, AutosarVersion};
+
+    const FILEBUF_STR: &str = r#"<?xml version="1.0" encoding="utf-8" standalone="no"?>
+<AUTOSAR xsi:schemaLocation="http://autosar.org/schema/r4.0 AUTOSAR_00050.xsd" xmlns="http://autosar.org/schema/r4.0" xmlns:xsi="http://www.w3.org/2001/XMLSchema-instance">
+  <AR-PACKAGES>
+    <AR-PACKAGE UUID="1234">
+      <SHORT-NAME>Pkg</SHORT-NAME>
+      <ELEMENTS>
+        <SYSTEM>
+          <SHORT-NAME>System</SHORT-NAME>
+          <ADMIN-DATA/>
+        </SYSTEM>
+      </ELEMENTS>
+    </AR-PACKAGE>
+  </AR-PACKAGES>
+</AUTOSAR>"#;
+    const FILEBUF: &[u8] = FILEBUF_STR.as_bytes();
+
+    #[test]
+    fn default_options() {
+        let model = AutosarModel::new();
+        let (file, _) = model.load_buffer(FILEBUF, "test", true).unwrap();
+        let text = file.serialize_with_options(&SerializeOptions::default()).unwrap();
+        assert_eq!(text, file.serialize().unwrap());
+        assert_eq!(text.as_bytes(), FILEBUF);
+    }
+
+    #[test]
+    fn formatting() {
+        let model = AutosarModel::new();
+        let (file, _) = model.load_buffer(FILEBUF, "test", true).unwrap();
+
+        let options = SerializeOptions {
+            indentation: Indentation::Tabs,
+            line_ending: LineEnding::CrLf,
+            xml_declaration: XmlDeclaration::Uppercase,
+            empty_elements: EmptyElementStyle::StartEndTags,
+            attribute_quotes: QuoteStyle::Single,
+            omit_standalone: false,
+        };
+        let text = file.serialize_with_options(&options).unwrap();
+        let expected = FILEBUF_STR
+            .replace("utf-8", "UTF-8")
+            .replace('"', "'")
+            .replace("  ", "\t")
+            .replace('\n', "\r\n")
+            .replace("<ADMIN-DATA/>", "<ADMIN-DATA></ADMIN-DATA>");
+        assert_eq!(text, expected);
+
+        // indentation with a different number of spaces
+        let options = SerializeOptions {
+            indentation: Indentation::Spaces(4),
+            ..Default::default()
+        };
+        let text = file.serialize_with_options(&options).unwrap();
+        assert!(text.contains("\n            <SHORT-NAME>Pkg</SHORT-NAME>\n"));
+
+        // the streamed output uses the same options
+        let mut buffer = Vec::new();
+        file.write_to_with_options(&mut buffer, &options).unwrap();
+        assert_eq!(buffer, text.into_bytes());
+    }
+
+    #[test]
+    fn xml_declaration() {
+        let model = AutosarModel::new();
+        let (file, _) = model.load_buffer(FILEBUF, "test", true).unwrap();
+
+        let options = SerializeOptions {
+            omit_standalone: true,
+            ..Default::default()
+        };
+        let text = file.serialize_with_options(&options).unwrap();
+        assert!(text.starts_with("<?xml version=\"1.0\" encoding=\"utf-8\"?>\n<AUTOSAR "));
+
+        file.0.write().xml_standalone = Some(true);
+        let text = file.serialize().unwrap();
+        assert!(text.starts_with("<?xml version=\"1.0\" encoding=\"utf-8\" standalone=\"yes\"?>\n"));
+
+        // without a declaration, the file starts directly with the AUTOSAR element
+        let options = SerializeOptions {
+            xml_declaration: XmlDeclaration::Omit,
+            ..Default::default()
+        };
+        let text = file.serialize_with_options(&options).unwrap();
+        assert!(text.starts_with("<AUTOSAR "));
+
+        // a new file has no standalone value
+        let file2 = model.create_file("test2", AutosarVersion::Autosar_00050).unwrap();
+        let text = file2.serialize_with_options(&SerializeOptions::default()).unwrap();
+        assert!(text.starts_with("<?xml version=\"1.0\" encoding=\"utf-8\"?>\n"));
+    }
+}
@@ -0,0 +1,1238 @@
+use crate::*;
+use parking_lot::{Mutex, RwLock};
+use std::ops::Deref;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// A `Transaction` groups a sequence of modifications of an [`AutosarModel`]
+///
+/// A transaction is started with [`AutosarModel::begin_transaction`]. While the transaction is active,
+/// the model is modified through the usual methods of [`Element`], [`ArxmlFile`] and [`AutosarModel`].
+/// Each modified item is recorded the first time it changes, so the cost of a transaction depends on the
+/// size of the changes, not on the size of the model.
+///
+/// When the transaction is committed, all changes that were made since it began are recorded as one step in the
+/// undo history of the model. These steps can be reverted with [`AutosarModel::undo`] and re-applied with [`AutosarModel::redo`].
+///
+/// If the transaction is rolled back, or if it is dropped without calling `commit()`, then all changes are reverted.
+///
+/// Transactions can be nested: a transaction that is started while another one is active works like a savepoint.
+/// Committing it makes its changes part of the enclosing transaction, and rolling it back only reverts the changes
+/// made since it began.
+///
+/// # Example
+///
+/// ```
+/// # use autosar_data::*;
+/// # fn main() -> Result<(), AutosarDataError> {
+/// let model = AutosarModel::new();
+/// model.create_file("filename.arxml", AutosarVersion::Autosar_00050)?;
+/// let transaction = model.begin_transaction();
+/// let packages = model.root_element().create_sub_element(ElementName::ArPackages)?;
+/// packages.create_named_sub_element(ElementName::ArPackage, "Pkg")?;
+/// transaction.commit();
+/// assert!(model.get_element_by_path("/Pkg").is_some());
+///
+/// model.undo()?;
+/// assert!(model.get_element_by_path("/Pkg").is_none());
+/// model.redo()?;
+/// assert!(model.get_element_by_path("/Pkg").is_some());
+/// # Ok(())
+/// # }
+/// ```
+pub struct Transaction {
+    model: AutosarModel,
+    // identifies the level of the transaction in the history of the model; None after commit or rollback
+    level_id: Option<u64>,
+}
+
+/// The undo and redo history of a model, and the state of the active transactions
+#[derive(Default)]
+pub(crate) struct ModelHistory {
+    undo_stack: Vec<ChangeSet>,
+    redo_stack: Vec<ChangeSet>,
+    // one entry per active transaction; the innermost transaction is the last one
+    levels: Vec<HistoryLevel>,
+    next_level_id: u64,
+}
+
+// the data of an active transaction that is stored in the model
+struct HistoryLevel {
+    id: u64,
+    // the root element and the list of files, if they were changed during the transaction
+    file_list: Option<FileList>,
+}
+
+// The models with active transactions
+//
+// A modified element is recorded in the journal of its model, which it finds by following its parents. If one of its
+// parents is locked, then the model is the one whose journal contains this parent.
+static ACTIVE_MODELS: RwLock<Vec<WeakAutosarModel>> = RwLock::new(Vec::new());
+// the number of entries in ACTIVE_MODELS, which allows modifications to skip the recording if no transaction is active
+static ACTIVE_COUNT: AtomicUsize = AtomicUsize::new(0);
+
+/// The states of the elements and files of a model before they were first modified in each of its active transactions
+#[derive(Default)]
+pub(crate) struct Journal {
+    // one log per active transaction of the model
+    levels: Mutex<Vec<ObjectLog>>,
+}
+
+#[derive(Default)]
+struct ObjectLog {
+    elements: FxHashMap<Element, ElementState>,
+    files: FxHashMap<ArxmlFile, FileState>,
+}
+
+// the data of an ElementRaw; the element name only changes during a version migration
+#[derive(Clone)]
+struct ElementState {
+    parent: ElementOrModel,
+    elemname: ElementName,
+    elemtype: ElementType,
+    content: SmallVec<[ElementContent; 4]>,
+    attributes: SmallVec<[Attribute; 1]>,
+    file_membership: HashSet<WeakArxmlFile>,
+    source_location: Option<SourceLocation>,
+    modified: bool,
+}
+
+// the data of an ArxmlFileRaw, excluding the model
+#[derive(Clone, PartialEq)]
+struct FileState {
+    version: AutosarVersion,
+    filename: PathBuf,
+    xml_standalone: Option<bool>,
+}
+
+// the root element, the list of files and the root comment of the model
+#[derive(PartialEq)]
+struct FileList {
+    root_element: Element,
+    files: Vec<ArxmlFile>,
+    root_comment: Option<String>,
+}
+
+impl FileList {
+    fn from_model(model: &AutosarModelRaw) -> Self {
+        Self {
+            root_element: model.root_element.clone(),
+            files: model.files.clone(),
+            root_comment: model.root_comment.clone(),
+        }
+    }
+}
+
+// a single change in one of the index maps of the model
+struct IndexChange<V> {
+    key: String,
+    before: Option<V>,
+    after: Option<V>,
+}
+
+// All changes made in one transaction
+//
+// Each changed item is stored with its state before and after the transaction, so that the changes can be reverted and re-applied.
+struct ChangeSet {
+    elements: Vec<(Element, ElementState, ElementState)>,
+    files: Vec<(ArxmlFile, FileState, FileState)>,
+    file_list: Option<(FileList, FileList)>,
+    identifiables: Vec<IndexChange<WeakElement>>,
+    reference_origins: Vec<IndexChange<Vec<WeakElement>>>,
+    relative_reference_origins: Vec<IndexChange<Vec<(WeakElement, String)>>>,
+    reference_bases: Vec<IndexChange<Vec<ReferenceBaseInfo>>>,
+}
+
+/// common access to the `FxIndexMap` and `FxHashMap` types used as indexes in the model
+pub(crate) trait IndexTable: Default {
+    type Value: Clone + PartialEq;
+
+    fn get_value(&self, key: &str) -> Option<&Self::Value>;
+    fn get_value_mut(&mut self, key: &str) -> Option<&mut Self::Value>;
+    fn set_value(&mut self, key: &str, value: Option<Self::Value>) -> Option<Self::Value>;
+    fn keys_iter(&self) -> impl Iterator<Item = &String>;
+    fn clear_all(&mut self);
+    fn reserve_additional(&mut self, additional: usize);
+}
+
+/// An index of the model, which records the previous value of each entry that is modified during a transaction
+///
+/// Read access works through `Deref`; all modifications go through the methods of `TrackedIndex`.
+pub(crate) struct TrackedIndex<T: IndexTable> {
+    table: T,
+    // one log per active transaction, containing the values of the modified entries before the first modification
+    logs: Vec<FxHashMap<String, Option<T::Value>>>,
+}
+
+impl Transaction {
+    pub(crate) fn new(model: &AutosarModel) -> Self {
+        let mut model_locked = model.0.write();
+        let id = model_locked.history.next_level_id;
+        model_locked.history.next_level_id += 1;
+        model_locked.history.levels.push(HistoryLevel { id, file_list: None });
+        model_locked.identifiables.logs.push(FxHashMap::default());
+        model_locked.reference_origins.logs.push(FxHashMap::default());
+        model_locked.relative_reference_origins.logs.push(FxHashMap::default());
+        model_locked.reference_bases.logs.push(FxHashMap::default());
+
+        drop(model_locked);
+
+        let first_level = {
+            let mut levels = model.0.journal().levels.lock();
+            levels.push(ObjectLog::default());
+            levels.len() == 1
+        };
+        if first_level {
+            ACTIVE_MODELS.write().push(model.downgrade());
+            ACTIVE_COUNT.fetch_add(1, Ordering::AcqRel);
+        }
+
+        Self {
+            model: model.clone(),
+            level_id: Some(id),
+        }
+    }
+
+    /// Commit the transaction
+    ///
+    /// All changes made since the transaction began become one step in the undo history of the model.
+    /// If nothing was changed, then the undo history is not modified.
+    ///
+    /// If the transaction is nested inside another transaction, then its changes become part of the enclosing transaction instead.
+    /// Transactions that were started inside this transaction and are still active are committed as well.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use autosar_data::*;
+    /// # fn main() -> Result<(), AutosarDataError> {
+    /// # let model = AutosarModel::new();
+    /// # model.create_file("filename.arxml", AutosarVersion::Autosar_00050)?;
+    /// let transaction = model.begin_transaction();
+    /// model.root_element().create_sub_element(ElementName::ArPackages)?;
+    /// transaction.commit();
+    /// assert!(model.can_undo());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn commit(mut self) {
+        let Some(level) = self.finish_nested() else {
+            return;
+        };
+        if level > 0 {
+            merge_level(&self.model);
+            return;
+        }
+
+        let changes = take_level(&self.model);
+        if !changes.is_empty() {
+            let mut model_locked = self.model.0.write();
+            model_locked.history.undo_stack.push(changes);
+            model_locked.history.redo_stack.clear();
+        }
+    }
+
+    /// Roll back the transaction
+    ///
+    /// All changes made since the transaction began are reverted, including the changes to the
+    /// internal indexes of Autosar paths and references. Elements that were created during the
+    /// transaction are detached from the model and behave like deleted elements.
+    ///
+    /// Dropping a `Transaction` without calling `commit()` has the same effect.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use autosar_data::*;
+    /// # fn main() -> Result<(), AutosarDataError> {
+    /// # let model = AutosarModel::new();
+    /// # model.create_file("filename.arxml", AutosarVersion::Autosar_00050)?;
+    /// let transaction = model.begin_transaction();
+    /// model.root_element().create_sub_element(ElementName::ArPackages)?;
+    /// transaction.rollback();
+    /// assert!(model.root_element().get_sub_element(ElementName::ArPackages).is_none());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn rollback(mut self) {
+        self.rollback_internal();
+    }
+
+    fn rollback_internal(&mut self) {
+        if self.finish_nested().is_some() {
+            let changes = take_level(&self.model);
+            changes.apply(&self.model, false);
+        }
+    }
+
+    // mark the transaction as finished, and merge the levels of all transactions nested inside it into its level
+    //
+    // Returns the nesting level of the transaction, or None if it was already finished, either directly or by
+    // finishing an enclosing transaction.
+    fn finish_nested(&mut self) -> Option<usize> {
+        let id = self.level_id.take()?;
+        let (level, depth) = {
+            let model_locked = self.model.0.read();
+            let levels = &model_locked.history.levels;
+            (levels.iter().position(|level| level.id == id)?, levels.len())
+        };
+        for _ in level + 1..depth {
+            merge_level(&self.model);
+        }
+        Some(level)
+    }
+}
+
+impl Drop for Transaction {
+    fn drop(&mut self) {
+        self.rollback_internal();
+    }
+}
+
+impl std::fmt::Debug for Transaction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Transaction")
+            .field("active", &self.level_id.is_some())
+            .finish()
+    }
+}
+
+impl ModelHistory {
+    pub(crate) fn can_undo(&self) -> bool {
+        !self.undo_stack.is_empty()
+    }
+
+    pub(crate) fn can_redo(&self) -> bool {
+        !self.redo_stack.is_empty()
+    }
+
+    pub(crate) fn clear(&mut self) {
+        self.undo_stack.clear();
+        self.redo_stack.clear();
+    }
+
+    pub(crate) fn transaction_active(&self) -> bool {
+        !self.levels.is_empty()
+    }
+}
+
+impl AutosarModelRaw {
+    // record the root element, the list of files and the root comment before they are modified in a transaction
+    pub(crate) fn record_file_list(&mut self) {
+        if self
+            .history
+            .levels
+            .last()
+            .is_some_and(|level| level.file_list.is_none())
+        {
+            let file_list = FileList::from_model(self);
+            if let Some(level) = self.history.levels.last_mut() {
+                level.file_list = Some(file_list);
+            }
+        }
+    }
+}
+
+impl Journal {
+    fn record_element(&self, element: &Element, element_raw: &ElementRaw) {
+        if let Some(log) = self.levels.lock().last_mut() {
+            log.elements
+                .entry(element.clone())
+                .or_insert_with(|| ElementState::from_raw(element_raw));
+        }
+    }
+
+    fn record_file(&self, file: &ArxmlFile, file_raw: &ArxmlFileRaw) {
+        if let Some(log) = self.levels.lock().last_mut() {
+            log.files
+                .entry(file.clone())
+                .or_insert_with(|| FileState::from_raw(file_raw));
+        }
+    }
+
+    fn contains(&self, element: &Element) -> bool {
+        self.levels.lock().iter().any(|log| log.elements.contains_key(element))
+    }
+}
+
+// record the state of an element before it is modified, if a transaction of its model is active
+pub(crate) fn record_element(element: &Element, element_raw: &ElementRaw) {
+    if ACTIVE_COUNT.load(Ordering::Acquire) == 0 {
+        return;
+    }
+    match find_model(element_raw) {
+        Ok(Some(model)) => model.0.journal().record_element(element, element_raw),
+        // a detached element is not part of any model
+        Ok(None) => {}
+        Err(locked_parent) => {
+            // The parent is locked by the current thread in order to modify its content, and it was recorded when it
+            // was locked. If no journal contains it, then its model has no active transaction
+            let active_models: Vec<AutosarModel> = ACTIVE_MODELS
+                .read()
+                .iter()
+                .filter_map(WeakAutosarModel::upgrade)
+                .collect();
+            if let Some(model) = active_models
+                .iter()
+                .find(|model| model.0.journal().contains(&locked_parent))
+            {
+                model.0.journal().record_element(element, element_raw);
+            }
+        }
+    }
+}
+
+// find the model of an element by following its parents
+//
+// The parents are only locked if they are available, since the caller may already hold a lock on one of them.
+// In this case the locked parent is returned as the error value.
+fn find_model(element_raw: &ElementRaw) -> Result<Option<AutosarModel>, Element> {
+    let mut parent = element_raw.parent.clone();
+    loop {
+        match parent {
+            ElementOrModel::Element(weak_parent) => {
+                let Some(parent_element) = weak_parent.upgrade() else {
+                    return Ok(None);
+                };
+                let Some(parent_locked) = parent_element.0.try_read_recursive() else {
+                    return Err(parent_element);
+                };
+                parent = parent_locked.parent.clone();
+            }
+            ElementOrModel::Model(weak_model) => return Ok(weak_model.upgrade()),
+            ElementOrModel::None => return Ok(None),
+        }
+    }
+}
+
+// record the state of a file before it is modified, if a transaction of its model is active
+pub(crate) fn record_file(file: &ArxmlFile, file_raw: &ArxmlFileRaw) {
+    if ACTIVE_COUNT.load(Ordering::Acquire) == 0 {
+        return;
+    }
+    if let Some(model) = file_raw.model.upgrade() {
+        model.0.journal().record_file(file, file_raw);
+    }
+}
+
+// merge the records of the innermost transaction into the enclosing transaction
+//
+// The enclosing transaction keeps its own records, since they contain older states.
+fn merge_level(model: &AutosarModel) {
+    let mut model_locked = model.0.write();
+    if let Some(level) = model_locked.history.levels.pop()
+        && let Some(outer) = model_locked.history.levels.last_mut()
+        && outer.file_list.is_none()
+    {
+        outer.file_list = level.file_list;
+    }
+    model_locked.identifiables.merge_log();
+    model_locked.reference_origins.merge_log();
+    model_locked.relative_reference_origins.merge_log();
+    model_locked.reference_bases.merge_log();
+
+    drop(model_locked);
+
+    let mut levels = model.0.journal().levels.lock();
+    if let Some(log) = levels.pop()
+        && let Some(outer) = levels.last_mut()
+    {
+        for (element, state) in log.elements {
+            outer.elements.entry(element).or_insert(state);
+        }
+        for (file, state) in log.files {
+            outer.files.entry(file).or_insert(state);
+        }
+    }
+}
+
+// remove the records of the innermost transaction, and calculate the changes made since it began
+fn take_level(model: &AutosarModel) -> ChangeSet {
+    let (file_list, identifiables, reference_origins, relative_reference_origins, reference_bases) = {
+        let mut model_locked = model.0.write();
+        let file_list = model_locked.history.levels.pop().and_then(|level| level.file_list);
+        (
+            file_list,
+            model_locked.identifiables.take_changes(),
+            model_locked.reference_origins.take_changes(),
+            model_locked.relative_reference_origins.take_changes(),
+            model_locked.reference_bases.take_changes(),
+        )
+    };
+
+    let (log, last_level) = {
+        let mut levels = model.0.journal().levels.lock();
+        (levels.pop().unwrap_or_default(), levels.is_empty())
+    };
+    if last_level {
+        let mut active_models = ACTIVE_MODELS.write();
+        if let Some(position) = active_models.iter().position(|active| *active == model.downgrade()) {
+            active_models.swap_remove(position);
+            ACTIVE_COUNT.fetch_sub(1, Ordering::AcqRel);
+        }
+    }
+
+    let current_files = FileList::from_model(&model.0.read());
+    let file_list = file_list.filter(|before| *before != current_files);
+
+    let mut membership = Membership {
+        log: &log.elements,
+        start_root: file_list.as_ref().map_or_else(
+            || current_files.root_element.clone(),
+            |before| before.root_element.clone(),
+        ),
+        current_root: current_files.root_element.clone(),
+        in_model_at_start: FxHashMap::default(),
+        in_model_now: FxHashMap::default(),
+    };
+
+    ChangeSet {
+        elements: membership.element_changes(),
+        files: log
+            .files
+            .iter()
+            .filter(|(file, _)| file.0.read().model == model.downgrade())
+            .filter_map(|(file, before)| {
+                let after = FileState::from_raw(&file.0.read());
+                (*before != after).then(|| (file.clone(), before.clone(), after))
+            })
+            .collect(),
+        file_list: file_list.map(|before| (before, current_files)),
+        identifiables,
+        reference_origins,
+        relative_reference_origins,
+        reference_bases,
+    }
+}
+
+// Sorts out which of the recorded elements belong to the model
+//
+// An element belongs to the model if all of its parents up to the root element contain it. At the start of the
+// transaction, the recorded state is used for the recorded elements, and all other elements are unchanged.
+struct Membership<'a> {
+    log: &'a FxHashMap<Element, ElementState>,
+    start_root: Element,
+    current_root: Element,
+    in_model_at_start: FxHashMap<Element, bool>,
+    in_model_now: FxHashMap<Element, bool>,
+}
+
+#[allow(clippy::mutable_key_type)] // Element uses the pointer for hashing, so the key does not change
+impl Membership<'_> {
+    // collect the changed elements of the model, including new elements that were never modified after their creation
+    fn element_changes(&mut self) -> Vec<(Element, ElementState, ElementState)> {
+        let mut changes = Vec::new();
+        let mut new_elements = Vec::new();
+        for (element, before) in self.log {
+            let at_start = self.in_model(element, true);
+            if at_start {
+                let after = ElementState::from_raw(&element.0.read());
+                // sub elements that were not modified after they were created are not recorded, but they are new if
+                // they were not part of the content before
+                new_elements.extend(after.sub_elements().filter(|sub_element| {
+                    !self.log.contains_key(sub_element) && !before.sub_elements().any(|item| item == *sub_element)
+                }));
+                if !before.same_as(&after) {
+                    changes.push((element.clone(), before.clone(), after));
+                }
+            } else if self.in_model(element, false) {
+                new_elements.push(element.clone());
+            }
+        }
+
+        // new elements are detached before the transaction; the same applies to all their unrecorded sub elements
+        let mut visited = HashSet::new();
+        while let Some(element) = new_elements.pop() {
+            if !visited.insert(element.clone()) {
+                continue;
+            }
+            let after = ElementState::from_raw(&element.0.read());
+            new_elements.extend(
+                after
+                    .sub_elements()
+                    .filter(|sub_element| !self.log.contains_key(sub_element)),
+            );
+            let mut before = after.clone();
+            before.parent = ElementOrModel::None;
+            changes.push((element, before, after));
+        }
+        changes
+    }
+
+    // check if the element was part of the model at the start of the transaction (at_start = true), or if it is now
+    fn in_model(&mut self, element: &Element, at_start: bool) -> bool {
+        let cache = if at_start {
+            &self.in_model_at_start
+        } else {
+            &self.in_model_now
+        };
+        if let Some(result) = cache.get(element) {
+            return *result;
+        }
+
+        let parent = match self.log.get(element) {
+            Some(state) if at_start => state.parent.clone(),
+            _ => element.0.read().parent.clone(),
+        };
+        let result = match parent {
+            ElementOrModel::Model(_) => {
+                *element
+                    == if at_start {
+                        self.start_root.clone()
+                    } else {
+                        self.current_root.clone()
+                    }
+            }
+            ElementOrModel::Element(weak_parent) => weak_parent.upgrade().is_some_and(|parent| {
+                let contained = match self.log.get(&parent) {
+                    Some(state) if at_start => state.sub_elements().any(|item| item == *element),
+                    _ => parent.sub_elements().any(|item| item == *element),
+                };
+                contained && self.in_model(&parent, at_start)
+            }),
+            ElementOrModel::None => false,
+        };
+
+        let cache = if at_start {
+            &mut self.in_model_at_start
+        } else {
+            &mut self.in_model_now
+        };
+        cache.insert(element.clone(), result);
+        result
+    }
+}
+
+// revert the most recent step in the undo history (undo = true), or re-apply the most recently reverted step (undo = false)
+pub(crate) fn step_history(model: &AutosarModel, undo: bool) -> Result<bool, AutosarDataError> {
+    let changes = {
+        let mut model_locked = model.0.write();
+        if model_locked.history.transaction_active() {
+            return Err(AutosarDataError::TransactionInProgress);
+        }
+        let stack = if undo {
+            &mut model_locked.history.undo_stack
+        } else {
+            &mut model_locked.history.redo_stack
+        };
+        let Some(changes) = stack.pop() else {
+            return Ok(false);
+        };
+        changes
+    };
+
+    // the changes can only be applied if the model has not been modified outside of a transaction in the meantime
+    if !changes.matches(model, undo) {
+        model.0.write().history.clear();
+        return Err(AutosarDataError::HistoryConflict);
+    }
+    changes.apply(model, !undo);
+
+    let mut model_locked = model.0.write();
+    if undo {
+        model_locked.history.redo_stack.push(changes);
+    } else {
+        model_locked.history.undo_stack.push(changes);
+    }
+    Ok(true)
+}
+
+impl ElementState {
+    fn from_raw(element: &ElementRaw) -> Self {
+        Self {
+            parent: element.parent.clone(),
+            elemname: element.elemname,
+            elemtype: element.elemtype,
+            content: element.content.clone(),
+            attributes: element.attributes.clone(),
+            file_membership: element.file_membership.clone(),
+            source_location: element.source_location.clone(),
+            modified: element.modified,
+        }
+    }
+
+    fn sub_elements(&self) -> impl Iterator<Item = Element> + '_ {
+        self.content.iter().filter_map(ElementContent::unwrap_element)
+    }
+
+    fn same_as(&self, other: &Self) -> bool {
+        let same_parent = match (&self.parent, &other.parent) {
+            (ElementOrModel::Element(parent_a), ElementOrModel::Element(parent_b)) => parent_a == parent_b,
+            (ElementOrModel::Model(model_a), ElementOrModel::Model(model_b)) => model_a == model_b,
+            (ElementOrModel::None, ElementOrModel::None) => true,
+            _ => false,
+        };
+        same_parent
+            && self.elemname == other.elemname
+            && self.elemtype == other.elemtype
+            && self.content == other.content
+            && self.attributes == other.attributes
+            && self.file_membership == other.file_membership
+            && self.source_location == other.source_location
+            && self.modified == other.modified
+    }
+
+    // restore the state; this is not recorded by the active transactions
+    fn restore(&self, element: &Element) {
+        let mut element_locked = element.0.write();
+        element_locked.parent = self.parent.clone();
+        element_locked.elemname = self.elemname;
+        element_locked.elemtype = self.elemtype;
+        element_locked.content = self.content.clone();
+        element_locked.attributes = self.attributes.clone();
+        element_locked.file_membership = self.file_membership.clone();
+        element_locked.source_location = self.source_location.clone();
+        element_locked.modified = self.modified;
+    }
+}
+
+impl FileState {
+    fn from_raw(file: &ArxmlFileRaw) -> Self {
+        Self {
+            version: file.version,
+            filename: file.filename.clone(),
+            xml_standalone: file.xml_standalone,
+        }
+    }
+
+    // restore the state; this is not recorded by the active transactions
+    fn restore(&self, file: &ArxmlFile) {
+        let mut file_locked = file.0.write();
+        file_locked.version = self.version;
+        file_locked.filename = self.filename.clone();
+        file_locked.xml_standalone = self.xml_standalone;
+    }
+}
+
+impl ChangeSet {
+    fn is_empty(&self) -> bool {
+        self.elements.is_empty()
+            && self.files.is_empty()
+            && self.file_list.is_none()
+            && self.identifiables.is_empty()
+            && self.reference_origins.is_empty()
+            && self.relative_reference_origins.is_empty()
+            && self.reference_bases.is_empty()
+    }
+
+    // check if the model is currently in the state after the changes (forward = true) or before them (forward = false)
+    fn matches(&self, model: &AutosarModel, forward: bool) -> bool {
+        let elements_match = self.elements.iter().all(|(element, before, after)| {
+            let expected = if forward { after } else { before };
+            expected.same_as(&ElementState::from_raw(&element.0.read()))
+        });
+        let files_match = self.files.iter().all(|(file, before, after)| {
+            let expected = if forward { after } else { before };
+            *expected == FileState::from_raw(&file.0.read())
+        });
+
+        let model_locked = model.0.read();
+        let file_list_match = self.file_list.as_ref().is_none_or(|(before, after)| {
+            let expected = if forward { after } else { before };
+            FileList::from_model(&model_locked) == *expected
+        });
+
+        elements_match
+            && files_match
+            && file_list_match
+            && model_locked.identifiables.matches(&self.identifiables, forward)
+            && model_locked.reference_origins.matches(&self.reference_origins, forward)
+            && model_locked
+                .relative_reference_origins
+                .matches(&self.relative_reference_origins, forward)
+            && model_locked.reference_bases.matches(&self.reference_bases, forward)
+    }
+
+    // apply the changes (forward = true), or revert them (forward = false)
+    fn apply(&self, model: &AutosarModel, forward: bool) {
+        for (element, before, after) in &self.elements {
+            if forward {
+                after.restore(element);
+            } else {
+                before.restore(element);
+            }
+        }
+        for (file, before, after) in &self.files {
+            if forward {
+                after.restore(file);
+            } else {
+                before.restore(file);
+            }
+        }
+        // restoring the state only emits a ModelReset event, so the files are marked as modified here
+        for (element, _, _) in &self.elements {
+            element.mark_files_modified();
+        }
+        for (file, _, _) in &self.files {
+            file.0.write().modified = true;
+        }
+
+        let mut model_locked = model.0.write();
+        if let Some((before, after)) = &self.file_list {
+            let target = if forward { after } else { before };
+            model_locked.root_element = target.root_element.clone();
+            model_locked.root_comment = target.root_comment.clone();
+            model_locked.files = target.files.clone();
+        }
+        model_locked.identifiables.apply(&self.identifiables, forward);
+        model_locked.reference_origins.apply(&self.reference_origins, forward);
+        model_locked
+            .relative_reference_origins
+            .apply(&self.relative_reference_origins, forward);
+        model_locked.reference_bases.apply(&self.reference_bases, forward);
+        drop(model_locked);
+
+        if !self.is_empty() {
+            model.notify_observers(|| ModelEvent::ModelReset);
+        }
+    }
+}
+
+impl<V: Clone + PartialEq> IndexTable for FxIndexMap<String, V> {
+    type Value = V;
+
+    fn get_value(&self, key: &str) -> Option<&V> {
+        self.get(key)
+    }
+
+    fn get_value_mut(&mut self, key: &str) -> Option<&mut V> {
+        self.get_mut(key)
+    }
+
+    fn set_value(&mut self, key: &str, value: Option<V>) -> Option<V> {
+        if let Some(value) = value {
+            self.insert(key.to_owned(), value)
+        } else {
+            self.swap_remove(key)
+        }
+    }
+
+    fn keys_iter(&self) -> impl Iterator<Item = &String> {
+        self.keys()
+    }
+
+    fn clear_all(&mut self) {
+        self.clear();
+    }
+
+    fn reserve_additional(&mut self, additional: usize) {
+        self.reserve(additional);
+    }
+}
+
+impl<V: Clone + PartialEq> IndexTable for FxHashMap<String, V> {
+    type Value = V;
+
+    fn get_value(&self, key: &str) -> Option<&V> {
+        self.get(key)
+    }
+
+    fn get_value_mut(&mut self, key: &str) -> Option<&mut V> {
+        self.get_mut(key)
+    }
+
+    fn set_value(&mut self, key: &str, value: Option<V>) -> Option<V> {
+        if let Some(value) = value {
+            self.insert(key.to_owned(), value)
+        } else {
+            self.remove(key)
+        }
+    }
+
+    fn keys_iter(&self) -> impl Iterator<Item = &String> {
+        self.keys()
+    }
+
+    fn clear_all(&mut self) {
+        self.clear();
+    }
+
+    fn reserve_additional(&mut self, additional: usize) {
+        self.reserve(additional);
+    }
+}
+
+impl<T: IndexTable> TrackedIndex<T> {
+    pub(crate) fn insert(&mut self, key: String, value: T::Value) -> Option<T::Value> {
+        self.record(&key);
+        self.table.set_value(&key, Some(value))
+    }
+
+    pub(crate) fn remove(&mut self, key: &str) -> Option<T::Value> {
+        self.record(key);
+        self.table.set_value(key, None)
+    }
+
+    pub(crate) fn get_mut(&mut self, key: &str) -> Option<&mut T::Value> {
+        self.record(key);
+        self.table.get_value_mut(key)
+    }
+
+    pub(crate) fn get_or_insert_default(&mut self, key: String) -> &mut T::Value
+    where
+        T::Value: Default,
+    {
+        if self.table.get_value(&key).is_none() {
+            self.insert(key.clone(), T::Value::default());
+        }
+        self.get_mut(&key).expect("the entry was inserted")
+    }
+
+    pub(crate) fn clear(&mut self) {
+        if !self.logs.is_empty() {
+            let keys: Vec<String> = self.table.keys_iter().cloned().collect();
+            for key in keys {
+                self.record(&key);
+            }
+        }
+        self.table.clear_all();
+    }
+
+    pub(crate) fn reserve(&mut self, additional: usize) {
+        self.table.reserve_additional(additional);
+    }
+
+    // store the current value of the entry, if this is its first modification in the innermost transaction
+    fn record(&mut self, key: &str) {
+        if let Some(log) = self.logs.last_mut()
+            && !log.contains_key(key)
+        {
+            log.insert(key.to_owned(), self.table.get_value(key).cloned());
+        }
+    }
+
+    fn merge_log(&mut self) {
+        if let Some(log) = self.logs.pop()
+            && let Some(outer) = self.logs.last_mut()
+        {
+            for (key, value) in log {
+                outer.entry(key).or_insert(value);
+            }
+        }
+    }
+
+    // remove the log of the innermost transaction, and return all entries that differ from their previous values
+    fn take_changes(&mut self) -> Vec<IndexChange<T::Value>> {
+        let log = self.logs.pop().unwrap_or_default();
+        log.into_iter()
+            .filter_map(|(key, before)| {
+                let after = self.table.get_value(&key).cloned();
+                (before != after).then_some(IndexChange { key, before, after })
+            })
+            .collect()
+    }
+
+    // check if the index contains the values after the changes (forward = true) or before them (forward = false)
+    fn matches(&self, changes: &[IndexChange<T::Value>], forward: bool) -> bool {
+        changes.iter().all(|change| {
+            let expected = if forward { &change.after } else { &change.before };
+            self.table.get_value(&change.key) == expected.as_ref()
+        })
+    }
+
+    // apply the changes (forward = true), or revert them (forward = false); this is not recorded by the active transactions
+    fn apply(&mut self, changes: &[IndexChange<T::Value>], forward: bool) {
+        for change in changes {
+            let value = if forward { &change.after } else { &change.before };
+            self.table.set_value(&change.key, value.clone());
+        }
+    }
+}
+
+impl<T: IndexTable> Default for TrackedIndex<T> {
+    fn default() -> Self {
+        Self {
+            table: T::default(),
+            logs: Vec::new(),
+        }
+    }
+}
+
+impl<T: IndexTable + std::fmt::Debug> std::fmt::Debug for TrackedIndex<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.table.fmt(f)
+    }
+}
+
+impl<T: IndexTable> Deref for TrackedIndex<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.table
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const FILEBUF: &[u8] = br#"<?xml version="1.0" encoding="utf-8"?>
+<AUTOSAR xsi:schemaLocation="http://autosar.org/schema/r4.0 AUTOSAR_00050.xsd" xmlns="http://autosar.org/schema/r4.0" xmlns:xsi="http://www.w3.org/2001/XMLSchema-instance">
+  <AR-PACKAGES>
+    <AR-PACKAGE><SHORT-NAME>Pkg</SHORT-NAME>
+      <ELEMENTS>
+        <SYSTEM><SHORT-NAME>System</SHORT-NAME>
+          <FIBEX-ELEMENTS>
+            <FIBEX-ELEMENT-REF-CONDITIONAL>
+              <FIBEX-ELEMENT-REF DEST="ECU-INSTANCE">/Pkg/EcuInstance</FIBEX-ELEMENT-REF>
+            </FIBEX-ELEMENT-REF-CONDITIONAL>
+          </FIBEX-ELEMENTS>
+        </SYSTEM>
+        <ECU-INSTANCE><SHORT-NAME>EcuInstance</SHORT-NAME></ECU-INSTANCE>
+      </ELEMENTS>
+    </AR-PACKAGE>
+  </AR-PACKAGES>
+</AUTOSAR>"#;
+
+    // describe the elements of the model and the content of the identifiables index
+    fn model_content(model: &AutosarModel) -> Vec<String> {
+        let mut paths: Vec<String> = model.identifiable_elements().map(|(path, _)| path).collect();
+        paths.sort();
+        model
+            .elements_dfs()
+            .map(|(depth, elem)| format!("{depth} {} {:?}", elem.element_name(), elem.character_data()))
+            .chain(paths)
+            .collect()
+    }
+
+    #[test]
+    fn rollback() {
+        let model = AutosarModel::new();
+        model.load_buffer(FILEBUF, "test.arxml", true).unwrap();
+        let original = model_content(&model);
+        let original_serialized = model.files().next().unwrap().serialize().unwrap();
+
+        let transaction = model.begin_transaction();
+        let ecu = model.get_element_by_path("/Pkg/EcuInstance").unwrap();
+        let system = model.get_element_by_path("/Pkg/System").unwrap();
+        let pkg = model.get_element_by_path("/Pkg").unwrap();
+        ecu.set_item_name("Renamed").unwrap();
+        let elements = pkg.get_sub_element(ElementName::Elements).unwrap();
+        let new_pkg = pkg
+            .parent()
+            .unwrap()
+            .unwrap()
+            .create_named_sub_element(ElementName::ArPackage, "NewPkg")
+            .unwrap();
+        let new_elements = new_pkg.create_sub_element(ElementName::Elements).unwrap();
+        new_elements.move_element_here(&system).unwrap();
+        elements.remove_sub_element(ecu.clone()).unwrap();
+        pkg.set_attribute_string(AttributeName::Uuid, "1234").unwrap();
+        assert!(model.get_element_by_path("/Pkg/Renamed").is_none());
+        assert!(model.get_element_by_path("/NewPkg/System").is_some());
+        transaction.rollback();
+
+        // the model is back in its original state
+        assert_eq!(model_content(&model), original);
+        assert_eq!(model.files().next().unwrap().serialize().unwrap(), original_serialized);
+        assert_eq!(model.get_element_by_path("/Pkg/EcuInstance").unwrap(), ecu);
+        assert_eq!(model.get_element_by_path("/Pkg/System").unwrap(), system);
+        assert_eq!(system.path().unwrap(), "/Pkg/System");
+        assert!(model.get_element_by_path("/NewPkg").is_none());
+        assert!(new_pkg.model().is_err());
+        // the reference_origins index was restored
+        assert_eq!(model.get_references_to("/Pkg/EcuInstance").len(), 1);
+        assert!(model.get_references_to("/Pkg/Renamed").is_empty());
+        assert!(model.check_references().is_empty());
+        // the source location of the modified element is restored
+        assert!(pkg.source_location().is_some());
+        // the rollback is not part of the history
+        assert!(!model.can_undo());
+
+        // dropping a transaction also rolls it back
+        {
+            let _transaction = model.begin_transaction();
+            system.set_item_name("OtherName").unwrap();
+        }
+        assert_eq!(model_content(&model), original);
+    }
+
+    #[test]
+    fn undo_redo() {
+        let model = AutosarModel::new();
+        let transaction = model.begin_transaction();
+        model.load_buffer(FILEBUF, "test.arxml", true).unwrap();
+        transaction.commit();
+        let loaded = model_content(&model);
+
+        let transaction = model.begin_transaction();
+        let ecu = model.get_element_by_path("/Pkg/EcuInstance").unwrap();
+        ecu.set_item_name("Renamed").unwrap();
+        transaction.commit();
+        let renamed = model_content(&model);
+
+        let transaction = model.begin_transaction();
+        let system = model.get_element_by_path("/Pkg/System").unwrap();
+        system.remove_sub_element_kind(ElementName::FibexElements).unwrap();
+        transaction.commit();
+        let removed = model_content(&model);
+
+        // an empty transaction does not create a step in the history
+        let transaction = model.begin_transaction();
+        transaction.commit();
+
+        assert!(model.can_undo());
+        assert!(!model.can_redo());
+        assert!(model.undo().unwrap());
+        assert_eq!(model_content(&model), renamed);
+        assert_eq!(model.get_references_to("/Pkg/Renamed").len(), 1);
+        assert!(model.undo().unwrap());
+        assert_eq!(model_content(&model), loaded);
+        assert_eq!(model.get_references_to("/Pkg/EcuInstance").len(), 1);
+        assert!(model.undo().unwrap());
+        assert_eq!(model.files().count(), 0);
+        assert!(model.get_element_by_path("/Pkg").is_none());
+        assert!(!model.undo().unwrap());
+
+        assert!(model.redo().unwrap());
+        assert_eq!(model_content(&model), loaded);
+        assert_eq!(model.files().count(), 1);
+        assert!(model.redo().unwrap());
+        assert_eq!(model_content(&model), renamed);
+        assert!(model.redo().unwrap());
+        assert_eq!(model_content(&model), removed);
+        assert!(model.get_references_to("/Pkg/Renamed").is_empty());
+        assert!(!model.redo().unwrap());
+
+        // a new transaction clears the redo history
+        model.undo().unwrap();
+        assert!(model.can_redo());
+        let transaction = model.begin_transaction();
+        ecu.set_item_name("Renamed2").unwrap();
+        transaction.commit();
+        assert!(!model.can_redo());
+
+        // undo and redo are not possible while a transaction is active
+        let transaction = model.begin_transaction();
+        assert!(matches!(model.undo(), Err(AutosarDataError::TransactionInProgress)));
+        assert!(matches!(model.redo(), Err(AutosarDataError::TransactionInProgress)));
+        transaction.commit();
+
+        model.clear_history();
+        assert!(!model.can_undo());
+        assert!(!model.can_redo());
+    }
+
+    #[test]
+    fn nested() {
+        let model = AutosarModel::new();
+        model.load_buffer(FILEBUF, "test.arxml", true).unwrap();
+        let original = model_content(&model);
+        let ecu = model.get_element_by_path("/Pkg/EcuInstance").unwrap();
+        let system = model.get_element_by_path("/Pkg/System").unwrap();
+
+        let transaction = model.begin_transaction();
+        ecu.set_item_name("Renamed").unwrap();
+        let renamed = model_content(&model);
+
+        // rolling back a nested transaction only reverts the changes made since it began
+        let inner = model.begin_transaction();
+        system.set_item_name("System2").unwrap();
+        ecu.set_item_name("Renamed2").unwrap();
+        inner.rollback();
+        assert_eq!(model_content(&model), renamed);
+        assert_eq!(model.get_references_to("/Pkg/Renamed").len(), 1);
+
+        // committing a nested transaction adds its changes to the enclosing transaction
+        let inner = model.begin_transaction();
+        let elements = system.parent().unwrap().unwrap();
+        elements
+            .create_named_sub_element(ElementName::EcuInstance, "Ecu2")
+            .unwrap();
+        inner.commit();
+        assert!(!model.can_undo());
+
+        // internal operations like apply_patch join the active transaction
+        let other = model.duplicate().unwrap();
+        other
+            .get_element_by_path("/Pkg/Ecu2")
+            .unwrap()
+            .set_item_name("Ecu3")
+            .unwrap();
+        let patch = diff::create_patch(&model, &other).unwrap();
+        let inner = model.begin_transaction();
+        model.apply_patch(&patch).unwrap();
+        inner.commit();
+        assert!(model.get_element_by_path("/Pkg/Ecu3").is_some());
+        assert!(!model.can_undo());
+        transaction.commit();
+        let modified = model_content(&model);
+
+        // all changes are one step in the history
+        assert!(model.undo().unwrap());
+        assert_eq!(model_content(&model), original);
+        assert!(!model.can_undo());
+        assert!(model.redo().unwrap());
+        assert_eq!(model_content(&model), modified);
+        assert!(model.get_element_by_path("/Pkg/Ecu3").is_some());
+
+        // finishing a transaction also finishes the transactions nested inside it
+        let transaction = model.begin_transaction();
+        let inner = model.begin_transaction();
+        ecu.set_item_name("Renamed3").unwrap();
+        transaction.rollback();
+        assert_eq!(model_content(&model), modified);
+        inner.commit();
+        assert_eq!(model_content(&model), modified);
+        assert!(model.undo().unwrap());
+        assert_eq!(model_content(&model), original);
+    }
+
+    #[test]
+    fn history_conflict() {
+        let model = AutosarModel::new();
+        model.load_buffer(FILEBUF, "test.arxml", true).unwrap();
+        let ecu = model.get_element_by_path("/Pkg/EcuInstance").unwrap();
+
+        let transaction = model.begin_transaction();
+        ecu.set_item_name("Renamed").unwrap();
+        transaction.commit();
+
+        // modify the same element outside of a transaction
+        ecu.set_item_name("Renamed2").unwrap();
+        assert!(matches!(model.undo(), Err(AutosarDataError::HistoryConflict)));
+        // the history is cleared, and the model is unchanged
+        assert!(!model.can_undo());
+        assert_eq!(ecu.path().unwrap(), "/Pkg/Renamed2");
+    }
+
+    #[test]
+    fn separate_models() {
+        let model = AutosarModel::new();
+        model.load_buffer(FILEBUF, "test.arxml", true).unwrap();
+        let other_model = AutosarModel::new();
+        other_model.load_buffer(FILEBUF, "test.arxml", true).unwrap();
+        let other_ecu = other_model.get_element_by_path("/Pkg/EcuInstance").unwrap();
+        let other_system = other_model.get_element_by_path("/Pkg/System").unwrap();
+        let other_short_name = other_system.get_sub_element(ElementName::ShortName).unwrap();
+
+        // modifications of the other model are not recorded by the transaction
+        let transaction = model.begin_transaction();
+        model
+            .get_element_by_path("/Pkg/EcuInstance")
+            .unwrap()
+            .set_item_name("Renamed")
+            .unwrap();
+        other_ecu.set_item_name("OtherRenamed").unwrap();
+        let other_elements = other_system.parent().unwrap().unwrap();
+        other_elements.remove_sub_element(other_system).unwrap();
+        assert!(!model.0.journal().contains(&other_ecu));
+        assert!(!model.0.journal().contains(&other_elements));
+        assert!(!model.0.journal().contains(&other_short_name));
+        // the removed elements of the other model are not kept alive by the journal
+        let weak_short_name = other_short_name.downgrade();
+        drop(other_short_name);
+        assert!(weak_short_name.upgrade().is_none());
+
+        transaction.rollback();
+        assert!(model.get_element_by_path("/Pkg/EcuInstance").is_some());
+        assert_eq!(other_ecu.path().unwrap(), "/Pkg/OtherRenamed");
+        assert!(other_model.get_element_by_path("/Pkg/System").is_none());
+    }
+
+    #[test]
+    fn traits() {
+        let model = AutosarModel::new();
+        let transaction = model.begin_transaction();
+        assert_eq!(format!("{transaction:?}"), "Transaction { active: true }");
+    }
+}
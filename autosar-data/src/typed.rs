@@ -0,0 +1,315 @@
+//! Typed wrappers for the elements of the specification
+//!
+//! This module is only available if the feature `typed` is enabled.
+//! The generated code is large, about 108,000 lines or 7.5 MB, so enabling the feature noticeably increases the compile time.
+//! It contains one wrapper struct for each element that can contain sub elements, e.g. [`CanCluster`] for `CAN-CLUSTER`.
+//! The wrappers are generated from the specification, and they are a thin layer over [`Element`](crate::Element).
+//!
+//! Each wrapper has one accessor for each possible sub element:
+//!
+//!  - Sub elements that can occur at most once are returned as an `Option`, sub elements that can occur multiple times
+//!    are returned as an iterator by an accessor with the suffix `_iter`.
+//!  - Sub elements that contain sub elements of their own are returned as wrappers.
+//!  - Character data is converted to an [`crate::EnumItem`], a `String`, an integer, a float or a bool, depending on its
+//!    type in the specification.
+//!  - For references, the accessor returns the target of the reference.
+//!  - If a sub element is only valid in some Autosar versions, then the accessor only returns it
+//!    if the element belongs to a file with one of these versions.
+//!
+//! The wrappers are named like the variants of [`ElementName`]. Since this includes the names `Element` and `Option`,
+//! the module should not be imported with a glob import.
+//!
+//! The content of the first `<X>-VARIANTS/<X>-CONDITIONAL` is also available directly in the wrapper of `<X>`, so that
+//! `CAN-CLUSTER-VARIANTS/CAN-CLUSTER-CONDITIONAL/BAUDRATE` can be read with [`CanCluster::baudrate`].
+//!
+//! # Example
+//!
+//! ```
+//! # use autosar_data::*;
+//! use autosar_data::typed::CanCluster;
+//! # fn main() -> Result<(), AutosarDataError> {
+//! # let model = AutosarModel::new();
+//! # model.create_file("file", AutosarVersion::LATEST)?;
+//! # let elements = model
+//! #     .root_element()
+//! #     .create_sub_element(ElementName::ArPackages)?
+//! #     .create_named_sub_element(ElementName::ArPackage, "Pkg")?
+//! #     .create_sub_element(ElementName::Elements)?;
+//! # let element = elements.create_named_sub_element(ElementName::CanCluster, "Cluster")?;
+//! # element
+//! #     .create_sub_element(ElementName::CanClusterVariants)?
+//! #     .create_sub_element(ElementName::CanClusterConditional)?
+//! #     .create_sub_element(ElementName::Baudrate)?
+//! #     .set_character_data("500000")?;
+//! let can_cluster = CanCluster::try_from(element)?;
+//! assert_eq!(can_cluster.short_name().as_deref(), Some("Cluster"));
+//! assert_eq!(can_cluster.baudrate(), Some(500000));
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::{AutosarDataError, ElementName};
+
+/// Common functionality of all typed element wrappers
+pub trait TypedElement: TryFrom<crate::Element, Error = AutosarDataError> + Into<crate::Element> {
+    /// The name of the wrapped element
+    const ELEMENT_NAME: ElementName;
+
+    /// Get the wrapped element
+    fn element(&self) -> &crate::Element;
+}
+
+/// define a wrapper struct and its trait implementations
+macro_rules! typed_element {
+    ($name:ident, $xml_name:literal) => {
+        #[doc = concat!("Typed wrapper for the element `", $xml_name, "`")]
+        #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+        pub struct $name(crate::Element);
+
+        impl TypedElement for $name {
+            const ELEMENT_NAME: ElementName = ElementName::$name;
+
+            fn element(&self) -> &crate::Element {
+                &self.0
+            }
+        }
+
+        impl TryFrom<crate::Element> for $name {
+            type Error = AutosarDataError;
+
+            fn try_from(element: crate::Element) -> Result<Self, Self::Error> {
+                if element.element_name() == ElementName::$name {
+                    Ok(Self(element))
+                } else {
+                    Err(AutosarDataError::UnexpectedElement {
+                        expected: ElementName::$name,
+                        element: element.element_name(),
+                    })
+                }
+            }
+        }
+
+        impl From<$name> for crate::Element {
+            fn from(wrapper: $name) -> Self {
+                wrapper.0
+            }
+        }
+    };
+}
+
+// the generated code uses the functions of this module
+include!(concat!(env!("OUT_DIR"), "/typed.rs"));
+
+/// The wrappers shadow `Element` and `Option`, so the helper functions live in a separate module
+mod support {
+    use crate::{CharacterData, Element, ElementName, EnumItem, WeakArxmlFile};
+
+    /// check if the element belongs to at least one file whose version is in the `version_mask`
+    fn version_compatible(element: &Element, version_mask: u32) -> bool {
+        if version_mask == u32::MAX {
+            return true;
+        }
+        element.file_membership().is_ok_and(|(_, files)| {
+            files.is_empty()
+                || files
+                    .iter()
+                    .filter_map(WeakArxmlFile::upgrade)
+                    .any(|file| file.version().compatible(version_mask))
+        })
+    }
+
+    /// get the container of a sub element by following the path
+    fn container(element: &Element, path: &[ElementName], version_mask: u32) -> Option<Element> {
+        if !version_compatible(element, version_mask) {
+            return None;
+        }
+        path.iter()
+            .try_fold(element.clone(), |current, name| current.get_sub_element(*name))
+    }
+
+    pub(super) fn sub_element(
+        element: &Element,
+        path: &[ElementName],
+        name: ElementName,
+        version_mask: u32,
+    ) -> Option<Element> {
+        container(element, path, version_mask)?.get_sub_element(name)
+    }
+
+    pub(super) fn sub_elements(
+        element: &Element,
+        path: &[ElementName],
+        name: ElementName,
+        version_mask: u32,
+    ) -> impl Iterator<Item = Element> + use<> {
+        container(element, path, version_mask)
+            .into_iter()
+            .flat_map(|container| container.sub_elements())
+            .filter(move |sub_element| sub_element.element_name() == name)
+    }
+
+    pub(super) fn reference_target(element: Element) -> Option<Element> {
+        element.get_reference_target().ok()
+    }
+
+    pub(super) fn character_data(element: Element) -> Option<CharacterData> {
+        element.character_data()
+    }
+
+    pub(super) fn enum_value(element: Element) -> Option<EnumItem> {
+        element.character_data()?.enum_value()
+    }
+
+    pub(super) fn string_value(element: Element) -> Option<String> {
+        element.character_data()?.string_value()
+    }
+
+    pub(super) fn integer_value<T: num_traits::Num + TryFrom<u64>>(element: Element) -> Option<T> {
+        element.character_data()?.parse_integer()
+    }
+
+    pub(super) fn float_value(element: Element) -> Option<f64> {
+        element.character_data()?.parse_float()
+    }
+
+    pub(super) fn bool_value(element: Element) -> Option<bool> {
+        element.character_data()?.parse_bool()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{AutosarModel, AutosarVersion, EnumItem};
+
+    #[test]
+    fn typed_access() {
+        let model = AutosarModel::new();
+        model.create_file("file", AutosarVersion::LATEST).unwrap();
+        let elements = model
+            .root_element()
+            .create_sub_element(ElementName::ArPackages)
+            .and_then(|packages| packages.create_named_sub_element(ElementName::ArPackage, "Pkg"))
+            .and_then(|package| package.create_sub_element(ElementName::Elements))
+            .unwrap();
+        let cluster_element = elements
+            .create_named_sub_element(ElementName::CanCluster, "Cluster")
+            .unwrap();
+        let conditional = cluster_element
+            .create_sub_element(ElementName::CanClusterVariants)
+            .and_then(|variants| variants.create_sub_element(ElementName::CanClusterConditional))
+            .unwrap();
+        conditional
+            .create_sub_element(ElementName::Baudrate)
+            .and_then(|baudrate| baudrate.set_character_data("0x100"))
+            .unwrap();
+        let channels = conditional.create_sub_element(ElementName::PhysicalChannels).unwrap();
+        let channel = channels
+            .create_named_sub_element(ElementName::CanPhysicalChannel, "Channel1")
+            .unwrap();
+        channels
+            .create_named_sub_element(ElementName::CanPhysicalChannel, "Channel2")
+            .unwrap();
+        let triggering = channel
+            .create_sub_element(ElementName::FrameTriggerings)
+            .and_then(|triggerings| triggerings.create_named_sub_element(ElementName::CanFrameTriggering, "Ft"))
+            .unwrap();
+        triggering
+            .create_sub_element(ElementName::CanAddressingMode)
+            .and_then(|mode| mode.set_character_data(EnumItem::Extended))
+            .unwrap();
+        triggering
+            .create_sub_element(ElementName::CanFdFrameSupport)
+            .and_then(|support| support.set_character_data("true"))
+            .unwrap();
+        let frame = elements
+            .create_named_sub_element(ElementName::CanFrame, "Frame")
+            .unwrap();
+        triggering
+            .create_sub_element(ElementName::FrameRef)
+            .and_then(|frame_ref| frame_ref.set_reference_target(&frame))
+            .unwrap();
+
+        // wrapping checks the element name
+        let result = CanCluster::try_from(frame.clone());
+        assert!(matches!(
+            result,
+            Err(AutosarDataError::UnexpectedElement {
+                expected: ElementName::CanCluster,
+                element: ElementName::CanFrame
+            })
+        ));
+
+        let cluster = CanCluster::try_from(cluster_element.clone()).unwrap();
+        assert_eq!(CanCluster::ELEMENT_NAME, ElementName::CanCluster);
+        assert_eq!(cluster.element(), &cluster_element);
+        assert_eq!(cluster.short_name().as_deref(), Some("Cluster"));
+        assert_eq!(cluster.baudrate(), Some(0x100));
+        assert_eq!(cluster.speed(), None);
+        // the same value through the VARIANTS and CONDITIONAL wrappers
+        let baudrate = cluster
+            .can_cluster_variants()
+            .and_then(|variants| variants.can_cluster_conditional_iter().next())
+            .and_then(|conditional| conditional.baudrate());
+        assert_eq!(baudrate, Some(0x100));
+
+        let channels: Vec<CanPhysicalChannel> = cluster
+            .physical_channels()
+            .map(|channels| channels.can_physical_channel_iter().collect())
+            .unwrap();
+        assert_eq!(channels.len(), 2);
+        assert_eq!(channels[1].short_name().as_deref(), Some("Channel2"));
+
+        let triggering = channels[0]
+            .frame_triggerings()
+            .and_then(|triggerings| triggerings.can_frame_triggering_iter().next())
+            .unwrap();
+        assert_eq!(triggering.can_addressing_mode(), Some(EnumItem::Extended));
+        assert_eq!(triggering.can_fd_frame_support(), Some(true));
+        assert_eq!(triggering.frame_ref(), Some(frame));
+
+        // conversion back to an element
+        let element: crate::Element = cluster.into();
+        assert_eq!(element, cluster_element);
+    }
+
+    #[test]
+    fn accessor_kinds() {
+        // the return types are derived from the patterns of the character data in the specification
+        let _: fn(&CanFrameTriggering) -> ::core::option::Option<bool> = CanFrameTriggering::can_fd_frame_support;
+        let _: fn(&CanCluster) -> ::core::option::Option<u64> = CanCluster::baudrate;
+        let _: fn(&CanCluster) -> ::core::option::Option<i64> = CanCluster::speed;
+        let _: fn(&CanControllerConfigurationRequirements) -> ::core::option::Option<f64> =
+            CanControllerConfigurationRequirements::max_sample_point;
+        // numbers with symbolic alternatives and other patterns are strings
+        let _: fn(&Ipv4Configuration) -> ::core::option::Option<String> = Ipv4Configuration::ipv_4_address;
+        let _: fn(&EthernetCommunicationControllerConditional) -> ::core::option::Option<String> =
+            EthernetCommunicationControllerConditional::mac_unicast_address;
+    }
+
+    #[test]
+    fn version_mask() {
+        let model = AutosarModel::new();
+        let file = model.create_file("file", AutosarVersion::LATEST).unwrap();
+        let cluster_element = model
+            .root_element()
+            .create_sub_element(ElementName::ArPackages)
+            .and_then(|packages| packages.create_named_sub_element(ElementName::ArPackage, "Pkg"))
+            .and_then(|package| package.create_sub_element(ElementName::Elements))
+            .and_then(|elements| elements.create_named_sub_element(ElementName::CanCluster, "Cluster"))
+            .unwrap();
+        cluster_element
+            .create_sub_element(ElementName::CanClusterVariants)
+            .and_then(|variants| variants.create_sub_element(ElementName::CanClusterConditional))
+            .and_then(|conditional| conditional.create_sub_element(ElementName::CanXlBaudrate))
+            .and_then(|baudrate| baudrate.set_character_data("1000"))
+            .unwrap();
+        let cluster = CanCluster::try_from(cluster_element).unwrap();
+        assert_eq!(cluster.can_xl_baudrate(), Some(1000));
+
+        // CAN-XL-BAUDRATE does not exist in older versions
+        file.0.write().version = AutosarVersion::Autosar_4_3_0;
+        assert_eq!(cluster.can_xl_baudrate(), None);
+        assert_eq!(cluster.short_name().as_deref(), Some("Cluster"));
+    }
+}
@@ -0,0 +1,561 @@
+use crate::*;
+use autosar_data_specification::ElementMultiplicity;
+use fxhash::FxHashSet;
+
+/// A violation of the Autosar specification that was found by [`AutosarModel::validate`] or [`Element::validate`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct ValidationDiagnostic {
+    /// The element where the problem was found
+    pub element: Element,
+    /// The xml path of the element, see [`Element::xml_path`]
+    pub path: String,
+    /// The Autosar version that was used to check the element
+    pub version: AutosarVersion,
+    /// The kind of problem that was found
+    pub kind: ValidationErrorKind,
+}
+
+/// The kinds of problems that can be reported in a [`ValidationDiagnostic`]
+#[derive(Debug, Error, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ValidationErrorKind {
+    /// A required sub element is missing: either the SHORT-NAME of an identifiable element, or a direct sub element of a sequence with multiplicity `One`
+    #[error("The required sub element {sub_element} is missing")]
+    MissingSubElement {
+        /// The missing sub element
+        sub_element: ElementName,
+    },
+
+    /// The sub element may only occur once, but there are several
+    #[error("Only one {sub_element} is allowed, but there are several")]
+    TooManySubElements {
+        /// The repeated sub element
+        sub_element: ElementName,
+    },
+
+    /// The sub element conflicts with other sub elements, because only one of them may be present
+    #[error("The sub element {sub_element} conflicts with another sub element")]
+    ElementChoiceConflict {
+        /// The conflicting sub element
+        sub_element: ElementName,
+    },
+
+    /// The sub element is not allowed in this element
+    #[error("The sub element {sub_element} is not allowed")]
+    InvalidSubElement {
+        /// The sub element that is not allowed
+        sub_element: ElementName,
+    },
+
+    /// The element is not allowed in the version of the file
+    #[error("The element is not allowed in this version")]
+    ElementVersion {
+        /// The version mask of the element which indicates all allowed versions
+        version_mask: u32,
+    },
+
+    /// A required attribute is missing
+    #[error("The required attribute {attribute} is missing")]
+    MissingAttribute {
+        /// The missing attribute
+        attribute: AttributeName,
+    },
+
+    /// The attribute is not allowed in this element
+    #[error("The attribute {attribute} is not allowed")]
+    UnknownAttribute {
+        /// The attribute that is not allowed
+        attribute: AttributeName,
+    },
+
+    /// The attribute is not allowed in the version of the file
+    #[error("The attribute {attribute} is not allowed in this version")]
+    AttributeVersion {
+        /// The attribute that is not allowed
+        attribute: AttributeName,
+        /// The version mask of the attribute which indicates all allowed versions
+        version_mask: u32,
+    },
+
+    /// The value of the attribute does not match its specification
+    #[error("The value \"{value}\" of the attribute {attribute} is not valid")]
+    InvalidAttributeValue {
+        /// The attribute
+        attribute: AttributeName,
+        /// The invalid value
+        value: String,
+    },
+
+    /// The character data of the element does not match its specification
+    #[error("The character data \"{value}\" is not valid")]
+    InvalidCharacterData {
+        /// The invalid value
+        value: String,
+    },
+}
+
+impl std::fmt::Display for ValidationDiagnostic {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} ({}): {}", self.path, self.version, self.kind)
+    }
+}
+
+struct Validator {
+    version: AutosarVersion,
+    files: FxHashSet<WeakArxmlFile>,
+    diagnostics: Vec<ValidationDiagnostic>,
+}
+
+/// validate all elements in each file of the model, using the version of the file
+pub(crate) fn validate_model(model: &AutosarModel) -> Vec<ValidationDiagnostic> {
+    let files: Vec<WeakArxmlFile> = model.files().map(|file| file.downgrade()).collect();
+    validate_files(&model.root_element(), ElementType::ROOT, &files)
+}
+
+/// validate an element and its sub elements, using the versions of the files that contain it
+pub(crate) fn validate_element(element: &Element) -> Result<Vec<ValidationDiagnostic>, AutosarDataError> {
+    let (_, files) = element.file_membership()?;
+    let files: Vec<WeakArxmlFile> = files.into_iter().collect();
+    Ok(validate_files(element, element.element_type(), &files))
+}
+
+// elements that are present in several files with the same version only need to be checked once per version
+fn validate_files(element: &Element, elemtype: ElementType, files: &[WeakArxmlFile]) -> Vec<ValidationDiagnostic> {
+    let mut versions: Vec<AutosarVersion> = files
+        .iter()
+        .filter_map(|file| file.upgrade().map(|file| file.version()))
+        .collect();
+    versions.sort();
+    versions.dedup();
+
+    let mut diagnostics = Vec::new();
+    for version in versions {
+        let mut validator = Validator {
+            version,
+            files: files
+                .iter()
+                .filter(|file| file.upgrade().is_some_and(|file| file.version() == version))
+                .cloned()
+                .collect(),
+            diagnostics: Vec::new(),
+        };
+        // the element type can depend on the version; the spec of the parent determines which one applies
+        let elemtype = element
+            .parent()
+            .ok()
+            .flatten()
+            .and_then(|parent| {
+                parent
+                    .element_type()
+                    .find_sub_element(element.element_name(), version as u32)
+            })
+            .map_or(elemtype, |(etype, _)| etype);
+        validator.validate(element, elemtype);
+        diagnostics.append(&mut validator.diagnostics);
+    }
+    diagnostics
+}
+
+impl Validator {
+    fn report(&mut self, element: &Element, kind: ValidationErrorKind) {
+        self.diagnostics.push(ValidationDiagnostic {
+            element: element.clone(),
+            path: element.xml_path(),
+            version: self.version,
+            kind,
+        });
+    }
+
+    // check if the element is part of one of the files, given that its parent is
+    fn is_member(&self, element: &Element) -> bool {
+        let membership = element.file_membership_local();
+        membership.is_empty() || membership.iter().any(|file| self.files.contains(file))
+    }
+
+    fn validate(&mut self, element: &Element, elemtype: ElementType) {
+        self.validate_attributes(element, elemtype);
+        self.validate_character_data(element, elemtype);
+        self.validate_sub_elements(element, elemtype);
+    }
+
+    fn validate_attributes(&mut self, element: &Element, elemtype: ElementType) {
+        for attribute in element.attributes() {
+            if let Some(AttributeSpec {
+                spec,
+                version: version_mask,
+                ..
+            }) = elemtype.find_attribute_spec(attribute.attrname)
+            {
+                if !self.version.compatible(version_mask) {
+                    self.report(
+                        element,
+                        ValidationErrorKind::AttributeVersion {
+                            attribute: attribute.attrname,
+                            version_mask,
+                        },
+                    );
+                } else if !CharacterData::check_value(&attribute.content, spec, self.version) {
+                    self.report(
+                        element,
+                        ValidationErrorKind::InvalidAttributeValue {
+                            attribute: attribute.attrname,
+                            value: attribute.content.to_string(),
+                        },
+                    );
+                }
+            } else {
+                self.report(
+                    element,
+                    ValidationErrorKind::UnknownAttribute {
+                        attribute: attribute.attrname,
+                    },
+                );
+            }
+        }
+
+        for (attribute, _, required) in elemtype.attribute_spec_iter() {
+            if required && element.attribute_value(attribute).is_none() {
+                self.report(element, ValidationErrorKind::MissingAttribute { attribute });
+            }
+        }
+    }
+
+    fn validate_character_data(&mut self, element: &Element, elemtype: ElementType) {
+        // mixed content is always stored as strings, so only pure character content can be checked
+        if elemtype.content_mode() == ContentMode::Characters
+            && let Some(spec) = elemtype.chardata_spec()
+            && let Some(value) = element.character_data()
+            && !CharacterData::check_value(&value, spec, self.version)
+        {
+            self.report(
+                element,
+                ValidationErrorKind::InvalidCharacterData {
+                    value: value.to_string(),
+                },
+            );
+        }
+    }
+
+    fn validate_sub_elements(&mut self, element: &Element, elemtype: ElementType) {
+        let mut found_names = FxHashSet::default();
+        let mut reported_repeated = FxHashSet::default();
+        let mut reported_conflicts = FxHashSet::default();
+        let mut prev_indices: Option<Vec<usize>> = None;
+
+        let sub_elements: Vec<Element> = element
+            .sub_elements()
+            .filter(|sub_element| self.is_member(sub_element))
+            .collect();
+        for sub_element in sub_elements {
+            let name = sub_element.element_name();
+            let is_repeated = !found_names.insert(name);
+
+            let (sub_elemtype, indices, version_ok) =
+                if let Some((sub_elemtype, indices)) = elemtype.find_sub_element(name, self.version as u32) {
+                    (sub_elemtype, indices, true)
+                } else if let Some((sub_elemtype, indices)) = elemtype.find_sub_element(name, u32::MAX) {
+                    // the element exists in other versions of the standard
+                    let version_mask = elemtype.get_sub_element_version_mask(&indices).unwrap_or(0);
+                    self.report(&sub_element, ValidationErrorKind::ElementVersion { version_mask });
+                    (sub_elemtype, indices, false)
+                } else {
+                    self.report(element, ValidationErrorKind::InvalidSubElement { sub_element: name });
+                    self.validate(&sub_element, sub_element.element_type());
+                    continue;
+                };
+
+            // multiplicity only matters if the mode is Choice or Sequence - modes Mixed and Bag allow arbitrary amounts of all elements
+            let container_mode = elemtype.get_sub_element_container_mode(&indices);
+            if is_repeated
+                && (container_mode == ContentMode::Sequence || container_mode == ContentMode::Choice)
+                && elemtype.get_sub_element_multiplicity(&indices) != Some(ElementMultiplicity::Any)
+                && reported_repeated.insert(name)
+            {
+                self.report(element, ValidationErrorKind::TooManySubElements { sub_element: name });
+            }
+
+            // only one of the alternatives in a choice may be present
+            if let Some(prev_indices) = &prev_indices
+                && *prev_indices != indices
+                && elemtype.find_common_group(prev_indices, &indices).content_mode() == ContentMode::Choice
+                && reported_conflicts.insert(name)
+            {
+                self.report(
+                    element,
+                    ValidationErrorKind::ElementChoiceConflict { sub_element: name },
+                );
+            }
+            prev_indices = Some(indices);
+
+            // the content of an element that is not allowed in this version is not checked, since all of it would be reported
+            if version_ok {
+                self.validate(&sub_element, sub_elemtype);
+            }
+        }
+
+        let mut required: Vec<ElementName> = required_sub_elements(elemtype, self.version)
+            .into_iter()
+            .map(|(name, _)| name)
+            .collect();
+        if elemtype.is_named_in_version(self.version) && !required.contains(&ElementName::ShortName) {
+            required.insert(0, ElementName::ShortName);
+        }
+        for sub_element in required {
+            if !found_names.contains(&sub_element) {
+                self.report(element, ValidationErrorKind::MissingSubElement { sub_element });
+            }
+        }
+    }
+}
+
+// get the sub elements that must be present in an element of the given type and version
+//
+// only the direct sub elements of a sequence are required; elements inside of groups are alternatives
+pub(crate) fn required_sub_elements(elemtype: ElementType, version: AutosarVersion) -> Vec<(ElementName, ElementType)> {
+    if elemtype.content_mode() != ContentMode::Sequence {
+        return Vec::new();
+    }
+    elemtype
+        .sub_element_spec_iter()
+        .filter(|(_, _, version_mask, _)| version.compatible(*version_mask))
+        .filter_map(|(name, ..)| {
+            let (sub_elemtype, indices) = elemtype.find_sub_element(name, version as u32)?;
+            (indices.len() == 1 && elemtype.get_sub_element_multiplicity(&indices) == Some(ElementMultiplicity::One))
+                .then_some((name, sub_elemtype))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const INVALID_FILE: &str = r#"<?xml version="1.0" encoding="utf-8"?>
+    <AUTOSAR xsi:schemaLocation="http://autosar.org/schema/r4.0 AUTOSAR_00050.xsd" xmlns="http://autosar.org/schema/r4.0" xmlns:xsi="http://www.w3.org/2001/XMLSchema-instance">
+    <AR-PACKAGES>
+        <AR-PACKAGE>
+            <SHORT-NAME>Pkg</SHORT-NAME>
+            <ELEMENTS>
+                <SYSTEM>
+                    <SHORT-NAME>System</SHORT-NAME>
+                    <FIBEX-ELEMENTS>
+                        <FIBEX-ELEMENT-REF-CONDITIONAL>
+                            <FIBEX-ELEMENT-REF>/Pkg/Pdu</FIBEX-ELEMENT-REF>
+                        </FIBEX-ELEMENT-REF-CONDITIONAL>
+                    </FIBEX-ELEMENTS>
+                </SYSTEM>
+                <I-SIGNAL-I-PDU>
+                    <SHORT-NAME>Pdu</SHORT-NAME>
+                    <LENGTH>eight</LENGTH>
+                </I-SIGNAL-I-PDU>
+                <DIAGNOSTIC-CONTRIBUTION-SET>
+                    <SHORT-NAME>Dcs</SHORT-NAME>
+                    <COMMON-PROPERTIES>
+                        <DIAGNOSTIC-COMMON-PROPS-VARIANTS>
+                            <DIAGNOSTIC-COMMON-PROPS-CONDITIONAL>
+                                <DEBOUNCE-ALGORITHM-PROPSS>
+                                    <DIAGNOSTIC-DEBOUNCE-ALGORITHM-PROPS>
+                                        <SHORT-NAME>Props</SHORT-NAME>
+                                        <DEBOUNCE-ALGORITHM>
+                                            <DIAG-EVENT-DEBOUNCE-COUNTER-BASED>
+                                                <SHORT-NAME>Counter</SHORT-NAME>
+                                            </DIAG-EVENT-DEBOUNCE-COUNTER-BASED>
+                                            <DIAG-EVENT-DEBOUNCE-TIME-BASED>
+                                                <SHORT-NAME>Time</SHORT-NAME>
+                                            </DIAG-EVENT-DEBOUNCE-TIME-BASED>
+                                        </DEBOUNCE-ALGORITHM>
+                                    </DIAGNOSTIC-DEBOUNCE-ALGORITHM-PROPS>
+                                </DEBOUNCE-ALGORITHM-PROPSS>
+                            </DIAGNOSTIC-COMMON-PROPS-CONDITIONAL>
+                        </DIAGNOSTIC-COMMON-PROPS-VARIANTS>
+                    </COMMON-PROPERTIES>
+                </DIAGNOSTIC-CONTRIBUTION-SET>
+            </ELEMENTS>
+        </AR-PACKAGE>
+        <AR-PACKAGE>
+        </AR-PACKAGE>
+    </AR-PACKAGES>
+    </AUTOSAR>"#;
+
+    const OLD_VERSION_FILE: &str = r#"<?xml version="1.0" encoding="utf-8"?>
+    <AUTOSAR xsi:schemaLocation="http://autosar.org/schema/r4.0 AUTOSAR_4-0-1.xsd" xmlns="http://autosar.org/schema/r4.0" xmlns:xsi="http://www.w3.org/2001/XMLSchema-instance">
+    <AR-PACKAGES>
+        <AR-PACKAGE>
+            <SHORT-NAME>Old</SHORT-NAME>
+            <ELEMENTS>
+                <DIAGNOSTIC-ACCESS-PERMISSION>
+                    <SHORT-NAME>Permission</SHORT-NAME>
+                </DIAGNOSTIC-ACCESS-PERMISSION>
+            </ELEMENTS>
+        </AR-PACKAGE>
+    </AR-PACKAGES>
+    </AUTOSAR>"#;
+
+    #[test]
+    fn valid_model() {
+        let model = AutosarModel::new();
+        model.create_file("test.arxml", AutosarVersion::LATEST).unwrap();
+        let pkg = model
+            .root_element()
+            .create_sub_element(ElementName::ArPackages)
+            .and_then(|packages| packages.create_named_sub_element(ElementName::ArPackage, "Pkg"))
+            .unwrap();
+        let elements = pkg.create_sub_element(ElementName::Elements).unwrap();
+        let pdu = elements
+            .create_named_sub_element(ElementName::ISignalIPdu, "Pdu")
+            .unwrap();
+        pdu.create_sub_element(ElementName::Length)
+            .and_then(|length| length.set_character_data("8"))
+            .unwrap();
+        let system = elements
+            .create_named_sub_element(ElementName::System, "System")
+            .unwrap();
+        system
+            .create_sub_element(ElementName::FibexElements)
+            .and_then(|fibex| fibex.create_sub_element(ElementName::FibexElementRefConditional))
+            .and_then(|conditional| conditional.create_sub_element(ElementName::FibexElementRef))
+            .and_then(|reference| reference.set_reference_target(&pdu))
+            .unwrap();
+
+        assert_eq!(model.validate(), Vec::new());
+        assert_eq!(system.validate().unwrap(), Vec::new());
+    }
+
+    #[test]
+    fn invalid_model() {
+        let model = AutosarModel::new();
+        model.load_buffer(INVALID_FILE.as_bytes(), "test.arxml", false).unwrap();
+        let diagnostics = model.validate();
+        let kinds: Vec<ValidationErrorKind> = diagnostics.iter().map(|diag| diag.kind.clone()).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                ValidationErrorKind::MissingAttribute {
+                    attribute: AttributeName::Dest
+                },
+                ValidationErrorKind::InvalidCharacterData {
+                    value: "eight".to_string()
+                },
+                ValidationErrorKind::ElementChoiceConflict {
+                    sub_element: ElementName::DiagEventDebounceTimeBased
+                },
+                ValidationErrorKind::MissingSubElement {
+                    sub_element: ElementName::ShortName
+                },
+            ]
+        );
+        assert!(
+            diagnostics
+                .iter()
+                .all(|diag| diag.version == AutosarVersion::Autosar_00050)
+        );
+        assert_eq!(
+            diagnostics[1].path,
+            "/<AUTOSAR>/<AR-PACKAGES>/Pkg/<ELEMENTS>/Pdu/<LENGTH>"
+        );
+        assert_eq!(diagnostics[1].element.element_name(), ElementName::Length);
+        assert_eq!(
+            diagnostics[1].to_string(),
+            "/<AUTOSAR>/<AR-PACKAGES>/Pkg/<ELEMENTS>/Pdu/<LENGTH> (AUTOSAR R21-11): The character data \"eight\" is not valid"
+        );
+
+        // validate only a part of the model
+        let pdu = model.get_element_by_path("/Pkg/Pdu").unwrap();
+        assert_eq!(pdu.validate().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn versions() {
+        let model = AutosarModel::new();
+        model
+            .load_buffer(OLD_VERSION_FILE.as_bytes(), "old.arxml", false)
+            .unwrap();
+        let diagnostics = model.validate();
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].version, AutosarVersion::Autosar_4_0_1);
+        assert_eq!(
+            diagnostics[0].element.element_name(),
+            ElementName::DiagnosticAccessPermission
+        );
+        assert!(matches!(
+            diagnostics[0].kind,
+            ValidationErrorKind::ElementVersion { .. }
+        ));
+
+        // the same content is valid in a file with a newer version
+        let new_file = model.create_file("new.arxml", AutosarVersion::Autosar_00050).unwrap();
+        let permission = model.get_element_by_path("/Old/Permission").unwrap();
+        permission.add_to_file(&new_file).unwrap();
+        let diagnostics = permission.validate().unwrap();
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].version, AutosarVersion::Autosar_4_0_1);
+
+        // elements that are only present in the new file are not checked with the old version
+        let old_file = model
+            .files()
+            .find(|file| file.version() == AutosarVersion::Autosar_4_0_1)
+            .unwrap();
+        permission.remove_from_file(&old_file).unwrap();
+        assert_eq!(model.validate(), Vec::new());
+    }
+
+    #[test]
+    fn missing_sub_elements() {
+        // in AUTOSAR 4.2.1 all the direct sub elements of a table ENTRY are required
+        let model = AutosarModel::new();
+        model.create_file("test.arxml", AutosarVersion::Autosar_4_2_1).unwrap();
+        let pkg = model
+            .root_element()
+            .create_sub_element(ElementName::ArPackages)
+            .and_then(|packages| packages.create_named_sub_element(ElementName::ArPackage, "Pkg"))
+            .unwrap();
+        let entry = pkg
+            .create_sub_element(ElementName::Elements)
+            .and_then(|elements| elements.create_named_sub_element(ElementName::Documentation, "Doc"))
+            .and_then(|doc| doc.create_sub_element(ElementName::DocumentationContent))
+            .and_then(|content| content.create_sub_element(ElementName::Table))
+            .and_then(|table| table.create_sub_element(ElementName::Tgroup))
+            .and_then(|tgroup| tgroup.create_sub_element(ElementName::Tbody))
+            .and_then(|tbody| tbody.create_sub_element(ElementName::Row))
+            .and_then(|row| row.create_sub_element(ElementName::Entry))
+            .unwrap();
+        entry.create_sub_element(ElementName::P).unwrap();
+        let required = required_sub_elements(entry.element_type(), AutosarVersion::Autosar_4_2_1);
+        assert!(required.len() > 1);
+
+        let missing: Vec<ElementName> = entry
+            .validate()
+            .unwrap()
+            .into_iter()
+            .filter(|diag| diag.element == entry)
+            .filter_map(|diag| match diag.kind {
+                ValidationErrorKind::MissingSubElement { sub_element } => Some(sub_element),
+                _ => None,
+            })
+            .collect();
+        let expected: Vec<ElementName> = required
+            .into_iter()
+            .map(|(name, _)| name)
+            .filter(|name| *name != ElementName::P)
+            .collect();
+        assert_eq!(missing, expected);
+    }
+
+    #[test]
+    fn traits() {
+        let kind = ValidationErrorKind::MissingAttribute {
+            attribute: AttributeName::Dest,
+        };
+        assert_eq!(kind.clone(), kind);
+        assert!(!format!("{kind:?} {kind}").is_empty());
+        let model = AutosarModel::new();
+        let diagnostic = ValidationDiagnostic {
+            element: model.root_element(),
+            path: model.root_element().xml_path(),
+            version: AutosarVersion::LATEST,
+            kind,
+        };
+        assert_eq!(diagnostic.clone(), diagnostic);
+        assert!(!format!("{diagnostic:?}").is_empty());
+    }
+}